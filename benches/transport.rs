@@ -0,0 +1,152 @@
+//! Benchmarks of the transport layer, the formatting of the requests and
+//! the parsing of the responses, run with `cargo bench`.
+#[macro_use]
+extern crate criterion;
+extern crate rust_docker;
+
+use std::hint::black_box;
+use std::io::Cursor;
+
+use criterion::Criterion;
+
+use rust_docker::api::api_utils::RequestBuilder;
+use rust_docker::api::events::{Event, EventStream};
+use rust_docker::api::logs::{ContainerLogs, LogFrames};
+use rust_docker::utils::{
+    Response, ResponseParser, StreamHandle, StreamResponse,
+};
+
+fn container_list(count: usize) -> String {
+    let container = r#"{"Id":"8dfafdbc3a40","Names":["/boring_feynman"],"Image":"ubuntu:latest","ImageID":"d74508fb6632","Command":"echo 1","Created":1367854155,"State":"running","Status":"Up 2 hours","Ports":[{"PrivatePort":2222,"PublicPort":3333,"Type":"tcp"}],"Labels":{"com.example.vendor":"Acme"},"HostConfig":{"NetworkMode":"default"},"Mounts":[]}"#;
+    let items: Vec<&str> = (0..count).map(|_| container).collect();
+    format!("[{}]", items.join(","))
+}
+
+fn with_length(body: &str) -> Vec<u8> {
+    format!(
+        "HTTP/1.1 200 OK\r\nApi-Version: 1.41\r\nContent-Type: application/json\r\n\
+         Docker-Experimental: false\r\nOstype: linux\r\nServer: Docker/20.10.7 (linux)\r\n\
+         Content-Length: {}\r\n\r\n{}",
+        body.len(),
+        body
+    )
+    .into_bytes()
+}
+
+fn chunked(body: &[u8], chunk_size: usize) -> Vec<u8> {
+    let mut resp = b"HTTP/1.1 200 OK\r\nApi-Version: 1.41\r\nContent-Type: application/json\r\n\
+                     Transfer-Encoding: chunked\r\n\r\n"
+        .to_vec();
+    for chunk in body.chunks(chunk_size) {
+        resp.extend_from_slice(format!("{:x}\r\n", chunk.len()).as_bytes());
+        resp.extend_from_slice(chunk);
+        resp.extend_from_slice(b"\r\n");
+    }
+    resp.extend_from_slice(b"0\r\n\r\n");
+    resp
+}
+
+fn stream(resp: &[u8]) -> StreamResponse {
+    StreamResponse::from_reader(
+        Box::new(Cursor::new(resp.to_vec())),
+        StreamHandle::new(),
+    )
+    .unwrap()
+}
+
+/// Multiplexed log frames of `lines` lines each.
+fn log_frames(frames: usize, lines: usize) -> Vec<u8> {
+    let mut body = Vec::new();
+    for frame in 0..frames {
+        let mut payload = String::new();
+        for line in 0..lines {
+            payload.push_str(&format!(
+                "2018-05-01T10:00:01.123456789Z frame {} line {}\n",
+                frame, line
+            ));
+        }
+        body.extend_from_slice(&[1, 0, 0, 0]);
+        body.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+        body.extend_from_slice(payload.as_bytes());
+    }
+    body
+}
+
+fn requests(c: &mut Criterion) {
+    c.bench_function("format request", |b| {
+        b.iter(|| {
+            RequestBuilder::new("GET", "/containers/json?all=true")
+                .header("X-Registry-Auth", "eyJ1c2VybmFtZSI6ImEifQ==")
+                .build()
+                .unwrap()
+        })
+    });
+
+    c.bench_function("format request with body", |b| {
+        b.iter(|| {
+            RequestBuilder::new("POST", "/containers/create")
+                .body(r#"{"Image":"alpine","Cmd":["sleep","60"]}"#)
+                .build()
+                .unwrap()
+        })
+    });
+}
+
+fn responses(c: &mut Criterion) {
+    let small = with_length(r#"{"Id":"8dfafdbc3a40","Warnings":[]}"#);
+    c.bench_function("parse small response", |b| {
+        b.iter(|| Response::parse_http_response(small.clone()).unwrap())
+    });
+
+    let list = container_list(1000);
+    let large = with_length(&list);
+    c.bench_function("parse 1000 containers", |b| {
+        b.iter(|| Response::parse_http_response(large.clone()).unwrap())
+    });
+
+    let large_chunked = chunked(list.as_bytes(), 4096);
+    c.bench_function("parse 1000 containers chunked", |b| {
+        b.iter(|| Response::parse_http_response(large_chunked.clone()).unwrap())
+    });
+
+    // The socket is read 16KiB at a time, the completeness of the response
+    // is checked after every read.
+    c.bench_function("check completeness while reading", |b| {
+        b.iter(|| {
+            let mut parser = ResponseParser::new();
+            let mut end = 0;
+            while end < large_chunked.len() {
+                end = (end + 16 * 1024).min(large_chunked.len());
+                black_box(parser.is_complete(&large_chunked[..end]));
+            }
+        })
+    });
+}
+
+fn streams(c: &mut Criterion) {
+    let event = r#"{"Type":"container","Action":"start","Actor":{"ID":"8dfafdbc3a40","Attributes":{"image":"alpine","name":"web"}},"scope":"local","time":1530000005,"timeNano":1530000005000000000}"#;
+    let events: Vec<&str> = (0..1000).map(|_| event).collect();
+    let events = chunked((events.join("\n") + "\n").as_bytes(), 512);
+    c.bench_function("stream 1000 events", |b| {
+        b.iter(|| {
+            let stream: EventStream = EventStream::new(stream(&events));
+            for event in stream {
+                let event: Event = event.unwrap();
+                black_box(event);
+            }
+        })
+    });
+
+    let logs = chunked(&log_frames(100, 10), 4096);
+    c.bench_function("stream 1000 log lines", |b| {
+        b.iter(|| {
+            let logs = ContainerLogs::new(LogFrames::new(stream(&logs)), true);
+            for line in logs {
+                black_box(line.unwrap());
+            }
+        })
+    });
+}
+
+criterion_group!(benches, requests, responses, streams);
+criterion_main!(benches);
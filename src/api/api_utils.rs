@@ -1,4 +1,137 @@
-static API_VERSION: &'static str = "v1.37";
+use errors::DockerApiError;
+
+static API_VERSION: &str = "v1.37";
+
+/// Builder for the raw HTTP requests written to the socket to docker.
+///
+/// Besides `Host`, and `Content-Length` and `Content-Type` for the POST and
+/// PUT requests, any header can be added, like `X-Registry-Config` for
+/// builds or trace propagation headers for a daemon behind a proxy. A
+/// `Content-Type` header replaces the default `application/json` one.
+///
+/// ```rust,ignore
+/// let request = RequestBuilder::new("POST", "/build")
+///     .header("Content-Type", "application/x-tar")
+///     .header("X-Registry-Config", &config)
+///     .body(&context);
+/// let resp = client.send(&request)?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct RequestBuilder {
+    method: String,
+    endpoint: String,
+    headers: Vec<(String, String)>,
+    body: String,
+    chunked: bool,
+}
+
+impl RequestBuilder {
+    pub fn new(method: &str, api_endpoint: &str) -> RequestBuilder {
+        RequestBuilder {
+            method: method.to_uppercase(),
+            endpoint: api_endpoint.to_string(),
+            headers: Vec::new(),
+            body: String::new(),
+            chunked: false,
+        }
+    }
+
+    /// Adds a header, the same header can be added several times.
+    pub fn header(mut self, name: &str, value: &str) -> RequestBuilder {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Adds all the provided headers.
+    pub fn headers(mut self, headers: &[(&str, &str)]) -> RequestBuilder {
+        for &(name, value) in headers {
+            self = self.header(name, value);
+        }
+        self
+    }
+
+    /// Sets the body of the request. For GET, HEAD and DELETE requests it is
+    /// appended to the endpoint as the query string.
+    pub fn body(mut self, body: &str) -> RequestBuilder {
+        self.body = body.to_string();
+        self
+    }
+
+    /// Marks the body of a POST or PUT request as sent afterwards with the
+    /// chunked transfer encoding, like the build context of a build. The
+    /// request then only has the headers.
+    pub fn chunked(mut self) -> RequestBuilder {
+        self.chunked = true;
+        self
+    }
+
+    fn has_header(&self, name: &str) -> bool {
+        self.headers
+            .iter()
+            .any(|header| header.0.eq_ignore_ascii_case(name))
+    }
+
+    /// Formats the request. Fails for unsupported methods and for headers
+    /// which would break the request, like values with line breaks.
+    pub fn build(&self) -> Result<String, DockerApiError> {
+        let mut headers = String::new();
+        for (name, value) in &self.headers {
+            if !is_valid_header(name, value) {
+                return Err(DockerApiError::RequestPrepareError(
+                    "Invalid request header",
+                ));
+            }
+            headers.push_str(&format!("{}: {}\r\n", name, value));
+        }
+
+        match self.method.as_str() {
+            "GET" | "HEAD" | "DELETE" => Ok(format!(
+                "{method} {endpoint}{body} HTTP/1.1\r\nHost: {version}\r\n{headers}\r\n",
+                method = self.method,
+                endpoint = self.endpoint,
+                body = self.body,
+                version = API_VERSION,
+                headers = headers
+            )),
+            "POST" | "PUT" => {
+                let content_type = if self.has_header("Content-Type") {
+                    ""
+                } else {
+                    "Content-Type: application/json\r\n"
+                };
+                let (length, body) = if self.chunked {
+                    ("Transfer-Encoding: chunked\r\n".to_string(), "")
+                } else {
+                    (
+                        format!("Content-Length: {}\r\n", self.body.len()),
+                        self.body.as_str(),
+                    )
+                };
+                Ok(format!(
+                    "{method} {endpoint} HTTP/1.1\r\nHost: {version}\r\n{headers}{length}{content_type}\r\n{external_body}",
+                    method = self.method,
+                    endpoint = self.endpoint,
+                    version = API_VERSION,
+                    headers = headers,
+                    length = length,
+                    content_type = content_type,
+                    external_body = body
+                ))
+            }
+            _ => Err(DockerApiError::RequestPrepareError(
+                "Unsupported request method",
+            )),
+        }
+    }
+}
+
+/// Checks that the header can be written to a request as it is, its name
+/// being a token and its value not breaking the line.
+pub fn is_valid_header(name: &str, value: &str) -> bool {
+    let valid_name = !name.is_empty()
+        && name.bytes().all(|b| b.is_ascii_graphic() && b != b':');
+    valid_name && !value.contains('\r') && !value.contains('\n')
+}
 
 /// Gives a formatted API request which should be writtern
 /// to the socket to docker.
@@ -7,24 +140,20 @@ pub fn get_formatted_api_request(
     method: &str,
     body: &str,
 ) -> Option<String> {
-    if method == "GET" || method == "get" {
-        return Some(format!(
-            "GET {endpoint}{body} HTTP/1.1\r\nHost: {version}\r\n\r\n",
-            endpoint = api_endpoint,
-            body = body,
-            version = API_VERSION
-        ));
-    }
-
-    if method == "POST" || method == "post" {
-        return Some(format!(
-            "POST {endpoint} HTTP/1.1\r\nHost: {version}\r\nContent-Length: {length}\r\nContent-Type: application/json\r\n\r\n{external_body}\r\n\r\n",
-            endpoint = api_endpoint,
-            version = API_VERSION,
-            length = body.len(),
-            external_body = body
-        ));
-    }
+    get_formatted_api_request_with_headers(api_endpoint, method, body, &[])
+}
 
-    None
+/// Same as `get_formatted_api_request` but also writes the provided extra
+/// headers, like `X-Registry-Auth`, to the request.
+pub fn get_formatted_api_request_with_headers(
+    api_endpoint: &str,
+    method: &str,
+    body: &str,
+    headers: &[(&str, &str)],
+) -> Option<String> {
+    RequestBuilder::new(method, api_endpoint)
+        .headers(headers)
+        .body(body)
+        .build()
+        .ok()
 }
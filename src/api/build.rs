@@ -0,0 +1,598 @@
+use std::collections::HashMap;
+use std::io;
+use std::io::{BufRead, Read};
+use std::time::{Duration, Instant};
+
+use api::api_utils::RequestBuilder;
+use api::images::TransferProgress;
+use api::{DockerApiClient, PruneFilters};
+use utils::{
+    deserialize_body, deserialize_null_default, duration_to_secs,
+    encode_query_value,
+};
+
+use serde_json;
+
+use errors::{ApiObject, DockerApiError};
+
+/// Options of an image build, given to the daemon in the query of the
+/// request. Only the options which are set are sent.
+///
+/// * tags: Names given to the built image, like `app:1.0`.
+/// * dockerfile: Path of the Dockerfile in the build context.
+/// * build_args: Values of the `ARG`s of the Dockerfile.
+/// * target: Stage of a multi-stage build to stop at.
+/// * cache_from: Images to use as cache sources.
+/// * network: Network mode of the `RUN` instructions, like `host`.
+/// * platform: Platform to build for, like `linux/arm64`.
+/// * squash: Squashes the new layers into one, needs an experimental daemon.
+///
+/// ```rust,ignore
+/// let options = BuildOptions::new()
+///     .tag("app:1.0")
+///     .build_arg("VERSION", "1.0")
+///     .target("release");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BuildOptions {
+    pub tags: Vec<String>,
+    pub dockerfile: Option<String>,
+    pub build_args: HashMap<String, String>,
+    pub labels: HashMap<String, String>,
+    pub target: Option<String>,
+    pub cache_from: Vec<String>,
+    pub network: Option<String>,
+    pub platform: Option<String>,
+    pub squash: bool,
+    pub no_cache: bool,
+    pub pull: bool,
+}
+
+impl BuildOptions {
+    pub fn new() -> BuildOptions {
+        BuildOptions::default()
+    }
+
+    pub fn tag(mut self, tag: &str) -> BuildOptions {
+        self.tags.push(tag.to_string());
+        self
+    }
+
+    pub fn dockerfile(mut self, path: &str) -> BuildOptions {
+        self.dockerfile = Some(path.to_string());
+        self
+    }
+
+    pub fn build_arg(mut self, name: &str, value: &str) -> BuildOptions {
+        self.build_args.insert(name.to_string(), value.to_string());
+        self
+    }
+
+    pub fn label(mut self, key: &str, value: &str) -> BuildOptions {
+        self.labels.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    pub fn target(mut self, stage: &str) -> BuildOptions {
+        self.target = Some(stage.to_string());
+        self
+    }
+
+    pub fn cache_from(mut self, image: &str) -> BuildOptions {
+        self.cache_from.push(image.to_string());
+        self
+    }
+
+    pub fn network(mut self, network: &str) -> BuildOptions {
+        self.network = Some(network.to_string());
+        self
+    }
+
+    pub fn platform(mut self, platform: &str) -> BuildOptions {
+        self.platform = Some(platform.to_string());
+        self
+    }
+
+    pub fn squash(mut self, squash: bool) -> BuildOptions {
+        self.squash = squash;
+        self
+    }
+
+    /// Builds without the cache of the previous builds.
+    pub fn no_cache(mut self, no_cache: bool) -> BuildOptions {
+        self.no_cache = no_cache;
+        self
+    }
+
+    /// Pulls the base images even if they are present.
+    pub fn pull(mut self, pull: bool) -> BuildOptions {
+        self.pull = pull;
+        self
+    }
+
+    /// Formats the options as the query of the build endpoint, the maps
+    /// and lists are JSON encoded.
+    pub fn to_query(&self) -> String {
+        let mut params: Vec<String> = Vec::new();
+        for tag in &self.tags {
+            params.push(format!("t={}", encode_query_value(tag)));
+        }
+
+        let strings = [
+            ("dockerfile", &self.dockerfile),
+            ("target", &self.target),
+            ("networkmode", &self.network),
+            ("platform", &self.platform),
+        ];
+        for &(name, value) in strings.iter() {
+            if let Some(ref value) = *value {
+                params.push(format!("{}={}", name, encode_query_value(value)));
+            }
+        }
+
+        let maps = [("buildargs", &self.build_args), ("labels", &self.labels)];
+        for &(name, map) in maps.iter() {
+            if !map.is_empty() {
+                // Through a Value for the keys to be sorted.
+                let json = json!(map).to_string();
+                params.push(format!("{}={}", name, encode_query_value(&json)));
+            }
+        }
+        if !self.cache_from.is_empty() {
+            let json = json!(self.cache_from).to_string();
+            params.push(format!("cachefrom={}", encode_query_value(&json)));
+        }
+
+        let flags = [
+            ("squash", self.squash),
+            ("nocache", self.no_cache),
+            ("pull", self.pull),
+        ];
+        for &(name, set) in flags.iter() {
+            if set {
+                params.push(format!("{}=true", name));
+            }
+        }
+
+        if params.is_empty() {
+            String::new()
+        } else {
+            format!("?{}", params.join("&"))
+        }
+    }
+}
+
+/// A single message from the stream the daemon writes while building.
+///
+/// * stream: Output of the build, like the steps and the output of the
+///   `RUN` instructions.
+/// * status, id, progress: Progress of the pulls of the base images.
+/// * aux: Extra data, like the ID of the built image.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct BuildMessage {
+    #[serde(default)]
+    pub stream: Option<String>,
+
+    #[serde(default)]
+    pub status: Option<String>,
+
+    #[serde(default)]
+    pub id: Option<String>,
+
+    #[serde(default)]
+    pub progress: Option<String>,
+
+    #[serde(default)]
+    pub error: Option<String>,
+
+    #[serde(default)]
+    pub aux: Option<serde_json::Value>,
+}
+
+impl BuildMessage {
+    /// ID of the built image, given at the end of a successful build.
+    pub fn image_id(&self) -> Option<&str> {
+        self.aux.as_ref()?.get("ID")?.as_str()
+    }
+}
+
+/// A step of a build, from the output of the classic builder.
+///
+/// * number: Position of the step, from 1, out of `total`.
+/// * instruction: The Dockerfile instruction, like `RUN make`.
+/// * cache_hit: The layer of the step was taken from the cache.
+/// * duration: Time between the start of the step and the start of the
+///   next one, or the end of the build.
+/// * output: What the instruction wrote, for the `RUN` instructions.
+/// * image_id: ID of the image made by the step, as shortened by the daemon.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BuildStep {
+    pub number: usize,
+    pub total: usize,
+    pub instruction: String,
+    pub cache_hit: bool,
+    pub duration: Duration,
+    pub output: String,
+    pub image_id: Option<String>,
+}
+
+/// The steps of a build with its result.
+///
+/// * image_id: ID of the built image, None if the build did not complete.
+/// * tags: The names the image was tagged with.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BuildSummary {
+    pub steps: Vec<BuildStep>,
+    pub image_id: Option<String>,
+    pub tags: Vec<String>,
+    pub duration: Duration,
+}
+
+impl BuildSummary {
+    /// Number of steps whose layer was taken from the cache.
+    pub fn cached_steps(&self) -> usize {
+        self.steps.iter().filter(|step| step.cache_hit).count()
+    }
+}
+
+/// Splits the output of a build into steps as the messages arrive, timing
+/// them with the arrival of the messages.
+///
+/// Only the output of the classic builder has steps, the messages of
+/// BuildKit are not parsed.
+///
+/// ```rust,ignore
+/// let mut log = BuildLog::new();
+/// client.build_image_with_callback(&mut context, &options, &mut |msg| log.record(msg))?;
+/// for step in log.finish().steps {
+///     println!("{}/{} {} {:?}", step.number, step.total, step.instruction, step.duration);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct BuildLog {
+    summary: BuildSummary,
+    started: Option<Instant>,
+    step_started: Option<Instant>,
+    last: Option<Instant>,
+    partial: String,
+}
+
+impl Default for BuildLog {
+    fn default() -> BuildLog {
+        BuildLog::new()
+    }
+}
+
+impl BuildLog {
+    pub fn new() -> BuildLog {
+        BuildLog {
+            summary: BuildSummary::default(),
+            started: None,
+            step_started: None,
+            last: None,
+            partial: String::new(),
+        }
+    }
+
+    /// Records a message arriving now.
+    pub fn record(&mut self, msg: &BuildMessage) {
+        self.record_at(msg, Instant::now())
+    }
+
+    /// Records a message which arrived at `at`.
+    pub fn record_at(&mut self, msg: &BuildMessage, at: Instant) {
+        self.started.get_or_insert(at);
+        self.last = Some(at);
+        if let Some(id) = msg.image_id() {
+            self.summary.image_id = Some(id.to_string());
+        }
+
+        let stream = match msg.stream {
+            Some(ref stream) => stream,
+            None => return,
+        };
+        self.partial.push_str(stream);
+        while let Some(end) = self.partial.find('\n') {
+            let line: String = self.partial.drain(..=end).collect();
+            self.parse_line(&line, at);
+        }
+    }
+
+    /// The steps recorded so far, the last one still running.
+    pub fn steps(&self) -> &[BuildStep] {
+        &self.summary.steps
+    }
+
+    /// Ends the log at the last message, the build is over.
+    pub fn finish(mut self) -> BuildSummary {
+        if !self.partial.is_empty() {
+            let line = self.partial.split_off(0);
+            let at = self.last.unwrap_or_else(Instant::now);
+            self.parse_line(&line, at);
+        }
+        if let Some(last) = self.last {
+            self.end_step(last);
+            if let Some(started) = self.started {
+                self.summary.duration = last.duration_since(started);
+            }
+        }
+        self.summary
+    }
+
+    fn end_step(&mut self, at: Instant) {
+        if let (Some(step), Some(started)) =
+            (self.summary.steps.last_mut(), self.step_started.take())
+        {
+            step.duration = at.duration_since(started);
+        }
+    }
+
+    fn parse_line(&mut self, line: &str, at: Instant) {
+        let text = line.trim_end_matches(['\r', '\n']);
+        if let Some(step) = parse_step_line(text) {
+            self.end_step(at);
+            self.step_started = Some(at);
+            self.summary.steps.push(step);
+            return;
+        }
+        if let Some(id) = text.strip_prefix("Successfully built ") {
+            self.end_step(at);
+            self.summary.image_id.get_or_insert(id.trim().to_string());
+            return;
+        }
+        if let Some(tag) = text.strip_prefix("Successfully tagged ") {
+            self.summary.tags.push(tag.trim().to_string());
+            return;
+        }
+
+        let step = match self.summary.steps.last_mut() {
+            Some(step) if self.step_started.is_some() => step,
+            _ => return,
+        };
+        match text.strip_prefix(" ---> ") {
+            Some("Using cache") => step.cache_hit = true,
+            Some(arrow) if arrow.starts_with("Running in ") => (),
+            Some(id) => step.image_id = Some(id.trim().to_string()),
+            None if text.starts_with("Removing intermediate container ") => (),
+            None => step.output.push_str(line),
+        }
+    }
+}
+
+/// Parses a `Step 2/5 : RUN make` line.
+fn parse_step_line(line: &str) -> Option<BuildStep> {
+    let rest = line.strip_prefix("Step ")?;
+    let (position, instruction) = rest.split_once(" : ")?;
+    let (number, total) = position.split_once('/')?;
+
+    Some(BuildStep {
+        number: number.parse().ok()?,
+        total: total.parse().ok()?,
+        instruction: instruction.to_string(),
+        ..Default::default()
+    })
+}
+
+/// Reader of a build context calling a callback with the bytes read so
+/// far, which are the bytes sent to the daemon when the context is
+/// uploaded.
+pub struct ProgressReader<'a, R> {
+    inner: R,
+    progress: TransferProgress,
+    callback: &'a mut dyn FnMut(&TransferProgress),
+}
+
+impl<'a, R: Read> ProgressReader<'a, R> {
+    /// `total` is the size of the content of `inner` if it is known.
+    pub fn new(
+        inner: R,
+        total: Option<u64>,
+        callback: &'a mut dyn FnMut(&TransferProgress),
+    ) -> ProgressReader<'a, R> {
+        ProgressReader {
+            inner,
+            progress: TransferProgress {
+                transferred: 0,
+                total,
+            },
+            callback,
+        }
+    }
+}
+
+impl<'a, R: Read> Read for ProgressReader<'a, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        if read > 0 {
+            self.progress.transferred += read as u64;
+            (self.callback)(&self.progress);
+        }
+        Ok(read)
+    }
+}
+
+/// What `Build::prune_build_cache` removed.
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "PascalCase")]
+pub struct BuildCachePruneReport {
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub caches_deleted: Vec<String>,
+    #[serde(default)]
+    pub space_reclaimed: u64,
+}
+
+pub trait Build: DockerApiClient {
+    /// Builds an image from `context`, a tar archive with the Dockerfile and
+    /// the files it uses, which can be compressed with gzip, bzip2 or xz.
+    /// The context is uploaded as it is read, so it does not have to fit
+    /// in memory.
+    ///
+    /// Gives all the messages of the build, a failed step fails the build
+    /// with an `ImageError`.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// extern crate rust_docker;
+    ///
+    /// use std::fs::File;
+    ///
+    /// use rust_docker::api::build::{Build, BuildOptions};
+    /// use rust_docker::client::DockerClient;
+    ///
+    /// let client = match DockerClient::new("unix:///var/run/docker.sock") {
+    ///     Ok(a) => a,
+    ///     Err(err) => {
+    ///         println!("{}", err);
+    ///         std::process::exit(1);
+    ///     }
+    /// };
+    ///
+    /// let mut context = File::open("context.tar").unwrap();
+    /// let options = BuildOptions::new().tag("app:latest");
+    /// match client.build_image(&mut context, &options) {
+    ///     Ok(messages) => println!("{:?}", messages.last()),
+    ///     Err(err) => println!("An error occured : {}", err),
+    /// }
+    /// ```
+    fn build_image(
+        &self,
+        context: &mut dyn Read,
+        options: &BuildOptions,
+    ) -> Result<Vec<BuildMessage>, DockerApiError> {
+        self.build_image_with_callback(context, options, &mut |_| ())
+    }
+
+    /// Same as `build_image`, calling `on_upload` as the context is sent to
+    /// the daemon. `context_size` is the size of the archive if known, like
+    /// the length of the archive made by `tar_util::context_from_dir`.
+    ///
+    /// ```rust,ignore
+    /// let context = tar_util::context_from_dir(Path::new("."))?;
+    /// let size = context.len() as u64;
+    /// client.build_image_with_upload_progress(
+    ///     &mut Cursor::new(context),
+    ///     Some(size),
+    ///     &options,
+    ///     &mut |progress| {
+    ///         if let Some(fraction) = progress.fraction() {
+    ///             println!("Sending build context {:.0}%", fraction * 100.0);
+    ///         }
+    ///     },
+    /// )?;
+    /// ```
+    fn build_image_with_upload_progress(
+        &self,
+        context: &mut dyn Read,
+        context_size: Option<u64>,
+        options: &BuildOptions,
+        on_upload: &mut dyn FnMut(&TransferProgress),
+    ) -> Result<Vec<BuildMessage>, DockerApiError> {
+        let mut context = ProgressReader::new(context, context_size, on_upload);
+        self.build_image(&mut context, options)
+    }
+
+    /// Same as `build_image`, giving every message to `on_message` as soon
+    /// as the daemon writes it.
+    fn build_image_with_callback(
+        &self,
+        context: &mut dyn Read,
+        options: &BuildOptions,
+        on_message: &mut dyn FnMut(&BuildMessage),
+    ) -> Result<Vec<BuildMessage>, DockerApiError> {
+        let api_endpoint = format!("/build{}", options.to_query());
+        let method = "POST";
+
+        let request = RequestBuilder::new(method, &api_endpoint)
+            .header("Content-Type", "application/x-tar")
+            .chunked();
+        let mut resp = self.send_upload(&request, context)?;
+
+        if resp.status_code != 200 {
+            let status_code = resp.status_code;
+            return Err(DockerApiError::from_response(
+                ApiObject::Image,
+                status_code,
+                resp.body_to_string()?,
+            ));
+        }
+
+        let mut messages: Vec<BuildMessage> = Vec::new();
+        let mut line = String::new();
+        loop {
+            line.clear();
+            match resp.read_line(&mut line) {
+                Ok(0) => break,
+                Ok(_) => (),
+                Err(_) => {
+                    return Err(DockerApiError::HTTPResponseParseError(
+                        "Error while reading the build output",
+                    ))
+                }
+            }
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let msg: BuildMessage = match serde_json::from_str(&line) {
+                Ok(msg) => msg,
+                Err(err) => {
+                    return Err(DockerApiError::JsonDeserializationError(err))
+                }
+            };
+            if let Some(err) = msg.error {
+                return Err(DockerApiError::ImageError(err));
+            }
+            on_message(&msg);
+            messages.push(msg);
+        }
+
+        Ok(messages)
+    }
+
+    /// Builds the image like `build_image`, giving its steps with the time
+    /// each of them took.
+    fn build_image_steps(
+        &self,
+        context: &mut dyn Read,
+        options: &BuildOptions,
+    ) -> Result<BuildSummary, DockerApiError> {
+        let mut log = BuildLog::new();
+        self.build_image_with_callback(context, options, &mut |msg| {
+            log.record(msg)
+        })?;
+        Ok(log.finish())
+    }
+
+    /// Removes the build cache not used by a build, like
+    /// `docker builder prune`, or all of it with `all`. The cache has no
+    /// labels, only the `until` filter applies to it.
+    fn prune_build_cache(
+        &self,
+        filters: &PruneFilters,
+        all: bool,
+    ) -> Result<BuildCachePruneReport, DockerApiError> {
+        let mut api_endpoint = format!("/build/prune?all={}", all);
+        if let Some(until) = filters.until {
+            let filters =
+                json!({ "until": [format!("{}s", duration_to_secs(until))] });
+            api_endpoint.push_str(&format!(
+                "&filters={}",
+                encode_query_value(&filters.to_string())
+            ));
+        }
+        let method = "POST";
+
+        let resp = self.get_response_from_api(&api_endpoint, method, "")?;
+
+        if resp.status_code != 200 {
+            return Err(DockerApiError::from_response(
+                ApiObject::Image,
+                resp.status_code,
+                resp.body,
+            ));
+        }
+
+        deserialize_body(&resp.body, self.is_strict())
+    }
+}
@@ -1,130 +1,1783 @@
-#![allow(non_snake_case)]
 use std::collections::HashMap;
+use std::fmt;
+use std::io::Read;
+use std::mem::{self, ManuallyDrop};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpStream};
+use std::str::FromStr;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
-use api::DockerApiClient;
+use api::api_utils::RequestBuilder;
+use api::ids::{ContainerId, ContainerRef, ImageId};
+use api::networks::EndpointSettings;
+use api::platform::Platform;
+use api::version::{ApiFeature, ApiVersion, SystemInfo};
+use api::{DockerApiClient, PruneFilters};
+use utils::{
+    deserialize_body, deserialize_lenient, deserialize_null_default,
+    deserialize_string_or_list, deserialize_timestamp, duration_to_nanos,
+    duration_to_secs, encode_query_value, is_strict_deserialization,
+    serialize_rfc3339_timestamp, serialize_unix_timestamp, JsonArrayStream,
+    StreamResponse,
+};
 
+use serde::de::{Deserialize, Deserializer, Error};
+use serde::ser::{Serialize, Serializer};
 use serde_json;
 
-use errors::DockerApiError;
+use errors::{ApiObject, DockerApiError};
 
-#[derive(Serialize, Deserialize, Debug)]
+pub use api::ResourceCheck;
+pub use errors::{Resource, ResourceShortfall};
+
+/// Summary of a container as given by the container list.
+///
+/// * created: Unix timestamp of the creation of the container.
+/// * network_settings: The networks the container is attached to, with its
+///   addresses on each of them.
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "PascalCase")]
+#[serde(default)]
 pub struct Container {
-    pub Id: String,
-    pub Names: Vec<String>,
-    pub Image: String,
-    pub ImageID: String,
-    pub Command: String,
-    pub State: String,
-    pub Status: String,
-    pub Ports: Vec<Port>,
-    pub Labels: Option<HashMap<String, String>>,
+    pub id: ContainerId,
+
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub names: Vec<String>,
+
+    pub image: String,
+    #[serde(rename = "ImageID")]
+    pub image_id: ImageId,
+    pub command: String,
+
+    #[serde(
+        default,
+        deserialize_with = "deserialize_timestamp",
+        serialize_with = "serialize_unix_timestamp"
+    )]
+    pub created: Option<SystemTime>,
+    pub state: String,
+    pub status: String,
+
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub ports: Vec<Port>,
+
+    pub labels: Option<HashMap<String, String>>,
+
+    #[serde(default)]
+    pub size_rw: Option<i64>,
 
     #[serde(default)]
-    pub SizeRw: Option<i64>,
+    pub size_root_fs: u64,
+    pub host_config: HostConfig,
 
     #[serde(default)]
-    pub SizeRootFs: u64,
-    pub HostConfig: HostConfig,
-    pub Mounts: Vec<Mounts>,
+    pub network_settings: ContainerNetworkSettings,
+
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub mounts: Vec<Mounts>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+impl Container {
+    /// The name of the container without its leading `/`. The names list
+    /// also has the names the container is known by through the links of
+    /// other containers, like `/app/db`, which are left out.
+    pub fn name(&self) -> Option<&str> {
+        self.names
+            .iter()
+            .map(|name| name.strip_prefix('/').unwrap_or(name))
+            .find(|name| !name.contains('/'))
+    }
+}
+
+/// Network settings of a container, keyed by network name.
+///
+/// * ports: Host bindings of the exposed ports keyed like `80/tcp`, None
+///   for the ports which are not published. Only given by inspect.
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "PascalCase")]
+pub struct ContainerNetworkSettings {
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub networks: HashMap<String, EndpointSettings>,
+
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub ports: HashMap<String, Option<Vec<PortBinding>>>,
+}
+
+impl ContainerNetworkSettings {
+    /// Address of the host port the TCP port of the container is published
+    /// on. The daemon publishes the ports on IPv4 and IPv6 at once, the
+    /// IPv4 binding is preferred then. The loopback address is given when
+    /// it is published on all the interfaces, `0.0.0.0` or `::`.
+    pub fn published_addr(&self, port: u16) -> Option<SocketAddr> {
+        let bindings = self.ports.get(&format!("{}/tcp", port))?.as_ref()?;
+
+        let mut addrs: Vec<SocketAddr> = bindings
+            .iter()
+            .filter_map(|binding| {
+                let port = binding.host_port.parse::<u16>().ok()?;
+                let ip = match binding.host_ip.parse::<IpAddr>() {
+                    Ok(IpAddr::V6(ip)) if ip.is_unspecified() => {
+                        IpAddr::V6(Ipv6Addr::LOCALHOST)
+                    }
+                    Ok(ip) if !ip.is_unspecified() => ip,
+                    _ => IpAddr::V4(Ipv4Addr::LOCALHOST),
+                };
+                Some(SocketAddr::new(ip, port))
+            })
+            .collect();
+        addrs.sort_by_key(|addr| addr.is_ipv6());
+        addrs.into_iter().next()
+    }
+}
+
+/// A host address an exposed port of the container is published on.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct PortBinding {
+    #[serde(default)]
+    pub host_ip: String,
+
+    #[serde(default)]
+    pub host_port: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "PascalCase")]
+#[serde(default)]
 pub struct Port {
-    pub PrivatePort: u32,
-    pub PublicPort: u32,
-    pub Type: String,
+    pub private_port: u32,
+    pub public_port: u32,
+    #[serde(rename = "Type")]
+    pub typ: String,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+/// Host specific configuration for a container, this is the `HostConfig`
+/// from the container list as well as the one used to create containers.
+///
+/// * port_bindings: Host addresses the exposed ports are published on,
+///   keyed like `80/tcp`.
+/// * other: The settings without a field here, like `Privileged` or
+///   `CapAdd`, sent back as they were read.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "PascalCase")]
 pub struct HostConfig {
-    pub NetworkMode: String,
+    #[serde(default)]
+    pub network_mode: String,
+
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "HashMap::is_empty"
+    )]
+    pub port_bindings: HashMap<String, Vec<PortBinding>>,
+
+    #[serde(
+        default,
+        deserialize_with = "deserialize_lenient",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub mounts: Option<Vec<MountSpec>>,
+
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub group_add: Vec<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub userns_mode: Option<UsernsMode>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub init: Option<bool>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub oom_kill_disable: Option<bool>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub oom_score_adj: Option<i32>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pids_limit: Option<i64>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory: Option<i64>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpuset_cpus: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpuset_mems: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu_period: Option<i64>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu_quota: Option<i64>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu_shares: Option<i64>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nano_cpus: Option<i64>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blkio_weight: Option<u16>,
+
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub blkio_weight_device: Vec<WeightDevice>,
+
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub blkio_device_read_bps: Vec<ThrottleDevice>,
+
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub blkio_device_write_bps: Vec<ThrottleDevice>,
+
+    #[serde(
+        rename = "BlkioDeviceReadIOps",
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub blkio_device_read_iops: Vec<ThrottleDevice>,
+
+    #[serde(
+        rename = "BlkioDeviceWriteIOps",
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub blkio_device_write_iops: Vec<ThrottleDevice>,
+
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub dns: Vec<String>,
+
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub dns_search: Vec<String>,
+
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub dns_options: Vec<String>,
+
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub extra_hosts: Vec<String>,
+
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub device_requests: Vec<DeviceRequest>,
+
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub binds: Vec<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub restart_policy: Option<RestartPolicy>,
+
+    #[serde(flatten)]
+    pub other: HashMap<String, serde_json::Value>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+/// What the daemon does when the container exits.
+///
+/// * name: `no`, `always`, `unless-stopped` or `on-failure`.
+/// * maximum_retry_count: Restarts at most, for `on-failure`.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct RestartPolicy {
+    #[serde(default)]
+    pub name: String,
+
+    #[serde(default)]
+    pub maximum_retry_count: u32,
+}
+
+/// A request for devices of a driver, like the GPUs of the `nvidia` driver.
+///
+/// * count: Number of devices, -1 for all of them.
+/// * device_ids: The devices to use instead of a count.
+/// * capabilities: Alternatives of sets of capabilities the devices need,
+///   like `[["gpu"]]`.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct DeviceRequest {
+    #[serde(default)]
+    pub driver: String,
+
+    #[serde(default)]
+    pub count: i64,
+
+    #[serde(
+        rename = "DeviceIDs",
+        default,
+        deserialize_with = "deserialize_null_default"
+    )]
+    pub device_ids: Vec<String>,
+
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub capabilities: Vec<Vec<String>>,
+
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub options: HashMap<String, String>,
+}
+
+/// Relative I/O weight of the container on a block device.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct WeightDevice {
+    pub path: String,
+    pub weight: u16,
+}
+
+/// I/O limit of the container on a block device, in bytes or operations
+/// per second.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct ThrottleDevice {
+    pub path: String,
+    pub rate: u64,
+}
+
+fn throttle(devices: &mut Vec<ThrottleDevice>, path: &str, rate: u64) {
+    devices.retain(|device| device.path != path);
+    devices.push(ThrottleDevice {
+        path: path.to_string(),
+        rate,
+    });
+}
+
+impl HostConfig {
+    /// Adds a supplementary group, a name or a GID, to the user of the
+    /// container.
+    pub fn group_add(mut self, group: &str) -> HostConfig {
+        self.group_add.push(group.to_string());
+        self
+    }
+
+    pub fn userns_mode(mut self, mode: UsernsMode) -> HostConfig {
+        self.userns_mode = Some(mode);
+        self
+    }
+
+    /// Runs an init process as PID 1 of the container, which forwards the
+    /// signals and reaps the zombie processes.
+    pub fn init(mut self, init: bool) -> HostConfig {
+        self.init = Some(init);
+        self
+    }
+
+    /// Keeps the kernel from killing the processes of the container when
+    /// it runs out of memory, only use it with a memory limit.
+    pub fn oom_kill_disable(mut self, disable: bool) -> HostConfig {
+        self.oom_kill_disable = Some(disable);
+        self
+    }
+
+    /// Adjusts the OOM killer preference for the container, from -1000
+    /// (never killed) to 1000 (killed first). The value is clamped to that
+    /// range.
+    pub fn oom_score_adj(mut self, score: i32) -> HostConfig {
+        self.oom_score_adj = Some(score.clamp(-1000, 1000));
+        self
+    }
+
+    /// Limits the number of processes of the container, None for no limit.
+    pub fn pids_limit(mut self, limit: Option<u32>) -> HostConfig {
+        self.pids_limit = Some(limit.map_or(-1, i64::from));
+        self
+    }
+
+    /// Limits the memory of the container, in bytes. The kernel kills its
+    /// processes when they use more.
+    pub fn memory(mut self, bytes: u64) -> HostConfig {
+        self.memory = Some(bytes as i64);
+        self
+    }
+
+    /// Limits the container to a number of CPUs, which can be fractional
+    /// like `1.5`, as `--cpus` does.
+    pub fn cpus(mut self, cpus: f64) -> HostConfig {
+        self.nano_cpus = Some((cpus.max(0.0) * 1e9).round() as i64);
+        self
+    }
+
+    /// Pins the container to the CPUs, like `0-3` or `0,2`.
+    pub fn cpuset_cpus(mut self, cpus: &str) -> HostConfig {
+        self.cpuset_cpus = Some(cpus.to_string());
+        self
+    }
+
+    /// Pins the container to the memory nodes, like `0-1`, on NUMA
+    /// systems.
+    pub fn cpuset_mems(mut self, mems: &str) -> HostConfig {
+        self.cpuset_mems = Some(mems.to_string());
+        self
+    }
+
+    /// Gives the container `quota` of CPU time every `period`, both with a
+    /// microsecond precision. Can not be used with `cpus`.
+    pub fn cpu_quota(
+        mut self,
+        period: Duration,
+        quota: Duration,
+    ) -> HostConfig {
+        self.cpu_period = Some(period.as_micros() as i64);
+        self.cpu_quota = Some(quota.as_micros() as i64);
+        self
+    }
+
+    /// Relative weight of the container when the CPUs are contended, 1024
+    /// by default.
+    pub fn cpu_shares(mut self, shares: u32) -> HostConfig {
+        self.cpu_shares = Some(i64::from(shares));
+        self
+    }
+
+    /// Relative I/O weight of the container, from 10 to 1000.
+    pub fn blkio_weight(mut self, weight: u16) -> HostConfig {
+        self.blkio_weight = Some(weight);
+        self
+    }
+
+    /// Relative I/O weight of the container on the device at `path`, like
+    /// `/dev/sda`, from 10 to 1000.
+    pub fn blkio_weight_device(
+        mut self,
+        path: &str,
+        weight: u16,
+    ) -> HostConfig {
+        self.blkio_weight_device
+            .retain(|device| device.path != path);
+        self.blkio_weight_device.push(WeightDevice {
+            path: path.to_string(),
+            weight,
+        });
+        self
+    }
+
+    /// Limits the reads from the device at `path` in bytes per second, a
+    /// later limit on the same device replaces the earlier one.
+    pub fn device_read_bps(mut self, path: &str, rate: u64) -> HostConfig {
+        throttle(&mut self.blkio_device_read_bps, path, rate);
+        self
+    }
+
+    /// Limits the writes to the device at `path` in bytes per second.
+    pub fn device_write_bps(mut self, path: &str, rate: u64) -> HostConfig {
+        throttle(&mut self.blkio_device_write_bps, path, rate);
+        self
+    }
+
+    /// Limits the reads from the device at `path` in operations per second.
+    pub fn device_read_iops(mut self, path: &str, rate: u64) -> HostConfig {
+        throttle(&mut self.blkio_device_read_iops, path, rate);
+        self
+    }
+
+    /// Limits the writes to the device at `path` in operations per second.
+    pub fn device_write_iops(mut self, path: &str, rate: u64) -> HostConfig {
+        throttle(&mut self.blkio_device_write_iops, path, rate);
+        self
+    }
+
+    /// Adds a DNS server for the container, like `--dns`.
+    pub fn dns(mut self, server: &str) -> HostConfig {
+        self.dns.push(server.to_string());
+        self
+    }
+
+    /// Adds a DNS search domain for the container, like `--dns-search`.
+    pub fn dns_search(mut self, domain: &str) -> HostConfig {
+        self.dns_search.push(domain.to_string());
+        self
+    }
+
+    /// Adds a resolver option for the container, like `ndots:2`.
+    pub fn dns_option(mut self, option: &str) -> HostConfig {
+        self.dns_options.push(option.to_string());
+        self
+    }
+
+    /// Gives GPUs to the container like `--gpus`, all of them when no count
+    /// is given.
+    pub fn gpus(mut self, count: Option<u32>) -> HostConfig {
+        self.device_requests.push(DeviceRequest {
+            count: count.map_or(-1, i64::from),
+            capabilities: vec![vec!["gpu".to_string()]],
+            ..Default::default()
+        });
+        self
+    }
+
+    /// Resolves `host` to `ip` in the container, like `--add-host`. The IP
+    /// can also be `host-gateway` for the address of the host.
+    pub fn add_host(mut self, host: &str, ip: &str) -> HostConfig {
+        self.extra_hosts.push(format!("{}:{}", host, ip));
+        self
+    }
+}
+
+/// User namespace of a container when the daemon remaps the users.
+///
+/// * Private: The remapped namespace of the daemon, the default.
+/// * Host: The user namespace of the host, without remapping.
+#[derive(Debug, Clone, PartialEq)]
+pub enum UsernsMode {
+    Private,
+    Host,
+    Raw(String),
+}
+
+impl UsernsMode {
+    pub fn as_str(&self) -> &str {
+        match *self {
+            UsernsMode::Private => "",
+            UsernsMode::Host => "host",
+            UsernsMode::Raw(ref mode) => mode,
+        }
+    }
+}
+
+impl Serialize for UsernsMode {
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for UsernsMode {
+    fn deserialize<D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<UsernsMode, D::Error> {
+        let mode = String::deserialize(deserializer)?;
+        Ok(match mode.as_str() {
+            "" => UsernsMode::Private,
+            "host" => UsernsMode::Host,
+            _ => UsernsMode::Raw(mode),
+        })
+    }
+}
+
+/// Mount propagation for bind mounts, `Other` keeps the modes the crate
+/// does not know about.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Propagation {
+    Private,
+    RPrivate,
+    Shared,
+    RShared,
+    Slave,
+    RSlave,
+    Other(String),
+}
+
+impl Propagation {
+    pub fn as_str(&self) -> &str {
+        match *self {
+            Propagation::Private => "private",
+            Propagation::RPrivate => "rprivate",
+            Propagation::Shared => "shared",
+            Propagation::RShared => "rshared",
+            Propagation::Slave => "slave",
+            Propagation::RSlave => "rslave",
+            Propagation::Other(ref mode) => mode,
+        }
+    }
+}
+
+impl Serialize for Propagation {
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for Propagation {
+    fn deserialize<D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Propagation, D::Error> {
+        let mode = String::deserialize(deserializer)?;
+        Ok(match mode.as_str() {
+            "private" => Propagation::Private,
+            "rprivate" => Propagation::RPrivate,
+            "shared" => Propagation::Shared,
+            "rshared" => Propagation::RShared,
+            "slave" => Propagation::Slave,
+            "rslave" => Propagation::RSlave,
+            _ if is_strict_deserialization() => {
+                return Err(D::Error::custom(format!(
+                    "unknown mount propagation {}",
+                    mode
+                )))
+            }
+            _ => Propagation::Other(mode),
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "PascalCase")]
+pub struct BindOptions {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub propagation: Option<Propagation>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "PascalCase")]
+pub struct VolumeDriverConfig {
+    pub name: String,
+
+    #[serde(default)]
+    pub options: HashMap<String, String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "PascalCase")]
+pub struct VolumeOptions {
+    #[serde(default)]
+    pub no_copy: bool,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub labels: Option<HashMap<String, String>>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub driver_config: Option<VolumeDriverConfig>,
+}
+
+/// * size_bytes: Size of the tmpfs mount in bytes, unlimited if not set.
+/// * mode: Permission bits of the mount as an integer, e.g. `0o1777`.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "PascalCase")]
+pub struct TmpfsOptions {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size_bytes: Option<i64>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<u32>,
+}
+
+/// A structured mount for `HostConfig.mounts`, to be used instead of the
+/// `host-src:container-dest[:options]` strings of the legacy `Binds`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "Type")]
+pub enum MountSpec {
+    #[serde(rename = "bind", rename_all = "PascalCase")]
+    Bind {
+        source: String,
+        target: String,
+
+        #[serde(default)]
+        read_only: bool,
+
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        bind_options: Option<BindOptions>,
+    },
+
+    #[serde(rename = "volume", rename_all = "PascalCase")]
+    Volume {
+        source: String,
+        target: String,
+
+        #[serde(default)]
+        read_only: bool,
+
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        volume_options: Option<VolumeOptions>,
+    },
+
+    #[serde(rename = "tmpfs", rename_all = "PascalCase")]
+    Tmpfs {
+        target: String,
+
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tmpfs_options: Option<TmpfsOptions>,
+    },
+}
+
+impl MountSpec {
+    /// Path of the mount in the container.
+    pub fn target(&self) -> &str {
+        match *self {
+            MountSpec::Bind { ref target, .. }
+            | MountSpec::Volume { ref target, .. }
+            | MountSpec::Tmpfs { ref target, .. } => target,
+        }
+    }
+
+    /// Bind mount of the host path `source` at `target` in the container.
+    pub fn bind(
+        source: &str,
+        target: &str,
+        propagation: Option<Propagation>,
+    ) -> MountSpec {
+        MountSpec::Bind {
+            source: source.to_string(),
+            target: target.to_string(),
+            read_only: false,
+            bind_options: propagation.map(|p| BindOptions {
+                propagation: Some(p),
+            }),
+        }
+    }
+
+    /// Mount of the named volume `source` at `target` in the container.
+    /// With `nocopy` the data at `target` in the image is not copied into
+    /// a new volume.
+    pub fn volume(source: &str, target: &str, nocopy: bool) -> MountSpec {
+        MountSpec::Volume {
+            source: source.to_string(),
+            target: target.to_string(),
+            read_only: false,
+            volume_options: Some(VolumeOptions {
+                no_copy: nocopy,
+                ..Default::default()
+            }),
+        }
+    }
+
+    /// Mount of a tmpfs at `target` in the container.
+    pub fn tmpfs(
+        target: &str,
+        size_bytes: Option<i64>,
+        mode: Option<u32>,
+    ) -> MountSpec {
+        MountSpec::Tmpfs {
+            target: target.to_string(),
+            tmpfs_options: Some(TmpfsOptions { size_bytes, mode }),
+        }
+    }
+
+    /// Makes a bind or volume mount read only, tmpfs mounts are left as is.
+    pub fn read_only(mut self) -> MountSpec {
+        match self {
+            MountSpec::Bind {
+                ref mut read_only, ..
+            }
+            | MountSpec::Volume {
+                ref mut read_only, ..
+            } => *read_only = true,
+            MountSpec::Tmpfs { .. } => {}
+        }
+        self
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "PascalCase")]
+#[serde(default)]
 pub struct Mounts {
     #[serde(default)]
-    pub Name: Option<String>,
-    pub Source: String,
-    pub Destination: String,
+    pub name: Option<String>,
+    pub source: String,
+    pub destination: String,
+    #[serde(default)]
+    pub driver: String,
+    pub mode: String,
+    #[serde(rename = "RW")]
+    pub rw: bool,
+    pub propagation: String,
+}
+
+/// What `Containers::prune_containers` removed.
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "PascalCase")]
+pub struct ContainersPruneReport {
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub containers_deleted: Vec<ContainerId>,
     #[serde(default)]
-    pub Driver: String,
-    pub Mode: String,
-    pub RW: bool,
-    pub Propagation: String,
+    pub space_reclaimed: u64,
 }
 
 /// Structure for implementing Container Config
 /// Derives Default fot being able to get started even with minimal
 /// config.
-#[derive(Serialize, Deserialize, Debug, Default)]
+///
+/// The settings without a field here, like `Volumes` or `Shell`, are kept
+/// in `other` and sent back as they were read.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "PascalCase")]
+#[serde(default)]
 pub struct ContainerConfig {
-    pub Image: String,
-    pub Cmd: Vec<String>,
-
-    pub Hostname: String,
-    pub Domainname: String,
-    pub User: String,
-    pub AttachStdin: bool,
-    pub AttachStdout: bool,
-    pub AttachStderr: bool,
-    pub Tty: bool,
-    pub OpenStdin: bool,
-    pub StdinOnce: bool,
-    pub Env: Vec<String>,
-    pub Entrypoint: Option<String>,
-    pub Labels: Option<HashMap<String, String>>,
-    pub WorkingDir: String,
+    pub image: String,
+
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub cmd: Vec<String>,
+
+    pub hostname: String,
+    pub domainname: String,
+    pub user: String,
+    pub attach_stdin: bool,
+    pub attach_stdout: bool,
+    pub attach_stderr: bool,
+    pub tty: bool,
+    pub open_stdin: bool,
+    pub stdin_once: bool,
+
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub env: Vec<String>,
+
+    #[serde(
+        default,
+        deserialize_with = "deserialize_string_or_list",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub entrypoint: Vec<String>,
+
+    pub labels: Option<HashMap<String, String>>,
+    pub working_dir: String,
+
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "HashMap::is_empty"
+    )]
+    pub exposed_ports: HashMap<String, serde_json::Value>,
+
+    #[serde(
+        default,
+        deserialize_with = "deserialize_lenient",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub healthcheck: Option<HealthConfig>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stop_signal: Option<Signal>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stop_timeout: Option<u64>,
+
+    #[serde(
+        default,
+        deserialize_with = "deserialize_lenient",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub host_config: Option<HostConfig>,
+
+    #[serde(
+        default,
+        deserialize_with = "deserialize_lenient",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub networking_config: Option<NetworkingConfig>,
+
+    #[serde(flatten)]
+    pub other: HashMap<String, serde_json::Value>,
+}
+
+/// Networks to connect the container to when it is created, keyed by
+/// network name. Daemons before API 1.44 only accept a single network.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct NetworkingConfig {
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub endpoints_config: HashMap<String, EndpointSettings>,
+}
+
+/// Signal sent to the process of a container, `Raw` takes any other name
+/// or number the daemon accepts, like `SIGRTMIN+3` or `15`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Signal {
+    Term,
+    Kill,
+    Int,
+    Quit,
+    Hup,
+    Usr1,
+    Usr2,
+    Winch,
+    Raw(String),
+}
+
+impl Signal {
+    pub fn as_str(&self) -> &str {
+        match *self {
+            Signal::Term => "SIGTERM",
+            Signal::Kill => "SIGKILL",
+            Signal::Int => "SIGINT",
+            Signal::Quit => "SIGQUIT",
+            Signal::Hup => "SIGHUP",
+            Signal::Usr1 => "SIGUSR1",
+            Signal::Usr2 => "SIGUSR2",
+            Signal::Winch => "SIGWINCH",
+            Signal::Raw(ref name) => name,
+        }
+    }
+
+    /// The signal with the number, like 9 for SIGKILL.
+    pub fn number(number: u32) -> Signal {
+        Signal::Raw(number.to_string())
+    }
+
+    fn from_name(name: &str) -> Option<Signal> {
+        Some(match name.to_uppercase().trim_start_matches("SIG") {
+            "TERM" => Signal::Term,
+            "KILL" => Signal::Kill,
+            "INT" => Signal::Int,
+            "QUIT" => Signal::Quit,
+            "HUP" => Signal::Hup,
+            "USR1" => Signal::Usr1,
+            "USR2" => Signal::Usr2,
+            "WINCH" => Signal::Winch,
+            _ => return None,
+        })
+    }
+}
+
+impl Serialize for Signal {
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for Signal {
+    fn deserialize<D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Signal, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Ok(Signal::from_name(&name).unwrap_or(Signal::Raw(name)))
+    }
+}
+
+/// The other signals of Linux, without the `SIG` prefix.
+const OTHER_SIGNALS: [&str; 24] = [
+    "ABRT", "ALRM", "BUS", "CHLD", "CONT", "FPE", "ILL", "IO", "IOT", "PIPE",
+    "POLL", "PROF", "PWR", "STKFLT", "STOP", "SYS", "TRAP", "TSTP", "TTIN",
+    "TTOU", "URG", "VTALRM", "XCPU", "XFSZ",
+];
+
+impl FromStr for Signal {
+    type Err = DockerApiError;
+
+    /// Parses the name of a signal, with or without the `SIG` prefix and in
+    /// any case, or its number. Fails with a `ContainerError` for anything
+    /// else, so that a typo does not reach the daemon.
+    fn from_str(name: &str) -> Result<Signal, DockerApiError> {
+        if let Some(signal) = Signal::from_name(name) {
+            return Ok(signal);
+        }
+        if let Ok(number) = name.parse::<u32>() {
+            if (1..=64).contains(&number) {
+                return Ok(Signal::number(number));
+            }
+        }
+
+        let upper = name.to_uppercase();
+        let short = upper.trim_start_matches("SIG");
+        let realtime = ["RTMIN", "RTMAX"].iter().any(|base| {
+            short.starts_with(base)
+                && match &short[base.len()..] {
+                    "" => true,
+                    offset => {
+                        (offset.starts_with('+') || offset.starts_with('-'))
+                            && offset[1..].parse::<u8>().is_ok()
+                    }
+                }
+        });
+        if realtime || OTHER_SIGNALS.contains(&short) {
+            return Ok(Signal::Raw(format!("SIG{}", short)));
+        }
+
+        Err(DockerApiError::ContainerError(format!(
+            "Unknown signal {}",
+            name
+        )))
+    }
+}
+
+/// Healthcheck of the container, the durations are in nanoseconds on the
+/// wire, use the builder methods to set them from a `Duration`.
+///
+/// * test: The check to run, like `["CMD-SHELL", "curl -f localhost"]`.
+/// * interval, timeout, start_period: 0 to inherit the value of the image.
+/// * retries: Consecutive failures to consider the container unhealthy.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct HealthConfig {
+    #[serde(default)]
+    pub test: Vec<String>,
+
+    #[serde(default)]
+    pub interval: i64,
+
+    #[serde(default)]
+    pub timeout: i64,
+
+    #[serde(default)]
+    pub start_period: i64,
+
+    #[serde(default)]
+    pub retries: u32,
+}
+
+impl HealthConfig {
+    /// Healthcheck running `cmd` with the shell of the container.
+    pub fn shell(cmd: &str) -> HealthConfig {
+        HealthConfig {
+            test: vec!["CMD-SHELL".to_string(), cmd.to_string()],
+            ..Default::default()
+        }
+    }
+
+    /// Disables the healthcheck set in the image.
+    pub fn none() -> HealthConfig {
+        HealthConfig {
+            test: vec!["NONE".to_string()],
+            ..Default::default()
+        }
+    }
+
+    pub fn interval(mut self, interval: Duration) -> HealthConfig {
+        self.interval = duration_to_nanos(interval);
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> HealthConfig {
+        self.timeout = duration_to_nanos(timeout);
+        self
+    }
+
+    pub fn start_period(mut self, start_period: Duration) -> HealthConfig {
+        self.start_period = duration_to_nanos(start_period);
+        self
+    }
+
+    pub fn retries(mut self, retries: u32) -> HealthConfig {
+        self.retries = retries;
+        self
+    }
+}
+
+impl ContainerConfig {
+    /// The config to create a container like the inspected one, to clone
+    /// it with some changes.
+    ///
+    /// What the daemon chose for the container is left out, so that the new
+    /// container gets its own: the hostname when it is the short ID of the
+    /// container, and the IDs and addresses of its network endpoints
+    /// unless they were static. The host settings are all kept, fails with
+    /// a `JsonDeserializationError` if they cannot be read.
+    ///
+    /// ```rust,ignore
+    /// let details = client.inspect_container("web")?;
+    /// let config = ContainerConfig::from_inspect(&details)?.env("MODE", "canary");
+    /// client.create_container("web-canary", config)?;
+    /// ```
+    pub fn from_inspect(
+        details: &ContainerDetails,
+    ) -> Result<ContainerConfig, DockerApiError> {
+        let mut config = details.config.clone();
+        let is_own_id =
+            |name: &str| !name.is_empty() && details.id.matches(name);
+        if is_own_id(&config.hostname) {
+            config.hostname = String::new();
+        }
+
+        config.host_config = match details.host_config {
+            serde_json::Value::Null => None,
+            ref host_config => {
+                match serde_json::from_value(host_config.clone()) {
+                    Ok(host_config) => Some(host_config),
+                    Err(err) => {
+                        return Err(DockerApiError::JsonDeserializationError(
+                            err,
+                        ))
+                    }
+                }
+            }
+        };
+
+        let endpoints: HashMap<String, EndpointSettings> = details
+            .network_settings
+            .networks
+            .iter()
+            .map(|(network, endpoint)| {
+                let aliases = endpoint.aliases.as_ref().map(|aliases| {
+                    aliases
+                        .iter()
+                        .filter(|alias| !is_own_id(alias))
+                        .cloned()
+                        .collect()
+                });
+                let settings = EndpointSettings {
+                    ipam_config: endpoint.ipam_config.clone(),
+                    links: endpoint.links.clone(),
+                    aliases,
+                    ..Default::default()
+                };
+                (network.clone(), settings)
+            })
+            .collect();
+        config.networking_config = if endpoints.is_empty() {
+            None
+        } else {
+            Some(NetworkingConfig {
+                endpoints_config: endpoints,
+            })
+        };
+
+        Ok(config)
+    }
+
+    /// Sets the environment variable `key` to `value`, replacing an earlier
+    /// value of the same variable. The value is passed to the container as
+    /// is, it needs no quoting.
+    pub fn env(mut self, key: &str, value: &str) -> ContainerConfig {
+        let prefix = format!("{}=", key);
+        self.env
+            .retain(|var| !var.starts_with(&prefix) && var.as_str() != key);
+        self.env.push(format!("{}{}", prefix, value));
+        self
+    }
+
+    /// Sets all the environment variables of the map, see `env`. The
+    /// variables are added sorted by name so that the config is the same
+    /// for the same map.
+    pub fn env_map(mut self, vars: HashMap<String, String>) -> ContainerConfig {
+        let mut vars: Vec<(String, String)> = vars.into_iter().collect();
+        vars.sort();
+        for (key, value) in vars {
+            self = self.env(&key, &value);
+        }
+        self
+    }
+
+    /// Runs the container as the user, a name or a UID, and the group if
+    /// given, like `--user`.
+    pub fn user(mut self, user: &str, group: Option<&str>) -> ContainerConfig {
+        self.user = match group {
+            Some(group) => format!("{}:{}", user, group),
+            None => user.to_string(),
+        };
+        self
+    }
+
+    /// Runs the container as the UID and GID, which do not need to exist
+    /// in the image.
+    pub fn user_id(mut self, uid: u32, gid: u32) -> ContainerConfig {
+        self.user = format!("{}:{}", uid, gid);
+        self
+    }
+
+    /// Connects the container to the network when it is created, with the
+    /// aliases and the addresses of `endpoint`, so that it is reachable on
+    /// the network as soon as it starts. The first network is also used as
+    /// the network mode of the container if none is set.
+    ///
+    /// ```rust,ignore
+    /// let config = ContainerConfig { image: "redis".to_string(), ..Default::default() }
+    ///     .network("backend", EndpointSettings::default().alias("cache"));
+    /// ```
+    pub fn network(
+        mut self,
+        name: &str,
+        endpoint: EndpointSettings,
+    ) -> ContainerConfig {
+        let host_config = self.host_config.get_or_insert_with(Default::default);
+        if host_config.network_mode.is_empty() {
+            host_config.network_mode = name.to_string();
+        }
+        self.networking_config
+            .get_or_insert_with(Default::default)
+            .endpoints_config
+            .insert(name.to_string(), endpoint);
+        self
+    }
+
+    /// Exposes the TCP port of the container and publishes it on
+    /// `host_port` of all the host addresses, like `-p`. An empty
+    /// `host_port` lets the daemon pick a free one.
+    pub fn publish(mut self, port: u16, host_port: &str) -> ContainerConfig {
+        let key = format!("{}/tcp", port);
+        self.exposed_ports.insert(key.clone(), json!({}));
+        self.host_config
+            .get_or_insert_with(Default::default)
+            .port_bindings
+            .entry(key)
+            .or_default()
+            .push(PortBinding {
+                host_ip: String::new(),
+                host_port: host_port.to_string(),
+            });
+        self
+    }
+
+    /// Signal sent to stop the container, SIGTERM by default.
+    pub fn stop_signal(mut self, signal: Signal) -> ContainerConfig {
+        self.stop_signal = Some(signal);
+        self
+    }
+
+    /// Time given to the container to exit after the stop signal before it
+    /// is killed, rounded up to the second.
+    pub fn stop_timeout(mut self, timeout: Duration) -> ContainerConfig {
+        self.stop_timeout = Some(duration_to_secs(timeout));
+        self
+    }
+
+    /// The fields set in the config which need a newer API than
+    /// `version`, named like in the JSON of the request, with the API
+    /// version they need.
+    pub fn unsupported_fields(
+        &self,
+        version: ApiVersion,
+    ) -> Vec<(&'static str, ApiVersion)> {
+        let host = self.host_config.as_ref();
+        let networks = self
+            .networking_config
+            .as_ref()
+            .map_or(0, |config| config.endpoints_config.len());
+        let fields = [
+            (
+                "HostConfig.Init",
+                ApiFeature::Init,
+                host.is_some_and(|host| host.init.is_some()),
+            ),
+            (
+                "StopTimeout",
+                ApiFeature::StopTimeout,
+                self.stop_timeout.is_some(),
+            ),
+            (
+                "Healthcheck.StartPeriod",
+                ApiFeature::HealthStartPeriod,
+                self.healthcheck
+                    .as_ref()
+                    .is_some_and(|health| health.start_period != 0),
+            ),
+            (
+                "HostConfig.DeviceRequests",
+                ApiFeature::DeviceRequests,
+                host.is_some_and(|host| !host.device_requests.is_empty()),
+            ),
+            (
+                "NetworkingConfig.EndpointsConfig",
+                ApiFeature::MultipleNetworksOnCreate,
+                networks > 1,
+            ),
+        ];
+
+        fields
+            .iter()
+            .filter(|&&(_, feature, set)| {
+                set && version < feature.min_api_version()
+            })
+            .map(|&(field, feature, _)| (field, feature.min_api_version()))
+            .collect()
+    }
+
+    /// Fails with an `UnsupportedApiVersion` error naming the first field
+    /// which needs a newer API than `version`.
+    pub fn check_api_version(
+        &self,
+        version: ApiVersion,
+    ) -> Result<(), DockerApiError> {
+        match self.unsupported_fields(version).first() {
+            Some(&(field, required)) => Err(
+                DockerApiError::UnsupportedApiVersion(field, required, version),
+            ),
+            None => Ok(()),
+        }
+    }
+
+    /// Removes the fields which need a newer API than `version`, giving
+    /// back the removed ones. Only the first network is kept when several
+    /// are not supported, in the order of their names.
+    pub fn strip_unsupported(
+        &mut self,
+        version: ApiVersion,
+    ) -> Vec<&'static str> {
+        let fields: Vec<&'static str> = self
+            .unsupported_fields(version)
+            .into_iter()
+            .map(|(field, _)| field)
+            .collect();
+
+        for field in &fields {
+            match *field {
+                "HostConfig.Init" => {
+                    if let Some(ref mut host) = self.host_config {
+                        host.init = None;
+                    }
+                }
+                "StopTimeout" => self.stop_timeout = None,
+                "Healthcheck.StartPeriod" => {
+                    if let Some(ref mut health) = self.healthcheck {
+                        health.start_period = 0;
+                    }
+                }
+                "HostConfig.DeviceRequests" => {
+                    if let Some(ref mut host) = self.host_config {
+                        host.device_requests.clear();
+                    }
+                }
+                "NetworkingConfig.EndpointsConfig" => {
+                    if let Some(ref mut config) = self.networking_config {
+                        let first =
+                            config.endpoints_config.keys().min().cloned();
+                        config
+                            .endpoints_config
+                            .retain(|name, _| Some(name) == first.as_ref());
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        fields
+    }
+
+    /// The environment variables of the config as a map, for example
+    /// from the `Config` of an inspected container.
+    pub fn env_to_map(&self) -> HashMap<String, String> {
+        parse_env(&self.env)
+    }
+
+    /// The fields set in this config which the inspected container does
+    /// not match, named like in the JSON of the request. The fields left
+    /// empty are not compared, the environment variables, labels and
+    /// exposed ports only need to be in the container, which also has the
+    /// ones of its image. The entrypoint is not compared.
+    pub fn drift_from(&self, details: &ContainerDetails) -> Vec<String> {
+        let actual = &details.config;
+        let actual_labels = actual.labels.clone().unwrap_or_default();
+        let fields = [
+            ("Image", self.image != actual.image),
+            ("Cmd", !self.cmd.is_empty() && self.cmd != actual.cmd),
+            ("Env", self.env.iter().any(|var| !actual.env.contains(var))),
+            (
+                "Labels",
+                self.labels
+                    .iter()
+                    .flatten()
+                    .any(|(key, value)| actual_labels.get(key) != Some(value)),
+            ),
+            ("User", !self.user.is_empty() && self.user != actual.user),
+            (
+                "WorkingDir",
+                !self.working_dir.is_empty()
+                    && self.working_dir != actual.working_dir,
+            ),
+            (
+                "ExposedPorts",
+                self.exposed_ports
+                    .keys()
+                    .any(|port| !actual.exposed_ports.contains_key(port)),
+            ),
+        ];
+        let mut drift: Vec<String> = fields
+            .iter()
+            .filter(|&&(_, drifted)| drifted)
+            .map(|&(field, _)| field.to_string())
+            .collect();
+
+        if let Some(ref host_config) = self.host_config {
+            let desired = json!(host_config);
+            if let Some(desired) = desired.as_object() {
+                for (key, value) in desired {
+                    let matches = details
+                        .host_config
+                        .get(key)
+                        .is_some_and(|actual| json_includes(actual, value));
+                    if !is_empty_json(value) && !matches {
+                        drift.push(format!("HostConfig.{}", key));
+                    }
+                }
+            }
+        }
+
+        drift
+    }
+}
+
+fn is_empty_json(value: &serde_json::Value) -> bool {
+    match *value {
+        serde_json::Value::Null | serde_json::Value::Bool(false) => true,
+        serde_json::Value::String(ref s) => s.is_empty(),
+        serde_json::Value::Array(ref items) => items.is_empty(),
+        serde_json::Value::Object(ref map) => map.is_empty(),
+        serde_json::Value::Number(_) | serde_json::Value::Bool(true) => false,
+    }
+}
+
+/// Whether `actual` has the values set in `desired`, recursively for the
+/// objects and the arrays of the same length.
+fn json_includes(
+    actual: &serde_json::Value,
+    desired: &serde_json::Value,
+) -> bool {
+    match (actual, desired) {
+        (
+            serde_json::Value::Object(actual),
+            serde_json::Value::Object(desired),
+        ) => desired.iter().all(|(key, value)| {
+            is_empty_json(value)
+                || actual
+                    .get(key)
+                    .is_some_and(|actual| json_includes(actual, value))
+        }),
+        (
+            serde_json::Value::Array(actual),
+            serde_json::Value::Array(desired),
+        ) => {
+            actual.len() == desired.len()
+                && actual
+                    .iter()
+                    .zip(desired)
+                    .all(|(actual, desired)| json_includes(actual, desired))
+        }
+        (actual, desired) => actual == desired,
+    }
+}
+
+/// Parses `KEY=VALUE` environment entries into a map. The name ends at the
+/// first `=`, so values may contain `=` themselves. Entries without a `=`
+/// are set to an empty value, later entries win over earlier ones.
+pub fn parse_env(env: &[String]) -> HashMap<String, String> {
+    env.iter()
+        .map(|var| match var.split_once('=') {
+            Some((key, value)) => (key.to_string(), value.to_string()),
+            None => (var.to_string(), String::new()),
+        })
+        .collect()
+}
+
+/// Checks a container name the way the daemon does, giving it back without
+/// the leading `/` the daemon puts in front of the names. A name starts
+/// with a letter or a digit followed by at least one letter, digit, `_`,
+/// `.` or `-`.
+///
+/// ```rust
+/// extern crate rust_docker;
+///
+/// use rust_docker::api::containers::normalize_container_name;
+///
+/// assert_eq!(normalize_container_name("/web-1").unwrap(), "web-1");
+/// assert!(normalize_container_name("web 1").is_err());
+/// ```
+pub fn normalize_container_name(name: &str) -> Result<&str, DockerApiError> {
+    let normalized = name.strip_prefix('/').unwrap_or(name);
+    let valid = normalized.len() >= 2
+        && normalized.bytes().enumerate().all(|(idx, b)| {
+            b.is_ascii_alphanumeric()
+                || (idx > 0 && (b == b'_' || b == b'.' || b == b'-'))
+        });
+    if !valid {
+        return Err(DockerApiError::ContainerError(format!(
+            "Invalid container name {}, only [a-zA-Z0-9][a-zA-Z0-9_.-] are allowed",
+            name
+        )));
+    }
+
+    Ok(normalized)
+}
+
+/// Replaces the `{i}` placeholders of `text` by `index`, and the
+/// `{i+N}` ones by `index + N`, like `web-{i}` or `{i+8080}` for a port.
+/// Anything else between braces is kept as is.
+pub fn expand_index(text: &str, index: usize) -> String {
+    let mut expanded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{i") {
+        expanded.push_str(&rest[..start]);
+        let placeholder = &rest[start..];
+        let value = placeholder.find('}').and_then(|end| {
+            let offset = &placeholder[2..end];
+            if offset.is_empty() {
+                Some((index, end))
+            } else if let Some(offset) = offset.strip_prefix('+') {
+                offset.parse::<usize>().ok().map(|n| (index + n, end))
+            } else {
+                None
+            }
+        });
+        match value {
+            Some((value, end)) => {
+                expanded.push_str(&value.to_string());
+                rest = &placeholder[end + 1..];
+            }
+            None => {
+                expanded.push_str("{i");
+                rest = &placeholder[2..];
+            }
+        }
+    }
+    expanded.push_str(rest);
+    expanded
+}
+
+/// A container created several times, with `{i}` placeholders in its name,
+/// environment and published host ports replaced by the index of each
+/// container, see `expand_index`.
+///
+/// ```rust,ignore
+/// let config = ContainerConfig { image: "nginx".to_string(), ..Default::default() }
+///     .env("REPLICA", "{i}")
+///     .publish(80, "{i+8080}");
+/// let ids = client.create_containers_from_template(&ContainerTemplate::new("web-{i}", config), 3)?;
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ContainerTemplate {
+    pub name: String,
+    pub config: ContainerConfig,
+}
+
+impl ContainerTemplate {
+    pub fn new(name: &str, config: ContainerConfig) -> ContainerTemplate {
+        ContainerTemplate {
+            name: name.to_string(),
+            config,
+        }
+    }
+
+    /// The name and the config of the container with the index.
+    pub fn expand(&self, index: usize) -> (String, ContainerConfig) {
+        let mut config = self.config.clone();
+        for var in config.env.iter_mut() {
+            *var = expand_index(var, index);
+        }
+        if let Some(ref mut host_config) = config.host_config {
+            for bindings in host_config.port_bindings.values_mut() {
+                for binding in bindings.iter_mut() {
+                    binding.host_port = expand_index(&binding.host_port, index);
+                }
+            }
+        }
+        (expand_index(&self.name, index), config)
+    }
+}
+
+/// What `ensure_container` had to do for the container to match.
+///
+/// * Recreated: The container was removed and created again because the
+///   fields differed from the desired config.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EnsureAction {
+    Created,
+    Recreated(Vec<String>),
+    Started,
+    Unchanged,
+}
+
+#[derive(Debug, Clone)]
+pub struct EnsuredContainer {
+    pub id: ContainerId,
+    pub action: EnsureAction,
+}
+
+/// Whether `ensure_started` or `ensure_stopped` had to start or stop the
+/// container, Unchanged if it already was in that state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StateChange {
+    Changed,
+    Unchanged,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
 pub struct CreateContainerResponse {
-    pub Id: String,
+    pub id: ContainerId,
+
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub warnings: Vec<String>,
+
+    /// The `EXPOSED_HEADERS` of the response, they are not part of the
+    /// JSON body.
+    #[serde(skip)]
+    pub headers: HashMap<String, String>,
+
+    /// The resources the container asked for which the daemon did not
+    /// have left, with `ResourceCheck::Warn`.
+    #[serde(skip)]
+    pub resource_warnings: Vec<ResourceShortfall>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "PascalCase")]
+#[serde(default)]
 pub struct ContainerState {
-    pub Status: String,
-    pub Running: bool,
-    pub Paused: bool,
-    pub Restarting: bool,
-    pub OOMKilled: bool,
-    pub Dead: bool,
-    pub Pid: u64,
-    pub ExitCode: u64,
-    pub Error: String,
-    pub StartedAt: String,
-    pub FinishedAt: String,
+    pub status: String,
+    pub running: bool,
+    pub paused: bool,
+    pub restarting: bool,
+    #[serde(rename = "OOMKilled")]
+    pub oom_killed: bool,
+    pub dead: bool,
+    pub pid: u64,
+    pub exit_code: u64,
+    pub error: String,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_timestamp",
+        serialize_with = "serialize_rfc3339_timestamp"
+    )]
+    pub started_at: Option<SystemTime>,
+
+    #[serde(
+        default,
+        deserialize_with = "deserialize_timestamp",
+        serialize_with = "serialize_rfc3339_timestamp"
+    )]
+    pub finished_at: Option<SystemTime>,
+
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    pub health: Option<HealthState>,
+}
+
+/// Health of a container with a healthcheck.
+///
+/// * status: `starting`, `healthy` or `unhealthy`.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct HealthState {
+    pub status: String,
+
+    #[serde(default)]
+    pub failing_streak: u64,
 }
 
 /// * To use HostConfig use serde_json
 #[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "PascalCase")]
+#[serde(default)]
 pub struct ContainerDetails {
-    pub Id: String,
-    pub Created: String,
-    pub Path: String,
-    pub Platform: Option<String>,
-    pub Args: Vec<String>,
-    pub State: ContainerState,
-    pub Image: String,
-    pub ResolvConfPath: String,
-    pub Name: String,
-    pub HostnamePath: String,
-    pub HostsPath: String,
-    pub LogPath: String,
-    pub RestartCount: u64,
-    pub Driver: String,
-    pub MountLabel: String,
-    pub ProcessLabel: String,
-    pub AppArmorProfile: String,
-    pub ExecIDs: Option<String>,
-    pub HostConfig: serde_json::Value,
-    pub Config: ContainerConfig,
+    pub id: ContainerId,
+
+    #[serde(
+        default,
+        deserialize_with = "deserialize_timestamp",
+        serialize_with = "serialize_rfc3339_timestamp"
+    )]
+    pub created: Option<SystemTime>,
+
+    pub path: String,
+    pub platform: Option<String>,
+
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub args: Vec<String>,
+
+    pub state: ContainerState,
+    pub image: ImageId,
+    pub resolv_conf_path: String,
+    pub name: String,
+    pub hostname_path: String,
+    pub hosts_path: String,
+    pub log_path: String,
+    pub restart_count: u64,
+    pub driver: String,
+    pub mount_label: String,
+    pub process_label: String,
+    pub app_armor_profile: String,
+
+    #[serde(
+        rename = "ExecIDs",
+        default,
+        deserialize_with = "deserialize_null_default"
+    )]
+    pub exec_ids: Vec<String>,
+
+    pub host_config: serde_json::Value,
+    pub config: ContainerConfig,
+
+    #[serde(default)]
+    pub network_settings: ContainerNetworkSettings,
+
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub mounts: Vec<Mounts>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Default)]
+/// A file changed in the filesystem of a container since it was created.
+///
+/// * kind: 0 for a modified file, 1 for an added one and 2 for a deleted
+///   one.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(rename_all = "PascalCase")]
 pub struct ContainerFsChange {
-    Path: String,
-    Kind: u8,
+    pub path: String,
+    pub kind: u8,
+}
+
+impl ContainerFsChange {
+    /// The letter `docker diff` shows the change with, `C` for a modified
+    /// file, `A` for an added one and `D` for a deleted one.
+    pub fn kind_symbol(&self) -> char {
+        match self.kind {
+            0 => 'C',
+            1 => 'A',
+            2 => 'D',
+            _ => '?',
+        }
+    }
+}
+
+impl fmt::Display for ContainerFsChange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {}", self.kind_symbol(), self.path)
+    }
+}
+
+/// A container force-removed with its anonymous volumes when dropped, also
+/// when the thread panics, from `create_container_owned`. Removing it is
+/// best-effort, the errors are ignored.
+pub struct OwnedContainer<'a, C: 'a + Containers> {
+    client: &'a C,
+    id: ContainerId,
+}
+
+impl<'a, C: Containers> OwnedContainer<'a, C> {
+    pub fn id(&self) -> &ContainerId {
+        &self.id
+    }
+
+    /// Keeps the container, giving its ID back.
+    pub fn keep(self) -> ContainerId {
+        let mut owned = ManuallyDrop::new(self);
+        mem::take(&mut owned.id)
+    }
+}
+
+impl<'a, C: Containers> Drop for OwnedContainer<'a, C> {
+    fn drop(&mut self) {
+        let _ = self.client.remove_container(&self.id, true, true);
+    }
 }
 
 pub trait Containers: DockerApiClient {
@@ -143,7 +1796,8 @@ pub trait Containers: DockerApiClient {
                     if resp.status_code == 200 {
                         resp.body
                     } else {
-                        return Err(DockerApiError::InvalidApiResponseError(
+                        return Err(DockerApiError::from_response(
+                            ApiObject::Container,
                             resp.status_code,
                             resp.body,
                         ));
@@ -152,13 +1806,8 @@ pub trait Containers: DockerApiClient {
                 Err(err) => return Err(err),
             };
 
-        let containers: Vec<Container> = match serde_json::from_str(&json_resp)
-        {
-            Ok(info) => info,
-            Err(err) => {
-                return Err(DockerApiError::JsonDeserializationError(err))
-            }
-        };
+        let containers: Vec<Container> =
+            deserialize_body(&json_resp, self.is_strict())?;
 
         Ok(containers)
     }
@@ -168,7 +1817,7 @@ pub trait Containers: DockerApiClient {
     ///
     /// # Example
     ///
-    /// ```rust
+    /// ```rust,no_run
     /// extern crate rust_docker;
     ///
     /// use rust_docker::api::containers::Containers;
@@ -215,7 +1864,40 @@ pub trait Containers: DockerApiClient {
             None => "?all=true&size=true".to_string(),
         };
 
-        self.get_containers(api_endpoint, method, &query_params)
+        self.get_containers(api_endpoint, method, &query_params)
+    }
+
+    /// Lists the containers like `list_all_containers`, or only the running
+    /// ones unless `all`, giving them one by one as they are read from the
+    /// response. On hosts with many containers this avoids holding the
+    /// whole list, `size` adds the sizes which are slow to compute.
+    ///
+    /// ```rust,ignore
+    /// for container in client.iter_containers(true, false)? {
+    ///     println!("{}", container?.id);
+    /// }
+    /// ```
+    fn iter_containers(
+        &self,
+        all: bool,
+        size: bool,
+    ) -> Result<JsonArrayStream<Container>, DockerApiError> {
+        let api_endpoint =
+            format!("/containers/json?all={}&size={}", all, size);
+        let method = "GET";
+
+        let resp = self.get_stream_from_api(&api_endpoint, method, "", &[])?;
+
+        if resp.status_code != 200 {
+            let status_code = resp.status_code;
+            return Err(DockerApiError::from_response(
+                ApiObject::Container,
+                status_code,
+                resp.body_to_string()?,
+            ));
+        }
+
+        Ok(JsonArrayStream::new(resp, self.is_strict()))
     }
 
     /// List container with the filter provided, the filter can be looked from
@@ -230,10 +1912,9 @@ pub trait Containers: DockerApiClient {
         let method = "GET";
 
         let query_params = match limit {
-            Some(limit) => format!(
-                "?all=true&size=true&limit={}&filter={}",
-                limit, filter
-            ),
+            Some(limit) => {
+                format!("?all=true&size=true&limit={}&filter={}", limit, filter)
+            }
             None => format!("?all=true&size=true&filter={}", filter),
         };
 
@@ -249,24 +1930,350 @@ pub trait Containers: DockerApiClient {
         &self,
         name: &str,
         config: ContainerConfig,
-    ) -> Result<CreateContainerResponse, DockerApiError> {
-        let api_endpoint = format!("/containers/create?name={}", name);
+    ) -> Result<CreateContainerResponse, DockerApiError>
+    where
+        Self: Sized,
+    {
+        self.create_container_for_platform(name, config, None)
+    }
+
+    /// Same as `create_container`, the container being removed when the
+    /// returned guard is dropped, see `OwnedContainer`.
+    fn create_container_owned(
+        &self,
+        name: &str,
+        config: ContainerConfig,
+    ) -> Result<OwnedContainer<'_, Self>, DockerApiError>
+    where
+        Self: Sized,
+    {
+        let id = self.create_container(name, config)?.id;
+        Ok(OwnedContainer { client: self, id })
+    }
+
+    /// Creates `count` containers from the template, with the indexes 0 to
+    /// `count - 1`, giving their IDs in order. If one of them can not be
+    /// created, the ones created before it are removed and the error is
+    /// given back.
+    fn create_containers_from_template(
+        &self,
+        template: &ContainerTemplate,
+        count: usize,
+    ) -> Result<Vec<ContainerId>, DockerApiError>
+    where
+        Self: Sized,
+    {
+        let mut ids: Vec<ContainerId> = Vec::with_capacity(count);
+        for index in 0..count {
+            let (name, config) = template.expand(index);
+            match self.create_container(&name, config) {
+                Ok(resp) => ids.push(resp.id),
+                Err(err) => {
+                    for id in &ids {
+                        let _ = self.remove_container(id, true, true);
+                    }
+                    return Err(err);
+                }
+            }
+        }
+        Ok(ids)
+    }
+
+    /// Makes the container `name` run with the `desired` config. It is
+    /// created if it does not exist and replaced by a new one if its
+    /// config differs from the desired one, see
+    /// `ContainerConfig::drift_from`. A matching container is only started
+    /// if it is not running, so that calling it again does nothing.
+    ///
+    /// Like in `recreate_container` the old container is stopped and
+    /// renamed while the new one is created and started, it is put back if
+    /// this fails and removed otherwise.
+    ///
+    /// ```rust,ignore
+    /// let desired = ContainerConfig { image: "redis:7".to_string(), ..Default::default() }
+    ///     .publish(6379, "6379");
+    /// let ensured = client.ensure_container("cache", desired)?;
+    /// println!("{} : {:?}", ensured.id, ensured.action);
+    /// ```
+    fn ensure_container(
+        &self,
+        name: &str,
+        desired: ContainerConfig,
+    ) -> Result<EnsuredContainer, DockerApiError>
+    where
+        Self: Sized,
+    {
+        let details = match self.inspect_container(name) {
+            Ok(details) => details,
+            Err(DockerApiError::NoSuchContainer(_)) => {
+                let id = self.create_container(name, desired)?.id;
+                self.start_container(&id)?;
+                return Ok(EnsuredContainer {
+                    id,
+                    action: EnsureAction::Created,
+                });
+            }
+            Err(err) => return Err(err),
+        };
+
+        let drift = desired.drift_from(&details);
+        if !drift.is_empty() {
+            let old_name = format!("{}_old_{}", name, details.id.short());
+            self.ensure_stopped(&details.id, None)?;
+            self.rename_container(&details.id, &old_name)?;
+
+            let created = self.create_container(name, desired).and_then(
+                |resp| match self.start_container(&resp.id) {
+                    Ok(_) => Ok(resp.id),
+                    Err(err) => {
+                        let _ = self.remove_container(&resp.id, true, false);
+                        Err(err)
+                    }
+                },
+            );
+            let id = match created {
+                Ok(id) => id,
+                Err(err) => {
+                    let _ = self.rename_container(&details.id, name);
+                    if details.state.running {
+                        let _ = self.start_container(&details.id);
+                    }
+                    return Err(err);
+                }
+            };
+            self.remove_container(&details.id, true, false)?;
+            return Ok(EnsuredContainer {
+                id,
+                action: EnsureAction::Recreated(drift),
+            });
+        }
+
+        let action = if details.state.running {
+            EnsureAction::Unchanged
+        } else {
+            self.start_container(&details.id)?;
+            EnsureAction::Started
+        };
+        Ok(EnsuredContainer {
+            id: details.id,
+            action,
+        })
+    }
+
+    /// Replaces the container by a new one with the same name and config,
+    /// changed by `overrides`, like to run a newer image. The new container
+    /// keeps the volumes of the old one, its anonymous volumes included,
+    /// and is started if the old one was running. Gives the ID of the new
+    /// container.
+    ///
+    /// The old container is stopped and renamed while the new one is
+    /// created, it is put back if the creation fails and removed otherwise.
+    ///
+    /// ```rust,ignore
+    /// let id = client.recreate_container("web", |config| ContainerConfig {
+    ///     image: "nginx:1.26".to_string(),
+    ///     ..config
+    /// })?;
+    /// ```
+    fn recreate_container<F>(
+        &self,
+        id: impl ContainerRef,
+        overrides: F,
+    ) -> Result<ContainerId, DockerApiError>
+    where
+        Self: Sized,
+        F: FnOnce(ContainerConfig) -> ContainerConfig,
+    {
+        let id = id.container_ref();
+        let details = self.inspect_container(id)?;
+        let mut config = overrides(ContainerConfig::from_inspect(&details)?);
+
+        let host_config =
+            config.host_config.get_or_insert_with(Default::default);
+        for mount in &details.mounts {
+            let volume = match mount.name {
+                Some(ref name) if !name.is_empty() => name,
+                _ => continue,
+            };
+            let mounted =
+                host_config.binds.iter().any(|bind| {
+                    bind.split(':').nth(1) == Some(&mount.destination)
+                }) || host_config
+                    .mounts
+                    .iter()
+                    .flatten()
+                    .any(|spec| spec.target() == mount.destination);
+            if !mounted {
+                host_config
+                    .binds
+                    .push(format!("{}:{}", volume, mount.destination));
+            }
+        }
+
+        let name = details.name.trim_start_matches('/');
+        let old_name = format!("{}_old_{}", name, details.id.short());
+        self.ensure_stopped(&details.id, None)?;
+        self.rename_container(&details.id, &old_name)?;
+
+        let new_id = match self.create_container(name, config) {
+            Ok(resp) => resp.id,
+            Err(err) => {
+                let _ = self.rename_container(&details.id, name);
+                if details.state.running {
+                    let _ = self.start_container(&details.id);
+                }
+                return Err(err);
+            }
+        };
+        self.remove_container(&details.id, false, false)?;
+        if details.state.running {
+            self.start_container(&new_id)?;
+        }
+
+        Ok(new_id)
+    }
+
+    /// Compares the memory and the CPUs the config asks for with what the
+    /// daemon has, from `/info`, minus what its running containers already
+    /// reserved, giving the resources it does not have enough of. Nothing
+    /// is checked for a container without limits.
+    fn check_resources(
+        &self,
+        config: &ContainerConfig,
+    ) -> Result<Vec<ResourceShortfall>, DockerApiError>
+    where
+        Self: Sized,
+    {
+        let (memory, nano_cpus) = match config.host_config {
+            Some(ref host) => (
+                host.memory.unwrap_or(0).max(0),
+                host.nano_cpus.unwrap_or(0).max(0),
+            ),
+            None => (0, 0),
+        };
+        if memory == 0 && nano_cpus == 0 {
+            return Ok(Vec::new());
+        }
+
+        let resp = self.get_response_from_api("/info", "GET", "")?;
+        if resp.status_code != 200 {
+            return Err(DockerApiError::InvalidApiResponseError(
+                resp.status_code,
+                resp.body,
+            ));
+        }
+        let info: SystemInfo = deserialize_body(&resp.body, self.is_strict())?;
+
+        let (mut reserved_memory, mut reserved_cpus) = (0, 0);
+        for container in self.get_containers("/containers/json", "GET", "")? {
+            let details = self.inspect_container(&container.id)?;
+            let host = &details.host_config;
+            reserved_memory += host["Memory"].as_i64().unwrap_or(0).max(0);
+            reserved_cpus += host["NanoCpus"].as_i64().unwrap_or(0).max(0);
+        }
+
+        let mut shortfalls = Vec::new();
+        let total_memory = info.mem_total as i64;
+        if memory > 0 && reserved_memory + memory > total_memory {
+            shortfalls.push(ResourceShortfall {
+                resource: Resource::Memory,
+                requested: memory,
+                reserved: reserved_memory,
+                total: total_memory,
+            });
+        }
+        let total_cpus = info.ncpu as i64 * 1_000_000_000;
+        if nano_cpus > 0 && reserved_cpus + nano_cpus > total_cpus {
+            shortfalls.push(ResourceShortfall {
+                resource: Resource::Cpus,
+                requested: nano_cpus,
+                reserved: reserved_cpus,
+                total: total_cpus,
+            });
+        }
+        Ok(shortfalls)
+    }
+
+    /// Same as `create_container`, with the variant of a multi-arch image
+    /// for the platform when given. The image has to be pulled for that
+    /// platform beforehand.
+    fn create_container_for_platform(
+        &self,
+        name: &str,
+        config: ContainerConfig,
+        platform: Option<&Platform>,
+    ) -> Result<CreateContainerResponse, DockerApiError>
+    where
+        Self: Sized,
+    {
+        let mut config = config;
+        if let Some((key, value)) = self.owner_label() {
+            config
+                .labels
+                .get_or_insert_with(Default::default)
+                .insert(key.to_string(), value.to_string());
+        }
+        if let Some(version) = self.api_version() {
+            config.check_api_version(version)?;
+            let required = ApiFeature::CreatePlatform.min_api_version();
+            if platform.is_some() && version < required {
+                return Err(DockerApiError::UnsupportedApiVersion(
+                    "platform", required, version,
+                ));
+            }
+        }
+
+        // Without a name the daemon picks one.
+        let name = if name.is_empty() {
+            name
+        } else {
+            normalize_container_name(name)?
+        };
+
+        let resource_warnings = match self.resource_check() {
+            ResourceCheck::Off => Vec::new(),
+            check => {
+                let shortfalls = self.check_resources(&config)?;
+                if check == ResourceCheck::Deny && !shortfalls.is_empty() {
+                    return Err(DockerApiError::InsufficientResources(
+                        shortfalls,
+                    ));
+                }
+                shortfalls
+            }
+        };
+
+        let mut api_endpoint = format!("/containers/create?name={}", name);
+        if let Some(platform) = platform {
+            api_endpoint.push_str(&format!(
+                "&platform={}",
+                encode_query_value(&platform.to_string())
+            ));
+        }
         let method = "POST";
         let body = match serde_json::to_string(&config) {
             Ok(body) => body,
-            Err(err) => return Err(DockerApiError::JsonSerializationError(err)),
+            Err(err) => {
+                return Err(DockerApiError::JsonSerializationError(err))
+            }
         };
 
         let resp = self.get_response_from_api(&api_endpoint, method, &body)?;
 
         if resp.status_code != 201 {
-            return Err(DockerApiError::InvalidApiResponseError(
+            return Err(DockerApiError::from_response(
+                ApiObject::Image,
                 resp.status_code,
                 resp.body,
             ));
         }
-        match serde_json::from_str(&resp.body) {
-            Ok(info) => Ok(info),
+
+        match serde_json::from_str::<CreateContainerResponse>(&resp.body) {
+            Ok(mut info) => {
+                info.headers = resp.exposed_headers();
+                info.resource_warnings = resource_warnings;
+                Ok(info)
+            }
             Err(err) => Err(DockerApiError::JsonDeserializationError(err)),
         }
     }
@@ -274,12 +2281,12 @@ pub trait Containers: DockerApiClient {
     /// Creates/Spawn docker container from the configuration provided. It only
     ///
     /// * Rust does not provide named arguments, so we are doing it this way
-    /// Currently rust structures does not have default values, so all the
-    /// values for the structure needs to be specified.
+    ///   Currently rust structures does not have default values, so all the
+    ///   values for the structure needs to be specified.
     ///
     /// # Example
     ///
-    /// ```rust
+    /// ```rust,no_run
     /// extern crate rust_docker;
     ///
     /// use rust_docker::api::containers::Containers;
@@ -306,10 +2313,13 @@ pub trait Containers: DockerApiClient {
         name: &str,
         image: &str,
         cmd: Vec<String>,
-    ) -> Result<CreateContainerResponse, DockerApiError> {
+    ) -> Result<CreateContainerResponse, DockerApiError>
+    where
+        Self: Sized,
+    {
         let config = ContainerConfig {
-            Image: image.to_string(),
-            Cmd: cmd,
+            image: image.to_string(),
+            cmd,
             ..Default::default()
         };
 
@@ -321,7 +2331,7 @@ pub trait Containers: DockerApiClient {
     ///
     /// # Example
     ///
-    /// ```rust
+    /// ```rust,no_run
     /// extern crate rust_docker;
     ///
     /// use rust_docker::api::containers::Containers;
@@ -343,32 +2353,38 @@ pub trait Containers: DockerApiClient {
     /// ```
     fn inspect_container(
         &self,
-        id: &str,
-    ) -> Result<ContainerDetails, DockerApiError> {
+        id: impl ContainerRef,
+    ) -> Result<ContainerDetails, DockerApiError>
+    where
+        Self: Sized,
+    {
+        let id = id.container_ref();
         let api_endpoint = format!("/containers/{id}/json", id = id);
         let method = "GET";
 
         let resp = self.get_response_from_api(&api_endpoint, method, "")?;
 
         if resp.status_code != 200 {
-            return Err(DockerApiError::InvalidApiResponseError(
+            return Err(DockerApiError::from_response(
+                ApiObject::Container,
                 resp.status_code,
                 resp.body,
             ));
         }
 
-        match serde_json::from_str(&resp.body) {
-            Ok(info) => Ok(info),
-            Err(err) => Err(DockerApiError::JsonDeserializationError(err)),
-        }
+        deserialize_body(&resp.body, self.is_strict())
     }
 
     /// Gives the changes done to somewhere in the filesystem in the docker container as a list of
     /// files with the kind of changes.
     fn get_container_filesystem_changes(
         &self,
-        id: &str,
-    ) -> Result<Vec<ContainerFsChange>, DockerApiError> {
+        id: impl ContainerRef,
+    ) -> Result<Vec<ContainerFsChange>, DockerApiError>
+    where
+        Self: Sized,
+    {
+        let id = id.container_ref();
         let api_endpoint = format!("/containers/{id}/changes", id = id);
         let method = "GET";
 
@@ -377,7 +2393,8 @@ pub trait Containers: DockerApiClient {
         // system so just return and empty vector. Serializing this will
         // result in error.
         if resp.status_code != 200 {
-            return Err(DockerApiError::InvalidApiResponseError(
+            return Err(DockerApiError::from_response(
+                ApiObject::Container,
                 resp.status_code,
                 resp.body,
             ));
@@ -387,10 +2404,87 @@ pub trait Containers: DockerApiClient {
             return Ok(Vec::new());
         }
 
-        match serde_json::from_str(&resp.body) {
-            Ok(info) => Ok(info),
-            Err(err) => Err(DockerApiError::JsonDeserializationError(err)),
+        deserialize_body(&resp.body, self.is_strict())
+    }
+
+    /// Gives a tar archive of the file or directory at `path` in the
+    /// container, read from the response as the daemon writes it. With the
+    /// `tar` feature `tar_util::extract_archive` extracts it.
+    ///
+    /// ```rust,ignore
+    /// let mut archive = client.get_archive("web", "/var/log/nginx")?;
+    /// io::copy(&mut archive, &mut File::create("logs.tar")?)?;
+    /// ```
+    fn get_archive(
+        &self,
+        id: impl ContainerRef,
+        path: &str,
+    ) -> Result<StreamResponse, DockerApiError>
+    where
+        Self: Sized,
+    {
+        let id = id.container_ref();
+        let api_endpoint = format!(
+            "/containers/{id}/archive?path={path}",
+            id = id,
+            path = encode_query_value(path)
+        );
+        let method = "GET";
+
+        let resp = self.get_stream_from_api(&api_endpoint, method, "", &[])?;
+
+        if resp.status_code != 200 {
+            let status_code = resp.status_code;
+            return Err(DockerApiError::from_response(
+                ApiObject::Container,
+                status_code,
+                resp.body_to_string()?,
+            ));
+        }
+
+        Ok(resp)
+    }
+
+    /// Extracts the tar archive into the directory at `path` in the
+    /// container, the archive being sent as it is read. The directory must
+    /// exist, the container can be stopped.
+    ///
+    /// ```rust,ignore
+    /// let archive = client.get_archive("web", "/etc/nginx")?;
+    /// other.put_archive("web-copy", "/etc", &mut archive)?;
+    /// ```
+    fn put_archive(
+        &self,
+        id: impl ContainerRef,
+        path: &str,
+        archive: &mut dyn Read,
+    ) -> Result<(), DockerApiError>
+    where
+        Self: Sized,
+    {
+        let id = id.container_ref();
+        let api_endpoint = format!(
+            "/containers/{id}/archive?path={path}",
+            id = id,
+            path = encode_query_value(path)
+        );
+        let method = "PUT";
+
+        let request = RequestBuilder::new(method, &api_endpoint)
+            .header("Content-Type", "application/x-tar")
+            .chunked();
+        let resp = self.send_upload(&request, archive)?;
+
+        if resp.status_code != 200 {
+            let status_code = resp.status_code;
+            return Err(DockerApiError::from_response(
+                ApiObject::Container,
+                status_code,
+                resp.body_to_string()?,
+            ));
         }
+
+        Ok(())
     }
 
     /// Function to manipulate container status
@@ -410,7 +2504,7 @@ pub trait Containers: DockerApiClient {
     ///
     /// # Example
     ///
-    /// ```rust
+    /// ```rust,no_run
     /// extern crate rust_docker;
     ///
     /// use rust_docker::api::containers::Containers;
@@ -444,73 +2538,153 @@ pub trait Containers: DockerApiClient {
         id: &str,
         params: &str,
     ) -> Result<String, DockerApiError> {
+        let query_params = if params.is_empty() || params.starts_with('?') {
+            params.to_string()
+        } else {
+            format!("?{}", params)
+        };
         let api_endpoint = format!(
-            "/containers/{id}/{action}",
+            "/containers/{id}/{action}{query}",
             id = id,
-            action = action
+            action = action,
+            query = query_params
         );
-        let method = "GET";
+        let method = "POST";
 
-        let resp = self.get_response_from_api(&api_endpoint, method, params)?;
+        let resp = self.get_response_from_api(&api_endpoint, method, "")?;
 
-        if resp.status_code == 204 {
-            Ok(format!("Container {} successful", action))
-        } else if resp.status_code == 304 {
-            Err(DockerApiError::ContainerError(format!(
-                "Container already {}ed",
-                action
-            )))
-        } else {
-            Err(DockerApiError::InvalidApiResponseError(
+        match resp.status_code {
+            204 => Ok(format!("Container {} successful", action)),
+            // The daemon gives no body with a 304.
+            304 => Err(DockerApiError::NotModified(format!(
+                "The {} of container {} changed nothing",
+                action, id
+            ))),
+            _ => Err(DockerApiError::from_response(
+                ApiObject::Container,
                 resp.status_code,
                 resp.body,
-            ))
+            )),
         }
     }
 
-    fn start_container(&self, id: &str) -> Result<String, DockerApiError> {
+    fn start_container(
+        &self,
+        id: impl ContainerRef,
+    ) -> Result<String, DockerApiError>
+    where
+        Self: Sized,
+    {
+        let id = id.container_ref();
         self.manipulate_container_status("start", id, "")
     }
 
+    /// Stops the container, killing it if it is still running after
+    /// `timeout`, which the daemon takes in whole seconds.
     fn stop_container(
         &self,
-        id: &str,
-        delay: Option<&str>,
-    ) -> Result<String, DockerApiError> {
-        let param = match delay {
-            Some(d) => format!("t={}", d),
+        id: impl ContainerRef,
+        timeout: Option<Duration>,
+    ) -> Result<String, DockerApiError>
+    where
+        Self: Sized,
+    {
+        let id = id.container_ref();
+        let param = match timeout {
+            Some(t) => format!("t={}", duration_to_secs(t)),
             None => String::new(),
         };
         self.manipulate_container_status("stop", id, &param)
     }
 
-    fn pause_container(&self, id: &str) -> Result<String, DockerApiError> {
+    /// Starts the container unless it is already running, which is not an
+    /// error.
+    fn ensure_started(
+        &self,
+        id: impl ContainerRef,
+    ) -> Result<StateChange, DockerApiError>
+    where
+        Self: Sized,
+    {
+        let id = id.container_ref();
+        match self.start_container(id) {
+            Ok(_) => Ok(StateChange::Changed),
+            Err(DockerApiError::NotModified(_)) => Ok(StateChange::Unchanged),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Stops the container unless it is already stopped, which is not an
+    /// error. See `stop_container` for the `timeout`.
+    fn ensure_stopped(
+        &self,
+        id: impl ContainerRef,
+        timeout: Option<Duration>,
+    ) -> Result<StateChange, DockerApiError>
+    where
+        Self: Sized,
+    {
+        let id = id.container_ref();
+        match self.stop_container(id, timeout) {
+            Ok(_) => Ok(StateChange::Changed),
+            Err(DockerApiError::NotModified(_)) => Ok(StateChange::Unchanged),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn pause_container(
+        &self,
+        id: impl ContainerRef,
+    ) -> Result<String, DockerApiError>
+    where
+        Self: Sized,
+    {
+        let id = id.container_ref();
         self.manipulate_container_status("pause", id, "")
     }
 
-    fn unpause_container(&self, id: &str) -> Result<String, DockerApiError> {
+    fn unpause_container(
+        &self,
+        id: impl ContainerRef,
+    ) -> Result<String, DockerApiError>
+    where
+        Self: Sized,
+    {
+        let id = id.container_ref();
         self.manipulate_container_status("unpause", id, "")
     }
 
+    /// Restarts the container, `timeout` is the time to wait for it to
+    /// stop before killing it, see `stop_container`.
     fn restart_container(
         &self,
-        id: &str,
-        delay: Option<&str>,
-    ) -> Result<String, DockerApiError> {
-        let param = match delay {
-            Some(d) => format!("t={}", d),
+        id: impl ContainerRef,
+        timeout: Option<Duration>,
+    ) -> Result<String, DockerApiError>
+    where
+        Self: Sized,
+    {
+        let id = id.container_ref();
+        let param = match timeout {
+            Some(t) => format!("t={}", duration_to_secs(t)),
             None => String::new(),
         };
         self.manipulate_container_status("restart", id, &param)
     }
 
+    /// Sends the signal to the container, SIGKILL if None. Parse the name
+    /// of a signal with `str::parse` to have it checked beforehand.
     fn kill_container(
         &self,
-        id: &str,
-        signal: Option<&str>,
-    ) -> Result<String, DockerApiError> {
+        id: impl ContainerRef,
+        signal: Option<Signal>,
+    ) -> Result<String, DockerApiError>
+    where
+        Self: Sized,
+    {
+        let id = id.container_ref();
         let param = match signal {
-            Some(sig) => format!("signal={}", sig),
+            Some(sig) => format!("signal={}", encode_query_value(sig.as_str())),
             None => String::new(),
         };
         self.manipulate_container_status("kill", id, &param)
@@ -518,10 +2692,279 @@ pub trait Containers: DockerApiClient {
 
     fn rename_container(
         &self,
-        id: &str,
+        id: impl ContainerRef,
         name: &str,
-    ) -> Result<String, DockerApiError> {
+    ) -> Result<String, DockerApiError>
+    where
+        Self: Sized,
+    {
+        let id = id.container_ref();
         let name_param = &format!("name={}", name);
         self.manipulate_container_status("rename", id, name_param)
     }
+
+    /// Removes the container, `force` kills it first if it is running and
+    /// `volumes` also removes its anonymous volumes.
+    fn remove_container(
+        &self,
+        id: impl ContainerRef,
+        force: bool,
+        volumes: bool,
+    ) -> Result<(), DockerApiError>
+    where
+        Self: Sized,
+    {
+        let id = id.container_ref();
+        let api_endpoint = format!("/containers/{id}", id = id);
+        let method = "DELETE";
+        let query_params = format!("?force={}&v={}", force, volumes);
+
+        let resp =
+            self.get_response_from_api(&api_endpoint, method, &query_params)?;
+
+        if resp.status_code != 204 {
+            return Err(DockerApiError::from_response(
+                ApiObject::Container,
+                resp.status_code,
+                resp.body,
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Lists the exited containers which finished more than `age` ago.
+    ///
+    /// The container list only gives the creation time, so the containers
+    /// created before the cutoff are inspected to get the time they exited
+    /// at.
+    fn list_exited_containers_older_than(
+        &self,
+        age: Duration,
+    ) -> Result<Vec<Container>, DockerApiError>
+    where
+        Self: Sized,
+    {
+        let api_endpoint = "/containers/json";
+        let method = "GET";
+        let filters = json!({ "status": ["exited"] }).to_string();
+        let query_params =
+            format!("?all=true&filters={}", encode_query_value(&filters));
+
+        let cutoff = SystemTime::now().checked_sub(age).unwrap_or(UNIX_EPOCH);
+
+        let mut containers = Vec::new();
+        for container in
+            self.get_containers(api_endpoint, method, &query_params)?
+        {
+            // It can not have exited before the cutoff if it was created
+            // after it.
+            if container.created.is_some_and(|created| created > cutoff) {
+                continue;
+            }
+
+            let details = match self.inspect_container(&container.id) {
+                Ok(details) => details,
+                Err(DockerApiError::NoSuchContainer(_)) => continue,
+                Err(err) => return Err(err),
+            };
+            if let Some(finished_at) = details.state.finished_at {
+                if finished_at <= cutoff {
+                    containers.push(container);
+                }
+            }
+        }
+
+        Ok(containers)
+    }
+
+    /// Removes the exited containers which finished more than `age` ago,
+    /// with their anonymous volumes. Returns the containers removed,
+    /// containers already gone when they are removed are skipped.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// extern crate rust_docker;
+    ///
+    /// use std::time::Duration;
+    ///
+    /// use rust_docker::api::containers::Containers;
+    /// use rust_docker::client::DockerClient;
+    ///
+    /// let client = match DockerClient::new("unix:///var/run/docker.sock") {
+    ///     Ok(a) => a,
+    ///     Err(err) => {
+    ///         println!("{}", err);
+    ///         std::process::exit(1);
+    ///     }
+    /// };
+    ///
+    /// let day = Duration::from_secs(24 * 60 * 60);
+    /// match client.remove_exited_containers_older_than(day) {
+    ///     Ok(removed) => println!("Removed {} containers", removed.len()),
+    ///     Err(err) => println!("An error occured : {}", err),
+    /// }
+    /// ```
+    fn remove_exited_containers_older_than(
+        &self,
+        age: Duration,
+    ) -> Result<Vec<Container>, DockerApiError>
+    where
+        Self: Sized,
+    {
+        let mut removed = Vec::new();
+        for container in self.list_exited_containers_older_than(age)? {
+            match self.remove_container(&container.id, false, true) {
+                Ok(()) => removed.push(container),
+                Err(DockerApiError::NoSuchContainer(_)) => (),
+                Err(err) => return Err(err),
+            }
+        }
+
+        Ok(removed)
+    }
+
+    /// Gives the host address the TCP port of the container is published
+    /// on, None if it is not published. See
+    /// `ContainerNetworkSettings::published_addr` for the choice of the
+    /// address.
+    ///
+    /// ```rust,ignore
+    /// if let Some(addr) = client.mapped_port("f808ca...", 80)? {
+    ///     println!("Serving on http://{}", addr);
+    /// }
+    /// ```
+    fn mapped_port(
+        &self,
+        id: impl ContainerRef,
+        port: u16,
+    ) -> Result<Option<SocketAddr>, DockerApiError>
+    where
+        Self: Sized,
+    {
+        let id = id.container_ref();
+        let details = self.inspect_container(id)?;
+        Ok(details.network_settings.published_addr(port))
+    }
+
+    /// Waits for the TCP port of the container to be published and to
+    /// accept connections, giving the address it is reachable on. Fails if
+    /// the container stops or the port is not open after `timeout`.
+    ///
+    /// ```rust,ignore
+    /// let addr = client.wait_for_port("f808ca...", 5432, Duration::from_secs(30))?;
+    /// let conn = TcpStream::connect(addr)?;
+    /// ```
+    fn wait_for_port(
+        &self,
+        id: impl ContainerRef,
+        port: u16,
+        timeout: Duration,
+    ) -> Result<SocketAddr, DockerApiError>
+    where
+        Self: Sized,
+    {
+        let id = id.container_ref();
+        let started = Instant::now();
+
+        loop {
+            let details = self.inspect_container(id)?;
+            if !details.state.running {
+                return Err(DockerApiError::ContainerError(format!(
+                    "Container {} is not running : {}",
+                    id, details.state.status
+                )));
+            }
+
+            let remaining =
+                timeout.checked_sub(started.elapsed()).unwrap_or_default();
+            if let Some(addr) = details.network_settings.published_addr(port) {
+                let attempt = remaining.min(Duration::from_secs(1));
+                if attempt > Duration::from_millis(0)
+                    && TcpStream::connect_timeout(&addr, attempt).is_ok()
+                {
+                    return Ok(addr);
+                }
+            }
+
+            if started.elapsed() >= timeout {
+                return Err(DockerApiError::ContainerError(format!(
+                    "Port {} of container {} is not open after {:?}",
+                    port, id, timeout
+                )));
+            }
+            thread::sleep(remaining.min(Duration::from_millis(100)));
+        }
+    }
+
+    /// Waits for the healthcheck of the container to pass, or only for it
+    /// to run if it has no healthcheck. Fails if the container stops, turns
+    /// unhealthy or is still starting after `timeout`.
+    fn wait_for_healthy(
+        &self,
+        id: impl ContainerRef,
+        timeout: Duration,
+    ) -> Result<(), DockerApiError>
+    where
+        Self: Sized,
+    {
+        let id = id.container_ref();
+        let started = Instant::now();
+
+        loop {
+            let details = self.inspect_container(id)?;
+            if !details.state.running && !details.state.restarting {
+                return Err(DockerApiError::ContainerError(format!(
+                    "Container {} is not running : {}",
+                    id, details.state.status
+                )));
+            }
+
+            match details.state.health {
+                Some(ref health) if health.status == "healthy" => return Ok(()),
+                Some(ref health) if health.status == "unhealthy" => {
+                    return Err(DockerApiError::ContainerError(format!(
+                        "Container {} is unhealthy",
+                        id
+                    )))
+                }
+                None if details.state.running => return Ok(()),
+                _ => (),
+            }
+
+            if started.elapsed() >= timeout {
+                return Err(DockerApiError::ContainerError(format!(
+                    "Container {} is not healthy after {:?}",
+                    id, timeout
+                )));
+            }
+            let remaining =
+                timeout.checked_sub(started.elapsed()).unwrap_or_default();
+            thread::sleep(remaining.min(Duration::from_millis(250)));
+        }
+    }
+
+    /// Removes the stopped containers matching the filters, like
+    /// `docker container prune`.
+    fn prune_containers(
+        &self,
+        filters: &PruneFilters,
+    ) -> Result<ContainersPruneReport, DockerApiError> {
+        let api_endpoint =
+            format!("/containers/prune{}", filters.to_query(&[]));
+        let method = "POST";
+
+        let resp = self.get_response_from_api(&api_endpoint, method, "")?;
+
+        if resp.status_code != 200 {
+            return Err(DockerApiError::from_response(
+                ApiObject::Container,
+                resp.status_code,
+                resp.body,
+            ));
+        }
+
+        deserialize_body(&resp.body, self.is_strict())
+    }
 }
@@ -0,0 +1,811 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::thread::JoinHandle;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use api::DockerApiClient;
+use utils;
+use utils::{
+    deserialize_timestamp, serialize_unix_timestamp, JsonStream, StreamHandle,
+};
+
+use errors::DockerApiError;
+
+/// The object an event is about.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct EventActor {
+    #[serde(rename = "ID", default)]
+    pub id: String,
+
+    #[serde(default)]
+    pub attributes: HashMap<String, String>,
+}
+
+/// An event as reported by the daemon on the `/events` endpoint.
+///
+/// * time: Unix timestamp of the event in seconds.
+/// * time_nano: Unix timestamp of the event in nanoseconds.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct Event {
+    #[serde(rename = "Type", default)]
+    pub typ: String,
+
+    #[serde(default)]
+    pub action: String,
+
+    #[serde(default)]
+    pub actor: EventActor,
+
+    #[serde(rename = "scope", default)]
+    pub scope: String,
+
+    #[serde(
+        rename = "time",
+        default,
+        deserialize_with = "deserialize_timestamp",
+        serialize_with = "serialize_unix_timestamp"
+    )]
+    pub time: Option<SystemTime>,
+
+    #[serde(rename = "timeNano", default)]
+    pub time_nano: i64,
+}
+
+/// Type of object events can be filtered on.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EventType {
+    Container,
+    Image,
+    Volume,
+    Network,
+    Daemon,
+    Plugin,
+    Service,
+    Node,
+    Secret,
+    Config,
+}
+
+impl EventType {
+    pub fn as_str(&self) -> &str {
+        match *self {
+            EventType::Container => "container",
+            EventType::Image => "image",
+            EventType::Volume => "volume",
+            EventType::Network => "network",
+            EventType::Daemon => "daemon",
+            EventType::Plugin => "plugin",
+            EventType::Service => "service",
+            EventType::Node => "node",
+            EventType::Secret => "secret",
+            EventType::Config => "config",
+        }
+    }
+}
+
+/// Builder for the `filters` of the events endpoint. Adding the same filter
+/// more than once matches any of the values.
+///
+/// ```rust
+/// use rust_docker::api::events::{EventFilters, EventType};
+///
+/// let filters = EventFilters::new()
+///     .event_type(EventType::Container)
+///     .event("die")
+///     .label("com.example.app", Some("web"));
+///
+/// assert_eq!(
+///     filters.to_json(),
+///     r#"{"event":["die"],"label":["com.example.app=web"],"type":["container"]}"#
+/// );
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct EventFilters {
+    filters: BTreeMap<String, Vec<String>>,
+}
+
+impl EventFilters {
+    pub fn new() -> EventFilters {
+        EventFilters::default()
+    }
+
+    fn add(mut self, key: &str, value: &str) -> EventFilters {
+        self.filters
+            .entry(key.to_string())
+            .or_default()
+            .push(value.to_string());
+        self
+    }
+
+    pub fn event_type(self, event_type: EventType) -> EventFilters {
+        self.add("type", event_type.as_str())
+    }
+
+    /// Filters on the container name or ID.
+    pub fn container(self, container: &str) -> EventFilters {
+        self.add("container", container)
+    }
+
+    /// Filters on the image name or ID.
+    pub fn image(self, image: &str) -> EventFilters {
+        self.add("image", image)
+    }
+
+    /// Filters on a label, `key` alone or `key=value`.
+    pub fn label(self, key: &str, value: Option<&str>) -> EventFilters {
+        match value {
+            Some(value) => self.add("label", &format!("{}={}", key, value)),
+            None => self.add("label", key),
+        }
+    }
+
+    /// Filters on the network name or ID.
+    pub fn network(self, network: &str) -> EventFilters {
+        self.add("network", network)
+    }
+
+    /// Filters on the volume name.
+    pub fn volume(self, volume: &str) -> EventFilters {
+        self.add("volume", volume)
+    }
+
+    /// Filters on the event action, e.g. `start`, `die` or `pull`.
+    pub fn event(self, action: &str) -> EventFilters {
+        self.add("event", action)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.filters.is_empty()
+    }
+
+    /// JSON encoded filters as expected by the daemon.
+    pub fn to_json(&self) -> String {
+        json!(self.filters).to_string()
+    }
+}
+
+/// Iterator over the events read from the events endpoint. It blocks until
+/// the next event arrives and ends when the daemon closes the stream.
+pub type EventStream = JsonStream<Event>;
+
+/// Exponential backoff between the reconnection attempts of a
+/// `ReconnectingEventStream`.
+///
+/// * initial: Delay before the first attempt.
+/// * max: Upper bound of the delay.
+/// * factor: Multiplier for the delay after each failed attempt.
+/// * max_retries: Consecutive failed attempts after which the stream gives
+///   up, retries forever if None.
+#[derive(Debug, Clone)]
+pub struct Backoff {
+    pub initial: Duration,
+    pub max: Duration,
+    pub factor: u32,
+    pub max_retries: Option<u32>,
+}
+
+impl Default for Backoff {
+    fn default() -> Backoff {
+        Backoff {
+            initial: Duration::from_millis(500),
+            max: Duration::from_secs(30),
+            factor: 2,
+            max_retries: None,
+        }
+    }
+}
+
+impl Backoff {
+    /// Delay before the nth consecutive attempt, starting at 1.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let mut delay = self.initial;
+        for _ in 1..attempt {
+            delay = delay.checked_mul(self.factor).unwrap_or(self.max);
+            if delay >= self.max {
+                return self.max;
+            }
+        }
+
+        delay.min(self.max)
+    }
+}
+
+/// Information about a dropped events connection, given to the gap callback
+/// of a `ReconnectingEventStream` before it reconnects.
+///
+/// * since: Timestamp in nanoseconds the events are replayed from.
+/// * attempt: Consecutive reconnection attempt, starting at 1.
+/// * error: The error which dropped the connection, None if the daemon
+///   closed the stream.
+#[derive(Debug, Clone)]
+pub struct EventGap {
+    pub since: i64,
+    pub attempt: u32,
+    pub error: Option<String>,
+}
+
+type GapCallback<'a> = Box<dyn FnMut(&EventGap) + 'a>;
+
+/// Events stream which reconnects when the connection to the daemon drops.
+///
+/// On reconnection the events are requested again since the timestamp of
+/// the last event received, so no event is lost. The replayed events which
+/// were already seen are skipped, the events of that last timestamp being
+/// told apart by their type, actor and action.
+pub struct ReconnectingEventStream<'a, C: 'a + Events + ?Sized> {
+    client: &'a C,
+    filters: Option<EventFilters>,
+    backoff: Backoff,
+    stream: Option<EventStream>,
+    last_time_nano: i64,
+    seen_at_last_time: HashSet<(String, String, String)>,
+    failures: u32,
+    finished: bool,
+    handle: StreamHandle,
+    on_gap: Option<GapCallback<'a>>,
+}
+
+impl<'a, C: 'a + Events + ?Sized> ReconnectingEventStream<'a, C> {
+    pub fn new(
+        client: &'a C,
+        filters: Option<EventFilters>,
+        backoff: Backoff,
+    ) -> ReconnectingEventStream<'a, C> {
+        ReconnectingEventStream {
+            client,
+            filters,
+            backoff,
+            stream: None,
+            last_time_nano: now_nano(),
+            seen_at_last_time: HashSet::new(),
+            failures: 0,
+            finished: false,
+            handle: StreamHandle::new(),
+            on_gap: None,
+        }
+    }
+
+    /// Sets the callback called each time the connection drops, before
+    /// reconnecting.
+    pub fn on_gap<F>(mut self, callback: F) -> ReconnectingEventStream<'a, C>
+    where
+        F: FnMut(&EventGap) + 'a,
+    {
+        self.on_gap = Some(Box::new(callback));
+        self
+    }
+
+    /// Handle to stop the stream from another thread, it stays valid across
+    /// the reconnections.
+    pub fn handle(&self) -> StreamHandle {
+        self.handle.clone()
+    }
+
+    /// Timestamp in nanoseconds of the last event received.
+    pub fn last_event_time(&self) -> i64 {
+        self.last_time_nano
+    }
+
+    fn disconnected(&mut self, error: Option<String>) {
+        self.stream = None;
+        if self.handle.is_cancelled() {
+            self.finished = true;
+            return;
+        }
+        self.failures += 1;
+
+        let gap = EventGap {
+            since: self.last_time_nano,
+            attempt: self.failures,
+            error,
+        };
+        if let Some(ref mut callback) = self.on_gap {
+            callback(&gap);
+        }
+    }
+
+    /// Sleeps for the delay in small steps, to notice a cancellation.
+    fn sleep(&self, delay: Duration) {
+        let step = Duration::from_millis(50);
+        let mut slept = Duration::from_millis(0);
+        while slept < delay && !self.handle.is_cancelled() {
+            let remaining = delay - slept;
+            thread::sleep(remaining.min(step));
+            slept += remaining.min(step);
+        }
+    }
+
+    /// Records the event as seen, returns false if it was already seen
+    /// before a reconnection.
+    fn record(&mut self, event: &Event) -> bool {
+        if event.time_nano < self.last_time_nano {
+            return false;
+        }
+        if event.time_nano > self.last_time_nano {
+            self.last_time_nano = event.time_nano;
+            self.seen_at_last_time.clear();
+        }
+        self.seen_at_last_time.insert((
+            event.typ.clone(),
+            event.actor.id.clone(),
+            event.action.clone(),
+        ))
+    }
+
+    fn retries_exhausted(&self) -> bool {
+        match self.backoff.max_retries {
+            Some(max) => self.failures > max,
+            None => false,
+        }
+    }
+}
+
+impl<'a, C: 'a + Events + ?Sized> Iterator for ReconnectingEventStream<'a, C> {
+    type Item = Result<Event, DockerApiError>;
+
+    fn next(&mut self) -> Option<Result<Event, DockerApiError>> {
+        loop {
+            if self.finished || self.handle.is_cancelled() {
+                return None;
+            }
+
+            if self.stream.is_none() {
+                if self.failures > 0 {
+                    self.sleep(self.backoff.delay(self.failures));
+                    if self.handle.is_cancelled() {
+                        return None;
+                    }
+                }
+
+                let since = format!(
+                    "{}.{:09}",
+                    self.last_time_nano / 1_000_000_000,
+                    self.last_time_nano % 1_000_000_000
+                );
+                match self.client.get_events_since(
+                    &since,
+                    None,
+                    self.filters.as_ref(),
+                ) {
+                    Ok(stream) => {
+                        let inner = stream.handle();
+                        self.handle.set_shutdown(move || inner.cancel());
+                        self.stream = Some(stream);
+                    }
+                    Err(err) => {
+                        self.disconnected(Some(err.to_string()));
+                        if self.retries_exhausted() {
+                            self.finished = true;
+                            return Some(Err(err));
+                        }
+                        continue;
+                    }
+                }
+            }
+
+            let next = match self.stream {
+                Some(ref mut stream) => stream.next(),
+                None => continue,
+            };
+
+            match next {
+                Some(Ok(event)) => {
+                    self.failures = 0;
+                    if event.time_nano != 0 && !self.record(&event) {
+                        continue;
+                    }
+                    return Some(Ok(event));
+                }
+                Some(Err(DockerApiError::JsonDeserializationError(err))) => {
+                    return Some(Err(DockerApiError::JsonDeserializationError(
+                        err,
+                    )))
+                }
+                Some(Err(err)) => self.disconnected(Some(err.to_string())),
+                None => self.disconnected(None),
+            }
+
+            if self.retries_exhausted() {
+                self.finished = true;
+                return Some(Err(DockerApiError::RequestError(
+                    "Could not reconnect to the events stream",
+                )));
+            }
+        }
+    }
+}
+
+/// The background thread of `Events::events_into`, pushing the events into
+/// a channel.
+pub struct EventsForwarder {
+    handle: StreamHandle,
+    thread: JoinHandle<Result<(), DockerApiError>>,
+}
+
+impl EventsForwarder {
+    /// Handle to stop the forwarding from another thread.
+    pub fn handle(&self) -> StreamHandle {
+        self.handle.clone()
+    }
+
+    /// Stops the forwarding and waits for the thread to end, see `join`.
+    pub fn stop(self) -> Result<(), DockerApiError> {
+        self.handle.cancel();
+        self.join()
+    }
+
+    /// Waits for the thread to end, which happens once it is stopped or the
+    /// receiver of the channel is dropped. Gives the error which ended it
+    /// otherwise, like an event which could not be read.
+    pub fn join(self) -> Result<(), DockerApiError> {
+        match self.thread.join() {
+            Ok(result) => result,
+            Err(_) => {
+                Err(DockerApiError::RequestError("The events thread panicked"))
+            }
+        }
+    }
+}
+
+/// Counters of the activity of the daemon, from its events.
+///
+/// * dies: Containers which stopped, for any reason.
+/// * exit_codes: The dies by exit code of the container.
+/// * oom_kills: Containers killed because they ran out of memory, they
+///   also die, with the exit code 137.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EventStats {
+    pub starts: u64,
+    pub dies: u64,
+    pub exit_codes: BTreeMap<i64, u64>,
+    pub oom_kills: u64,
+    pub pulls: u64,
+}
+
+impl EventStats {
+    pub fn new() -> EventStats {
+        EventStats::default()
+    }
+
+    /// The filters of the events counted, to stream only them.
+    pub fn filters() -> EventFilters {
+        EventFilters::new()
+            .event_type(EventType::Container)
+            .event_type(EventType::Image)
+            .event("start")
+            .event("die")
+            .event("oom")
+            .event("pull")
+    }
+
+    /// Counts the event, the ones of the other types or actions are
+    /// ignored.
+    pub fn record(&mut self, event: &Event) {
+        match (event.typ.as_str(), event.action.as_str()) {
+            ("container", "start") => self.starts += 1,
+            ("container", "die") => {
+                self.dies += 1;
+                let exit_code = event
+                    .actor
+                    .attributes
+                    .get("exitCode")
+                    .and_then(|code| code.parse().ok());
+                if let Some(exit_code) = exit_code {
+                    *self.exit_codes.entry(exit_code).or_insert(0) += 1;
+                }
+            }
+            ("container", "oom") => self.oom_kills += 1,
+            ("image", "pull") => self.pulls += 1,
+            _ => {}
+        }
+    }
+}
+
+/// The background thread of `Events::event_stats`, counting the events.
+pub struct EventStatsCollector {
+    stats: Arc<Mutex<EventStats>>,
+    handle: StreamHandle,
+    thread: JoinHandle<Result<(), DockerApiError>>,
+}
+
+impl EventStatsCollector {
+    /// The counters as they are now.
+    pub fn snapshot(&self) -> EventStats {
+        read_stats(&self.stats)
+    }
+
+    /// Handle to stop the counting from another thread.
+    pub fn handle(&self) -> StreamHandle {
+        self.handle.clone()
+    }
+
+    /// Stops the counting and waits for the thread to end, giving the
+    /// final counters, or the error which ended the thread before.
+    pub fn stop(self) -> Result<EventStats, DockerApiError> {
+        self.handle.cancel();
+        let stats = self.stats;
+        match self.thread.join() {
+            Ok(result) => result.map(|_| read_stats(&stats)),
+            Err(_) => {
+                Err(DockerApiError::RequestError("The events thread panicked"))
+            }
+        }
+    }
+}
+
+fn read_stats(stats: &Mutex<EventStats>) -> EventStats {
+    match stats.lock() {
+        Ok(stats) => stats.clone(),
+        Err(poisoned) => poisoned.into_inner().clone(),
+    }
+}
+
+fn now_nano() -> i64 {
+    match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(d) => d.as_secs() as i64 * 1_000_000_000 + d.subsec_nanos() as i64,
+        Err(_) => 0,
+    }
+}
+
+pub trait Events: DockerApiClient {
+    /// Get the events from the daemon as they happen.
+    ///
+    /// * since: Unix timestamp to replay the events from.
+    /// * until: Unix timestamp to stream the events till, the stream ends
+    ///   there.
+    /// * filters: Only the events matching the filters are streamed.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// extern crate rust_docker;
+    ///
+    /// use rust_docker::api::events::{EventFilters, EventType, Events};
+    /// use rust_docker::client::DockerClient;
+    ///
+    /// let client = match DockerClient::new("unix:///var/run/docker.sock") {
+    ///     Ok(a) => a,
+    ///     Err(err) => {
+    ///         println!("{}", err);
+    ///         std::process::exit(1);
+    ///     }
+    /// };
+    ///
+    /// let filters = EventFilters::new().event_type(EventType::Container);
+    /// let events = client.get_events(None, None, Some(&filters)).expect("Error");
+    /// for event in events {
+    ///     println!("{:?}", event);
+    /// }
+    /// ```
+    fn get_events(
+        &self,
+        since: Option<i64>,
+        until: Option<i64>,
+        filters: Option<&EventFilters>,
+    ) -> Result<EventStream, DockerApiError> {
+        let since = since.map(|s| s.to_string()).unwrap_or_default();
+        self.get_events_since(&since, until, filters)
+    }
+
+    /// Same as `get_events` but with `since` as given to the daemon, which
+    /// also takes timestamps with nanoseconds like `1530000000.000000001`.
+    /// An empty `since` streams only the new events.
+    fn get_events_since(
+        &self,
+        since: &str,
+        until: Option<i64>,
+        filters: Option<&EventFilters>,
+    ) -> Result<EventStream, DockerApiError> {
+        let mut query_params: Vec<String> = Vec::new();
+        if !since.is_empty() {
+            query_params.push(format!("since={}", since));
+        }
+        if let Some(until) = until {
+            query_params.push(format!("until={}", until));
+        }
+        if let Some(filters) = filters {
+            if !filters.is_empty() {
+                query_params.push(format!(
+                    "filters={}",
+                    utils::encode_query_value(&filters.to_json())
+                ));
+            }
+        }
+
+        let api_endpoint = if query_params.is_empty() {
+            "/events".to_string()
+        } else {
+            format!("/events?{}", query_params.join("&"))
+        };
+
+        let resp = self.get_stream_from_api(&api_endpoint, "GET", "", &[])?;
+
+        if resp.status_code != 200 {
+            let status_code = resp.status_code;
+            return Err(DockerApiError::InvalidApiResponseError(
+                status_code,
+                resp.body_to_string()?,
+            ));
+        }
+
+        Ok(EventStream::new(resp))
+    }
+
+    /// Get the events which happened between the unix timestamps `since` and
+    /// `until`, for instance for a post-mortem analysis of what happened on
+    /// the host.
+    ///
+    /// The daemon keeps the stream open until `until` when it is in the
+    /// future, so it is capped to the current time to always get a finite
+    /// list. Events are only kept in memory by the daemon for a while, older
+    /// ones are not returned.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// extern crate rust_docker;
+    ///
+    /// use rust_docker::api::events::Events;
+    /// use rust_docker::client::DockerClient;
+    ///
+    /// let client = match DockerClient::new("unix:///var/run/docker.sock") {
+    ///     Ok(a) => a,
+    ///     Err(err) => {
+    ///         println!("{}", err);
+    ///         std::process::exit(1);
+    ///     }
+    /// };
+    ///
+    /// match client.events_between(1530000000, 1530003600, None) {
+    ///     Ok(events) => println!("{} events", events.len()),
+    ///     Err(err) => println!("An error occured : {}", err),
+    /// }
+    /// ```
+    fn events_between(
+        &self,
+        since: i64,
+        until: i64,
+        filters: Option<&EventFilters>,
+    ) -> Result<Vec<Event>, DockerApiError> {
+        let until = until.min(now_nano() / 1_000_000_000);
+        if since > until {
+            return Err(DockerApiError::MismatchedParametersError(
+                "since must not be later than until or the current time",
+            ));
+        }
+
+        let stream =
+            self.get_events_since(&since.to_string(), Some(until), filters)?;
+
+        stream.collect()
+    }
+
+    /// Get the events from the daemon as they happen, reconnecting when the
+    /// connection drops. See `ReconnectingEventStream`.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// extern crate rust_docker;
+    ///
+    /// use rust_docker::api::events::{Backoff, Events};
+    /// use rust_docker::client::DockerClient;
+    ///
+    /// let client = match DockerClient::new("unix:///var/run/docker.sock") {
+    ///     Ok(a) => a,
+    ///     Err(err) => {
+    ///         println!("{}", err);
+    ///         std::process::exit(1);
+    ///     }
+    /// };
+    ///
+    /// let events = client
+    ///     .get_events_reconnecting(None, Backoff::default())
+    ///     .on_gap(|gap| println!("Reconnecting since {}", gap.since));
+    /// for event in events {
+    ///     println!("{:?}", event);
+    /// }
+    /// ```
+    fn get_events_reconnecting<'a>(
+        &'a self,
+        filters: Option<EventFilters>,
+        backoff: Backoff,
+    ) -> ReconnectingEventStream<'a, Self>
+    where
+        Self: Sized,
+    {
+        ReconnectingEventStream::new(self, filters, backoff)
+    }
+
+    /// Sends the events from the daemon into `sender` from a thread of its
+    /// own, reconnecting when the connection drops like
+    /// `get_events_reconnecting`. For applications with an event loop or
+    /// actors, which can not block on the stream.
+    ///
+    /// ```rust,ignore
+    /// let (sender, receiver) = channel();
+    /// let forwarder = client.events_into(sender, None);
+    /// for event in receiver.iter().take(10) {
+    ///     println!("{} {}", event.typ, event.action);
+    /// }
+    /// forwarder.stop()?;
+    /// ```
+    fn events_into(
+        &self,
+        sender: Sender<Event>,
+        filters: Option<EventFilters>,
+    ) -> EventsForwarder
+    where
+        Self: Clone + Send + 'static,
+    {
+        let client = self.clone();
+        let handle = StreamHandle::new();
+        let stop = handle.clone();
+
+        let thread = thread::spawn(move || {
+            let events =
+                client.get_events_reconnecting(filters, Backoff::default());
+            let inner = events.handle();
+            stop.set_shutdown(move || inner.cancel());
+
+            for event in events {
+                if sender.send(event?).is_err() {
+                    break;
+                }
+            }
+            Ok(())
+        });
+
+        EventsForwarder { handle, thread }
+    }
+
+    /// Counts the containers started, dying and killed for lack of memory,
+    /// and the images pulled, from a thread of its own streaming the events
+    /// like `events_into`. Only the events from now are counted.
+    ///
+    /// ```rust,ignore
+    /// let collector = client.event_stats();
+    /// loop {
+    ///     thread::sleep(Duration::from_secs(60));
+    ///     let stats = collector.snapshot();
+    ///     println!("{} starts, {} OOM kills", stats.starts, stats.oom_kills);
+    /// }
+    /// ```
+    fn event_stats(&self) -> EventStatsCollector
+    where
+        Self: Clone + Send + 'static,
+    {
+        let client = self.clone();
+        let stats = Arc::new(Mutex::new(EventStats::new()));
+        let counters = stats.clone();
+        let handle = StreamHandle::new();
+        let stop = handle.clone();
+
+        let thread = thread::spawn(move || {
+            let events = client.get_events_reconnecting(
+                Some(EventStats::filters()),
+                Backoff::default(),
+            );
+            let inner = events.handle();
+            stop.set_shutdown(move || inner.cancel());
+
+            for event in events {
+                let event = event?;
+                if let Ok(mut counters) = counters.lock() {
+                    counters.record(&event);
+                }
+            }
+            Ok(())
+        });
+
+        EventStatsCollector {
+            stats,
+            handle,
+            thread,
+        }
+    }
+}
@@ -0,0 +1,352 @@
+use std::io;
+use std::io::Write;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use api::ids::{ContainerId, ContainerRef};
+use api::logs::{LogFrames, StreamType};
+use api::DockerApiClient;
+use utils::{CloseWrite, StreamResponse};
+
+use serde_json;
+
+use errors::{ApiObject, DockerApiError};
+
+/// How long `Exec::exec_output` waits for the exit code of an exec once its
+/// output is closed.
+const EXIT_CODE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Structure for the request body of the exec create endpoint.
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "PascalCase")]
+pub struct ExecConfig {
+    pub attach_stdin: bool,
+    pub attach_stdout: bool,
+    pub attach_stderr: bool,
+    pub tty: bool,
+    pub cmd: Vec<String>,
+    pub env: Vec<String>,
+    pub privileged: bool,
+    pub user: String,
+    pub working_dir: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct CreateExecResponse {
+    pub id: String,
+}
+
+/// Low level information about an exec instance.
+///
+/// * exit_code: None while the command is running.
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "PascalCase")]
+pub struct ExecDetails {
+    #[serde(rename = "ID")]
+    pub id: String,
+
+    #[serde(rename = "ContainerID", default)]
+    pub container_id: ContainerId,
+    pub running: bool,
+
+    #[serde(default)]
+    pub exit_code: Option<i64>,
+
+    #[serde(default)]
+    pub pid: u64,
+}
+
+/// Options for `exec_output` and `exec_interactive`.
+///
+/// * env: Environment variables as `KEY=value`.
+/// * user: User to run the command as, the container user by default.
+/// * working_dir: Directory to run the command in.
+#[derive(Debug, Clone, Default)]
+pub struct ExecOptions {
+    pub env: Vec<String>,
+    pub user: Option<String>,
+    pub working_dir: Option<String>,
+    pub privileged: bool,
+}
+
+impl ExecOptions {
+    /// Config for the command attached to stdout and stderr.
+    pub fn to_config(&self, cmd: &[&str]) -> ExecConfig {
+        ExecConfig {
+            attach_stdout: true,
+            attach_stderr: true,
+            cmd: cmd.iter().map(|arg| arg.to_string()).collect(),
+            env: self.env.clone(),
+            privileged: self.privileged,
+            user: self.user.clone().unwrap_or_default(),
+            working_dir: self.working_dir.clone().unwrap_or_default(),
+            ..Default::default()
+        }
+    }
+}
+
+/// The output of a command run with `exec_output`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExecOutput {
+    pub stdout: Vec<u8>,
+    pub stderr: Vec<u8>,
+    pub exit_code: i64,
+}
+
+/// Stdin of an interactive exec session.
+pub struct ExecStdin {
+    writer: Box<dyn CloseWrite>,
+}
+
+impl ExecStdin {
+    /// Closes stdin, the command reads EOF while its output can still be
+    /// read from the session.
+    pub fn close(mut self) -> Result<(), DockerApiError> {
+        match self.writer.close_write() {
+            Ok(_) => Ok(()),
+            Err(_) => {
+                Err(DockerApiError::RequestError("Error while closing stdin"))
+            }
+        }
+    }
+}
+
+impl Write for ExecStdin {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.writer.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// An exec session attached to stdin, stdout and stderr of the command.
+///
+/// The fields can be moved apart to write to stdin from one thread while
+/// reading the output from another. Call `inspect_exec` with `exec_id` once
+/// the output ends to get the exit code.
+pub struct ExecSession {
+    pub exec_id: String,
+    pub stdin: ExecStdin,
+    pub output: LogFrames<StreamResponse>,
+}
+
+pub trait Exec: DockerApiClient {
+    /// Creates an exec instance running `config.cmd` in the container with
+    /// the provided ID, returns the ID of the exec instance.
+    fn create_exec(
+        &self,
+        id: impl ContainerRef,
+        config: &ExecConfig,
+    ) -> Result<String, DockerApiError>
+    where
+        Self: Sized,
+    {
+        let id = id.container_ref();
+        let api_endpoint = format!("/containers/{id}/exec", id = id);
+        let method = "POST";
+        let body = match serde_json::to_string(config) {
+            Ok(body) => body,
+            Err(err) => {
+                return Err(DockerApiError::JsonSerializationError(err))
+            }
+        };
+
+        let resp = self.get_response_from_api(&api_endpoint, method, &body)?;
+
+        if resp.status_code != 201 {
+            return Err(DockerApiError::from_response(
+                ApiObject::Container,
+                resp.status_code,
+                resp.body,
+            ));
+        }
+
+        match serde_json::from_str::<CreateExecResponse>(&resp.body) {
+            Ok(info) => Ok(info.id),
+            Err(err) => Err(DockerApiError::JsonDeserializationError(err)),
+        }
+    }
+
+    /// Starts the exec instance attached to its output, which is read as
+    /// frames like the container logs.
+    fn start_exec(
+        &self,
+        exec_id: &str,
+        tty: bool,
+    ) -> Result<LogFrames<StreamResponse>, DockerApiError> {
+        let api_endpoint = format!("/exec/{id}/start", id = exec_id);
+        let body = json!({ "Detach": false, "Tty": tty }).to_string();
+
+        let resp =
+            self.get_stream_from_api(&api_endpoint, "POST", &body, &[])?;
+
+        if resp.status_code != 200 {
+            let status_code = resp.status_code;
+            return Err(DockerApiError::from_response(
+                ApiObject::Exec,
+                status_code,
+                resp.body_to_string()?,
+            ));
+        }
+
+        Ok(LogFrames::new(resp))
+    }
+
+    /// Starts the exec instance on a hijacked connection, so that its stdin
+    /// can be written to while the output is read. The exec instance must
+    /// have been created with `AttachStdin`.
+    fn start_exec_interactive(
+        &self,
+        exec_id: &str,
+        tty: bool,
+    ) -> Result<ExecSession, DockerApiError> {
+        let api_endpoint = format!("/exec/{id}/start", id = exec_id);
+        let body = json!({ "Detach": false, "Tty": tty }).to_string();
+
+        let (resp, writer) =
+            self.get_hijacked_stream_from_api(&api_endpoint, "POST", &body)?;
+
+        if resp.status_code != 101 && resp.status_code != 200 {
+            let status_code = resp.status_code;
+            return Err(DockerApiError::from_response(
+                ApiObject::Exec,
+                status_code,
+                resp.body_to_string()?,
+            ));
+        }
+
+        Ok(ExecSession {
+            exec_id: exec_id.to_string(),
+            stdin: ExecStdin { writer },
+            output: LogFrames::new(resp),
+        })
+    }
+
+    /// Runs the command in the container attached to its stdin, like
+    /// `docker exec -i`, with a TTY if `tty` is set.
+    ///
+    /// ```rust,ignore
+    /// let session = client.exec_interactive(id, &["sh"], &opts, false)?;
+    /// let (mut stdin, mut output) = (session.stdin, session.output);
+    /// stdin.write_all(b"echo hello\n")?;
+    /// stdin.close()?;
+    /// while let Some(frame) = output.next_frame()? {
+    ///     io::stdout().write_all(frame.payload)?;
+    /// }
+    /// ```
+    fn exec_interactive(
+        &self,
+        id: impl ContainerRef,
+        cmd: &[&str],
+        opts: &ExecOptions,
+        tty: bool,
+    ) -> Result<ExecSession, DockerApiError>
+    where
+        Self: Sized,
+    {
+        let id = id.container_ref();
+        let config = ExecConfig {
+            attach_stdin: true,
+            tty,
+            ..opts.to_config(cmd)
+        };
+
+        let exec_id = self.create_exec(id, &config)?;
+        self.start_exec_interactive(&exec_id, tty)
+    }
+
+    /// Inspects the exec instance with the provided ID.
+    fn inspect_exec(
+        &self,
+        exec_id: &str,
+    ) -> Result<ExecDetails, DockerApiError> {
+        let api_endpoint = format!("/exec/{id}/json", id = exec_id);
+        let method = "GET";
+
+        let resp = self.get_response_from_api(&api_endpoint, method, "")?;
+
+        if resp.status_code != 200 {
+            return Err(DockerApiError::from_response(
+                ApiObject::Exec,
+                resp.status_code,
+                resp.body,
+            ));
+        }
+
+        match serde_json::from_str(&resp.body) {
+            Ok(info) => Ok(info),
+            Err(err) => Err(DockerApiError::JsonDeserializationError(err)),
+        }
+    }
+
+    /// Runs the command in the container and waits for it to exit, returns
+    /// everything it wrote to stdout and stderr and its exit code.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// extern crate rust_docker;
+    ///
+    /// use rust_docker::api::exec::{Exec, ExecOptions};
+    /// use rust_docker::client::DockerClient;
+    ///
+    /// let client = match DockerClient::new("unix:///var/run/docker.sock") {
+    ///     Ok(a) => a,
+    ///     Err(err) => {
+    ///         println!("{}", err);
+    ///         std::process::exit(1);
+    ///     }
+    /// };
+    ///
+    /// match client.exec_output("f808ca...", &["ls", "/"], &ExecOptions::default()) {
+    ///     Ok(output) => println!("{}", String::from_utf8_lossy(&output.stdout)),
+    ///     Err(err) => println!("An error occured : {}", err),
+    /// }
+    /// ```
+    fn exec_output(
+        &self,
+        id: impl ContainerRef,
+        cmd: &[&str],
+        opts: &ExecOptions,
+    ) -> Result<ExecOutput, DockerApiError>
+    where
+        Self: Sized,
+    {
+        let id = id.container_ref();
+        let exec_id = self.create_exec(id, &opts.to_config(cmd))?;
+        let mut frames = self.start_exec(&exec_id, false)?;
+
+        let mut output = ExecOutput::default();
+        while let Some(frame) = frames.next_frame()? {
+            match frame.stream {
+                StreamType::Stderr => {
+                    output.stderr.extend_from_slice(frame.payload)
+                }
+                _ => output.stdout.extend_from_slice(frame.payload),
+            }
+        }
+
+        // The daemon can close the output a little before it records the
+        // exit code, so the exec is inspected again for a short while.
+        let started = Instant::now();
+        loop {
+            let details = self.inspect_exec(&exec_id)?;
+            match details.exit_code {
+                Some(exit_code) if !details.running => {
+                    output.exit_code = exit_code;
+                    return Ok(output);
+                }
+                _ if started.elapsed() >= EXIT_CODE_TIMEOUT => {
+                    return Err(DockerApiError::ContainerError(format!(
+                        "Exec {} is still running {:?} after its output was closed",
+                        exec_id, EXIT_CODE_TIMEOUT
+                    )))
+                }
+                _ => thread::sleep(Duration::from_millis(50)),
+            }
+        }
+    }
+}
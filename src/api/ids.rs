@@ -0,0 +1,174 @@
+use std::fmt;
+
+use errors::DockerApiError;
+
+/// Length of the short IDs shown by the docker CLI.
+const SHORT_ID_LEN: usize = 12;
+
+/// Prefix of the content addressable image IDs.
+const IMAGE_ID_PREFIX: &str = "sha256:";
+
+fn is_hex_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= 64
+        && id
+            .bytes()
+            .all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b))
+}
+
+/// ID of a container, as given back by the daemon.
+///
+/// It is a `ContainerRef`, so it can be passed to all the methods taking
+/// a container, while an `ImageId` cannot be mistaken for it, neither in
+/// these methods nor in the fields of the API structs.
+#[derive(
+    Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq, Hash,
+)]
+#[serde(transparent)]
+pub struct ContainerId(String);
+
+impl ContainerId {
+    /// Validates a full or abbreviated container ID, lowercase hex digits.
+    pub fn new(id: &str) -> Result<ContainerId, DockerApiError> {
+        if !is_hex_id(id) {
+            return Err(DockerApiError::InvalidIdError(format!(
+                "{} is not a valid container ID",
+                id
+            )));
+        }
+
+        Ok(ContainerId(id.to_string()))
+    }
+
+    /// The 12 characters ID shown by `docker ps`.
+    pub fn short(&self) -> &str {
+        &self.0[..SHORT_ID_LEN.min(self.0.len())]
+    }
+
+    /// Checks if `prefix` is an abbreviation of the ID, the way the daemon
+    /// matches IDs given by the user.
+    pub fn matches(&self, prefix: &str) -> bool {
+        !prefix.is_empty() && self.0.starts_with(prefix)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+/// A container as taken by the methods operating on one, its ID, full or
+/// abbreviated, or its name.
+///
+/// It is implemented for the strings and `ContainerId`, but not for
+/// `ImageId`.
+pub trait ContainerRef {
+    fn container_ref(&self) -> &str;
+}
+
+impl ContainerRef for str {
+    fn container_ref(&self) -> &str {
+        self
+    }
+}
+
+impl ContainerRef for String {
+    fn container_ref(&self) -> &str {
+        self
+    }
+}
+
+impl ContainerRef for ContainerId {
+    fn container_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl<T: ContainerRef + ?Sized> ContainerRef for &T {
+    fn container_ref(&self) -> &str {
+        (**self).container_ref()
+    }
+}
+
+/// ID of an image, `sha256:` followed by the digest of its config.
+///
+/// See `ContainerId`, the short form and the prefix matching leave out the
+/// `sha256:` prefix like the docker CLI does.
+#[derive(
+    Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq, Hash,
+)]
+#[serde(transparent)]
+pub struct ImageId(String);
+
+impl ImageId {
+    /// Validates a full or abbreviated image ID, with or without the
+    /// `sha256:` prefix.
+    pub fn new(id: &str) -> Result<ImageId, DockerApiError> {
+        if !is_hex_id(id.trim_start_matches(IMAGE_ID_PREFIX)) {
+            return Err(DockerApiError::InvalidIdError(format!(
+                "{} is not a valid image ID",
+                id
+            )));
+        }
+
+        Ok(ImageId(id.to_string()))
+    }
+
+    /// The digest of the ID, without the `sha256:` prefix.
+    pub fn digest(&self) -> &str {
+        self.0.trim_start_matches(IMAGE_ID_PREFIX)
+    }
+
+    /// The 12 characters ID shown by `docker images`.
+    pub fn short(&self) -> &str {
+        let digest = self.digest();
+        &digest[..SHORT_ID_LEN.min(digest.len())]
+    }
+
+    /// Checks if `prefix`, with or without `sha256:`, is an abbreviation of
+    /// the ID.
+    pub fn matches(&self, prefix: &str) -> bool {
+        let prefix = prefix.trim_start_matches(IMAGE_ID_PREFIX);
+        !prefix.is_empty() && self.digest().starts_with(prefix)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+macro_rules! impl_id_traits {
+    ($id:ident) => {
+        impl AsRef<str> for $id {
+            fn as_ref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl fmt::Display for $id {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str(&self.0)
+            }
+        }
+
+        impl<'a> PartialEq<&'a str> for $id {
+            fn eq(&self, other: &&'a str) -> bool {
+                self.0 == *other
+            }
+        }
+
+        impl PartialEq<str> for $id {
+            fn eq(&self, other: &str) -> bool {
+                self.0 == other
+            }
+        }
+
+        impl From<$id> for String {
+            fn from(id: $id) -> String {
+                id.0
+            }
+        }
+    };
+}
+
+impl_id_traits!(ContainerId);
+impl_id_traits!(ImageId);
@@ -1,26 +1,599 @@
-#![allow(non_snake_case)]
+use std::collections::{BTreeMap, HashMap};
+use std::io::{Read, Write};
+use std::time::{Duration, SystemTime};
 
-use std::collections::HashMap;
-
-use api::DockerApiClient;
+use api::api_utils::RequestBuilder;
+use api::ids::ImageId;
+use api::platform::Platform;
+use api::reference::Reference;
+use api::{DockerApiClient, PruneFilters};
 use utils;
+use utils::{
+    deserialize_body, deserialize_null_default, deserialize_timestamp,
+    serialize_rfc3339_timestamp, serialize_unix_timestamp, Response,
+    StreamResponse,
+};
 
+use base64::engine::general_purpose::URL_SAFE;
+use base64::Engine;
 use serde_json;
 
-use errors::DockerApiError;
+use errors::{ApiObject, DockerApiError};
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "PascalCase")]
+#[serde(default)]
 pub struct ImageCompactInfo {
-    pub Id: String,
-    pub ParentId: String,
-    pub RepoTags: Vec<String>,
-    pub RepoDigests: Option<Vec<String>>,
-    pub Created: u64,
-    pub Size: u64,
-    pub VirtualSize: u64,
-    pub SharedSize: i64,
-    pub Labels: Option<HashMap<String, String>>,
-    pub Containers: i32,
+    pub id: ImageId,
+    pub parent_id: ImageId,
+
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub repo_tags: Vec<String>,
+
+    pub repo_digests: Option<Vec<String>>,
+
+    #[serde(
+        default,
+        deserialize_with = "deserialize_timestamp",
+        serialize_with = "serialize_unix_timestamp"
+    )]
+    pub created: Option<SystemTime>,
+
+    pub size: u64,
+    pub virtual_size: u64,
+    pub shared_size: i64,
+    pub labels: Option<HashMap<String, String>>,
+    pub containers: i32,
+}
+
+/// Low level information about an image as returned by the image inspect
+/// endpoint.
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "PascalCase")]
+#[serde(default)]
+pub struct ImageDetails {
+    pub id: ImageId,
+
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub repo_tags: Vec<String>,
+
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub repo_digests: Vec<String>,
+
+    pub parent: ImageId,
+    pub comment: String,
+
+    #[serde(
+        default,
+        deserialize_with = "deserialize_timestamp",
+        serialize_with = "serialize_rfc3339_timestamp"
+    )]
+    pub created: Option<SystemTime>,
+
+    pub docker_version: String,
+    pub author: String,
+    pub architecture: String,
+    pub os: String,
+    pub size: u64,
+
+    #[serde(default)]
+    pub virtual_size: u64,
+
+    #[serde(default)]
+    pub config: ImageConfig,
+
+    #[serde(rename = "RootFS", default)]
+    pub root_fs: RootFs,
+
+    /// The OCI descriptor of the image, only given by the daemons storing
+    /// the images in containerd.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub descriptor: Option<ImageDescriptor>,
+}
+
+impl ImageDetails {
+    /// The OCI annotations of the image, falling back to the labels of its
+    /// config for the keys the manifest has no annotation for.
+    pub fn annotations(&self) -> Annotations {
+        let mut values = self.config.labels.clone();
+        if let Some(ref descriptor) = self.descriptor {
+            values.extend(descriptor.annotations.clone());
+        }
+        Annotations::new(values)
+    }
+}
+
+/// The OCI descriptor of the manifest of an image.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageDescriptor {
+    #[serde(default)]
+    pub media_type: String,
+
+    #[serde(default)]
+    pub digest: String,
+
+    #[serde(default)]
+    pub size: u64,
+
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub annotations: HashMap<String, String>,
+}
+
+/// A platform of the image a registry has for a reference.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct DistributionPlatform {
+    #[serde(default)]
+    pub architecture: String,
+
+    #[serde(default)]
+    pub os: String,
+
+    #[serde(default)]
+    pub variant: Option<String>,
+}
+
+/// What the registry has for a reference, from the distribution endpoint,
+/// without pulling the image.
+///
+/// * descriptor: Descriptor of the manifest, or of the index of a
+///   multi-arch image, the digest changes when the reference is pushed
+///   again.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "PascalCase")]
+#[serde(default)]
+pub struct DistributionInspect {
+    pub descriptor: ImageDescriptor,
+
+    #[serde(deserialize_with = "deserialize_null_default")]
+    pub platforms: Vec<DistributionPlatform>,
+}
+
+/// Prefix of the annotation keys defined by the OCI image spec.
+pub const OCI_ANNOTATION_PREFIX: &str = "org.opencontainers.image.";
+
+/// The `org.opencontainers.image.*` annotations and labels of an image,
+/// telling where it comes from, as used by compliance and vulnerability
+/// tooling.
+///
+/// ```rust
+/// extern crate rust_docker;
+///
+/// use std::collections::HashMap;
+///
+/// use rust_docker::api::images::Annotations;
+///
+/// let mut labels = HashMap::new();
+/// labels.insert(
+///     "org.opencontainers.image.source".to_string(),
+///     "https://github.com/example/app".to_string(),
+/// );
+/// let annotations = Annotations::new(labels);
+/// assert_eq!(annotations.source(), Some("https://github.com/example/app"));
+/// assert_eq!(annotations.revision(), None);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Annotations {
+    values: HashMap<String, String>,
+}
+
+impl Annotations {
+    pub fn new(values: HashMap<String, String>) -> Annotations {
+        Annotations { values }
+    }
+
+    /// The value of `org.opencontainers.image.<name>`, an empty value being
+    /// None.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.values
+            .get(&format!("{}{}", OCI_ANNOTATION_PREFIX, name))
+            .map(|value| value.as_str())
+            .filter(|value| !value.is_empty())
+    }
+
+    /// All the annotations and labels, the OCI ones and the others.
+    pub fn all(&self) -> &HashMap<String, String> {
+        &self.values
+    }
+
+    /// URL of the source code the image was built from.
+    pub fn source(&self) -> Option<&str> {
+        self.get("source")
+    }
+
+    /// Revision of the source code, like a commit hash.
+    pub fn revision(&self) -> Option<&str> {
+        self.get("revision")
+    }
+
+    /// When the image was built, None if it is not a RFC 3339 date.
+    pub fn created(&self) -> Option<SystemTime> {
+        utils::parse_rfc3339(self.get("created")?)
+    }
+
+    /// SPDX license expression of the software in the image, like
+    /// `Apache-2.0 OR MIT`.
+    pub fn licenses(&self) -> Option<&str> {
+        self.get("licenses")
+    }
+
+    /// The licenses of the SPDX expression, without its operators and
+    /// parentheses.
+    pub fn license_ids(&self) -> Vec<&str> {
+        self.licenses()
+            .unwrap_or("")
+            .split(|c: char| c.is_whitespace() || c == '(' || c == ')')
+            .filter(|id| !id.is_empty() && !["AND", "OR", "WITH"].contains(id))
+            .collect()
+    }
+
+    pub fn version(&self) -> Option<&str> {
+        self.get("version")
+    }
+
+    pub fn vendor(&self) -> Option<&str> {
+        self.get("vendor")
+    }
+
+    pub fn title(&self) -> Option<&str> {
+        self.get("title")
+    }
+
+    /// Reference of the image the image was built on, like `debian:12`.
+    pub fn base_name(&self) -> Option<&str> {
+        self.get("base.name")
+    }
+
+    /// Digest of the image the image was built on.
+    pub fn base_digest(&self) -> Option<&str> {
+        self.get("base.digest")
+    }
+}
+
+/// The configuration the containers of an image start with, unless the
+/// container overrides it.
+///
+/// * exposed_ports: Ports like `80/tcp`, the values are always empty.
+/// * volumes: Paths of the anonymous volumes, the values are always empty.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct ImageConfig {
+    #[serde(default)]
+    pub user: String,
+
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub env: Vec<String>,
+
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub entrypoint: Vec<String>,
+
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub cmd: Vec<String>,
+
+    #[serde(default)]
+    pub working_dir: String,
+
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub exposed_ports: HashMap<String, serde_json::Value>,
+
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub volumes: HashMap<String, serde_json::Value>,
+
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub labels: HashMap<String, String>,
+
+    #[serde(default)]
+    pub stop_signal: Option<String>,
+}
+
+impl ImageConfig {
+    /// The exposed ports as numbers with their protocol, sorted.
+    pub fn ports(&self) -> Vec<(u16, String)> {
+        let mut ports: Vec<(u16, String)> = self
+            .exposed_ports
+            .keys()
+            .filter_map(|port| {
+                let mut parts = port.splitn(2, '/');
+                let number = parts.next()?.parse().ok()?;
+                let proto = parts.next().unwrap_or("tcp").to_string();
+                Some((number, proto))
+            })
+            .collect();
+        ports.sort();
+        ports
+    }
+}
+
+/// The layers of an image, as the digests of their uncompressed content
+/// from the base layer up.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct RootFs {
+    #[serde(rename = "Type", default)]
+    pub typ: String,
+
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub layers: Vec<String>,
+}
+
+/// A tag of a repository available locally.
+///
+/// * tag: The tag alone, like `1.14` for `nginx:1.14`.
+/// * created: Unix timestamp of the creation of the image.
+/// * size: Size of the image in bytes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageTag {
+    pub tag: String,
+    pub id: ImageId,
+    pub created: Option<SystemTime>,
+    pub size: u64,
+}
+
+/// Credentials for a registry, sent base64url encoded in the
+/// `X-Registry-Auth` header with the requests which talk to a registry.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct RegistryAuth {
+    pub username: String,
+    pub password: String,
+    pub email: String,
+    pub serveraddress: String,
+}
+
+impl RegistryAuth {
+    /// Encodes the credentials to the value expected in `X-Registry-Auth`.
+    pub fn encode(&self) -> Result<String, DockerApiError> {
+        match serde_json::to_string(self) {
+            Ok(json) => Ok(URL_SAFE.encode(json)),
+            Err(err) => Err(DockerApiError::JsonSerializationError(err)),
+        }
+    }
+}
+
+/// A single progress message from the stream of JSON objects the daemon
+/// writes while pulling an image.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct ImageProgress {
+    #[serde(default)]
+    pub id: Option<String>,
+
+    #[serde(default)]
+    pub status: Option<String>,
+
+    #[serde(default)]
+    pub progress: Option<String>,
+
+    #[serde(default)]
+    pub error: Option<String>,
+
+    /// Output of the load of an archive, like `Loaded image: nginx:1.25`.
+    #[serde(default)]
+    pub stream: Option<String>,
+
+    /// Result of the operation, like the digest of a pushed image.
+    #[serde(default)]
+    pub aux: Option<serde_json::Value>,
+}
+
+impl ImageProgress {
+    /// The digest of the manifest the registry stored, in the last message
+    /// of a push.
+    pub fn pushed_digest(&self) -> Option<&str> {
+        self.aux.as_ref()?["Digest"].as_str()
+    }
+
+    /// The reference, or the ID of an untagged image, in a message of the
+    /// load of an archive.
+    pub fn loaded_image(&self) -> Option<&str> {
+        let stream = self.stream.as_ref()?.trim();
+        stream
+            .strip_prefix("Loaded image: ")
+            .or_else(|| stream.strip_prefix("Loaded image ID: "))
+    }
+}
+
+/// Options of `commit_container`.
+///
+/// * reference: Name of the image, untagged if None.
+/// * pause: Pauses the container while it is committed, so that the files
+///   are consistent. The daemon does it by default.
+/// * changes: Dockerfile instructions applied to the image, like
+///   `CMD ["app"]` or `ENV MODE=production`.
+#[derive(Debug, Clone, Default)]
+pub struct CommitOptions {
+    pub reference: Option<String>,
+    pub comment: Option<String>,
+    pub author: Option<String>,
+    pub pause: Option<bool>,
+    pub changes: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct CommitResponse {
+    #[serde(rename = "Id")]
+    id: ImageId,
+}
+
+/// Parses the stream of progress messages of a pull or a push, the daemon
+/// reports failures in the stream itself with a 200 status code.
+fn parse_progress(body: &str) -> Result<Vec<ImageProgress>, DockerApiError> {
+    let mut progress: Vec<ImageProgress> = Vec::new();
+    for line in body.lines().filter(|l| !l.trim().is_empty()) {
+        let msg: ImageProgress = match serde_json::from_str(line) {
+            Ok(msg) => msg,
+            Err(err) => {
+                return Err(DockerApiError::JsonDeserializationError(err))
+            }
+        };
+
+        if let Some(err) = msg.error {
+            if is_rate_limit_message(&err) {
+                return Err(DockerApiError::TooManyRequestsError(None, err));
+            }
+            return Err(DockerApiError::ImageError(err));
+        }
+        progress.push(msg);
+    }
+
+    Ok(progress)
+}
+
+/// Progress of a transfer to or from the daemon, like an image export.
+///
+/// * transferred: Bytes transferred so far.
+/// * total: Size of the whole transfer, None when the daemon streams it
+///   without telling its length.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TransferProgress {
+    pub transferred: u64,
+    pub total: Option<u64>,
+}
+
+impl TransferProgress {
+    /// Part of the transfer done, between 0 and 1, if the total is known.
+    pub fn fraction(&self) -> Option<f64> {
+        match self.total {
+            Some(0) => Some(1.0),
+            Some(total) => Some(self.transferred as f64 / total as f64),
+            None => None,
+        }
+    }
+}
+
+/// Registries answer `toomanyrequests` when their rate limit is reached, the
+/// daemon passes the message on.
+fn is_rate_limit_message(msg: &str) -> bool {
+    msg.contains("toomanyrequests")
+}
+
+/// Error for a failed response of an endpoint hitting a registry, with the
+/// rate limit responses mapped to a `TooManyRequestsError`. Daemons behind
+/// a proxy can answer a 429 themselves, with a `Retry-After` in seconds.
+fn registry_error(resp: Response) -> DockerApiError {
+    if resp.status_code == 429 {
+        let retry_after = resp
+            .header("Retry-After")
+            .and_then(|secs| secs.trim().parse::<u64>().ok())
+            .map(Duration::from_secs);
+        return DockerApiError::TooManyRequestsError(retry_after, resp.body);
+    }
+    if is_rate_limit_message(&resp.body) {
+        return DockerApiError::TooManyRequestsError(None, resp.body);
+    }
+
+    DockerApiError::from_response(ApiObject::Image, resp.status_code, resp.body)
+}
+
+/// A result of an image search on Docker Hub.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct ImageSearchResult {
+    pub name: String,
+
+    #[serde(default)]
+    pub description: String,
+
+    #[serde(default)]
+    pub star_count: u64,
+
+    #[serde(default)]
+    pub is_official: bool,
+
+    #[serde(default)]
+    pub is_automated: bool,
+}
+
+/// Filters of the image list, the values of a filter are ORed and the
+/// filters ANDed by the daemon.
+///
+/// ```rust
+/// extern crate rust_docker;
+///
+/// use rust_docker::api::images::ImageFilters;
+///
+/// let filters = ImageFilters::new()
+///     .dangling(false)
+///     .reference("nginx:1.*")
+///     .label("com.example.team", Some("web"));
+///
+/// assert_eq!(
+///     filters.to_json(),
+///     r#"{"dangling":["false"],"label":["com.example.team=web"],"reference":["nginx:1.*"]}"#
+/// );
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct ImageFilters {
+    filters: BTreeMap<String, Vec<String>>,
+}
+
+impl ImageFilters {
+    pub fn new() -> ImageFilters {
+        ImageFilters::default()
+    }
+
+    fn add(mut self, key: &str, value: &str) -> ImageFilters {
+        self.filters
+            .entry(key.to_string())
+            .or_default()
+            .push(value.to_string());
+        self
+    }
+
+    /// Only the untagged images if true, only the tagged ones if false.
+    pub fn dangling(mut self, dangling: bool) -> ImageFilters {
+        self.filters
+            .insert("dangling".to_string(), vec![dangling.to_string()]);
+        self
+    }
+
+    /// Filters on a label, `key` alone or `key=value`.
+    pub fn label(self, key: &str, value: Option<&str>) -> ImageFilters {
+        match value {
+            Some(value) => self.add("label", &format!("{}={}", key, value)),
+            None => self.add("label", key),
+        }
+    }
+
+    /// Filters on the references of the images, which may have globs like
+    /// `nginx:1.*` or `team/*`.
+    pub fn reference(self, reference: &str) -> ImageFilters {
+        self.add("reference", reference)
+    }
+
+    /// Only the images created before the image with this reference or ID.
+    pub fn before(self, image: &str) -> ImageFilters {
+        self.add("before", image)
+    }
+
+    /// Only the images created after the image with this reference or ID.
+    pub fn since(self, image: &str) -> ImageFilters {
+        self.add("since", image)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.filters.is_empty()
+    }
+
+    /// JSON encoded filters as expected by the daemon.
+    pub fn to_json(&self) -> String {
+        json!(self.filters).to_string()
+    }
+}
+
+/// An image removed or untagged by `Images::prune_images`.
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "PascalCase")]
+pub struct ImageDeleteItem {
+    pub untagged: Option<String>,
+    pub deleted: Option<String>,
+}
+
+/// What `Images::prune_images` removed.
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "PascalCase")]
+pub struct ImagesPruneReport {
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub images_deleted: Vec<ImageDeleteItem>,
+    #[serde(default)]
+    pub space_reclaimed: u64,
 }
 
 pub trait Images: DockerApiClient {
@@ -30,7 +603,7 @@ pub trait Images: DockerApiClient {
     ///
     /// # Example
     ///
-    /// ```rust
+    /// ```rust,no_run
     /// extern crate rust_docker;
     ///
     /// use rust_docker::api::images::Images;
@@ -66,22 +639,655 @@ pub trait Images: DockerApiClient {
         let resp =
             self.get_response_from_api(api_endpoint, method, query_params)?;
         if resp.status_code != 200 {
-            return Err(DockerApiError::InvalidApiResponseError(
+            return Err(DockerApiError::from_response(
+                ApiObject::Image,
                 resp.status_code,
                 resp.body,
             ));
         }
 
         let images_info: Vec<ImageCompactInfo> =
-            match serde_json::from_str(&resp.body) {
-                Ok(info) => info,
-                Err(err) => {
-                    return Err(DockerApiError::JsonDeserializationError(err))
+            deserialize_body(&resp.body, self.is_strict())?;
+
+        Ok(images_info)
+    }
+
+    /// Lists the images matching the filters, the intermediate images are
+    /// not listed.
+    ///
+    /// ```rust,ignore
+    /// let filters = ImageFilters::new().dangling(true);
+    /// for image in client.list_images_filtered(&filters)? {
+    ///     println!("{} {} bytes", image.id, image.size);
+    /// }
+    /// ```
+    fn list_images_filtered(
+        &self,
+        filters: &ImageFilters,
+    ) -> Result<Vec<ImageCompactInfo>, DockerApiError> {
+        let api_endpoint = "/images/json";
+        let method = "GET";
+        let query_params = if filters.is_empty() {
+            String::new()
+        } else {
+            format!(
+                "?filters={}",
+                utils::encode_query_value(&filters.to_json())
+            )
+        };
+
+        let resp =
+            self.get_response_from_api(api_endpoint, method, &query_params)?;
+        if resp.status_code != 200 {
+            return Err(DockerApiError::from_response(
+                ApiObject::Image,
+                resp.status_code,
+                resp.body,
+            ));
+        }
+
+        deserialize_body(&resp.body, self.is_strict())
+    }
+
+    /// Lists the tags of `repository` available locally, sorted by tag.
+    /// The repository is normalized like the references, so `nginx` and
+    /// `docker.io/library/nginx` are the same.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// extern crate rust_docker;
+    ///
+    /// use rust_docker::api::images::Images;
+    /// use rust_docker::client::DockerClient;
+    ///
+    /// let client = match DockerClient::new("unix:///var/run/docker.sock") {
+    ///     Ok(a) => a,
+    ///     Err(err) => {
+    ///         println!("{}", err);
+    ///         std::process::exit(1);
+    ///     }
+    /// };
+    ///
+    /// match client.list_image_tags("nginx") {
+    ///     Ok(tags) => for tag in tags {
+    ///         println!("{} {} bytes", tag.tag, tag.size);
+    ///     },
+    ///     Err(err) => println!("An error occured : {}", err),
+    /// }
+    /// ```
+    fn list_image_tags(
+        &self,
+        repository: &str,
+    ) -> Result<Vec<ImageTag>, DockerApiError> {
+        let repository = Reference::parse(repository)?;
+        let api_endpoint = "/images/json";
+        let method = "GET";
+        let filters =
+            json!({ "reference": [repository.familiar_name()] }).to_string();
+        let query_params =
+            format!("?filters={}", utils::encode_query_value(&filters));
+
+        let resp =
+            self.get_response_from_api(api_endpoint, method, &query_params)?;
+        if resp.status_code != 200 {
+            return Err(DockerApiError::from_response(
+                ApiObject::Image,
+                resp.status_code,
+                resp.body,
+            ));
+        }
+
+        let images: Vec<ImageCompactInfo> =
+            deserialize_body(&resp.body, self.is_strict())?;
+
+        // The images listed can also have tags of other repositories.
+        let mut tags: Vec<ImageTag> = Vec::new();
+        for image in &images {
+            for repo_tag in &image.repo_tags {
+                let reference = match Reference::parse(repo_tag) {
+                    Ok(reference) => reference,
+                    Err(_) => continue,
+                };
+                if reference.full_name() != repository.full_name() {
+                    continue;
+                }
+                if let Some(ref tag) = reference.tag {
+                    tags.push(ImageTag {
+                        tag: tag.clone(),
+                        id: image.id.clone(),
+                        created: image.created,
+                        size: image.size,
+                    });
+                }
+            }
+        }
+        tags.sort_by(|a, b| a.tag.cmp(&b.tag));
+        tags.dedup_by(|a, b| a.tag == b.tag);
+
+        Ok(tags)
+    }
+
+    /// Inspects the image with the provided name or ID
+    /// Returns Low level information about the image.
+    fn inspect_image(
+        &self,
+        name: &str,
+    ) -> Result<ImageDetails, DockerApiError> {
+        let api_endpoint = format!("/images/{name}/json", name = name);
+        let method = "GET";
+
+        let resp = self.get_response_from_api(&api_endpoint, method, "")?;
+
+        if resp.status_code != 200 {
+            return Err(DockerApiError::from_response(
+                ApiObject::Image,
+                resp.status_code,
+                resp.body,
+            ));
+        }
+
+        deserialize_body(&resp.body, self.is_strict())
+    }
+
+    /// Checks if the image with the provided reference exists locally.
+    /// A 404 from the daemon means the image is missing, it is not treated
+    /// as an error.
+    fn image_exists(&self, reference: &str) -> Result<bool, DockerApiError> {
+        let api_endpoint = format!("/images/{name}/json", name = reference);
+        let method = "GET";
+
+        let resp = self.get_response_from_api(&api_endpoint, method, "")?;
+
+        match resp.status_code {
+            200 => Ok(true),
+            404 => Ok(false),
+            _ => Err(DockerApiError::from_response(
+                ApiObject::Image,
+                resp.status_code,
+                resp.body,
+            )),
+        }
+    }
+
+    /// Pulls the image with the provided reference from the registry.
+    /// When no tag is given in the reference, `latest` is pulled. Invalid
+    /// references fail with an `ImageError` without reaching the daemon.
+    ///
+    /// The daemon reports failures of the pull in the progress stream itself
+    /// with a 200 status code, these are returned as an `ImageError`, or a
+    /// `TooManyRequestsError` when the rate limit of the registry is hit.
+    ///
+    /// The pull waits on the rate limiter of the client, if any.
+    fn pull_image(
+        &self,
+        reference: &str,
+        auth: Option<&RegistryAuth>,
+    ) -> Result<Vec<ImageProgress>, DockerApiError> {
+        self.pull_image_for_platform(reference, None, auth)
+    }
+
+    /// Same as `pull_image`, pulling the variant of a multi-arch image for
+    /// the platform instead of the one of the daemon when given.
+    fn pull_image_for_platform(
+        &self,
+        reference: &str,
+        platform: Option<&Platform>,
+        auth: Option<&RegistryAuth>,
+    ) -> Result<Vec<ImageProgress>, DockerApiError> {
+        let reference = Reference::parse(reference)?;
+        let mut api_endpoint = format!(
+            "/images/create?fromImage={}&tag={}",
+            reference.familiar_name(),
+            reference.tag_or_digest()
+        );
+        if let Some(platform) = platform {
+            api_endpoint.push_str(&format!(
+                "&platform={}",
+                utils::encode_query_value(&platform.to_string())
+            ));
+        }
+        let method = "POST";
+
+        let encoded_auth = match auth {
+            Some(auth) => auth.encode()?,
+            None => String::new(),
+        };
+        let mut headers: Vec<(&str, &str)> = Vec::new();
+        if auth.is_some() {
+            headers.push(("X-Registry-Auth", &encoded_auth));
+        }
+
+        self.wait_for_rate_limit();
+        let resp = self.get_response_from_api_with_headers(
+            &api_endpoint,
+            method,
+            "",
+            &headers,
+        )?;
+
+        if resp.status_code != 200 {
+            return Err(registry_error(resp));
+        }
+
+        parse_progress(&resp.body)
+    }
+
+    /// Asks the registry of the reference for the digest and platforms of
+    /// the image, like `docker buildx imagetools inspect`. The request
+    /// waits on the rate limiter of the client, if any.
+    fn inspect_distribution(
+        &self,
+        reference: &str,
+        auth: Option<&RegistryAuth>,
+    ) -> Result<DistributionInspect, DockerApiError> {
+        let reference = Reference::parse(reference)?;
+        let api_endpoint = format!("/distribution/{}/json", reference);
+        let method = "GET";
+
+        let encoded_auth = match auth {
+            Some(auth) => auth.encode()?,
+            None => String::new(),
+        };
+        let mut headers: Vec<(&str, &str)> = Vec::new();
+        if auth.is_some() {
+            headers.push(("X-Registry-Auth", &encoded_auth));
+        }
+
+        self.wait_for_rate_limit();
+        let resp = self.get_response_from_api_with_headers(
+            &api_endpoint,
+            method,
+            "",
+            &headers,
+        )?;
+
+        if resp.status_code != 200 {
+            return Err(registry_error(resp));
+        }
+
+        deserialize_body(&resp.body, self.is_strict())
+    }
+
+    /// Tags the image `image`, a name or an ID, as `target`. The target
+    /// reference is checked before the request is sent.
+    fn tag_image(
+        &self,
+        image: &str,
+        target: &str,
+    ) -> Result<(), DockerApiError> {
+        let target = Reference::parse(target)?;
+        let api_endpoint = format!(
+            "/images/{image}/tag?repo={repo}&tag={tag}",
+            image = image,
+            repo = target.familiar_name(),
+            tag = target.tag_or_digest()
+        );
+        let method = "POST";
+
+        let resp = self.get_response_from_api(&api_endpoint, method, "")?;
+
+        if resp.status_code != 201 {
+            return Err(DockerApiError::from_response(
+                ApiObject::Image,
+                resp.status_code,
+                resp.body,
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Creates an image from the container with its changes, like
+    /// `docker commit`, giving the ID of the image.
+    fn commit_container(
+        &self,
+        container: &str,
+        opts: &CommitOptions,
+    ) -> Result<ImageId, DockerApiError> {
+        let mut api_endpoint = format!(
+            "/commit?container={}",
+            utils::encode_query_value(container)
+        );
+        if let Some(ref reference) = opts.reference {
+            let reference = Reference::parse(reference)?;
+            api_endpoint.push_str(&format!(
+                "&repo={}&tag={}",
+                utils::encode_query_value(&reference.familiar_name()),
+                utils::encode_query_value(reference.tag_or_digest())
+            ));
+        }
+        if let Some(ref comment) = opts.comment {
+            api_endpoint.push_str(&format!(
+                "&comment={}",
+                utils::encode_query_value(comment)
+            ));
+        }
+        if let Some(ref author) = opts.author {
+            api_endpoint.push_str(&format!(
+                "&author={}",
+                utils::encode_query_value(author)
+            ));
+        }
+        if let Some(pause) = opts.pause {
+            api_endpoint.push_str(&format!("&pause={}", pause));
+        }
+        for change in &opts.changes {
+            api_endpoint.push_str(&format!(
+                "&changes={}",
+                utils::encode_query_value(change)
+            ));
+        }
+        let method = "POST";
+
+        let resp = self.get_response_from_api(&api_endpoint, method, "{}")?;
+
+        if resp.status_code != 201 {
+            return Err(DockerApiError::from_response(
+                ApiObject::Container,
+                resp.status_code,
+                resp.body,
+            ));
+        }
+
+        let commit: CommitResponse = deserialize_body(&resp.body, false)?;
+        Ok(commit.id)
+    }
+
+    /// Pushes the image with the provided reference to its registry, the
+    /// credentials are needed for most registries. The push waits on the
+    /// rate limiter of the client, if any.
+    fn push_image(
+        &self,
+        reference: &str,
+        auth: Option<&RegistryAuth>,
+    ) -> Result<Vec<ImageProgress>, DockerApiError> {
+        let reference = Reference::parse(reference)?;
+        let api_endpoint = format!(
+            "/images/{name}/push?tag={tag}",
+            name = reference.familiar_name(),
+            tag = reference.tag_or_digest()
+        );
+        let method = "POST";
+
+        // The daemon refuses pushes without the header, even for registries
+        // which do not need credentials.
+        let encoded_auth = match auth {
+            Some(auth) => auth.encode()?,
+            None => RegistryAuth::default().encode()?,
+        };
+
+        self.wait_for_rate_limit();
+        let resp = self.get_response_from_api_with_headers(
+            &api_endpoint,
+            method,
+            "",
+            &[("X-Registry-Auth", &encoded_auth)],
+        )?;
+
+        if resp.status_code != 200 {
+            return Err(registry_error(resp));
+        }
+
+        parse_progress(&resp.body)
+    }
+
+    /// Searches Docker Hub for images matching `term`, at most `limit` of
+    /// them. The search waits on the rate limiter of the client, if any.
+    fn search_images(
+        &self,
+        term: &str,
+        limit: Option<u32>,
+    ) -> Result<Vec<ImageSearchResult>, DockerApiError> {
+        let api_endpoint = "/images/search";
+        let method = "GET";
+        let mut query_params =
+            format!("?term={}", utils::encode_query_value(term));
+        if let Some(limit) = limit {
+            query_params.push_str(&format!("&limit={}", limit));
+        }
+
+        self.wait_for_rate_limit();
+        let resp =
+            self.get_response_from_api(api_endpoint, method, &query_params)?;
+
+        if resp.status_code != 200 {
+            return Err(registry_error(resp));
+        }
+
+        deserialize_body(&resp.body, self.is_strict())
+    }
+
+    /// Pulls the image with the provided reference only if it does not exist
+    /// locally. Returns true if the image had to be pulled.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// extern crate rust_docker;
+    ///
+    /// use rust_docker::api::images::Images;
+    /// use rust_docker::client::DockerClient;
+    ///
+    /// let client = match DockerClient::new("unix:///var/run/docker.sock") {
+    ///     Ok(a) => a,
+    ///     Err(err) => {
+    ///         println!("{}", err);
+    ///         std::process::exit(1);
+    ///     }
+    /// };
+    ///
+    /// match client.ensure_image("debian:jessie", None) {
+    ///     Ok(pulled) => println!("Image pulled : {}", pulled),
+    ///     Err(err) => println!("An error occured : {}", err),
+    /// }
+    /// ```
+    fn ensure_image(
+        &self,
+        reference: &str,
+        auth: Option<&RegistryAuth>,
+    ) -> Result<bool, DockerApiError> {
+        if self.image_exists(reference)? {
+            return Ok(false);
+        }
+
+        self.pull_image(reference, auth)?;
+        Ok(true)
+    }
+
+    /// Removes the image with the provided name or ID. Removing a tag of an
+    /// image with other tags only untags it, `force` removes the image even
+    /// if it has several tags or is used by a stopped container.
+    fn remove_image(
+        &self,
+        image: &str,
+        force: bool,
+    ) -> Result<(), DockerApiError> {
+        let api_endpoint = format!("/images/{image}", image = image);
+        let method = "DELETE";
+        let query_params = format!("?force={}", force);
+
+        let resp =
+            self.get_response_from_api(&api_endpoint, method, &query_params)?;
+
+        if resp.status_code != 200 {
+            return Err(DockerApiError::from_response(
+                ApiObject::Image,
+                resp.status_code,
+                resp.body,
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Writes the image, with all its layers and tags, as a tar archive to
+    /// `out`, like `docker save`. The archive is copied as the daemon writes
+    /// it, so it does not have to fit in memory. Gives the number of bytes
+    /// written.
+    fn export_image(
+        &self,
+        image: &str,
+        out: &mut dyn Write,
+    ) -> Result<u64, DockerApiError>
+    where
+        Self: Sized,
+    {
+        self.export_image_with_progress(image, out, |_| ())
+    }
+
+    /// Same as `export_image`, calling `on_progress` each time a part of the
+    /// archive is written, the archives of large images taking a while.
+    ///
+    /// ```rust,ignore
+    /// let mut file = File::create("app.tar")?;
+    /// client.export_image_with_progress("app:1.0", &mut file, |progress| {
+    ///     match progress.fraction() {
+    ///         Some(fraction) => println!("{:.0}%", fraction * 100.0),
+    ///         None => println!("{} bytes", progress.transferred),
+    ///     }
+    /// })?;
+    /// ```
+    fn export_image_with_progress<F>(
+        &self,
+        image: &str,
+        out: &mut dyn Write,
+        mut on_progress: F,
+    ) -> Result<u64, DockerApiError>
+    where
+        Self: Sized,
+        F: FnMut(&TransferProgress),
+    {
+        let mut resp = self.image_archive(image)?;
+
+        let mut progress = TransferProgress {
+            transferred: 0,
+            total: resp
+                .headers
+                .get("content-length")
+                .and_then(|len| len.parse().ok()),
+        };
+        let mut buffer = vec![0; 64 * 1024];
+        loop {
+            let len = match resp.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(len) => len,
+                Err(_) => {
+                    return Err(DockerApiError::HTTPResponseParseError(
+                        "Error while reading the image archive",
+                    ))
                 }
             };
+            out.write_all(&buffer[..len])
+                .map_err(DockerApiError::IoError)?;
 
-        Ok(images_info)
+            progress.transferred += len as u64;
+            on_progress(&progress);
+        }
+
+        Ok(progress.transferred)
+    }
+
+    /// The archive of the image, like the one of `export_image`, as the
+    /// daemon writes it.
+    fn image_archive(
+        &self,
+        image: &str,
+    ) -> Result<StreamResponse, DockerApiError> {
+        let api_endpoint = format!("/images/{image}/get", image = image);
+        let method = "GET";
+
+        let resp = self.get_stream_from_api(&api_endpoint, method, "", &[])?;
+
+        if resp.status_code != 200 {
+            let status_code = resp.status_code;
+            return Err(DockerApiError::from_response(
+                ApiObject::Image,
+                status_code,
+                resp.body_to_string()?,
+            ));
+        }
+
+        Ok(resp)
+    }
+
+    /// Loads the images of an archive made by `docker save`, like
+    /// `docker load`. The archive is sent as it is read.
+    fn load_image(
+        &self,
+        archive: &mut dyn Read,
+    ) -> Result<Vec<ImageProgress>, DockerApiError> {
+        let api_endpoint = "/images/load";
+        let method = "POST";
+
+        let request = RequestBuilder::new(method, api_endpoint)
+            .header("Content-Type", "application/x-tar")
+            .chunked();
+        let resp = self.send_upload(&request, archive)?;
+
+        if resp.status_code != 200 {
+            let status_code = resp.status_code;
+            return Err(DockerApiError::from_response(
+                ApiObject::Image,
+                status_code,
+                resp.body_to_string()?,
+            ));
+        }
+
+        parse_progress(&resp.body_to_string()?)
     }
 
     fn build_image_from_tarball(&self, _tar_path: &str) {}
+
+    /// Removes the dangling images matching the filters, like
+    /// `docker image prune`, or all the images not used by a container
+    /// with `all`.
+    fn prune_images(
+        &self,
+        filters: &PruneFilters,
+        all: bool,
+    ) -> Result<ImagesPruneReport, DockerApiError> {
+        let dangling: &[(&str, &str)] =
+            if all { &[("dangling", "false")] } else { &[] };
+        let api_endpoint =
+            format!("/images/prune{}", filters.to_query(dangling));
+        let method = "POST";
+
+        let resp = self.get_response_from_api(&api_endpoint, method, "")?;
+
+        if resp.status_code != 200 {
+            return Err(DockerApiError::from_response(
+                ApiObject::Image,
+                resp.status_code,
+                resp.body,
+            ));
+        }
+
+        deserialize_body(&resp.body, self.is_strict())
+    }
+}
+
+/// Copies the image from the daemon of `src` to the one of `dst`, like
+/// `docker save | docker -H dst load`. The archive goes from one daemon to
+/// the other as it is read, without being stored on disk or in memory.
+/// Gives the messages of the load, see `ImageProgress::loaded_image`.
+///
+/// ```rust,ignore
+/// for msg in copy_image(&registry_host, &edge_host, "app:1.0")? {
+///     if let Some(image) = msg.loaded_image() {
+///         println!("Copied {}", image);
+///     }
+/// }
+/// ```
+pub fn copy_image<S, D>(
+    src: &S,
+    dst: &D,
+    reference: &str,
+) -> Result<Vec<ImageProgress>, DockerApiError>
+where
+    S: Images,
+    D: Images,
+{
+    let mut archive = src.image_archive(reference)?;
+    dst.load_image(&mut archive)
 }
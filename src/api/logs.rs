@@ -0,0 +1,746 @@
+use std::collections::VecDeque;
+use std::fs;
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, Utc};
+#[cfg(feature = "regex")]
+use regex::Regex;
+
+use api::ids::ContainerRef;
+use api::DockerApiClient;
+use utils::{parse_rfc3339, StreamHandle, StreamResponse};
+
+use errors::{ApiObject, DockerApiError};
+
+/// The stream of the container a log output was written to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StreamType {
+    Stdin,
+    Stdout,
+    Stderr,
+}
+
+impl StreamType {
+    fn index(self) -> usize {
+        match self {
+            StreamType::Stdin => 0,
+            StreamType::Stdout => 1,
+            StreamType::Stderr => 2,
+        }
+    }
+}
+
+/// A single line of the container logs, without the trailing newline.
+///
+/// * timestamp: Time the line was written at, when the logs are requested
+///   with `timestamps`. The timestamp is removed from the message.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogLine {
+    pub stream: StreamType,
+    pub message: String,
+    pub timestamp: Option<SystemTime>,
+}
+
+impl LogLine {
+    /// Creates a line from the output of the container, splitting the
+    /// timestamp prefix from the message if `timestamps` is set.
+    fn new(stream: StreamType, line: &str, timestamps: bool) -> LogLine {
+        let line = line.trim_end_matches('\r');
+
+        if timestamps {
+            if let Some((prefix, message)) = line.split_once(' ') {
+                if let Some(timestamp) = parse_rfc3339(prefix) {
+                    return LogLine {
+                        stream,
+                        message: message.to_string(),
+                        timestamp: Some(timestamp),
+                    };
+                }
+            }
+        }
+
+        LogLine {
+            stream,
+            message: line.to_string(),
+            timestamp: None,
+        }
+    }
+
+    /// The timestamp of the line as a chrono `DateTime`.
+    #[cfg(feature = "chrono")]
+    pub fn datetime(&self) -> Option<DateTime<Utc>> {
+        self.timestamp.map(DateTime::<Utc>::from)
+    }
+}
+
+/// Query options for the logs endpoint.
+///
+/// * follow: Keep streaming the logs as the container writes them.
+/// * stdout, stderr: Streams to get the logs from, both by default.
+/// * timestamps: Prefix every line with its RFC3339 timestamp.
+/// * since: Only the logs since this unix timestamp.
+/// * tail: Only this number of lines from the end of the logs.
+#[derive(Debug, Clone)]
+pub struct LogsOptions {
+    pub follow: bool,
+    pub stdout: bool,
+    pub stderr: bool,
+    pub timestamps: bool,
+    pub since: Option<i64>,
+    pub tail: Option<u64>,
+}
+
+impl Default for LogsOptions {
+    fn default() -> LogsOptions {
+        LogsOptions {
+            follow: false,
+            stdout: true,
+            stderr: true,
+            timestamps: false,
+            since: None,
+            tail: None,
+        }
+    }
+}
+
+impl LogsOptions {
+    fn to_query(&self) -> String {
+        let mut query = format!(
+            "?follow={}&stdout={}&stderr={}&timestamps={}",
+            self.follow, self.stdout, self.stderr, self.timestamps
+        );
+        if let Some(since) = self.since {
+            query.push_str(&format!("&since={}", since));
+        }
+        if let Some(tail) = self.tail {
+            query.push_str(&format!("&tail={}", tail));
+        }
+
+        query
+    }
+}
+
+/// A frame of the container output, borrowed from the buffer of the
+/// `LogFrames` it was read from.
+#[derive(Debug, PartialEq)]
+pub struct LogFrame<'a> {
+    pub stream: StreamType,
+    pub payload: &'a [u8],
+}
+
+/// Reader of the raw frames of the container logs.
+///
+/// When the container has no TTY the daemon multiplexes stdout and stderr
+/// in frames with an 8 byte header, `[stream, 0, 0, 0, size(4 bytes BE)]`.
+/// With a TTY the output is sent as is and is reported as stdout, in chunks
+/// of whatever was read from the connection.
+///
+/// The payloads are read into a buffer which is reused for every frame, so
+/// no allocation happens per frame once the buffer has grown to the size of
+/// the largest frame.
+pub struct LogFrames<R: BufRead> {
+    reader: R,
+    buf: Vec<u8>,
+    multiplexed: Option<bool>,
+}
+
+impl<R: BufRead> LogFrames<R> {
+    pub fn new(reader: R) -> LogFrames<R> {
+        LogFrames {
+            reader,
+            buf: Vec::new(),
+            multiplexed: None,
+        }
+    }
+
+    /// Multiplexed streams start with a frame header, whose first byte is
+    /// the stream type followed by three zero bytes.
+    fn is_multiplexed(&mut self) -> Result<bool, DockerApiError> {
+        if let Some(multiplexed) = self.multiplexed {
+            return Ok(multiplexed);
+        }
+
+        let multiplexed = match self.reader.fill_buf() {
+            Ok(buf) => {
+                !buf.is_empty()
+                    && buf[0] <= 2
+                    && buf.iter().skip(1).take(3).all(|b| *b == 0)
+            }
+            Err(_) => {
+                return Err(DockerApiError::HTTPResponseParseError(
+                    "Error while reading logs stream",
+                ))
+            }
+        };
+        self.multiplexed = Some(multiplexed);
+
+        Ok(multiplexed)
+    }
+
+    /// Reads the next frame, None at the end of the stream.
+    pub fn next_frame(
+        &mut self,
+    ) -> Result<Option<LogFrame<'_>>, DockerApiError> {
+        let read_err = DockerApiError::HTTPResponseParseError(
+            "Error while reading logs stream",
+        );
+
+        if !self.is_multiplexed()? {
+            let len = match self.reader.fill_buf() {
+                Ok(available) => {
+                    self.buf.clear();
+                    self.buf.extend_from_slice(available);
+                    available.len()
+                }
+                Err(_) => return Err(read_err),
+            };
+            self.reader.consume(len);
+
+            if len == 0 {
+                return Ok(None);
+            }
+            return Ok(Some(LogFrame {
+                stream: StreamType::Stdout,
+                payload: &self.buf,
+            }));
+        }
+
+        let mut header = [0; 8];
+        let mut read = 0;
+        while read < header.len() {
+            match self.reader.read(&mut header[read..]) {
+                Ok(0) if read == 0 => return Ok(None),
+                Ok(0) => return Err(read_err),
+                Ok(len) => read += len,
+                Err(_) => return Err(read_err),
+            }
+        }
+
+        let stream = match header[0] {
+            0 => StreamType::Stdin,
+            1 => StreamType::Stdout,
+            _ => StreamType::Stderr,
+        };
+        let size =
+            u32::from_be_bytes([header[4], header[5], header[6], header[7]]);
+
+        self.buf.resize(size as usize, 0);
+        match self.reader.read_exact(&mut self.buf) {
+            Ok(_) => Ok(Some(LogFrame {
+                stream,
+                payload: &self.buf,
+            })),
+            Err(_) => Err(read_err),
+        }
+    }
+}
+
+impl LogFrames<StreamResponse> {
+    /// Handle to stop following the logs from another thread.
+    pub fn handle(&self) -> StreamHandle {
+        self.reader.handle()
+    }
+}
+
+/// Removes the RFC3339 timestamp and the space after it from the start of
+/// the output, if it starts with one.
+fn strip_timestamp(output: &[u8]) -> &[u8] {
+    let end = match output.iter().take(40).position(|b| *b == b' ') {
+        Some(end) => end,
+        None => return output,
+    };
+    match ::std::str::from_utf8(&output[..end])
+        .ok()
+        .and_then(parse_rfc3339)
+    {
+        Some(_) => &output[end + 1..],
+        None => output,
+    }
+}
+
+/// Iterator over the lines of the container logs, built on `LogFrames`.
+pub struct ContainerLogs {
+    frames: LogFrames<StreamResponse>,
+    pending: VecDeque<LogLine>,
+    partial: [Vec<u8>; 3],
+    timestamps: bool,
+    done: bool,
+}
+
+impl ContainerLogs {
+    /// Creates the iterator over the frames, `timestamps` tells if the lines
+    /// are prefixed by their timestamp.
+    pub fn new(
+        frames: LogFrames<StreamResponse>,
+        timestamps: bool,
+    ) -> ContainerLogs {
+        ContainerLogs {
+            frames,
+            pending: VecDeque::new(),
+            partial: [Vec::new(), Vec::new(), Vec::new()],
+            timestamps,
+            done: false,
+        }
+    }
+
+    /// Handle to stop following the logs from another thread.
+    pub fn handle(&self) -> StreamHandle {
+        self.frames.handle()
+    }
+
+    /// Joins the lines continuing a record into it, see `LogRecords`.
+    pub fn records<F>(self, is_continuation: F) -> LogRecords<ContainerLogs, F>
+    where
+        F: FnMut(&str) -> bool,
+    {
+        LogRecords::new(self, is_continuation)
+    }
+
+    /// Splits the output into lines, keeping the last incomplete line of
+    /// each stream until the rest of it is read.
+    ///
+    /// The daemon splits the lines longer than 16KB in several frames, each
+    /// one with its own timestamp, the timestamps of the frames continuing
+    /// a line are dropped.
+    fn push_output(
+        pending: &mut VecDeque<LogLine>,
+        partial: &mut Vec<u8>,
+        stream: StreamType,
+        output: &[u8],
+        timestamps: bool,
+    ) {
+        let mut rest = output;
+        if timestamps && !partial.is_empty() {
+            rest = strip_timestamp(rest);
+        }
+        while let Some(pos) = rest.iter().position(|b| *b == b'\n') {
+            // The line is only copied into the message of the LogLine.
+            if partial.is_empty() {
+                let line = String::from_utf8_lossy(&rest[..pos]);
+                pending.push_back(LogLine::new(stream, &line, timestamps));
+            } else {
+                partial.extend_from_slice(&rest[..pos]);
+                pending.push_back(LogLine::new(
+                    stream,
+                    &String::from_utf8_lossy(partial),
+                    timestamps,
+                ));
+                partial.clear();
+            }
+            rest = &rest[pos + 1..];
+        }
+        partial.extend_from_slice(rest);
+    }
+
+    fn flush_partial(&mut self) {
+        for stream in
+            &[StreamType::Stdin, StreamType::Stdout, StreamType::Stderr]
+        {
+            let partial = &mut self.partial[stream.index()];
+            if !partial.is_empty() {
+                let line = String::from_utf8_lossy(partial);
+                self.pending.push_back(LogLine::new(
+                    *stream,
+                    &line,
+                    self.timestamps,
+                ));
+                partial.clear();
+            }
+        }
+    }
+}
+
+impl Iterator for ContainerLogs {
+    type Item = Result<LogLine, DockerApiError>;
+
+    fn next(&mut self) -> Option<Result<LogLine, DockerApiError>> {
+        loop {
+            if let Some(line) = self.pending.pop_front() {
+                return Some(Ok(line));
+            }
+
+            if self.done {
+                return None;
+            }
+
+            let cancelled = self.handle().is_cancelled();
+            match self.frames.next_frame() {
+                Ok(Some(frame)) => ContainerLogs::push_output(
+                    &mut self.pending,
+                    &mut self.partial[frame.stream.index()],
+                    frame.stream,
+                    frame.payload,
+                    self.timestamps,
+                ),
+                Ok(None) => {
+                    self.done = true;
+                    self.flush_partial();
+                }
+                Err(err) => {
+                    self.done = true;
+                    if cancelled || self.handle().is_cancelled() {
+                        return None;
+                    }
+                    return Some(Err(err));
+                }
+            }
+        }
+    }
+}
+
+/// Iterator joining the lines of the logs into records spanning several
+/// lines, like stack traces. The lines for which `is_continuation` is true
+/// are added to the record of the line before them on the same stream,
+/// separated by newlines, and the timestamp of a record is the one of its
+/// first line.
+///
+/// A record is only complete once the line after it is read, so when
+/// following the logs the last record is given with the next line.
+///
+/// ```rust,ignore
+/// let records = client
+///     .get_container_logs("web", &options)?
+///     .records(is_stack_trace_continuation);
+/// for record in records {
+///     println!("{}", record?.message);
+/// }
+/// ```
+pub struct LogRecords<I, F> {
+    lines: I,
+    is_continuation: F,
+    pending: Option<LogLine>,
+    error: Option<DockerApiError>,
+    done: bool,
+}
+
+impl<I, F> LogRecords<I, F>
+where
+    I: Iterator<Item = Result<LogLine, DockerApiError>>,
+    F: FnMut(&str) -> bool,
+{
+    pub fn new(lines: I, is_continuation: F) -> LogRecords<I, F> {
+        LogRecords {
+            lines,
+            is_continuation,
+            pending: None,
+            error: None,
+            done: false,
+        }
+    }
+}
+
+impl<I, F> Iterator for LogRecords<I, F>
+where
+    I: Iterator<Item = Result<LogLine, DockerApiError>>,
+    F: FnMut(&str) -> bool,
+{
+    type Item = Result<LogLine, DockerApiError>;
+
+    fn next(&mut self) -> Option<Result<LogLine, DockerApiError>> {
+        if let Some(err) = self.error.take() {
+            return Some(Err(err));
+        }
+
+        loop {
+            if self.done {
+                return self.pending.take().map(Ok);
+            }
+
+            let line = match self.lines.next() {
+                Some(Ok(line)) => line,
+                Some(Err(err)) => match self.pending.take() {
+                    Some(record) => {
+                        self.error = Some(err);
+                        return Some(Ok(record));
+                    }
+                    None => return Some(Err(err)),
+                },
+                None => {
+                    self.done = true;
+                    continue;
+                }
+            };
+
+            if let Some(ref mut record) = self.pending {
+                if record.stream == line.stream
+                    && (self.is_continuation)(&line.message)
+                {
+                    record.message.push('\n');
+                    record.message.push_str(&line.message);
+                    continue;
+                }
+            }
+            if let Some(record) = self.pending.replace(line) {
+                return Some(Ok(record));
+            }
+        }
+    }
+}
+
+/// Continuation of the stack traces of most languages: indented lines,
+/// like the frames of Java and Python, and the `Caused by:` of Java.
+pub fn is_stack_trace_continuation(line: &str) -> bool {
+    line.starts_with(' ')
+        || line.starts_with('\t')
+        || line.starts_with("Caused by:")
+}
+
+/// Continuation matching the lines with the regex, for `LogRecords`.
+#[cfg(feature = "regex")]
+pub fn continuation_regex(regex: Regex) -> impl FnMut(&str) -> bool {
+    move |line| regex.is_match(line)
+}
+
+/// When the log file written by `pipe_logs_to_file` is rotated.
+///
+/// * max_size: Size in bytes the file is rotated at, before a line would
+///   make it bigger.
+/// * max_age: Time the file is written to before it is rotated.
+/// * max_files: Rotated files kept, as `path.1` for the most recent one to
+///   `path.N` for the oldest, 5 by default. With 0 the file is truncated.
+#[derive(Debug, Clone)]
+pub struct RotationPolicy {
+    pub max_size: Option<u64>,
+    pub max_age: Option<Duration>,
+    pub max_files: usize,
+}
+
+impl Default for RotationPolicy {
+    fn default() -> RotationPolicy {
+        RotationPolicy {
+            max_size: None,
+            max_age: None,
+            max_files: 5,
+        }
+    }
+}
+
+impl RotationPolicy {
+    pub fn new() -> RotationPolicy {
+        RotationPolicy::default()
+    }
+
+    pub fn max_size(mut self, bytes: u64) -> RotationPolicy {
+        self.max_size = Some(bytes);
+        self
+    }
+
+    pub fn max_age(mut self, age: Duration) -> RotationPolicy {
+        self.max_age = Some(age);
+        self
+    }
+
+    pub fn max_files(mut self, count: usize) -> RotationPolicy {
+        self.max_files = count;
+        self
+    }
+}
+
+/// A file written line by line and rotated following a `RotationPolicy`.
+/// An existing file is appended to.
+pub struct RotatingFile {
+    path: PathBuf,
+    policy: RotationPolicy,
+    file: File,
+    size: u64,
+    opened_at: Instant,
+}
+
+impl RotatingFile {
+    pub fn open(
+        path: &Path,
+        policy: RotationPolicy,
+    ) -> io::Result<RotatingFile> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let size = file.metadata()?.len();
+        Ok(RotatingFile {
+            path: path.to_path_buf(),
+            policy,
+            file,
+            size,
+            opened_at: Instant::now(),
+        })
+    }
+
+    fn rotated_path(&self, index: usize) -> PathBuf {
+        let mut name = self.path.clone().into_os_string();
+        name.push(format!(".{}", index));
+        PathBuf::from(name)
+    }
+
+    /// Renames the file to `path.1`, shifting the older ones, and starts a
+    /// new one.
+    pub fn rotate(&mut self) -> io::Result<()> {
+        if self.policy.max_files == 0 {
+            fs::remove_file(&self.path)?;
+        } else {
+            let oldest = self.rotated_path(self.policy.max_files);
+            if oldest.exists() {
+                fs::remove_file(oldest)?;
+            }
+            for index in (1..self.policy.max_files).rev() {
+                let rotated = self.rotated_path(index);
+                if rotated.exists() {
+                    fs::rename(rotated, self.rotated_path(index + 1))?;
+                }
+            }
+            fs::rename(&self.path, self.rotated_path(1))?;
+        }
+
+        self.file = File::create(&self.path)?;
+        self.size = 0;
+        self.opened_at = Instant::now();
+        Ok(())
+    }
+
+    /// Writes the line followed by a newline, rotating the file first if
+    /// the policy says so.
+    pub fn write_line(&mut self, line: &[u8]) -> io::Result<()> {
+        let len = line.len() as u64 + 1;
+        let too_big = self
+            .policy
+            .max_size
+            .is_some_and(|max| self.size > 0 && self.size + len > max);
+        let too_old = self
+            .policy
+            .max_age
+            .is_some_and(|age| self.opened_at.elapsed() >= age);
+        if too_big || too_old {
+            self.rotate()?;
+        }
+
+        self.file.write_all(line)?;
+        self.file.write_all(b"\n")?;
+        self.size += len;
+        Ok(())
+    }
+}
+
+pub trait Logs: DockerApiClient {
+    /// Get the logs of the container line by line. With `follow` set in the
+    /// options the iterator blocks waiting for new output, use the handle
+    /// from `ContainerLogs::handle` to stop it.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// extern crate rust_docker;
+    ///
+    /// use rust_docker::api::logs::{Logs, LogsOptions};
+    /// use rust_docker::client::DockerClient;
+    ///
+    /// let client = match DockerClient::new("unix:///var/run/docker.sock") {
+    ///     Ok(a) => a,
+    ///     Err(err) => {
+    ///         println!("{}", err);
+    ///         std::process::exit(1);
+    ///     }
+    /// };
+    ///
+    /// let options = LogsOptions {
+    ///     tail: Some(100),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// match client.get_container_logs("f808ca...", &options) {
+    ///     Ok(logs) => for line in logs {
+    ///         println!("{:?}", line);
+    ///     },
+    ///     Err(err) => println!("An error occured : {}", err),
+    /// }
+    /// ```
+    fn get_container_logs(
+        &self,
+        id: impl ContainerRef,
+        options: &LogsOptions,
+    ) -> Result<ContainerLogs, DockerApiError>
+    where
+        Self: Sized,
+    {
+        let id = id.container_ref();
+        Ok(ContainerLogs::new(
+            self.get_container_log_frames(id, options)?,
+            options.timestamps,
+        ))
+    }
+
+    /// Follows the logs of the container, stdout and stderr, and writes
+    /// them line by line to the file at `path`, rotated following the
+    /// policy. Blocks until the container stops, giving the number of
+    /// lines written, so it is usually run on a thread of its own.
+    ///
+    /// ```rust,ignore
+    /// let rotation = RotationPolicy::new().max_size(10 * 1024 * 1024).max_files(3);
+    /// client.pipe_logs_to_file("web", Path::new("/var/log/web.log"), rotation)?;
+    /// ```
+    fn pipe_logs_to_file(
+        &self,
+        id: impl ContainerRef,
+        path: &Path,
+        rotation: RotationPolicy,
+    ) -> Result<u64, DockerApiError>
+    where
+        Self: Sized,
+    {
+        let id = id.container_ref();
+        let options = LogsOptions {
+            follow: true,
+            ..Default::default()
+        };
+        let logs = self.get_container_logs(id, &options)?;
+        let mut file = RotatingFile::open(path, rotation)
+            .map_err(DockerApiError::IoError)?;
+
+        let mut written = 0;
+        for line in logs {
+            file.write_line(line?.message.as_bytes())
+                .map_err(DockerApiError::IoError)?;
+            written += 1;
+        }
+
+        Ok(written)
+    }
+
+    /// Get the logs of the container as raw frames, for consumers which do
+    /// not want to allocate a String per line.
+    ///
+    /// ```rust,ignore
+    /// let mut frames = client.get_container_log_frames(id, &options)?;
+    /// while let Some(frame) = frames.next_frame()? {
+    ///     out.write_all(frame.payload)?;
+    /// }
+    /// ```
+    fn get_container_log_frames(
+        &self,
+        id: impl ContainerRef,
+        options: &LogsOptions,
+    ) -> Result<LogFrames<StreamResponse>, DockerApiError>
+    where
+        Self: Sized,
+    {
+        let id = id.container_ref();
+        let api_endpoint = format!(
+            "/containers/{id}/logs{query}",
+            id = id,
+            query = options.to_query()
+        );
+
+        let resp = self.get_stream_from_api(&api_endpoint, "GET", "", &[])?;
+
+        if resp.status_code != 200 {
+            let status_code = resp.status_code;
+            return Err(DockerApiError::from_response(
+                ApiObject::Container,
+                status_code,
+                resp.body_to_string()?,
+            ));
+        }
+
+        Ok(LogFrames::new(resp))
+    }
+}
@@ -1,10 +1,130 @@
 pub mod api_utils;
+#[cfg(feature = "build")]
+pub mod build;
+#[cfg(feature = "containers")]
 pub mod containers;
+#[cfg(feature = "events")]
+pub mod events;
+#[cfg(feature = "exec")]
+pub mod exec;
+pub mod ids;
+#[cfg(feature = "images")]
 pub mod images;
+#[cfg(feature = "logs")]
+pub mod logs;
+#[cfg(feature = "networks")]
+pub mod networks;
+pub mod platform;
+pub mod reference;
+#[cfg(feature = "secrets")]
+pub mod secrets;
+#[cfg(feature = "services")]
+pub mod services;
+#[cfg(feature = "stats")]
+pub mod stats;
+#[cfg(feature = "swarm")]
+pub mod swarm;
 pub mod version;
+#[cfg(feature = "volumes")]
+pub mod volumes;
 
+use std::io::Read;
+use std::time::Duration;
+
+use api::api_utils::RequestBuilder;
+use api::version::ApiVersion;
 use errors::DockerApiError;
-use utils::Response;
+use ratelimit::RateLimiter;
+use utils::{
+    deserialize_body, CloseWrite, Response, StreamHandle, StreamResponse,
+};
+
+use serde::de::DeserializeOwned;
+
+/// What `Containers::create_container` does when the memory or the CPUs a container
+/// asks for are more than the daemon has left, see
+/// `DockerApiClient::resource_check`.
+///
+/// * Off: The container is created without checking, the default.
+/// * Warn: The container is created, the shortfalls are in the
+///   `resource_warnings` of the response.
+/// * Deny: The creation fails with an `InsufficientResources` error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ResourceCheck {
+    #[default]
+    Off,
+    Warn,
+    Deny,
+}
+
+/// The filters of the prune endpoints, like `docker container prune
+/// --filter`.
+///
+/// * until: Only the objects created longer ago than this are removed.
+/// * labels: Only the objects with all these labels, `key` or
+///   `key=value`, are removed.
+/// * exclude_labels: The objects with any of these labels are kept.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PruneFilters {
+    pub until: Option<Duration>,
+    pub labels: Vec<String>,
+    pub exclude_labels: Vec<String>,
+}
+
+impl PruneFilters {
+    pub fn new() -> PruneFilters {
+        PruneFilters::default()
+    }
+
+    pub fn until(mut self, age: Duration) -> PruneFilters {
+        self.until = Some(age);
+        self
+    }
+
+    pub fn label(mut self, label: &str) -> PruneFilters {
+        self.labels.push(label.to_string());
+        self
+    }
+
+    pub fn exclude_label(mut self, label: &str) -> PruneFilters {
+        self.exclude_labels.push(label.to_string());
+        self
+    }
+
+    /// The query with the filters and the `extra` ones, empty if there are
+    /// none.
+    #[cfg(any(
+        feature = "containers",
+        feature = "images",
+        feature = "networks",
+        feature = "volumes"
+    ))]
+    fn to_query(&self, extra: &[(&str, &str)]) -> String {
+        use std::collections::BTreeMap;
+        use utils::{duration_to_secs, encode_query_value};
+
+        let mut filters: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+        if let Some(until) = self.until {
+            filters
+                .insert("until", vec![format!("{}s", duration_to_secs(until))]);
+        }
+        if !self.labels.is_empty() {
+            filters.insert("label", self.labels.clone());
+        }
+        if !self.exclude_labels.is_empty() {
+            filters.insert("label!", self.exclude_labels.clone());
+        }
+        for &(key, value) in extra {
+            filters.entry(key).or_default().push(value.to_string());
+        }
+
+        if filters.is_empty() {
+            return String::new();
+        }
+        let filters = json!(filters).to_string();
+        format!("?filters={}", encode_query_value(&filters))
+    }
+}
 
 /// Highest level trait for a DockerAPI client
 ///
@@ -26,14 +146,32 @@ pub trait DockerApiClient {
         method: &str,
         body: &str,
     ) -> Result<Response, DockerApiError> {
-        let req = match api_utils::get_formatted_api_request(
-            api_endpoint,
-            method,
-            body,
-        ) {
-            Some(req) => req,
-            None => return Err(DockerApiError::RequestPrepareError("Error")),
-        };
+        self.get_response_from_api_with_headers(api_endpoint, method, body, &[])
+    }
+
+    /// Same as `get_response_from_api`, with extra headers added to the
+    /// request, for example `X-Registry-Auth` for the image endpoints.
+    fn get_response_from_api_with_headers(
+        &self,
+        api_endpoint: &str,
+        method: &str,
+        body: &str,
+        headers: &[(&str, &str)],
+    ) -> Result<Response, DockerApiError> {
+        let request = RequestBuilder::new(method, api_endpoint)
+            .headers(headers)
+            .body(body);
+
+        self.send(&request)
+    }
+
+    /// Sends the request built with a `RequestBuilder`, for requests which
+    /// need headers of their own.
+    fn send(
+        &self,
+        request: &RequestBuilder,
+    ) -> Result<Response, DockerApiError> {
+        let req = request.build()?;
 
         match self.request(&req) {
             Some(resp) => Response::parse_http_response(resp),
@@ -43,6 +181,272 @@ pub trait DockerApiClient {
         }
     }
 
+    /// Gets the endpoint, with its query, and deserializes the body into any
+    /// type. With a `serde_json::Value` this gives the fields the typed
+    /// structs do not have yet, like the ones of a newer daemon.
+    ///
+    /// ```rust,ignore
+    /// let info: serde_json::Value = client.get_json("/containers/web/json")?;
+    /// println!("{}", info["State"]["Health"]["Log"]);
+    /// ```
+    fn get_json<T: DeserializeOwned>(
+        &self,
+        api_endpoint: &str,
+    ) -> Result<T, DockerApiError>
+    where
+        Self: Sized,
+    {
+        let resp = self.get_response_from_api(api_endpoint, "GET", "")?;
+
+        if resp.status_code / 100 != 2 {
+            return Err(DockerApiError::InvalidApiResponseError(
+                resp.status_code,
+                resp.body,
+            ));
+        }
+
+        deserialize_body(&resp.body, self.is_strict())
+    }
+
+    /// Sends the request with `body` uploaded after the headers, for the
+    /// endpoints taking a large body like the build context of a build.
+    /// The request should be marked as `chunked`. The response is given back
+    /// as soon as the headers are read like `get_stream_from_api`.
+    fn send_upload(
+        &self,
+        request: &RequestBuilder,
+        body: &mut dyn Read,
+    ) -> Result<StreamResponse, DockerApiError> {
+        let req = request.build()?;
+
+        let handle = StreamHandle::new();
+        match self.upload_request(&req, body, &handle) {
+            Some(reader) => StreamResponse::from_reader(reader, handle),
+            None => Err(DockerApiError::RequestError(
+                "Uploads are not supported by the client.",
+            )),
+        }
+    }
+
+    /// Same as `get_response_from_api_with_headers`, but gives back the
+    /// response as soon as the headers are read, so that the body can be
+    /// consumed while the daemon is still writing it.
+    fn get_stream_from_api(
+        &self,
+        api_endpoint: &str,
+        method: &str,
+        body: &str,
+        headers: &[(&str, &str)],
+    ) -> Result<StreamResponse, DockerApiError> {
+        let request = RequestBuilder::new(method, api_endpoint)
+            .headers(headers)
+            .body(body);
+
+        self.send_streaming(&request)
+    }
+
+    /// Same as `send`, but gives back the response as soon as the headers
+    /// are read like `get_stream_from_api`.
+    fn send_streaming(
+        &self,
+        request: &RequestBuilder,
+    ) -> Result<StreamResponse, DockerApiError> {
+        let req = request.build()?;
+
+        let handle = StreamHandle::new();
+        match self.stream_request(&req, &handle) {
+            Some(reader) => StreamResponse::from_reader(reader, handle),
+            None => Err(DockerApiError::RequestError(
+                "Streaming requests are not supported by the client.",
+            )),
+        }
+    }
+
+    /// Same as `get_stream_from_api`, but asks the daemon to hijack the
+    /// connection, for the endpoints which read from the client after the
+    /// response headers like the interactive exec sessions. The write half
+    /// of the connection is given back with the response.
+    fn get_hijacked_stream_from_api(
+        &self,
+        api_endpoint: &str,
+        method: &str,
+        body: &str,
+    ) -> Result<(StreamResponse, Box<dyn CloseWrite>), DockerApiError> {
+        let req = RequestBuilder::new(method, api_endpoint)
+            .header("Connection", "Upgrade")
+            .header("Upgrade", "tcp")
+            .body(body)
+            .build()?;
+
+        let handle = StreamHandle::new();
+        match self.hijack_request(&req, &handle) {
+            Some((reader, writer)) => {
+                Ok((StreamResponse::from_reader(reader, handle)?, writer))
+            }
+            None => Err(DockerApiError::RequestError(
+                "Hijacked connections are not supported by the client.",
+            )),
+        }
+    }
+
     /// Implement this function to use this trait.
     fn request(&self, request: &str) -> Option<Vec<u8>>;
+
+    /// Implement this function to use the streaming endpoints like events.
+    /// The request should be sent on a connection of its own, which is
+    /// then given back to read the response from.
+    ///
+    /// The function shutting down the connection should be set on the
+    /// handle, so that the stream can be cancelled from another thread.
+    fn stream_request(
+        &self,
+        _request: &str,
+        _handle: &StreamHandle,
+    ) -> Option<Box<dyn Read + Send>> {
+        None
+    }
+
+    /// Implement this function to use the endpoints taking a large body like
+    /// the builds. Like `stream_request` the request is sent on a connection
+    /// of its own, followed by `body` with the chunked transfer encoding,
+    /// and the connection is given back to read the response from.
+    fn upload_request(
+        &self,
+        _request: &str,
+        _body: &mut dyn Read,
+        _handle: &StreamHandle,
+    ) -> Option<Box<dyn Read + Send>> {
+        None
+    }
+
+    /// The limiter the operations hitting a registry wait on, like pulls,
+    /// pushes and searches. None by default, so they are not limited.
+    fn rate_limiter(&self) -> Option<&RateLimiter> {
+        None
+    }
+
+    /// The API version the requests are checked against, None by default
+    /// so that they are sent as they are.
+    fn api_version(&self) -> Option<ApiVersion> {
+        None
+    }
+
+    /// Whether the responses are deserialized strictly, see
+    /// `utils::deserialize_body`. False by default.
+    fn is_strict(&self) -> bool {
+        false
+    }
+
+    /// The label, as a key and a value, set on the containers, networks and
+    /// volumes created with the client, so that the ones it owns can be
+    /// found and removed, see `gc::cleanup_owned`. None by default.
+    fn owner_label(&self) -> Option<(&str, &str)> {
+        None
+    }
+
+    /// What `Containers::create_container` does when the daemon does not
+    /// have the resources the container asks for left, see
+    /// `ResourceCheck`. Off by default.
+    fn resource_check(&self) -> ResourceCheck {
+        ResourceCheck::Off
+    }
+
+    /// Waits for the rate limiter of the client, if any, before an
+    /// operation hitting a registry.
+    fn wait_for_rate_limit(&self) {
+        if let Some(limiter) = self.rate_limiter() {
+            limiter.acquire();
+        }
+    }
+
+    /// Implement this function to use the endpoints hijacking the connection.
+    /// Like `stream_request` the request is sent on a connection of its own,
+    /// which is given back both to read the response and to write to.
+    fn hijack_request(
+        &self,
+        _request: &str,
+        _handle: &StreamHandle,
+    ) -> Option<(Box<dyn Read + Send>, Box<dyn CloseWrite>)> {
+        None
+    }
+}
+
+/// All the API traits in one, so that a client can be kept behind a trait
+/// object, like a `Box<dyn DockerApi + Send + Sync>` given to the parts of
+/// an application which get either a `DockerClient` or a mock of it.
+///
+/// Every client implementing all the traits implements it. The methods
+/// taking closures, like `Containers::recreate_container`, or a container,
+/// generic over `ids::ContainerRef`, need the type of the client and are
+/// not available on the trait object, nor the ones using them like
+/// `Containers::create_container`. It needs all the API features.
+///
+/// ```rust,ignore
+/// fn running(api: &dyn DockerApi) -> Result<usize, DockerApiError> {
+///     Ok(api.list_running_containers(None)?.len())
+/// }
+///
+/// let api: Box<dyn DockerApi + Send + Sync> = Box::new(DockerClient::new(address)?);
+/// println!("{} containers are running", running(&*api)?);
+/// ```
+#[cfg(all(
+    feature = "build",
+    feature = "containers",
+    feature = "events",
+    feature = "exec",
+    feature = "images",
+    feature = "logs",
+    feature = "networks",
+    feature = "secrets",
+    feature = "services",
+    feature = "stats",
+    feature = "swarm",
+    feature = "volumes"
+))]
+pub trait DockerApi:
+    build::Build
+    + containers::Containers
+    + events::Events
+    + exec::Exec
+    + images::Images
+    + logs::Logs
+    + networks::Networks
+    + secrets::Secrets
+    + services::Services
+    + stats::Stats
+    + swarm::Swarm
+    + version::Version
+    + volumes::Volumes
+{
+}
+
+#[cfg(all(
+    feature = "build",
+    feature = "containers",
+    feature = "events",
+    feature = "exec",
+    feature = "images",
+    feature = "logs",
+    feature = "networks",
+    feature = "secrets",
+    feature = "services",
+    feature = "stats",
+    feature = "swarm",
+    feature = "volumes"
+))]
+impl<C> DockerApi for C where
+    C: build::Build
+        + containers::Containers
+        + events::Events
+        + exec::Exec
+        + images::Images
+        + logs::Logs
+        + networks::Networks
+        + secrets::Secrets
+        + services::Services
+        + stats::Stats
+        + swarm::Swarm
+        + version::Version
+        + volumes::Volumes
+{
 }
@@ -0,0 +1,638 @@
+use std::collections::HashMap;
+use std::mem::{self, ManuallyDrop};
+use std::time::SystemTime;
+
+#[cfg(feature = "containers")]
+use api::containers::Container;
+use api::{DockerApiClient, PruneFilters};
+#[cfg(feature = "containers")]
+use utils::encode_query_value;
+use utils::{
+    deserialize_body, deserialize_null_default, deserialize_timestamp,
+    serialize_rfc3339_timestamp,
+};
+
+use serde::de::{Deserialize, Deserializer};
+use serde::ser::{Serialize, Serializer};
+use serde_json;
+
+use errors::{ApiObject, DockerApiError};
+
+/// IP address management configuration of a network.
+///
+/// * driver: IPAM driver to use, `default` if empty.
+/// * config: List of pools, each with its subnet, range and gateway.
+/// * options: Driver specific options.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct Ipam {
+    #[serde(default)]
+    pub driver: String,
+
+    #[serde(default)]
+    pub config: Option<Vec<IpamConfig>>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub options: Option<HashMap<String, String>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct IpamConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subnet: Option<String>,
+
+    #[serde(
+        rename = "IPRange",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub ip_range: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gateway: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aux_addresses: Option<HashMap<String, String>>,
+}
+
+impl Ipam {
+    /// IPAM configuration with the default driver and a single pool for
+    /// `subnet`, e.g. `Ipam::with_subnet("172.28.0.0/16", Some("172.28.0.1"))`
+    pub fn with_subnet(subnet: &str, gateway: Option<&str>) -> Ipam {
+        Ipam {
+            driver: "default".to_string(),
+            config: Some(vec![IpamConfig {
+                subnet: Some(subnet.to_string()),
+                gateway: gateway.map(|g| g.to_string()),
+                ..Default::default()
+            }]),
+            options: None,
+        }
+    }
+}
+
+/// Static addresses of a container's endpoint on a network.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct EndpointIpamConfig {
+    #[serde(
+        rename = "IPv4Address",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub ipv4_address: Option<String>,
+
+    #[serde(
+        rename = "IPv6Address",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub ipv6_address: Option<String>,
+}
+
+/// Configuration of a container's endpoint on a network.
+///
+/// The fields from `NetworkID` on are filled by the daemon and are only
+/// read from responses.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct EndpointSettings {
+    #[serde(
+        rename = "IPAMConfig",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub ipam_config: Option<EndpointIpamConfig>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub links: Option<Vec<String>>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aliases: Option<Vec<String>>,
+
+    #[serde(
+        rename = "NetworkID",
+        default,
+        skip_serializing_if = "String::is_empty"
+    )]
+    pub network_id: String,
+
+    #[serde(
+        rename = "EndpointID",
+        default,
+        skip_serializing_if = "String::is_empty"
+    )]
+    pub endpoint_id: String,
+
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub gateway: String,
+
+    #[serde(
+        rename = "IPAddress",
+        default,
+        skip_serializing_if = "String::is_empty"
+    )]
+    pub ip_address: String,
+
+    #[serde(rename = "IPPrefixLen", default, skip_serializing_if = "is_zero")]
+    pub ip_prefix_len: u32,
+
+    #[serde(
+        rename = "GlobalIPv6Address",
+        default,
+        skip_serializing_if = "String::is_empty"
+    )]
+    pub global_ipv6_address: String,
+
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub mac_address: String,
+}
+
+fn is_zero(val: &u32) -> bool {
+    *val == 0
+}
+
+impl EndpointSettings {
+    /// Adds a network scoped alias the container can be reached with.
+    pub fn alias(mut self, alias: &str) -> EndpointSettings {
+        self.aliases
+            .get_or_insert_with(Vec::new)
+            .push(alias.to_string());
+        self
+    }
+
+    /// Adds a link to another container, `container` or `container:alias`.
+    pub fn link(mut self, link: &str) -> EndpointSettings {
+        self.links
+            .get_or_insert_with(Vec::new)
+            .push(link.to_string());
+        self
+    }
+
+    /// Sets a static IPv4 address for the container on the network.
+    pub fn ipv4_address(mut self, address: &str) -> EndpointSettings {
+        self.ipam_config
+            .get_or_insert_with(Default::default)
+            .ipv4_address = Some(address.to_string());
+        self
+    }
+
+    /// Sets a static IPv6 address for the container on the network.
+    pub fn ipv6_address(mut self, address: &str) -> EndpointSettings {
+        self.ipam_config
+            .get_or_insert_with(Default::default)
+            .ipv6_address = Some(address.to_string());
+        self
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "PascalCase")]
+#[serde(default)]
+pub struct Network {
+    pub name: String,
+    pub id: String,
+
+    #[serde(
+        default,
+        deserialize_with = "deserialize_timestamp",
+        serialize_with = "serialize_rfc3339_timestamp"
+    )]
+    pub created: Option<SystemTime>,
+
+    pub scope: String,
+    pub driver: String,
+    #[serde(rename = "EnableIPv6")]
+    pub enable_ipv6: bool,
+    #[serde(rename = "IPAM")]
+    pub ipam: Ipam,
+    pub internal: bool,
+    pub attachable: bool,
+    pub ingress: bool,
+    pub options: Option<HashMap<String, String>>,
+    pub labels: Option<HashMap<String, String>>,
+
+    /// The endpoints of the containers on the network, by container ID.
+    /// Only `inspect_network` fills it, it is empty in the list.
+    #[serde(deserialize_with = "deserialize_null_default")]
+    pub containers: HashMap<String, NetworkContainer>,
+}
+
+/// The endpoint of a container on a network.
+///
+/// * ipv4_address: The address with the prefix length of the subnet, like
+///   `172.28.0.5/16`, empty if the network has no IPv4 subnet.
+/// * ipv6_address: Same for IPv6, empty without IPv6.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+#[serde(default)]
+pub struct NetworkContainer {
+    pub name: String,
+    #[serde(rename = "EndpointID")]
+    pub endpoint_id: String,
+    pub mac_address: String,
+    #[serde(rename = "IPv4Address")]
+    pub ipv4_address: String,
+    #[serde(rename = "IPv6Address")]
+    pub ipv6_address: String,
+}
+
+/// Network driver to create a network with, `Custom` takes the name of a
+/// plugin driver.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum NetworkDriver {
+    #[default]
+    Bridge,
+    Overlay,
+    Macvlan,
+    None,
+    Custom(String),
+}
+
+impl NetworkDriver {
+    pub fn as_str(&self) -> &str {
+        match *self {
+            NetworkDriver::Bridge => "bridge",
+            NetworkDriver::Overlay => "overlay",
+            NetworkDriver::Macvlan => "macvlan",
+            NetworkDriver::None => "null",
+            NetworkDriver::Custom(ref name) => name,
+        }
+    }
+}
+
+impl Serialize for NetworkDriver {
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for NetworkDriver {
+    fn deserialize<D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<NetworkDriver, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Ok(match name.as_str() {
+            "bridge" => NetworkDriver::Bridge,
+            "overlay" => NetworkDriver::Overlay,
+            "macvlan" => NetworkDriver::Macvlan,
+            "null" | "none" => NetworkDriver::None,
+            _ => NetworkDriver::Custom(name),
+        })
+    }
+}
+
+/// Structure for the request body of the network create endpoint.
+///
+/// * attachable: Standalone containers can attach to a swarm scoped
+///   (overlay) network.
+/// * ingress: The network is the swarm routing-mesh network.
+/// * internal: No external access to the network.
+/// * options: Driver specific options, e.g. `parent` for macvlan.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct NetworkConfig {
+    pub name: String,
+    pub check_duplicate: bool,
+    pub driver: NetworkDriver,
+    pub attachable: bool,
+    pub ingress: bool,
+    pub internal: bool,
+    #[serde(rename = "EnableIPv6")]
+    pub enable_ipv6: bool,
+
+    #[serde(rename = "IPAM", default, skip_serializing_if = "Option::is_none")]
+    pub ipam: Option<Ipam>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub options: Option<HashMap<String, String>>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub labels: Option<HashMap<String, String>>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct CreateNetworkResponse {
+    pub id: String,
+
+    #[serde(default)]
+    pub warning: String,
+
+    /// The `EXPOSED_HEADERS` of the response, they are not part of the
+    /// JSON body.
+    #[serde(skip)]
+    pub headers: HashMap<String, String>,
+}
+
+/// A network removed when dropped, also when the thread panics, from
+/// `create_network_owned`. Removing it is best-effort, the errors are
+/// ignored.
+pub struct OwnedNetwork<'a, C: 'a + Networks> {
+    client: &'a C,
+    id: String,
+}
+
+impl<'a, C: Networks> OwnedNetwork<'a, C> {
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Keeps the network, giving its ID back.
+    pub fn keep(self) -> String {
+        let mut owned = ManuallyDrop::new(self);
+        mem::take(&mut owned.id)
+    }
+}
+
+impl<'a, C: Networks> Drop for OwnedNetwork<'a, C> {
+    fn drop(&mut self) {
+        let _ = self.client.remove_network(&self.id);
+    }
+}
+
+/// What `Networks::prune_networks` removed.
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "PascalCase")]
+pub struct NetworksPruneReport {
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub networks_deleted: Vec<String>,
+}
+
+pub trait Networks: DockerApiClient {
+    /// List all the networks.
+    fn list_networks(&self) -> Result<Vec<Network>, DockerApiError> {
+        let api_endpoint = "/networks";
+        let method = "GET";
+
+        let resp = self.get_response_from_api(api_endpoint, method, "")?;
+
+        if resp.status_code != 200 {
+            return Err(DockerApiError::from_response(
+                ApiObject::Network,
+                resp.status_code,
+                resp.body,
+            ));
+        }
+
+        deserialize_body(&resp.body, self.is_strict())
+    }
+
+    /// Inspects the network with the provided name or ID.
+    fn inspect_network(&self, id: &str) -> Result<Network, DockerApiError> {
+        let api_endpoint = format!("/networks/{id}", id = id);
+        let method = "GET";
+
+        let resp = self.get_response_from_api(&api_endpoint, method, "")?;
+
+        if resp.status_code != 200 {
+            return Err(DockerApiError::from_response(
+                ApiObject::Network,
+                resp.status_code,
+                resp.body,
+            ));
+        }
+
+        deserialize_body(&resp.body, self.is_strict())
+    }
+
+    /// Lists the containers attached to the network with the provided name
+    /// or ID, running or stopped, their `network_settings` giving their
+    /// addresses on it. Needs the `containers` feature.
+    #[cfg(feature = "containers")]
+    fn containers_on_network(
+        &self,
+        network: &str,
+    ) -> Result<Vec<Container>, DockerApiError> {
+        let filters = json!({ "network": [network] });
+        let api_endpoint = format!(
+            "/containers/json?all=true&filters={}",
+            encode_query_value(&filters.to_string())
+        );
+        let method = "GET";
+
+        let resp = self.get_response_from_api(&api_endpoint, method, "")?;
+
+        if resp.status_code != 200 {
+            return Err(DockerApiError::from_response(
+                ApiObject::Network,
+                resp.status_code,
+                resp.body,
+            ));
+        }
+
+        deserialize_body(&resp.body, self.is_strict())
+    }
+
+    /// Create a network from the NetworkConfig structure.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// extern crate rust_docker;
+    ///
+    /// use rust_docker::api::networks::{
+    ///     Ipam, NetworkConfig, NetworkDriver, Networks,
+    /// };
+    /// use rust_docker::client::DockerClient;
+    ///
+    /// let client = match DockerClient::new("unix:///var/run/docker.sock") {
+    ///     Ok(a) => a,
+    ///     Err(err) => {
+    ///         println!("{}", err);
+    ///         std::process::exit(1);
+    ///     }
+    /// };
+    ///
+    /// let config = NetworkConfig {
+    ///     name: "backend".to_string(),
+    ///     driver: NetworkDriver::Bridge,
+    ///     ipam: Some(Ipam::with_subnet("172.28.0.0/16", Some("172.28.0.1"))),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// match client.create_network(config) {
+    ///     Ok(network) => println!("{:?}", network),
+    ///     Err(err) => println!("An error occured : {}", err),
+    /// }
+    /// ```
+    fn create_network(
+        &self,
+        mut config: NetworkConfig,
+    ) -> Result<CreateNetworkResponse, DockerApiError> {
+        let api_endpoint = "/networks/create";
+        let method = "POST";
+        if let Some((key, value)) = self.owner_label() {
+            config
+                .labels
+                .get_or_insert_with(Default::default)
+                .insert(key.to_string(), value.to_string());
+        }
+        let body = match serde_json::to_string(&config) {
+            Ok(body) => body,
+            Err(err) => {
+                return Err(DockerApiError::JsonSerializationError(err))
+            }
+        };
+
+        let resp = self.get_response_from_api(api_endpoint, method, &body)?;
+
+        if resp.status_code != 201 {
+            return Err(DockerApiError::from_response(
+                ApiObject::Network,
+                resp.status_code,
+                resp.body,
+            ));
+        }
+
+        match serde_json::from_str::<CreateNetworkResponse>(&resp.body) {
+            Ok(mut info) => {
+                info.headers = resp.exposed_headers();
+                Ok(info)
+            }
+            Err(err) => Err(DockerApiError::JsonDeserializationError(err)),
+        }
+    }
+
+    /// Same as `create_network`, the network being removed when the
+    /// returned guard is dropped, see `OwnedNetwork`.
+    fn create_network_owned(
+        &self,
+        config: NetworkConfig,
+    ) -> Result<OwnedNetwork<'_, Self>, DockerApiError>
+    where
+        Self: Sized,
+    {
+        let id = self.create_network(config)?.id;
+        Ok(OwnedNetwork { client: self, id })
+    }
+
+    /// Removes the network with the provided name or ID.
+    fn remove_network(&self, id: &str) -> Result<(), DockerApiError> {
+        let api_endpoint = format!("/networks/{id}", id = id);
+        let method = "DELETE";
+
+        let resp = self.get_response_from_api(&api_endpoint, method, "")?;
+
+        if resp.status_code != 204 {
+            return Err(DockerApiError::from_response(
+                ApiObject::Network,
+                resp.status_code,
+                resp.body,
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Connects the container to the network. The endpoint settings give the
+    /// aliases, links and static addresses of the container on the network.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// extern crate rust_docker;
+    ///
+    /// use rust_docker::api::networks::{EndpointSettings, Networks};
+    /// use rust_docker::client::DockerClient;
+    ///
+    /// let client = match DockerClient::new("unix:///var/run/docker.sock") {
+    ///     Ok(a) => a,
+    ///     Err(err) => {
+    ///         println!("{}", err);
+    ///         std::process::exit(1);
+    ///     }
+    /// };
+    ///
+    /// let endpoint = EndpointSettings::default()
+    ///     .alias("db")
+    ///     .ipv4_address("172.28.0.5");
+    ///
+    /// match client.connect_container_to_network("backend", "f808ca...", Some(endpoint)) {
+    ///     Ok(_) => println!("Connected"),
+    ///     Err(err) => println!("An error occured : {}", err),
+    /// }
+    /// ```
+    fn connect_container_to_network(
+        &self,
+        network: &str,
+        container: &str,
+        endpoint: Option<EndpointSettings>,
+    ) -> Result<(), DockerApiError> {
+        let api_endpoint = format!("/networks/{id}/connect", id = network);
+        let method = "POST";
+        let body = match serde_json::to_string(&json!({
+            "Container": container,
+            "EndpointConfig": endpoint.unwrap_or_default(),
+        })) {
+            Ok(body) => body,
+            Err(err) => {
+                return Err(DockerApiError::JsonSerializationError(err))
+            }
+        };
+
+        let resp = self.get_response_from_api(&api_endpoint, method, &body)?;
+
+        if resp.status_code != 200 {
+            return Err(DockerApiError::from_response(
+                ApiObject::Network,
+                resp.status_code,
+                resp.body,
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Disconnects the container from the network, `force` disconnects the
+    /// container even if it is not running.
+    fn disconnect_container_from_network(
+        &self,
+        network: &str,
+        container: &str,
+        force: bool,
+    ) -> Result<(), DockerApiError> {
+        let api_endpoint = format!("/networks/{id}/disconnect", id = network);
+        let method = "POST";
+        let body =
+            json!({ "Container": container, "Force": force }).to_string();
+
+        let resp = self.get_response_from_api(&api_endpoint, method, &body)?;
+
+        if resp.status_code != 200 {
+            return Err(DockerApiError::from_response(
+                ApiObject::Network,
+                resp.status_code,
+                resp.body,
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Removes the networks matching the filters which no container uses,
+    /// like `docker network prune`.
+    fn prune_networks(
+        &self,
+        filters: &PruneFilters,
+    ) -> Result<NetworksPruneReport, DockerApiError> {
+        let api_endpoint = format!("/networks/prune{}", filters.to_query(&[]));
+        let method = "POST";
+
+        let resp = self.get_response_from_api(&api_endpoint, method, "")?;
+
+        if resp.status_code != 200 {
+            return Err(DockerApiError::from_response(
+                ApiObject::Network,
+                resp.status_code,
+                resp.body,
+            ));
+        }
+
+        deserialize_body(&resp.body, self.is_strict())
+    }
+}
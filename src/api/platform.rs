@@ -0,0 +1,89 @@
+use std::fmt;
+use std::str::FromStr;
+
+use errors::DockerApiError;
+
+/// A platform of a multi-arch image, `os[/architecture[/variant]]`.
+///
+/// The architectures are normalized the way the daemon does, `x86_64` is
+/// `amd64` and `aarch64` is `arm64`, so that platforms can be compared.
+///
+/// ```rust
+/// extern crate rust_docker;
+///
+/// use rust_docker::api::platform::Platform;
+///
+/// let platform = Platform::parse("linux/aarch64/v8").unwrap();
+/// assert_eq!(platform.architecture, "arm64");
+/// assert_eq!(platform.to_string(), "linux/arm64/v8");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Platform {
+    pub os: String,
+    pub architecture: String,
+    pub variant: Option<String>,
+}
+
+fn normalize_arch(arch: &str) -> &str {
+    match arch {
+        "x86_64" | "x86-64" => "amd64",
+        "aarch64" => "arm64",
+        "i386" | "i686" => "386",
+        _ => arch,
+    }
+}
+
+impl Platform {
+    pub fn new(os: &str, architecture: &str) -> Platform {
+        Platform {
+            os: os.to_lowercase(),
+            architecture: normalize_arch(&architecture.to_lowercase())
+                .to_string(),
+            variant: None,
+        }
+    }
+
+    pub fn variant(mut self, variant: &str) -> Platform {
+        self.variant = Some(variant.to_lowercase());
+        self
+    }
+
+    /// Parses `os/architecture` with an optional `/variant`. An empty
+    /// component or more than three of them fail with an `ImageError`.
+    pub fn parse(platform: &str) -> Result<Platform, DockerApiError> {
+        let parts: Vec<&str> = platform.split('/').collect();
+        if parts.len() < 2
+            || parts.len() > 3
+            || parts.iter().any(|part| part.trim().is_empty())
+        {
+            return Err(DockerApiError::ImageError(format!(
+                "Invalid platform {}, expected os/architecture[/variant]",
+                platform
+            )));
+        }
+
+        let platform = Platform::new(parts[0], parts[1]);
+        Ok(match parts.get(2) {
+            Some(variant) => platform.variant(variant),
+            None => platform,
+        })
+    }
+}
+
+impl FromStr for Platform {
+    type Err = DockerApiError;
+
+    fn from_str(platform: &str) -> Result<Platform, DockerApiError> {
+        Platform::parse(platform)
+    }
+}
+
+impl fmt::Display for Platform {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.os, self.architecture)?;
+        if let Some(ref variant) = self.variant {
+            write!(f, "/{}", variant)?;
+        }
+        Ok(())
+    }
+}
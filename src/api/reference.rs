@@ -0,0 +1,232 @@
+use std::fmt;
+use std::str::FromStr;
+
+use errors::DockerApiError;
+
+/// Registry used for references without a registry.
+pub const DEFAULT_REGISTRY: &str = "docker.io";
+
+/// Namespace of the official images on the default registry.
+pub const OFFICIAL_NAMESPACE: &str = "library";
+
+/// Tag used for references with neither a tag nor a digest.
+pub const DEFAULT_TAG: &str = "latest";
+
+/// A parsed and normalized image reference,
+/// `[registry/][namespace/]repo[:tag][@digest]`.
+///
+/// * registry: `docker.io` when the reference has no registry. The first
+///   component is a registry if it contains a `.` or a `:`, or is
+///   `localhost`.
+/// * namespace: Path between the registry and the repo, `library` for the
+///   official images of the default registry, it can be empty for other
+///   registries.
+/// * tag: `latest` when the reference has neither a tag nor a digest.
+///
+/// ```rust
+/// extern crate rust_docker;
+///
+/// use rust_docker::api::reference::Reference;
+///
+/// let reference = Reference::parse("nginx").unwrap();
+/// assert_eq!(reference.to_string(), "docker.io/library/nginx:latest");
+/// assert_eq!(reference.familiar_name(), "nginx");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Reference {
+    pub registry: String,
+    pub namespace: String,
+    pub repo: String,
+    pub tag: Option<String>,
+    pub digest: Option<String>,
+}
+
+fn invalid(reference: &str, reason: &str) -> DockerApiError {
+    DockerApiError::ImageError(format!(
+        "Invalid reference {} : {}",
+        reference, reason
+    ))
+}
+
+/// Path components are lowercase alphanumerics, separated by a single `.`,
+/// one or two `_`, or any number of `-`.
+fn is_valid_component(component: &str) -> bool {
+    let bytes = component.as_bytes();
+    if bytes.is_empty()
+        || !bytes[0].is_ascii_alphanumeric()
+        || !bytes[bytes.len() - 1].is_ascii_alphanumeric()
+    {
+        return false;
+    }
+
+    let mut separator = String::new();
+    for &b in bytes {
+        if b.is_ascii_lowercase() || b.is_ascii_digit() {
+            if !separator.is_empty()
+                && separator != "."
+                && separator != "_"
+                && separator != "__"
+                && !separator.bytes().all(|s| s == b'-')
+            {
+                return false;
+            }
+            separator.clear();
+        } else if b == b'.' || b == b'_' || b == b'-' {
+            separator.push(b as char);
+        } else {
+            return false;
+        }
+    }
+
+    true
+}
+
+fn is_valid_tag(tag: &str) -> bool {
+    let bytes = tag.as_bytes();
+    !bytes.is_empty()
+        && bytes.len() <= 128
+        && (bytes[0].is_ascii_alphanumeric() || bytes[0] == b'_')
+        && bytes
+            .iter()
+            .all(|b| b.is_ascii_alphanumeric() || b"_.-".contains(b))
+}
+
+fn is_valid_digest(digest: &str) -> bool {
+    match digest.split_once(':') {
+        Some((algorithm, hex)) => {
+            !algorithm.is_empty()
+                && algorithm
+                    .bytes()
+                    .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit())
+                && hex.len() >= 32
+                && hex.bytes().all(|b| b.is_ascii_hexdigit())
+        }
+        None => false,
+    }
+}
+
+fn is_registry(component: &str) -> bool {
+    component.contains('.')
+        || component.contains(':')
+        || component == "localhost"
+}
+
+impl Reference {
+    /// Parses and normalizes the reference, failing with an `ImageError`
+    /// if it is not valid.
+    pub fn parse(reference: &str) -> Result<Reference, DockerApiError> {
+        let (rest, digest) = match reference.split_once('@') {
+            Some((rest, digest)) => {
+                if !is_valid_digest(digest) {
+                    return Err(invalid(reference, "malformed digest"));
+                }
+                (rest, Some(digest.to_string()))
+            }
+            None => (reference, None),
+        };
+
+        let (name, tag) = match rest.rfind(':') {
+            Some(pos) if !rest[pos..].contains('/') => {
+                let tag = &rest[pos + 1..];
+                if !is_valid_tag(tag) {
+                    return Err(invalid(reference, "malformed tag"));
+                }
+                (&rest[..pos], Some(tag.to_string()))
+            }
+            _ => (rest, None),
+        };
+
+        if name.is_empty() || name.len() > 255 {
+            return Err(invalid(reference, "name must be 1 to 255 characters"));
+        }
+
+        let mut components: Vec<&str> = name.split('/').collect();
+        let registry = if components.len() > 1 && is_registry(components[0]) {
+            components.remove(0).to_string()
+        } else {
+            DEFAULT_REGISTRY.to_string()
+        };
+
+        if let Some(component) =
+            components.iter().find(|c| !is_valid_component(c))
+        {
+            return Err(invalid(
+                reference,
+                &format!("invalid name component {:?}", component),
+            ));
+        }
+
+        let repo = components.pop().unwrap_or_default().to_string();
+        let mut namespace = components.join("/");
+        if namespace.is_empty() && registry == DEFAULT_REGISTRY {
+            namespace = OFFICIAL_NAMESPACE.to_string();
+        }
+
+        let tag = match (tag, &digest) {
+            (None, &None) => Some(DEFAULT_TAG.to_string()),
+            (tag, _) => tag,
+        };
+
+        Ok(Reference {
+            registry,
+            namespace,
+            repo,
+            tag,
+            digest,
+        })
+    }
+
+    /// The repository with its registry, `docker.io/library/nginx`.
+    pub fn full_name(&self) -> String {
+        if self.namespace.is_empty() {
+            format!("{}/{}", self.registry, self.repo)
+        } else {
+            format!("{}/{}/{}", self.registry, self.namespace, self.repo)
+        }
+    }
+
+    /// The repository the way the docker CLI shows it, without the default
+    /// registry and the `library` namespace, `nginx`.
+    pub fn familiar_name(&self) -> String {
+        if self.registry != DEFAULT_REGISTRY {
+            return self.full_name();
+        }
+
+        if self.namespace == OFFICIAL_NAMESPACE {
+            self.repo.clone()
+        } else {
+            format!("{}/{}", self.namespace, self.repo)
+        }
+    }
+
+    /// The tag, or the digest for references pinned to a digest, as the
+    /// `tag` parameter of the image endpoints expects it.
+    pub fn tag_or_digest(&self) -> &str {
+        match (&self.digest, &self.tag) {
+            (Some(digest), _) => digest,
+            (None, Some(tag)) => tag,
+            (None, None) => DEFAULT_TAG,
+        }
+    }
+}
+
+impl FromStr for Reference {
+    type Err = DockerApiError;
+
+    fn from_str(reference: &str) -> Result<Reference, DockerApiError> {
+        Reference::parse(reference)
+    }
+}
+
+impl fmt::Display for Reference {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.full_name())?;
+        if let Some(ref tag) = self.tag {
+            write!(f, ":{}", tag)?;
+        }
+        if let Some(ref digest) = self.digest {
+            write!(f, "@{}", digest)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+use std::time::SystemTime;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+
+use api::swarm::ObjectVersion;
+use api::DockerApiClient;
+use utils::{
+    deserialize_body, deserialize_null_default, deserialize_timestamp,
+    encode_query_value, serialize_rfc3339_timestamp,
+};
+
+use serde_json;
+
+use errors::{ApiObject, DockerApiError};
+
+/// Structure for the request body of the secret create endpoint.
+///
+/// * data: The content of the secret, base64 encoded.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct SecretSpec {
+    #[serde(default)]
+    pub name: String,
+
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "HashMap::is_empty"
+    )]
+    pub labels: HashMap<String, String>,
+
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub data: String,
+}
+
+impl SecretSpec {
+    pub fn new(name: &str, data: &[u8]) -> SecretSpec {
+        SecretSpec {
+            name: name.to_string(),
+            labels: HashMap::new(),
+            data: STANDARD.encode(data),
+        }
+    }
+
+    pub fn label(mut self, key: &str, value: &str) -> SecretSpec {
+        self.labels.insert(key.to_string(), value.to_string());
+        self
+    }
+}
+
+/// A secret of the swarm, the daemon never gives back its content.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "PascalCase")]
+#[serde(default)]
+pub struct Secret {
+    #[serde(rename = "ID")]
+    pub id: String,
+
+    pub version: ObjectVersion,
+
+    #[serde(
+        default,
+        deserialize_with = "deserialize_timestamp",
+        serialize_with = "serialize_rfc3339_timestamp"
+    )]
+    pub created_at: Option<SystemTime>,
+
+    #[serde(
+        default,
+        deserialize_with = "deserialize_timestamp",
+        serialize_with = "serialize_rfc3339_timestamp"
+    )]
+    pub updated_at: Option<SystemTime>,
+
+    pub spec: SecretSpec,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct CreateSecretResponse {
+    #[serde(rename = "ID")]
+    pub id: String,
+}
+
+pub trait Secrets: DockerApiClient {
+    /// Lists the secrets of the swarm, only the ones with the label if
+    /// given as `key` or `key=value`.
+    fn list_secrets(
+        &self,
+        label: Option<&str>,
+    ) -> Result<Vec<Secret>, DockerApiError> {
+        let api_endpoint = "/secrets";
+        let method = "GET";
+        let query_params = match label {
+            Some(label) => {
+                let filters = json!({ "label": [label] }).to_string();
+                format!("?filters={}", encode_query_value(&filters))
+            }
+            None => String::new(),
+        };
+
+        let resp =
+            self.get_response_from_api(api_endpoint, method, &query_params)?;
+
+        if resp.status_code != 200 {
+            return Err(DockerApiError::from_response(
+                ApiObject::Secret,
+                resp.status_code,
+                resp.body,
+            ));
+        }
+
+        deserialize_body(&resp.body, self.is_strict())
+    }
+
+    fn create_secret(
+        &self,
+        spec: &SecretSpec,
+    ) -> Result<CreateSecretResponse, DockerApiError> {
+        let api_endpoint = "/secrets/create";
+        let method = "POST";
+        let body = match serde_json::to_string(spec) {
+            Ok(body) => body,
+            Err(err) => {
+                return Err(DockerApiError::JsonSerializationError(err))
+            }
+        };
+
+        let resp = self.get_response_from_api(api_endpoint, method, &body)?;
+
+        if resp.status_code != 201 {
+            return Err(DockerApiError::from_response(
+                ApiObject::Secret,
+                resp.status_code,
+                resp.body,
+            ));
+        }
+
+        deserialize_body(&resp.body, self.is_strict())
+    }
+
+    fn remove_secret(&self, id: &str) -> Result<(), DockerApiError> {
+        let api_endpoint = format!("/secrets/{id}", id = id);
+        let method = "DELETE";
+
+        let resp = self.get_response_from_api(&api_endpoint, method, "")?;
+
+        if resp.status_code != 204 {
+            return Err(DockerApiError::from_response(
+                ApiObject::Secret,
+                resp.status_code,
+                resp.body,
+            ));
+        }
+
+        Ok(())
+    }
+}
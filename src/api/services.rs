@@ -0,0 +1,363 @@
+use std::collections::HashMap;
+use std::time::SystemTime;
+
+use api::swarm::ObjectVersion;
+use api::DockerApiClient;
+use utils::{
+    deserialize_body, deserialize_null_default, deserialize_timestamp,
+    encode_query_value, serialize_rfc3339_timestamp,
+};
+
+use serde_json;
+
+use errors::{ApiObject, DockerApiError};
+
+/// A secret given to the containers of a service, as a file in
+/// `/run/secrets`.
+///
+/// * secret_id: ID of the secret, the daemon does not resolve the names.
+/// * file: The file the secret is written to, named like the secret by
+///   default.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct SecretReference {
+    #[serde(rename = "SecretID", default)]
+    pub secret_id: String,
+
+    #[serde(default)]
+    pub secret_name: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<SecretFile>,
+}
+
+/// * mode: Permissions of the file, like `0o444`.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct SecretFile {
+    #[serde(default)]
+    pub name: String,
+
+    #[serde(rename = "UID", default)]
+    pub uid: String,
+
+    #[serde(rename = "GID", default)]
+    pub gid: String,
+
+    #[serde(default)]
+    pub mode: u32,
+}
+
+/// The container run by the tasks of a service.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct ContainerSpec {
+    #[serde(default)]
+    pub image: String,
+
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "HashMap::is_empty"
+    )]
+    pub labels: HashMap<String, String>,
+
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub command: Vec<String>,
+
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub args: Vec<String>,
+
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub env: Vec<String>,
+
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub secrets: Vec<SecretReference>,
+}
+
+/// A network the tasks of a service are attached to, by name or ID.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct NetworkAttachmentConfig {
+    #[serde(default)]
+    pub target: String,
+
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub aliases: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct TaskSpec {
+    #[serde(default)]
+    pub container_spec: ContainerSpec,
+
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub networks: Vec<NetworkAttachmentConfig>,
+}
+
+/// Settings of a swarm service.
+///
+/// * mode: `{"Replicated": {"Replicas": 3}}` or `{"Global": {}}`,
+///   replicated with one task if None.
+/// * endpoint_spec: The published ports, like
+///   `{"Ports": [{"TargetPort": 80, "PublishedPort": 8080}]}`.
+///
+/// ```rust,ignore
+/// let spec = ServiceSpec::new("web", "nginx:1.25")
+///     .replicas(3)
+///     .env("MODE", "production")
+///     .network("frontend");
+/// ```
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct ServiceSpec {
+    #[serde(default)]
+    pub name: String,
+
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "HashMap::is_empty"
+    )]
+    pub labels: HashMap<String, String>,
+
+    #[serde(default)]
+    pub task_template: TaskSpec,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<serde_json::Value>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub endpoint_spec: Option<serde_json::Value>,
+}
+
+impl ServiceSpec {
+    pub fn new(name: &str, image: &str) -> ServiceSpec {
+        let mut spec = ServiceSpec {
+            name: name.to_string(),
+            ..Default::default()
+        };
+        spec.task_template.container_spec.image = image.to_string();
+        spec
+    }
+
+    pub fn label(mut self, key: &str, value: &str) -> ServiceSpec {
+        self.labels.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    pub fn replicas(mut self, replicas: u64) -> ServiceSpec {
+        self.mode = Some(json!({ "Replicated": { "Replicas": replicas } }));
+        self
+    }
+
+    /// Runs one task on every node of the swarm.
+    pub fn global(mut self) -> ServiceSpec {
+        self.mode = Some(json!({ "Global": {} }));
+        self
+    }
+
+    pub fn env(mut self, key: &str, value: &str) -> ServiceSpec {
+        self.task_template
+            .container_spec
+            .env
+            .push(format!("{}={}", key, value));
+        self
+    }
+
+    pub fn network(mut self, target: &str) -> ServiceSpec {
+        self.task_template.networks.push(NetworkAttachmentConfig {
+            target: target.to_string(),
+            aliases: Vec::new(),
+        });
+        self
+    }
+
+    /// Gives the secret to the containers as `/run/secrets/<name>`. The
+    /// ID of the secret is looked up when the service is deployed with
+    /// `stacks::deploy_stack`.
+    pub fn secret(mut self, name: &str) -> ServiceSpec {
+        self.task_template
+            .container_spec
+            .secrets
+            .push(SecretReference {
+                secret_name: name.to_string(),
+                file: Some(SecretFile {
+                    name: name.to_string(),
+                    uid: "0".to_string(),
+                    gid: "0".to_string(),
+                    mode: 0o444,
+                }),
+                ..Default::default()
+            });
+        self
+    }
+}
+
+/// A swarm service, from the service list or inspect.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "PascalCase")]
+#[serde(default)]
+pub struct Service {
+    #[serde(rename = "ID")]
+    pub id: String,
+
+    pub version: ObjectVersion,
+
+    #[serde(
+        default,
+        deserialize_with = "deserialize_timestamp",
+        serialize_with = "serialize_rfc3339_timestamp"
+    )]
+    pub created_at: Option<SystemTime>,
+
+    #[serde(
+        default,
+        deserialize_with = "deserialize_timestamp",
+        serialize_with = "serialize_rfc3339_timestamp"
+    )]
+    pub updated_at: Option<SystemTime>,
+
+    pub spec: ServiceSpec,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct CreateServiceResponse {
+    #[serde(rename = "ID")]
+    pub id: String,
+
+    #[serde(default)]
+    pub warning: Option<String>,
+}
+
+pub trait Services: DockerApiClient {
+    /// Lists the services of the swarm, only the ones with the label if
+    /// given as `key` or `key=value`.
+    fn list_services(
+        &self,
+        label: Option<&str>,
+    ) -> Result<Vec<Service>, DockerApiError> {
+        let api_endpoint = "/services";
+        let method = "GET";
+        let query_params = match label {
+            Some(label) => {
+                let filters = json!({ "label": [label] }).to_string();
+                format!("?filters={}", encode_query_value(&filters))
+            }
+            None => String::new(),
+        };
+
+        let resp =
+            self.get_response_from_api(api_endpoint, method, &query_params)?;
+
+        if resp.status_code != 200 {
+            return Err(DockerApiError::from_response(
+                ApiObject::Service,
+                resp.status_code,
+                resp.body,
+            ));
+        }
+
+        deserialize_body(&resp.body, self.is_strict())
+    }
+
+    fn create_service(
+        &self,
+        spec: &ServiceSpec,
+    ) -> Result<CreateServiceResponse, DockerApiError> {
+        let api_endpoint = "/services/create";
+        let method = "POST";
+        let body = match serde_json::to_string(spec) {
+            Ok(body) => body,
+            Err(err) => {
+                return Err(DockerApiError::JsonSerializationError(err))
+            }
+        };
+
+        let resp = self.get_response_from_api(api_endpoint, method, &body)?;
+
+        if resp.status_code != 201 {
+            return Err(DockerApiError::from_response(
+                ApiObject::Service,
+                resp.status_code,
+                resp.body,
+            ));
+        }
+
+        deserialize_body(&resp.body, self.is_strict())
+    }
+
+    /// Replaces the spec of the service, `version` being the version of the
+    /// service the spec is based on.
+    fn update_service(
+        &self,
+        id: &str,
+        version: ObjectVersion,
+        spec: &ServiceSpec,
+    ) -> Result<(), DockerApiError> {
+        let api_endpoint =
+            format!("/services/{}/update?version={}", id, version.index);
+        let method = "POST";
+        let body = match serde_json::to_string(spec) {
+            Ok(body) => body,
+            Err(err) => {
+                return Err(DockerApiError::JsonSerializationError(err))
+            }
+        };
+
+        let resp = self.get_response_from_api(&api_endpoint, method, &body)?;
+
+        if resp.status_code != 200 {
+            return Err(DockerApiError::from_response(
+                ApiObject::Service,
+                resp.status_code,
+                resp.body,
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn remove_service(&self, id: &str) -> Result<(), DockerApiError> {
+        let api_endpoint = format!("/services/{id}", id = id);
+        let method = "DELETE";
+
+        let resp = self.get_response_from_api(&api_endpoint, method, "")?;
+
+        if resp.status_code != 200 {
+            return Err(DockerApiError::from_response(
+                ApiObject::Service,
+                resp.status_code,
+                resp.body,
+            ));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,575 @@
+use std::collections::HashMap;
+use std::sync::mpsc::{channel, Receiver};
+use std::thread;
+use std::time::Duration;
+
+use api::ids::ContainerRef;
+use api::version::ApiFeature;
+use api::DockerApiClient;
+use utils::{deserialize_body, parse_rfc3339, JsonStream, StreamHandle};
+
+use errors::{ApiObject, DockerApiError};
+
+/// How often the daemon samples the usage of the containers, the time
+/// between the two samples of `Stats::get_container_stats_once`.
+const SAMPLING_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct CpuUsage {
+    #[serde(default)]
+    pub total_usage: u64,
+
+    #[serde(default)]
+    pub percpu_usage: Option<Vec<u64>>,
+
+    #[serde(default)]
+    pub usage_in_kernelmode: u64,
+
+    #[serde(default)]
+    pub usage_in_usermode: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct CpuStats {
+    #[serde(default)]
+    pub cpu_usage: CpuUsage,
+
+    #[serde(default)]
+    pub system_cpu_usage: u64,
+
+    #[serde(default)]
+    pub online_cpus: u32,
+}
+
+/// * stats: Raw memory statistics from the cgroup, like `cache`.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct MemoryStats {
+    #[serde(default)]
+    pub usage: u64,
+
+    #[serde(default)]
+    pub max_usage: u64,
+
+    #[serde(default)]
+    pub limit: u64,
+
+    #[serde(default)]
+    pub stats: HashMap<String, u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct NetworkStats {
+    #[serde(default)]
+    pub rx_bytes: u64,
+
+    #[serde(default)]
+    pub rx_packets: u64,
+
+    #[serde(default)]
+    pub rx_errors: u64,
+
+    #[serde(default)]
+    pub rx_dropped: u64,
+
+    #[serde(default)]
+    pub tx_bytes: u64,
+
+    #[serde(default)]
+    pub tx_packets: u64,
+
+    #[serde(default)]
+    pub tx_errors: u64,
+
+    #[serde(default)]
+    pub tx_dropped: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct PidsStats {
+    #[serde(default)]
+    pub current: Option<u64>,
+
+    #[serde(default)]
+    pub limit: Option<u64>,
+}
+
+/// A sample of the resource usage of a container.
+///
+/// * read: Time of the sample.
+/// * preread, precpu_stats: Time and CPU usage of the previous sample.
+/// * networks: Usage per network interface.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct ContainerStats {
+    #[serde(default)]
+    pub id: String,
+
+    #[serde(default)]
+    pub name: String,
+
+    #[serde(default)]
+    pub read: String,
+
+    #[serde(default)]
+    pub preread: String,
+
+    #[serde(default)]
+    pub cpu_stats: CpuStats,
+
+    #[serde(default)]
+    pub precpu_stats: CpuStats,
+
+    #[serde(default)]
+    pub memory_stats: MemoryStats,
+
+    #[serde(default)]
+    pub networks: Option<HashMap<String, NetworkStats>>,
+
+    #[serde(default)]
+    pub pids_stats: PidsStats,
+}
+
+impl ContainerStats {
+    /// Share of a CPU used since the previous sample, see
+    /// `UsageSnapshot::cpu_percent`. None if the sample has no previous
+    /// CPU usage.
+    pub fn cpu_percent(&self) -> Option<f64> {
+        cpu_percent(&self.precpu_stats, &self.cpu_stats)
+    }
+
+    /// Bytes received and sent on all the network interfaces.
+    pub fn network_totals(&self) -> (u64, u64) {
+        self.networks
+            .iter()
+            .flat_map(|networks| networks.values())
+            .fold((0, 0), |(rx, tx), network| {
+                (rx + network.rx_bytes, tx + network.tx_bytes)
+            })
+    }
+}
+
+/// Resource usage of a container computed from two of its samples.
+///
+/// * cpu_percent: Share of a CPU used between the samples, up to 100 per
+///   CPU of the host, like `docker stats` shows it.
+/// * memory_working_set: Memory used without the inactive page cache, which
+///   the kernel reclaims before killing the container.
+/// * rx_bytes_per_sec, tx_bytes_per_sec: Network throughput between the
+///   samples, summed over the interfaces.
+///
+/// The rates are None for the first sample of a container, and after its
+/// counters were reset, like when it restarted.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct UsageSnapshot {
+    pub read: String,
+    pub cpu_percent: Option<f64>,
+    pub memory_working_set: u64,
+    pub memory_limit: u64,
+    pub rx_bytes_per_sec: Option<f64>,
+    pub tx_bytes_per_sec: Option<f64>,
+}
+
+impl UsageSnapshot {
+    /// Computes the usage of `current` since `previous`, an earlier sample
+    /// of the same container.
+    pub fn between(
+        previous: Option<&ContainerStats>,
+        current: &ContainerStats,
+    ) -> UsageSnapshot {
+        let memory = &current.memory_stats;
+        // `total_inactive_file` on cgroup v1, `inactive_file` on v2.
+        let inactive = memory
+            .stats
+            .get("total_inactive_file")
+            .or_else(|| memory.stats.get("inactive_file"))
+            .cloned()
+            .unwrap_or(0);
+
+        let mut snapshot = UsageSnapshot {
+            read: current.read.clone(),
+            memory_working_set: memory.usage.saturating_sub(inactive),
+            memory_limit: memory.limit,
+            ..Default::default()
+        };
+        let previous = match previous {
+            Some(previous) => previous,
+            None => return snapshot,
+        };
+
+        snapshot.cpu_percent =
+            cpu_percent(&previous.cpu_stats, &current.cpu_stats);
+
+        let elapsed =
+            match (parse_rfc3339(&previous.read), parse_rfc3339(&current.read))
+            {
+                (Some(previous), Some(current)) => {
+                    current.duration_since(previous).unwrap_or_default()
+                }
+                _ => Duration::from_secs(0),
+            };
+        let (rx, tx) = current.network_totals();
+        let (previous_rx, previous_tx) = previous.network_totals();
+        if elapsed > Duration::from_secs(0) {
+            let secs = elapsed.as_secs_f64();
+            snapshot.rx_bytes_per_sec =
+                rx.checked_sub(previous_rx).map(|rx| rx as f64 / secs);
+            snapshot.tx_bytes_per_sec =
+                tx.checked_sub(previous_tx).map(|tx| tx as f64 / secs);
+        }
+
+        snapshot
+    }
+}
+
+fn cpu_percent(previous: &CpuStats, current: &CpuStats) -> Option<f64> {
+    // The first sample of a stream has no previous one.
+    if previous.system_cpu_usage == 0 {
+        return None;
+    }
+    let cpu_delta = current
+        .cpu_usage
+        .total_usage
+        .checked_sub(previous.cpu_usage.total_usage)?;
+    let system_delta = current
+        .system_cpu_usage
+        .checked_sub(previous.system_cpu_usage)?;
+    if system_delta == 0 {
+        return None;
+    }
+    let cpus = match current.online_cpus {
+        0 => current
+            .cpu_usage
+            .percpu_usage
+            .as_ref()
+            .map_or(1, |c| c.len()),
+        cpus => cpus as usize,
+    };
+
+    Some(cpu_delta as f64 / system_delta as f64 * cpus as f64 * 100.0)
+}
+
+fn supports_one_shot<C: DockerApiClient + ?Sized>(client: &C) -> bool {
+    client.api_version().is_some_and(|version| {
+        version >= ApiFeature::OneShotStats.min_api_version()
+    })
+}
+
+/// Keeps the last sample of every container it took one of, so that the
+/// usage is computed since the previous call for the same container.
+///
+/// ```rust,ignore
+/// let mut tracker = UsageTracker::new();
+/// loop {
+///     let usage = tracker.usage_snapshot(&client, "web")?;
+///     println!("{:?}% {} bytes", usage.cpu_percent, usage.memory_working_set);
+///     thread::sleep(Duration::from_secs(10));
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct UsageTracker {
+    previous: HashMap<String, ContainerStats>,
+}
+
+impl UsageTracker {
+    pub fn new() -> UsageTracker {
+        UsageTracker::default()
+    }
+
+    /// Takes a sample of the container and gives its usage since the
+    /// previous one.
+    pub fn usage_snapshot<C: Stats>(
+        &mut self,
+        client: &C,
+        id: impl ContainerRef,
+    ) -> Result<UsageSnapshot, DockerApiError> {
+        let id = id.container_ref();
+        let current =
+            client.get_container_stats_sample(id, supports_one_shot(client))?;
+        let snapshot = UsageSnapshot::between(self.previous.get(id), &current);
+        self.previous.insert(id.to_string(), current);
+        Ok(snapshot)
+    }
+
+    /// Forgets the last sample of the container, like once it is removed.
+    pub fn forget(&mut self, id: &str) {
+        self.previous.remove(id);
+    }
+}
+
+/// Iterator over the stats samples of a container, the daemon sends one
+/// every second.
+pub type StatsStream = JsonStream<ContainerStats>;
+
+/// Samples of a set of containers taken in the same round.
+///
+/// * read: Time of the most recent sample of the round.
+/// * stats: Latest sample of every container still streaming, in the order
+///   the ids were given to `stats_for`.
+#[derive(Debug, Default, Clone)]
+pub struct StatsSnapshot {
+    pub read: String,
+    pub stats: Vec<ContainerStats>,
+}
+
+enum StatsEvent {
+    Sample(usize, Box<ContainerStats>),
+    Error(DockerApiError),
+    Done(usize),
+}
+
+/// Iterator merging the stats streams of several containers.
+///
+/// Every stream is read on its own thread. A snapshot is yielded once all
+/// the containers still streaming have sent a new sample, so the samples of
+/// a snapshot are at most one stats interval apart. Containers whose stream
+/// ends, for instance because they stopped, are dropped from the following
+/// snapshots and the iterator ends when no stream is left.
+pub struct MultiStatsStream {
+    receiver: Receiver<StatsEvent>,
+    latest: Vec<Option<ContainerStats>>,
+    fresh: Vec<bool>,
+    live: Vec<bool>,
+    handle: StreamHandle,
+}
+
+impl MultiStatsStream {
+    pub fn new(streams: Vec<StatsStream>) -> MultiStatsStream {
+        let (sender, receiver) = channel();
+        let handles: Vec<StreamHandle> =
+            streams.iter().map(|stream| stream.handle()).collect();
+        let count = streams.len();
+
+        for (index, stream) in streams.into_iter().enumerate() {
+            let sender = sender.clone();
+            thread::spawn(move || {
+                for sample in stream {
+                    let event = match sample {
+                        Ok(stats) => StatsEvent::Sample(index, Box::new(stats)),
+                        Err(err) => StatsEvent::Error(err),
+                    };
+                    if sender.send(event).is_err() {
+                        return;
+                    }
+                }
+                let _ = sender.send(StatsEvent::Done(index));
+            });
+        }
+
+        let handle = StreamHandle::new();
+        handle.set_shutdown(move || {
+            for handle in &handles {
+                handle.cancel();
+            }
+        });
+
+        MultiStatsStream {
+            receiver,
+            latest: vec![None; count],
+            fresh: vec![false; count],
+            live: vec![true; count],
+            handle,
+        }
+    }
+
+    /// Handle to stop all the streams from another thread.
+    pub fn handle(&self) -> StreamHandle {
+        self.handle.clone()
+    }
+
+    fn is_round_complete(&self) -> bool {
+        let mut rounds = self.live.iter().zip(&self.fresh);
+        self.fresh.iter().any(|fresh| *fresh)
+            && rounds.all(|(live, fresh)| !*live || *fresh)
+    }
+
+    fn snapshot(&mut self) -> StatsSnapshot {
+        let stats: Vec<ContainerStats> = self
+            .latest
+            .iter()
+            .zip(self.live.iter().zip(&self.fresh))
+            .filter_map(|(stats, (live, fresh))| match stats {
+                Some(stats) if *live || *fresh => Some(stats.clone()),
+                _ => None,
+            })
+            .collect();
+
+        for fresh in &mut self.fresh {
+            *fresh = false;
+        }
+        let read = stats
+            .iter()
+            .map(|stats| stats.read.clone())
+            .max()
+            .unwrap_or_default();
+
+        StatsSnapshot { read, stats }
+    }
+}
+
+impl Iterator for MultiStatsStream {
+    type Item = Result<StatsSnapshot, DockerApiError>;
+
+    fn next(&mut self) -> Option<Result<StatsSnapshot, DockerApiError>> {
+        while self.live.iter().any(|live| *live) {
+            if self.handle.is_cancelled() {
+                return None;
+            }
+
+            match self.receiver.recv() {
+                Ok(StatsEvent::Sample(index, stats)) => {
+                    self.latest[index] = Some(*stats);
+                    self.fresh[index] = true;
+                }
+                Ok(StatsEvent::Error(err)) => return Some(Err(err)),
+                Ok(StatsEvent::Done(index)) => {
+                    self.live[index] = false;
+                }
+                Err(_) => return None,
+            }
+
+            if self.is_round_complete() {
+                return Some(Ok(self.snapshot()));
+            }
+        }
+
+        None
+    }
+}
+
+pub trait Stats: DockerApiClient {
+    /// Get the resource usage of the container as a stream of samples, use
+    /// the handle from `StatsStream::handle` to stop it.
+    fn get_container_stats(
+        &self,
+        id: impl ContainerRef,
+    ) -> Result<StatsStream, DockerApiError>
+    where
+        Self: Sized,
+    {
+        let id = id.container_ref();
+        let api_endpoint =
+            format!("/containers/{id}/stats?stream=true", id = id);
+
+        let resp = self.get_stream_from_api(&api_endpoint, "GET", "", &[])?;
+
+        if resp.status_code != 200 {
+            let status_code = resp.status_code;
+            return Err(DockerApiError::from_response(
+                ApiObject::Container,
+                status_code,
+                resp.body_to_string()?,
+            ));
+        }
+
+        Ok(StatsStream::new(resp))
+    }
+
+    /// Get a single sample of the resource usage of the container, with
+    /// the previous CPU usage so that `ContainerStats::cpu_percent` can be
+    /// computed.
+    ///
+    /// The daemons leave the previous usage empty in the first sample of a
+    /// container, a second one is then taken a second later. The samples
+    /// are taken with `one-shot` when the API version of the client has
+    /// it, so that the daemon does not wait for its own second sample.
+    fn get_container_stats_once(
+        &self,
+        id: impl ContainerRef,
+    ) -> Result<ContainerStats, DockerApiError>
+    where
+        Self: Sized,
+    {
+        let id = id.container_ref();
+        let one_shot = supports_one_shot(self);
+        let first = self.get_container_stats_sample(id, one_shot)?;
+        if first.precpu_stats.system_cpu_usage != 0 {
+            return Ok(first);
+        }
+
+        thread::sleep(SAMPLING_INTERVAL);
+        let mut second = self.get_container_stats_sample(id, one_shot)?;
+        second.precpu_stats = first.cpu_stats;
+        second.preread = first.read;
+        Ok(second)
+    }
+
+    /// Get a single sample of the resource usage of the container as the
+    /// daemon gives it, the previous CPU usage can be empty. `one_shot`
+    /// needs API 1.41.
+    fn get_container_stats_sample(
+        &self,
+        id: impl ContainerRef,
+        one_shot: bool,
+    ) -> Result<ContainerStats, DockerApiError>
+    where
+        Self: Sized,
+    {
+        let id = id.container_ref();
+        let mut api_endpoint =
+            format!("/containers/{id}/stats?stream=false", id = id);
+        if one_shot {
+            api_endpoint.push_str("&one-shot=true");
+        }
+        let method = "GET";
+
+        let resp = self.get_response_from_api(&api_endpoint, method, "")?;
+
+        if resp.status_code != 200 {
+            return Err(DockerApiError::from_response(
+                ApiObject::Container,
+                resp.status_code,
+                resp.body,
+            ));
+        }
+
+        deserialize_body(&resp.body, self.is_strict())
+    }
+
+    /// Get the resource usage of a set of containers, merged in snapshots
+    /// with one sample per container, like `docker stats` shows them.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// extern crate rust_docker;
+    ///
+    /// use rust_docker::api::stats::Stats;
+    /// use rust_docker::client::DockerClient;
+    ///
+    /// let client = match DockerClient::new("unix:///var/run/docker.sock") {
+    ///     Ok(a) => a,
+    ///     Err(err) => {
+    ///         println!("{}", err);
+    ///         std::process::exit(1);
+    ///     }
+    /// };
+    ///
+    /// match client.stats_for(&["web", "db"]) {
+    ///     Ok(snapshots) => for snapshot in snapshots.take(5) {
+    ///         println!("{:?}", snapshot);
+    ///     },
+    ///     Err(err) => println!("An error occured : {}", err),
+    /// }
+    /// ```
+    fn stats_for(
+        &self,
+        ids: &[&str],
+    ) -> Result<MultiStatsStream, DockerApiError>
+    where
+        Self: Sized,
+    {
+        let mut streams = Vec::new();
+        for id in ids {
+            match self.get_container_stats(id) {
+                Ok(stream) => streams.push(stream),
+                Err(err) => {
+                    for stream in &streams {
+                        stream.handle().cancel();
+                    }
+                    return Err(err);
+                }
+            }
+        }
+
+        Ok(MultiStatsStream::new(streams))
+    }
+}
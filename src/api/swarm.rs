@@ -0,0 +1,133 @@
+use std::time::SystemTime;
+
+use api::DockerApiClient;
+use utils::{
+    deserialize_body, deserialize_timestamp, serialize_rfc3339_timestamp,
+};
+
+use serde_json;
+
+use errors::{ApiObject, DockerApiError};
+
+/// Version of an object of the swarm. It is given back with the updates so
+/// that an update based on an outdated object is rejected.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct ObjectVersion {
+    #[serde(default)]
+    pub index: u64,
+}
+
+/// Tokens for the nodes to join the swarm as workers or as managers, with
+/// `docker swarm join --token`.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct JoinTokens {
+    #[serde(default)]
+    pub worker: String,
+
+    #[serde(default)]
+    pub manager: String,
+}
+
+/// The swarm, as only its managers can inspect it.
+///
+/// * spec: Settings of the swarm, sent back as they are when updating it.
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "PascalCase")]
+#[serde(default)]
+pub struct SwarmDetails {
+    #[serde(rename = "ID")]
+    pub id: String,
+
+    pub version: ObjectVersion,
+
+    #[serde(
+        default,
+        deserialize_with = "deserialize_timestamp",
+        serialize_with = "serialize_rfc3339_timestamp"
+    )]
+    pub created_at: Option<SystemTime>,
+
+    #[serde(
+        default,
+        deserialize_with = "deserialize_timestamp",
+        serialize_with = "serialize_rfc3339_timestamp"
+    )]
+    pub updated_at: Option<SystemTime>,
+
+    pub spec: serde_json::Value,
+    pub join_tokens: JoinTokens,
+}
+
+pub trait Swarm: DockerApiClient {
+    /// Inspects the swarm, the daemon has to be a manager of it.
+    fn inspect_swarm(&self) -> Result<SwarmDetails, DockerApiError> {
+        let api_endpoint = "/swarm";
+        let method = "GET";
+
+        let resp = self.get_response_from_api(api_endpoint, method, "")?;
+
+        if resp.status_code != 200 {
+            return Err(DockerApiError::from_response(
+                ApiObject::Swarm,
+                resp.status_code,
+                resp.body,
+            ));
+        }
+
+        deserialize_body(&resp.body, self.is_strict())
+    }
+
+    /// Token for a node to join the swarm as a worker.
+    fn worker_join_token(&self) -> Result<String, DockerApiError> {
+        Ok(self.inspect_swarm()?.join_tokens.worker)
+    }
+
+    /// Token for a node to join the swarm as a manager.
+    fn manager_join_token(&self) -> Result<String, DockerApiError> {
+        Ok(self.inspect_swarm()?.join_tokens.manager)
+    }
+
+    /// Replaces the join tokens of the workers and of the managers with new
+    /// ones, giving back the tokens in use afterwards. The nodes which
+    /// already joined are not affected.
+    ///
+    /// The update is done on the version of the swarm just inspected, it
+    /// fails if the swarm is updated in between.
+    ///
+    /// ```rust,ignore
+    /// let tokens = client.rotate_join_tokens(true, false)?;
+    /// println!("docker swarm join --token {} manager:2377", tokens.worker);
+    /// ```
+    fn rotate_join_tokens(
+        &self,
+        worker: bool,
+        manager: bool,
+    ) -> Result<JoinTokens, DockerApiError> {
+        let swarm = self.inspect_swarm()?;
+        let api_endpoint = format!(
+            "/swarm/update?version={}&rotateWorkerToken={}&rotateManagerToken={}",
+            swarm.version.index, worker, manager
+        );
+        let method = "POST";
+        let body = match serde_json::to_string(&swarm.spec) {
+            Ok(body) => body,
+            Err(err) => {
+                return Err(DockerApiError::JsonSerializationError(err))
+            }
+        };
+
+        let resp = self.get_response_from_api(&api_endpoint, method, &body)?;
+
+        if resp.status_code != 200 {
+            return Err(DockerApiError::from_response(
+                ApiObject::Swarm,
+                resp.status_code,
+                resp.body,
+            ));
+        }
+
+        Ok(self.inspect_swarm()?.join_tokens)
+    }
+}
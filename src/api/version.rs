@@ -1,6 +1,441 @@
+use std::fmt;
+use std::time::SystemTime;
+
 use api::api_utils::get_formatted_api_request;
 use api::DockerApiClient;
-use utils::Response;
+use utils::{
+    deserialize_body, deserialize_null_default, serialize_rfc3339_timestamp,
+    Response,
+};
+
+use serde::de::{Deserialize, Deserializer, Error};
+use serde::ser::{Serialize, Serializer};
+
+use errors::DockerApiError;
+
+/// A version of the Engine API, like `1.41`. Versions are compared by
+/// their major then minor number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct ApiVersion {
+    pub major: u32,
+    pub minor: u32,
+}
+
+impl ApiVersion {
+    pub fn new(major: u32, minor: u32) -> ApiVersion {
+        ApiVersion { major, minor }
+    }
+
+    /// Parses `major.minor`, with an optional `v` prefix like in the
+    /// request paths.
+    pub fn parse(version: &str) -> Option<ApiVersion> {
+        let version = version.trim().trim_start_matches('v');
+        let (major, minor) = version.split_once('.')?;
+        Some(ApiVersion {
+            major: major.parse().ok()?,
+            minor: minor.parse().ok()?,
+        })
+    }
+}
+
+impl fmt::Display for ApiVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+impl Serialize for ApiVersion {
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for ApiVersion {
+    fn deserialize<D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<ApiVersion, D::Error> {
+        let version = String::deserialize(deserializer)?;
+        match ApiVersion::parse(&version) {
+            Some(version) => Ok(version),
+            None => Err(D::Error::custom(format!(
+                "invalid API version {}",
+                version
+            ))),
+        }
+    }
+}
+
+/// Versions of the daemon, from the version endpoint.
+///
+/// * api_version: Highest API version the daemon supports.
+/// * min_api_version: Lowest API version the daemon supports.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct VersionInfo {
+    #[serde(default)]
+    pub version: String,
+
+    #[serde(default)]
+    pub api_version: ApiVersion,
+
+    #[serde(rename = "MinAPIVersion", default)]
+    pub min_api_version: ApiVersion,
+
+    #[serde(default)]
+    pub git_commit: String,
+
+    #[serde(default)]
+    pub os: String,
+
+    #[serde(default)]
+    pub arch: String,
+
+    #[serde(default)]
+    pub kernel_version: String,
+
+    #[serde(default)]
+    pub experimental: bool,
+}
+
+/// State of the swarm the daemon is part of, `inactive` when it is not.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct SwarmInfo {
+    #[serde(default)]
+    pub local_node_state: String,
+
+    #[serde(rename = "NodeID", default)]
+    pub node_id: String,
+}
+
+/// The drivers the daemon has, built in or from plugins, by kind.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct PluginsInfo {
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub volume: Vec<String>,
+
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub network: Vec<String>,
+
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub authorization: Vec<String>,
+
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub log: Vec<String>,
+}
+
+/// System wide information of the daemon, from the info endpoint. Only
+/// the most used fields are kept.
+///
+/// * cgroup_version: `1` or `2`, empty on the daemons before API 1.40.
+/// * security_options: Like `name=seccomp,profile=default`, with
+///   `name=rootless` for the rootless daemons.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct SystemInfo {
+    #[serde(rename = "ID", default)]
+    pub id: String,
+
+    #[serde(default)]
+    pub name: String,
+
+    #[serde(default)]
+    pub server_version: String,
+
+    #[serde(default)]
+    pub containers: u64,
+
+    #[serde(default)]
+    pub containers_running: u64,
+
+    #[serde(default)]
+    pub images: u64,
+
+    #[serde(default)]
+    pub driver: String,
+
+    #[serde(default)]
+    pub cgroup_driver: String,
+
+    #[serde(default)]
+    pub cgroup_version: String,
+
+    #[serde(rename = "OSType", default)]
+    pub os_type: String,
+
+    #[serde(default)]
+    pub architecture: String,
+
+    #[serde(rename = "NCPU", default)]
+    pub ncpu: u64,
+
+    #[serde(default)]
+    pub mem_total: u64,
+
+    #[serde(default)]
+    pub experimental_build: bool,
+
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub security_options: Vec<String>,
+
+    #[serde(default)]
+    pub swarm: SwarmInfo,
+
+    #[serde(default)]
+    pub plugins: PluginsInfo,
+}
+
+/// A plugin installed with `docker plugin install`.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct Plugin {
+    #[serde(rename = "Id", default)]
+    pub id: String,
+
+    #[serde(default)]
+    pub name: String,
+
+    #[serde(default)]
+    pub enabled: bool,
+
+    #[serde(default)]
+    pub plugin_reference: String,
+}
+
+/// Disk space of an image. The shared size is -1 unless computed.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct ImageUsage {
+    #[serde(rename = "Id", default)]
+    pub id: String,
+
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub repo_tags: Vec<String>,
+
+    #[serde(default)]
+    pub size: i64,
+
+    #[serde(default)]
+    pub shared_size: i64,
+
+    #[serde(default)]
+    pub containers: i64,
+}
+
+/// Disk space of a container, its writable layer and its whole filesystem.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct ContainerUsage {
+    #[serde(rename = "Id", default)]
+    pub id: String,
+
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub names: Vec<String>,
+
+    #[serde(default)]
+    pub image: String,
+
+    #[serde(default)]
+    pub state: String,
+
+    #[serde(default)]
+    pub size_rw: i64,
+
+    #[serde(default)]
+    pub size_root_fs: i64,
+}
+
+/// Size and number of containers using a volume, -1 when the driver does
+/// not tell.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct VolumeUsageData {
+    #[serde(default)]
+    pub size: i64,
+
+    #[serde(default)]
+    pub ref_count: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct VolumeUsage {
+    #[serde(default)]
+    pub name: String,
+
+    #[serde(default)]
+    pub driver: String,
+
+    #[serde(default)]
+    pub usage_data: VolumeUsageData,
+}
+
+/// Disk space of an entry of the build cache.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct BuildCacheUsage {
+    #[serde(rename = "ID", default)]
+    pub id: String,
+
+    #[serde(rename = "Type", default)]
+    pub cache_type: String,
+
+    #[serde(default)]
+    pub size: i64,
+
+    #[serde(default)]
+    pub in_use: bool,
+
+    #[serde(default)]
+    pub shared: bool,
+}
+
+/// Disk space used by the daemon, from the data usage endpoint like
+/// `docker system df -v`.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct DataUsage {
+    #[serde(default)]
+    pub layers_size: i64,
+
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub images: Vec<ImageUsage>,
+
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub containers: Vec<ContainerUsage>,
+
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub volumes: Vec<VolumeUsage>,
+
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub build_cache: Vec<BuildCacheUsage>,
+}
+
+impl DataUsage {
+    /// Size of the writable layers of the containers.
+    pub fn containers_size(&self) -> i64 {
+        self.containers.iter().map(|c| c.size_rw.max(0)).sum()
+    }
+
+    /// Size of the volumes whose driver tells it.
+    pub fn volumes_size(&self) -> i64 {
+        self.volumes.iter().map(|v| v.usage_data.size.max(0)).sum()
+    }
+
+    /// Size of the build cache, shared entries included.
+    pub fn build_cache_size(&self) -> i64 {
+        self.build_cache.iter().map(|b| b.size.max(0)).sum()
+    }
+}
+
+/// Everything known about a daemon in one place, for support bundles and
+/// inventories, serialized as JSON to be stored or sent.
+///
+/// * collected_at: When the report was made.
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct DaemonReport {
+    #[serde(serialize_with = "serialize_rfc3339_timestamp")]
+    pub collected_at: Option<SystemTime>,
+
+    pub version: VersionInfo,
+    pub info: SystemInfo,
+    pub data_usage: DataUsage,
+    pub plugins: Vec<Plugin>,
+}
+
+/// A feature of the API only available from some version on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ApiFeature {
+    /// `Init` in the `HostConfig`.
+    Init,
+    /// `StopTimeout` in the container config.
+    StopTimeout,
+    /// `StartPeriod` of the healthchecks.
+    HealthStartPeriod,
+    /// The `platform` of the image pulls.
+    PullPlatform,
+    /// BuildKit builds.
+    BuildKit,
+    /// `DeviceRequests` in the `HostConfig`, like GPUs.
+    DeviceRequests,
+    /// The `platform` of the container creates.
+    CreatePlatform,
+    /// More than one network in the `NetworkingConfig` of a create.
+    MultipleNetworksOnCreate,
+    /// `one-shot` stats samples, without the previous CPU usage.
+    OneShotStats,
+}
+
+impl ApiFeature {
+    /// Lowest API version with the feature.
+    pub fn min_api_version(self) -> ApiVersion {
+        match self {
+            ApiFeature::Init => ApiVersion::new(1, 25),
+            ApiFeature::StopTimeout => ApiVersion::new(1, 25),
+            ApiFeature::HealthStartPeriod => ApiVersion::new(1, 29),
+            ApiFeature::PullPlatform => ApiVersion::new(1, 32),
+            ApiFeature::BuildKit => ApiVersion::new(1, 39),
+            ApiFeature::DeviceRequests => ApiVersion::new(1, 40),
+            ApiFeature::CreatePlatform => ApiVersion::new(1, 41),
+            ApiFeature::MultipleNetworksOnCreate => ApiVersion::new(1, 44),
+            ApiFeature::OneShotStats => ApiVersion::new(1, 41),
+        }
+    }
+}
+
+/// What the daemon supports, to degrade gracefully on older daemons.
+///
+/// * buildkit: BuildKit builds are available, which needs a Linux daemon.
+/// * swarm_active: The daemon is a node of an active swarm.
+/// * cgroup_v2: The daemon runs on the unified cgroup hierarchy.
+/// * rootless: The daemon runs without root.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DaemonCapabilities {
+    pub version: String,
+    pub api_version: ApiVersion,
+    pub min_api_version: ApiVersion,
+    pub buildkit: bool,
+    pub swarm_active: bool,
+    pub cgroup_v2: bool,
+    pub rootless: bool,
+    pub experimental: bool,
+}
+
+impl DaemonCapabilities {
+    pub fn from_info(
+        version: &VersionInfo,
+        info: &SystemInfo,
+    ) -> DaemonCapabilities {
+        DaemonCapabilities {
+            version: version.version.clone(),
+            api_version: version.api_version,
+            min_api_version: version.min_api_version,
+            buildkit: version.api_version
+                >= ApiFeature::BuildKit.min_api_version()
+                && info.os_type == "linux",
+            swarm_active: info.swarm.local_node_state == "active",
+            cgroup_v2: info.cgroup_version == "2",
+            rootless: info
+                .security_options
+                .iter()
+                .any(|option| option.split(',').any(|o| o == "name=rootless")),
+            experimental: version.experimental || info.experimental_build,
+        }
+    }
+
+    /// Checks if the daemon supports the feature.
+    pub fn supports(&self, feature: ApiFeature) -> bool {
+        match feature {
+            ApiFeature::BuildKit => self.buildkit,
+            _ => self.api_version >= feature.min_api_version(),
+        }
+    }
+}
 
 pub trait Version: DockerApiClient {
     /// Get version info for Docker
@@ -8,10 +443,9 @@ pub trait Version: DockerApiClient {
     ///
     /// # Example
     ///
-    /// ```rust
+    /// ```rust,no_run
     /// extern crate rust_docker;
     ///
-    /// use rust_docker::api::containers::Containers;
     /// use rust_docker::api::version::Version;
     /// use rust_docker::client::DockerClient;
     ///
@@ -49,4 +483,106 @@ pub trait Version: DockerApiClient {
 
         Ok(resp)
     }
+
+    /// Gets the versions of the daemon and of the API it supports.
+    fn get_version(&self) -> Result<VersionInfo, DockerApiError> {
+        let api_endpoint = "/version";
+        let method = "GET";
+
+        let resp = self.get_response_from_api(api_endpoint, method, "")?;
+
+        if resp.status_code != 200 {
+            return Err(DockerApiError::InvalidApiResponseError(
+                resp.status_code,
+                resp.body,
+            ));
+        }
+
+        deserialize_body(&resp.body, self.is_strict())
+    }
+
+    /// Gets the system wide information of the daemon, typed unlike
+    /// `get_version_info`.
+    fn get_system_info(&self) -> Result<SystemInfo, DockerApiError> {
+        let api_endpoint = "/info";
+        let method = "GET";
+
+        let resp = self.get_response_from_api(api_endpoint, method, "")?;
+
+        if resp.status_code != 200 {
+            return Err(DockerApiError::InvalidApiResponseError(
+                resp.status_code,
+                resp.body,
+            ));
+        }
+
+        deserialize_body(&resp.body, self.is_strict())
+    }
+
+    /// Gets the disk space used by the images, containers, volumes and build
+    /// cache. The daemon computes it on request, which can take a while.
+    fn get_data_usage(&self) -> Result<DataUsage, DockerApiError> {
+        let api_endpoint = "/system/df";
+        let method = "GET";
+
+        let resp = self.get_response_from_api(api_endpoint, method, "")?;
+
+        if resp.status_code != 200 {
+            return Err(DockerApiError::InvalidApiResponseError(
+                resp.status_code,
+                resp.body,
+            ));
+        }
+
+        deserialize_body(&resp.body, self.is_strict())
+    }
+
+    /// Lists the plugins installed on the daemon, enabled or not.
+    fn list_plugins(&self) -> Result<Vec<Plugin>, DockerApiError> {
+        let api_endpoint = "/plugins";
+        let method = "GET";
+
+        let resp = self.get_response_from_api(api_endpoint, method, "")?;
+
+        if resp.status_code != 200 {
+            return Err(DockerApiError::InvalidApiResponseError(
+                resp.status_code,
+                resp.body,
+            ));
+        }
+
+        deserialize_body(&resp.body, self.is_strict())
+    }
+
+    /// Gathers the version, the system information, the data usage and the
+    /// plugins of the daemon in a `DaemonReport`.
+    ///
+    /// ```rust,ignore
+    /// let report = client.daemon_report()?;
+    /// fs::write("daemon.json", serde_json::to_string_pretty(&report)?)?;
+    /// ```
+    fn daemon_report(&self) -> Result<DaemonReport, DockerApiError> {
+        Ok(DaemonReport {
+            collected_at: Some(SystemTime::now()),
+            version: self.get_version()?,
+            info: self.get_system_info()?,
+            data_usage: self.get_data_usage()?,
+            plugins: self.list_plugins()?,
+        })
+    }
+
+    /// Gets what the daemon supports from its version and its system
+    /// information.
+    ///
+    /// ```rust,ignore
+    /// let capabilities = client.capabilities()?;
+    /// if !capabilities.supports(ApiFeature::DeviceRequests) {
+    ///     println!("No GPUs on a daemon with API {}", capabilities.api_version);
+    /// }
+    /// ```
+    fn capabilities(&self) -> Result<DaemonCapabilities, DockerApiError> {
+        let version = self.get_version()?;
+        let info = self.get_system_info()?;
+        Ok(DaemonCapabilities::from_info(&version, &info))
+    }
 }
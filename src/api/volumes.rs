@@ -0,0 +1,466 @@
+use std::collections::HashMap;
+#[cfg(feature = "containers")]
+use std::io::{self, Read, Write};
+use std::mem::{self, ManuallyDrop};
+use std::path::Path;
+use std::time::SystemTime;
+
+#[cfg(feature = "containers")]
+use api::containers::{ContainerConfig, Containers, HostConfig, MountSpec};
+use api::{DockerApiClient, PruneFilters};
+use utils::{
+    deserialize_body, deserialize_null_default, deserialize_timestamp,
+    encode_query_value, serialize_rfc3339_timestamp,
+};
+
+use serde_json;
+
+use errors::{ApiObject, DockerApiError};
+
+/// Image of the containers mounting the volumes for `backup_volume` and
+/// `restore_volume`. They are never started, the image only has to be
+/// there.
+#[cfg(feature = "containers")]
+pub const HELPER_IMAGE: &str = "busybox:latest";
+
+/// Where the helper containers mount the volume, the archives of the
+/// backups hold a directory of this name.
+#[cfg(feature = "containers")]
+const HELPER_MOUNT: &str = "/volume";
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "PascalCase")]
+#[serde(default)]
+pub struct Volume {
+    pub name: String,
+    pub driver: String,
+    pub mountpoint: String,
+
+    #[serde(
+        default,
+        deserialize_with = "deserialize_timestamp",
+        serialize_with = "serialize_rfc3339_timestamp"
+    )]
+    pub created_at: Option<SystemTime>,
+    pub labels: Option<HashMap<String, String>>,
+    pub scope: String,
+    pub options: Option<HashMap<String, String>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "PascalCase")]
+pub struct VolumeList {
+    pub volumes: Option<Vec<Volume>>,
+    pub warnings: Option<Vec<String>>,
+}
+
+/// What `Volumes::prune_volumes` removed.
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "PascalCase")]
+pub struct VolumesPruneReport {
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub volumes_deleted: Vec<String>,
+    #[serde(default)]
+    pub space_reclaimed: u64,
+}
+
+/// Structure for the request body of the volume create endpoint.
+/// Use `VolumeCreateBuilder` to get the driver options right for the
+/// `local` driver.
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "PascalCase")]
+pub struct VolumeConfig {
+    pub name: String,
+    pub driver: String,
+    pub driver_opts: HashMap<String, String>,
+    pub labels: HashMap<String, String>,
+}
+
+/// Builder for `VolumeConfig`.
+///
+/// The `local` driver takes the same options as the `mount` command, the
+/// helpers here set `device`, `o` and `type` for the common cases like NFS
+/// shares and bind mounts of a host directory.
+#[derive(Debug, Default)]
+pub struct VolumeCreateBuilder {
+    config: VolumeConfig,
+    verify_host_path: bool,
+}
+
+impl VolumeCreateBuilder {
+    /// Creates a builder for a volume with the `local` driver.
+    pub fn new(name: &str) -> VolumeCreateBuilder {
+        VolumeCreateBuilder {
+            config: VolumeConfig {
+                name: name.to_string(),
+                driver: "local".to_string(),
+                ..Default::default()
+            },
+            verify_host_path: false,
+        }
+    }
+
+    pub fn driver(mut self, driver: &str) -> VolumeCreateBuilder {
+        self.config.driver = driver.to_string();
+        self
+    }
+
+    pub fn driver_opt(mut self, key: &str, value: &str) -> VolumeCreateBuilder {
+        self.config
+            .driver_opts
+            .insert(key.to_string(), value.to_string());
+        self
+    }
+
+    pub fn label(mut self, key: &str, value: &str) -> VolumeCreateBuilder {
+        self.config
+            .labels
+            .insert(key.to_string(), value.to_string());
+        self
+    }
+
+    /// Sets the `device` option of the `local` driver.
+    pub fn device(self, device: &str) -> VolumeCreateBuilder {
+        self.driver_opt("device", device)
+    }
+
+    /// Sets the `o` option of the `local` driver, the comma separated mount
+    /// options.
+    pub fn mount_options(self, options: &str) -> VolumeCreateBuilder {
+        self.driver_opt("o", options)
+    }
+
+    /// Sets the `type` option of the `local` driver, the filesystem type.
+    pub fn fs_type(self, fs_type: &str) -> VolumeCreateBuilder {
+        self.driver_opt("type", fs_type)
+    }
+
+    /// Sets the options to mount the NFS export at `addr:export` as the
+    /// volume, `options` are extra mount options like `rw,nfsvers=4`.
+    pub fn nfs(
+        self,
+        addr: &str,
+        export: &str,
+        options: Option<&str>,
+    ) -> VolumeCreateBuilder {
+        let mount_options = match options {
+            Some(opts) => format!("addr={},{}", addr, opts),
+            None => format!("addr={}", addr),
+        };
+
+        self.fs_type("nfs")
+            .mount_options(&mount_options)
+            .device(&format!(":{}", export))
+    }
+
+    /// Sets the options to bind mount the host directory `host_path` as the
+    /// volume.
+    pub fn bind(self, host_path: &str) -> VolumeCreateBuilder {
+        self.fs_type("none").mount_options("bind").device(host_path)
+    }
+
+    /// Checks in `build` that the host path of a bind style volume exists.
+    /// The daemon only complains about a missing path when the volume is
+    /// first mounted, and the error it gives is not very helpful.
+    ///
+    /// * This only makes sense when the daemon runs on the same host.
+    pub fn verify_host_path(mut self, verify: bool) -> VolumeCreateBuilder {
+        self.verify_host_path = verify;
+        self
+    }
+
+    pub fn build(self) -> Result<VolumeConfig, DockerApiError> {
+        if self.verify_host_path && self.is_bind() {
+            if let Some(path) = self.config.driver_opts.get("device") {
+                if !Path::new(path).exists() {
+                    return Err(DockerApiError::VolumeError(format!(
+                        "Host path {} for volume {} does not exist",
+                        path, self.config.name
+                    )));
+                }
+            }
+        }
+
+        Ok(self.config)
+    }
+
+    fn is_bind(&self) -> bool {
+        match self.config.driver_opts.get("o") {
+            Some(opts) => opts.split(',').any(|o| o == "bind" || o == "rbind"),
+            None => false,
+        }
+    }
+}
+
+/// A volume force-removed when dropped, also when the thread panics, from
+/// `create_volume_owned`. Removing it is best-effort, the errors are
+/// ignored.
+pub struct OwnedVolume<'a, C: 'a + Volumes> {
+    client: &'a C,
+    volume: Volume,
+}
+
+impl<'a, C: Volumes> OwnedVolume<'a, C> {
+    pub fn name(&self) -> &str {
+        &self.volume.name
+    }
+
+    pub fn volume(&self) -> &Volume {
+        &self.volume
+    }
+
+    /// Keeps the volume, giving it back.
+    pub fn keep(self) -> Volume {
+        let mut owned = ManuallyDrop::new(self);
+        mem::take(&mut owned.volume)
+    }
+}
+
+impl<'a, C: Volumes> Drop for OwnedVolume<'a, C> {
+    fn drop(&mut self) {
+        let _ = self.client.remove_volume(&self.volume.name, true);
+    }
+}
+
+pub trait Volumes: DockerApiClient {
+    /// Create a volume from the VolumeConfig structure, the config can be
+    /// created using the VolumeCreateBuilder.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// extern crate rust_docker;
+    ///
+    /// use rust_docker::api::volumes::{VolumeCreateBuilder, Volumes};
+    /// use rust_docker::client::DockerClient;
+    ///
+    /// let client = match DockerClient::new("unix:///var/run/docker.sock") {
+    ///     Ok(a) => a,
+    ///     Err(err) => {
+    ///         println!("{}", err);
+    ///         std::process::exit(1);
+    ///     }
+    /// };
+    ///
+    /// let config = VolumeCreateBuilder::new("nfs_data")
+    ///     .nfs("192.168.1.10", "/exports/data", Some("rw,nfsvers=4"))
+    ///     .build()
+    ///     .expect("Error");
+    ///
+    /// match client.create_volume(config) {
+    ///     Ok(volume) => println!("{:?}", volume),
+    ///     Err(err) => println!("An error occured : {}", err),
+    /// }
+    /// ```
+    fn create_volume(
+        &self,
+        mut config: VolumeConfig,
+    ) -> Result<Volume, DockerApiError> {
+        let api_endpoint = "/volumes/create";
+        let method = "POST";
+        if let Some((key, value)) = self.owner_label() {
+            config.labels.insert(key.to_string(), value.to_string());
+        }
+        let body = match serde_json::to_string(&config) {
+            Ok(body) => body,
+            Err(err) => {
+                return Err(DockerApiError::JsonSerializationError(err))
+            }
+        };
+
+        let resp = self.get_response_from_api(api_endpoint, method, &body)?;
+
+        if resp.status_code != 201 {
+            return Err(DockerApiError::from_response(
+                ApiObject::Volume,
+                resp.status_code,
+                resp.body,
+            ));
+        }
+
+        deserialize_body(&resp.body, self.is_strict())
+    }
+
+    /// Same as `create_volume`, the volume being removed when the returned
+    /// guard is dropped, see `OwnedVolume`.
+    fn create_volume_owned(
+        &self,
+        config: VolumeConfig,
+    ) -> Result<OwnedVolume<'_, Self>, DockerApiError>
+    where
+        Self: Sized,
+    {
+        let volume = self.create_volume(config)?;
+        Ok(OwnedVolume {
+            client: self,
+            volume,
+        })
+    }
+
+    /// List all the volumes.
+    fn list_volumes(&self) -> Result<Vec<Volume>, DockerApiError> {
+        let api_endpoint = "/volumes";
+        let method = "GET";
+
+        let resp = self.get_response_from_api(api_endpoint, method, "")?;
+
+        if resp.status_code != 200 {
+            return Err(DockerApiError::from_response(
+                ApiObject::Volume,
+                resp.status_code,
+                resp.body,
+            ));
+        }
+
+        let volumes: VolumeList =
+            deserialize_body(&resp.body, self.is_strict())?;
+
+        Ok(volumes.volumes.unwrap_or_default())
+    }
+
+    /// List the volumes which are not used by any container.
+    fn list_dangling_volumes(&self) -> Result<Vec<Volume>, DockerApiError> {
+        let api_endpoint = "/volumes";
+        let method = "GET";
+        let filters = json!({ "dangling": ["true"] }).to_string();
+        let query_params = format!("?filters={}", encode_query_value(&filters));
+
+        let resp =
+            self.get_response_from_api(api_endpoint, method, &query_params)?;
+
+        if resp.status_code != 200 {
+            return Err(DockerApiError::from_response(
+                ApiObject::Volume,
+                resp.status_code,
+                resp.body,
+            ));
+        }
+
+        let volumes: VolumeList =
+            deserialize_body(&resp.body, self.is_strict())?;
+
+        Ok(volumes.volumes.unwrap_or_default())
+    }
+
+    /// Inspects the volume with the provided name.
+    fn inspect_volume(&self, name: &str) -> Result<Volume, DockerApiError> {
+        let api_endpoint = format!("/volumes/{name}", name = name);
+        let method = "GET";
+
+        let resp = self.get_response_from_api(&api_endpoint, method, "")?;
+
+        if resp.status_code != 200 {
+            return Err(DockerApiError::from_response(
+                ApiObject::Volume,
+                resp.status_code,
+                resp.body,
+            ));
+        }
+
+        deserialize_body(&resp.body, self.is_strict())
+    }
+
+    /// Removes the volume with the provided name, `force` removes the volume
+    /// even if it is in use.
+    fn remove_volume(
+        &self,
+        name: &str,
+        force: bool,
+    ) -> Result<(), DockerApiError> {
+        let api_endpoint = format!("/volumes/{name}", name = name);
+        let method = "DELETE";
+        let query_params = format!("?force={}", force);
+
+        let resp =
+            self.get_response_from_api(&api_endpoint, method, &query_params)?;
+
+        if resp.status_code != 204 {
+            return Err(DockerApiError::from_response(
+                ApiObject::Volume,
+                resp.status_code,
+                resp.body,
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Writes a tar archive of the content of the volume to `writer`,
+    /// through a container mounting it which is removed afterwards. The
+    /// `HELPER_IMAGE` must exist locally. Needs the `containers` feature.
+    ///
+    /// ```rust,ignore
+    /// client.backup_volume("pgdata", &mut File::create("pgdata.tar")?)?;
+    /// ```
+    #[cfg(feature = "containers")]
+    fn backup_volume(
+        &self,
+        name: &str,
+        writer: &mut dyn Write,
+    ) -> Result<(), DockerApiError>
+    where
+        Self: Containers + Sized,
+    {
+        // Mounting a volume which does not exist would create it.
+        self.inspect_volume(name)?;
+
+        let helper = self.create_container_owned("", helper_config(name))?;
+        let mut archive = self.get_archive(helper.id(), HELPER_MOUNT)?;
+        io::copy(&mut archive, writer).map_err(DockerApiError::IoError)?;
+        Ok(())
+    }
+
+    /// Extracts an archive made by `backup_volume` into the volume, which
+    /// is created if it does not exist. The files of the volume which are
+    /// not in the archive are kept. Needs the `containers` feature.
+    #[cfg(feature = "containers")]
+    fn restore_volume(
+        &self,
+        name: &str,
+        reader: &mut dyn Read,
+    ) -> Result<(), DockerApiError>
+    where
+        Self: Containers + Sized,
+    {
+        let helper = self.create_container_owned("", helper_config(name))?;
+        self.put_archive(helper.id(), "/", reader)
+    }
+
+    /// Removes the volumes matching the filters which no container uses,
+    /// like `docker volume prune`. Since API 1.42 only the anonymous
+    /// volumes are removed, unless `all` is set, which older daemons
+    /// refuse. The daemon refuses the `until` filter for the volumes.
+    fn prune_volumes(
+        &self,
+        filters: &PruneFilters,
+        all: bool,
+    ) -> Result<VolumesPruneReport, DockerApiError> {
+        let named: &[(&str, &str)] = if all { &[("all", "true")] } else { &[] };
+        let api_endpoint = format!("/volumes/prune{}", filters.to_query(named));
+        let method = "POST";
+
+        let resp = self.get_response_from_api(&api_endpoint, method, "")?;
+
+        if resp.status_code != 200 {
+            return Err(DockerApiError::from_response(
+                ApiObject::Volume,
+                resp.status_code,
+                resp.body,
+            ));
+        }
+
+        deserialize_body(&resp.body, self.is_strict())
+    }
+}
+
+#[cfg(feature = "containers")]
+fn helper_config(volume: &str) -> ContainerConfig {
+    ContainerConfig {
+        image: HELPER_IMAGE.to_string(),
+        host_config: Some(HostConfig {
+            mounts: Some(vec![MountSpec::volume(volume, HELPER_MOUNT, true)]),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
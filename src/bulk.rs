@@ -0,0 +1,364 @@
+//! Operations on many containers or images at once, spread over a pool of
+//! worker threads so that maintenance over hundreds of containers does not
+//! run one request after the other.
+//!
+//! Every item gets its own result, a failure does not stop the others.
+//!
+//! A `ConcurrencyPolicy` limits the requests in flight to a daemon, and
+//! overall when the operations of a `fleet::DockerFleet` run on several
+//! daemons at once. A plain number is the limit per daemon. The items wait
+//! in a queue for their turn.
+//!
+//! ```rust,ignore
+//! let results = bulk::stop_all(&client, &ids, None, 8);
+//! for result in results.iter().filter(|r| r.result.is_err()) {
+//!     println!("Could not stop {}", result.item);
+//! }
+//! ```
+//!
+//! `rolling_restart` is the exception, it restarts the containers a few at
+//! a time and stops at the first batch which does not come back healthy.
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Condvar, Mutex};
+use std::thread;
+#[cfg(feature = "containers")]
+use std::time::Duration;
+
+#[cfg(feature = "containers")]
+use api::containers::Containers;
+#[cfg(feature = "images")]
+use api::images::{ImageProgress, Images, RegistryAuth};
+use errors::DockerApiError;
+
+/// The result of the operation on a single item.
+#[derive(Debug)]
+pub struct BulkResult<T> {
+    pub item: String,
+    pub result: Result<T, DockerApiError>,
+}
+
+/// Limits of the requests in flight.
+///
+/// * per_host: To a single daemon, at least 1.
+/// * total: To all the daemons together, only the limit per daemon applies
+///   if None.
+///
+/// ```rust
+/// extern crate rust_docker;
+///
+/// use rust_docker::bulk::ConcurrencyPolicy;
+///
+/// let policy = ConcurrencyPolicy::per_host(2).total(8);
+/// assert_eq!(policy, ConcurrencyPolicy { per_host: 2, total: Some(8) });
+/// assert_eq!(ConcurrencyPolicy::from(4), ConcurrencyPolicy::per_host(4));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConcurrencyPolicy {
+    pub per_host: usize,
+    pub total: Option<usize>,
+}
+
+impl ConcurrencyPolicy {
+    pub fn per_host(per_host: usize) -> ConcurrencyPolicy {
+        ConcurrencyPolicy {
+            per_host,
+            total: None,
+        }
+    }
+
+    pub fn total(mut self, total: usize) -> ConcurrencyPolicy {
+        self.total = Some(total);
+        self
+    }
+
+    /// Requests in flight to a single daemon when it is the only one.
+    fn single_host(&self) -> usize {
+        match self.total {
+            Some(total) => self.per_host.min(total),
+            None => self.per_host,
+        }
+    }
+}
+
+impl Default for ConcurrencyPolicy {
+    fn default() -> ConcurrencyPolicy {
+        ConcurrencyPolicy::per_host(4)
+    }
+}
+
+impl From<usize> for ConcurrencyPolicy {
+    fn from(per_host: usize) -> ConcurrencyPolicy {
+        ConcurrencyPolicy::per_host(per_host)
+    }
+}
+
+/// A limit of requests in flight shared by several workers, the workers
+/// over the limit wait for one to be done.
+#[derive(Debug)]
+pub struct InFlightLimit {
+    max: usize,
+    in_flight: Mutex<usize>,
+    released: Condvar,
+}
+
+impl InFlightLimit {
+    pub fn new(max: usize) -> InFlightLimit {
+        InFlightLimit {
+            max: max.max(1),
+            in_flight: Mutex::new(0),
+            released: Condvar::new(),
+        }
+    }
+
+    /// Waits for a request to be allowed, it is in flight until the guard
+    /// is dropped.
+    pub fn acquire(&self) -> InFlightGuard<'_> {
+        let mut in_flight = match self.in_flight.lock() {
+            Ok(in_flight) => in_flight,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        while *in_flight >= self.max {
+            in_flight = match self.released.wait(in_flight) {
+                Ok(in_flight) => in_flight,
+                Err(poisoned) => poisoned.into_inner(),
+            };
+        }
+        *in_flight += 1;
+        InFlightGuard { limit: self }
+    }
+}
+
+/// A request allowed by an `InFlightLimit`.
+pub struct InFlightGuard<'a> {
+    limit: &'a InFlightLimit,
+}
+
+impl<'a> Drop for InFlightGuard<'a> {
+    fn drop(&mut self) {
+        let mut in_flight = match self.limit.in_flight.lock() {
+            Ok(in_flight) => in_flight,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        *in_flight -= 1;
+        self.limit.released.notify_one();
+    }
+}
+
+/// Runs `operation` on every item with at most as many of them in flight
+/// as the policy allows, see `ConcurrencyPolicy`. The results are in the
+/// order of the items.
+pub fn run_bulk<C, T, F, P>(
+    client: &C,
+    items: &[&str],
+    policy: P,
+    operation: F,
+) -> Vec<BulkResult<T>>
+where
+    C: Sync + ?Sized,
+    T: Send,
+    F: Fn(&C, &str) -> Result<T, DockerApiError> + Sync,
+    P: Into<ConcurrencyPolicy>,
+{
+    let workers = policy.into().single_host();
+    run_bulk_limited(client, items, workers, None, operation)
+}
+
+/// Runs `operation` on every item with at most `workers` of them in flight,
+/// each of them also waiting on the `shared` limit when given, like the
+/// limit of all the daemons of a fleet. The results are in the order of
+/// the items.
+pub fn run_bulk_limited<C, T, F>(
+    client: &C,
+    items: &[&str],
+    workers: usize,
+    shared: Option<&InFlightLimit>,
+    operation: F,
+) -> Vec<BulkResult<T>>
+where
+    C: Sync + ?Sized,
+    T: Send,
+    F: Fn(&C, &str) -> Result<T, DockerApiError> + Sync,
+{
+    let next = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<Result<T, DockerApiError>>>> =
+        Mutex::new(items.iter().map(|_| None).collect());
+    let workers = workers.max(1).min(items.len());
+
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::SeqCst);
+                if index >= items.len() {
+                    return;
+                }
+
+                let _guard = shared.map(|limit| limit.acquire());
+                let result = operation(client, items[index]);
+                if let Ok(mut results) = results.lock() {
+                    results[index] = Some(result);
+                }
+            });
+        }
+    });
+
+    let results = match results.into_inner() {
+        Ok(results) => results,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    items
+        .iter()
+        .zip(results)
+        .map(|(item, result)| BulkResult {
+            item: item.to_string(),
+            result: result.unwrap_or(Err(DockerApiError::RequestError(
+                "The operation did not complete",
+            ))),
+        })
+        .collect()
+}
+
+/// Stops the containers, waiting `timeout` for each of them before it is
+/// killed like `stop_container` does.
+#[cfg(feature = "containers")]
+pub fn stop_all<C, P>(
+    client: &C,
+    ids: &[&str],
+    timeout: Option<Duration>,
+    concurrency: P,
+) -> Vec<BulkResult<String>>
+where
+    C: Containers + Sync,
+    P: Into<ConcurrencyPolicy>,
+{
+    run_bulk(client, ids, concurrency, |client, id| {
+        client.stop_container(id, timeout)
+    })
+}
+
+/// Starts the containers.
+#[cfg(feature = "containers")]
+pub fn start_all<C, P>(
+    client: &C,
+    ids: &[&str],
+    concurrency: P,
+) -> Vec<BulkResult<String>>
+where
+    C: Containers + Sync,
+    P: Into<ConcurrencyPolicy>,
+{
+    run_bulk(client, ids, concurrency, |client, id| {
+        client.start_container(id)
+    })
+}
+
+/// Removes the containers, see `remove_container` for `force` and
+/// `volumes`.
+#[cfg(feature = "containers")]
+pub fn remove_all<C, P>(
+    client: &C,
+    ids: &[&str],
+    force: bool,
+    volumes: bool,
+    concurrency: P,
+) -> Vec<BulkResult<()>>
+where
+    C: Containers + Sync,
+    P: Into<ConcurrencyPolicy>,
+{
+    run_bulk(client, ids, concurrency, |client, id| {
+        client.remove_container(id, force, volumes)
+    })
+}
+
+/// Pulls the images, with the same credentials for all of them. The pulls
+/// still wait on the rate limiter of the client, if any.
+#[cfg(feature = "images")]
+pub fn pull_many<C, P>(
+    client: &C,
+    images: &[&str],
+    auth: Option<&RegistryAuth>,
+    concurrency: P,
+) -> Vec<BulkResult<Vec<ImageProgress>>>
+where
+    C: Images + Sync,
+    P: Into<ConcurrencyPolicy>,
+{
+    run_bulk(client, images, concurrency, |client, image| {
+        client.pull_image(image, auth)
+    })
+}
+
+/// What a rolling restart did.
+///
+/// * restarted: The containers restarted and healthy, in order.
+/// * failed_batch: Index of the batch the restart stopped at, None if all
+///   the batches were restarted.
+/// * failures: The results of the containers of that batch which failed.
+/// * not_restarted: The containers of the batches after it.
+#[cfg(feature = "containers")]
+#[derive(Debug, Default)]
+pub struct RollingRestartReport {
+    pub restarted: Vec<String>,
+    pub failed_batch: Option<usize>,
+    pub failures: Vec<BulkResult<()>>,
+    pub not_restarted: Vec<String>,
+}
+
+#[cfg(feature = "containers")]
+impl RollingRestartReport {
+    pub fn is_complete(&self) -> bool {
+        self.failed_batch.is_none()
+    }
+}
+
+/// Restarts the containers `batch_size` at a time, each batch being
+/// restarted together and waited for, see `Containers::wait_for_healthy`,
+/// before the next one. The restart stops after a batch where a container
+/// could not be restarted or did not get healthy within `health_timeout`,
+/// leaving the next batches as they are.
+///
+/// ```rust,ignore
+/// let report = bulk::rolling_restart(&client, &ids, 2, Duration::from_secs(60));
+/// if !report.is_complete() {
+///     for failure in &report.failures {
+///         println!("{} : {:?}", failure.item, failure.result);
+///     }
+/// }
+/// ```
+#[cfg(feature = "containers")]
+pub fn rolling_restart<C>(
+    client: &C,
+    ids: &[&str],
+    batch_size: usize,
+    health_timeout: Duration,
+) -> RollingRestartReport
+where
+    C: Containers + Sync,
+{
+    let mut report = RollingRestartReport::default();
+
+    for (index, batch) in ids.chunks(batch_size.max(1)).enumerate() {
+        if report.failed_batch.is_some() {
+            report
+                .not_restarted
+                .extend(batch.iter().map(|id| id.to_string()));
+            continue;
+        }
+
+        let results = run_bulk(client, batch, batch.len(), |client, id| {
+            client.restart_container(id, None)?;
+            client.wait_for_healthy(id, health_timeout)
+        });
+        for result in results {
+            match result.result {
+                Ok(()) => report.restarted.push(result.item),
+                Err(_) => {
+                    report.failed_batch = Some(index);
+                    report.failures.push(result);
+                }
+            }
+        }
+    }
+
+    report
+}
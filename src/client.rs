@@ -1,34 +1,257 @@
 //! A client for communicating with the docker server
+use std::io;
 use std::io::Read;
 use std::io::Write;
+use std::net::{Shutdown, TcpStream};
 use std::os::unix::net::UnixStream;
+use std::sync::{Arc, Mutex};
 
+use api::api_utils::is_valid_header;
+#[cfg(feature = "build")]
+use api::build::Build;
+#[cfg(feature = "containers")]
 use api::containers::Containers;
+#[cfg(feature = "events")]
+use api::events::Events;
+#[cfg(feature = "exec")]
+use api::exec::Exec;
+#[cfg(feature = "images")]
 use api::images::Images;
-use api::version::Version;
-use api::DockerApiClient;
+#[cfg(feature = "logs")]
+use api::logs::Logs;
+#[cfg(feature = "networks")]
+use api::networks::Networks;
+#[cfg(feature = "secrets")]
+use api::secrets::Secrets;
+#[cfg(feature = "services")]
+use api::services::Services;
+#[cfg(feature = "stats")]
+use api::stats::Stats;
+#[cfg(feature = "swarm")]
+use api::swarm::Swarm;
+use api::version::{ApiVersion, Version};
+#[cfg(feature = "volumes")]
+use api::volumes::Volumes;
+use api::{DockerApiClient, ResourceCheck};
 
-use errors::DockerClientError;
+use debug::DebugExchange;
+use errors::{DockerApiError, DockerClientError};
+use proxy;
+use proxy::ProxyConfig;
+use ratelimit::RateLimiter;
+use tls;
+use tls::{TlsConfig, TlsStream};
 use utils;
+use utils::{CloseWrite, ResponseParser, StreamHandle};
 
 /// A structure defining a Client to interact with the docker API
 ///
-/// * unix_socket: UnixStream connection for docker socket.
+/// * socket: Connection to the docker daemon, the requests made while it is
+///   busy with another thread's request use a connection of their own.
+/// * address: Path to the docker socket, or `host:port` of the daemon for
+///   TCP, used to open new connections for the streaming requests.
 /// * protocol: Underlying protocol we are using(UNIX by default.)
+/// * proxy: Proxies to reach the daemon through over TCP.
+/// * tls: Certificates for the TLS connections to the daemon, plain TCP is
+///   used without them.
+/// * rate_limiter: Limiter of the registry operations, shared by the clones
+///   of the client.
+/// * strict: Fail on the responses the typed structs do not fully describe,
+///   see `strict_deserialization`.
+/// * owner_label: Label set on the objects created with the client, see
+///   `with_owner_label`.
+/// * resource_check: What the creation of a container does when the daemon
+///   lacks the resources it asks for, see `with_resource_check`.
+/// * headers: Headers identifying the client, added to every request, the
+///   `User-Agent` being `docker-rs/<version>` by default.
+/// * debug: Callback getting the dumps of the requests and responses, see
+///   `with_debug`.
 pub struct DockerClient {
-    socket: UnixStream,
+    socket: Arc<Mutex<Connection>>,
+    address: &'static str,
     protocol: ConnectionProtocol,
+    proxy: ProxyConfig,
+    tls: Option<TlsConfig>,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    api_version: Option<ApiVersion>,
+    strict: bool,
+    owner_label: Option<(String, String)>,
+    resource_check: ResourceCheck,
+    headers: Vec<(String, String)>,
+    debug: Option<Arc<Mutex<DebugCallback>>>,
 }
 
+type DebugCallback = Box<dyn FnMut(&DebugExchange) + Send>;
+
+/// The `User-Agent` of the requests unless another one is set with
+/// `DockerClient::with_user_agent`.
+pub const DEFAULT_USER_AGENT: &str =
+    concat!("docker-rs/", env!("CARGO_PKG_VERSION"));
+
+fn default_headers() -> Vec<(String, String)> {
+    vec![("User-Agent".to_string(), DEFAULT_USER_AGENT.to_string())]
+}
+
+#[derive(Clone, Copy)]
 enum ConnectionProtocol {
-    UNIX,
+    Unix,
+    Tcp,
+}
+
+/// A connection to the daemon, over any of the supported protocols.
+///
+/// The TLS session can not be split in a read and a write half, the clones
+/// of a TLS connection share it and the TCP connection under it is kept to
+/// shut it down.
+enum Connection {
+    Unix(UnixStream),
+    Tcp(TcpStream),
+    Tls(Arc<Mutex<Box<dyn TlsStream>>>, TcpStream),
+}
+
+fn poisoned() -> io::Error {
+    io::Error::other("TLS session lock poisoned")
+}
+
+impl Connection {
+    fn try_clone(&self) -> io::Result<Connection> {
+        match *self {
+            Connection::Unix(ref sock) => {
+                sock.try_clone().map(Connection::Unix)
+            }
+            Connection::Tcp(ref sock) => sock.try_clone().map(Connection::Tcp),
+            Connection::Tls(ref session, ref sock) => {
+                Ok(Connection::Tls(session.clone(), sock.try_clone()?))
+            }
+        }
+    }
+
+    fn shutdown(&self, how: Shutdown) -> io::Result<()> {
+        match *self {
+            Connection::Unix(ref sock) => sock.shutdown(how),
+            Connection::Tcp(ref sock) => sock.shutdown(how),
+            Connection::Tls(_, ref sock) => sock.shutdown(how),
+        }
+    }
+}
+
+impl Read for Connection {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match *self {
+            Connection::Unix(ref mut sock) => sock.read(buf),
+            Connection::Tcp(ref mut sock) => sock.read(buf),
+            Connection::Tls(ref session, _) => {
+                session.lock().map_err(|_| poisoned())?.read(buf)
+            }
+        }
+    }
+}
+
+impl Write for Connection {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match *self {
+            Connection::Unix(ref mut sock) => sock.write(buf),
+            Connection::Tcp(ref mut sock) => sock.write(buf),
+            Connection::Tls(ref session, _) => {
+                session.lock().map_err(|_| poisoned())?.write(buf)
+            }
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match *self {
+            Connection::Unix(ref mut sock) => sock.flush(),
+            Connection::Tcp(ref mut sock) => sock.flush(),
+            Connection::Tls(ref session, _) => {
+                session.lock().map_err(|_| poisoned())?.flush()
+            }
+        }
+    }
+}
+
+impl CloseWrite for Connection {
+    fn close_write(&mut self) -> io::Result<()> {
+        self.shutdown(Shutdown::Write)
+    }
 }
 
 impl DockerClient {
-    /// Creates a new DockerClient object connected to docker's unix domain socket.
+    /// Creates a new DockerClient object connected to docker's unix domain
+    /// socket, or to a daemon listening on TCP with a `tcp://host:port`
+    /// address. The TCP connections go through the proxies set in the
+    /// environment, see `ProxyConfig::from_env`.
     pub fn new(
         connection_addr: &'static str,
     ) -> Result<DockerClient, DockerClientError> {
+        DockerClient::with_proxy(connection_addr, ProxyConfig::from_env())
+    }
+
+    /// Same as `new` with the proxies to reach a TCP daemon through given
+    /// explicitly, `ProxyConfig::default()` connects directly.
+    pub fn with_proxy(
+        connection_addr: &'static str,
+        proxy: ProxyConfig,
+    ) -> Result<DockerClient, DockerClientError> {
+        DockerClient::connect_to(connection_addr, proxy, None)
+    }
+
+    /// Creates a client connected with TLS to the daemon at the
+    /// `tcp://host:port` address, like `docker --tlsverify`. This needs
+    /// the `rustls` or the `native-tls` feature.
+    ///
+    /// ```rust,ignore
+    /// let tls = TlsConfig::from_cert_path(Path::new("/home/ci/.docker"));
+    /// let client = DockerClient::with_tls(
+    ///     "tcp://docker.example.com:2376",
+    ///     tls,
+    ///     ProxyConfig::from_env(),
+    /// )?;
+    /// ```
+    pub fn with_tls(
+        connection_addr: &'static str,
+        tls: TlsConfig,
+        proxy: ProxyConfig,
+    ) -> Result<DockerClient, DockerClientError> {
+        if !connection_addr.starts_with("tcp://") {
+            return Err(DockerClientError::InvalidTargetAddress(
+                connection_addr,
+            ));
+        }
+
+        DockerClient::connect_to(connection_addr, proxy, Some(tls))
+    }
+
+    fn connect_to(
+        connection_addr: &'static str,
+        proxy: ProxyConfig,
+        tls: Option<TlsConfig>,
+    ) -> Result<DockerClient, DockerClientError> {
+        if let Some(address) = connection_addr.strip_prefix("tcp://") {
+            let address = address.trim_end_matches('/');
+            if !address.contains(':') {
+                return Err(DockerClientError::InvalidTargetAddress(
+                    connection_addr,
+                ));
+            }
+
+            let socket = connect_tcp(address, &proxy, tls.as_ref())?;
+
+            return Ok(DockerClient {
+                socket: Arc::new(Mutex::new(socket)),
+                address,
+                protocol: ConnectionProtocol::Tcp,
+                proxy,
+                tls,
+                rate_limiter: None,
+                api_version: None,
+                strict: false,
+                owner_label: None,
+                resource_check: ResourceCheck::Off,
+                headers: default_headers(),
+                debug: None,
+            });
+        }
+
         // Check if the provided unix socket address is valid and return
         // components for the socket.
         let addr_components =
@@ -53,7 +276,7 @@ impl DockerClient {
 
         // Check if the protocol is unix or not.
         let protocol = match addr_components[0] {
-            "unix" => ConnectionProtocol::UNIX,
+            "unix" => ConnectionProtocol::Unix,
             _ => {
                 return Err(DockerClientError::InvalidTargetAddress(
                     connection_addr,
@@ -62,70 +285,376 @@ impl DockerClient {
         };
 
         let docker_client = DockerClient {
-            socket: unix_socket,
-            protocol: protocol,
+            socket: Arc::new(Mutex::new(Connection::Unix(unix_socket))),
+            address: addr_components[1],
+            protocol,
+            proxy,
+            tls: None,
+            rate_limiter: None,
+            api_version: None,
+            strict: false,
+            owner_label: None,
+            resource_check: ResourceCheck::Off,
+            headers: default_headers(),
+            debug: None,
         };
 
         Ok(docker_client)
     }
+
+    /// Limits the pulls, pushes and searches done with the client and its
+    /// clones.
+    pub fn with_rate_limiter(mut self, limiter: RateLimiter) -> DockerClient {
+        self.rate_limiter = Some(Arc::new(limiter));
+        self
+    }
+
+    /// Checks the requests against the API version, so that fields the
+    /// daemon does not know about fail before the request is sent.
+    pub fn with_api_version(mut self, version: ApiVersion) -> DockerClient {
+        self.api_version = Some(version);
+        self
+    }
+
+    /// Deserializes the responses strictly. By default the values of the
+    /// enums the crate does not know about are kept as `Other` and the
+    /// optional blocks which cannot be read are None, in strict mode both
+    /// fail the call with a `JsonDeserializationError`.
+    pub fn strict_deserialization(mut self, strict: bool) -> DockerClient {
+        self.strict = strict;
+        self
+    }
+
+    /// Labels the containers, networks and volumes created with the client
+    /// and its clones with `key=value`, so that `gc::cleanup_owned` removes
+    /// them and only them, like the objects of a test suite.
+    pub fn with_owner_label(mut self, key: &str, value: &str) -> DockerClient {
+        self.owner_label = Some((key.to_string(), value.to_string()));
+        self
+    }
+
+    /// Checks that the daemon has the memory and the CPUs a container asks
+    /// for left before creating it, so that it is not OOM-killed or
+    /// starved later, see `Containers::check_resources`.
+    pub fn with_resource_check(mut self, check: ResourceCheck) -> DockerClient {
+        self.resource_check = check;
+        self
+    }
+
+    /// Sets the `User-Agent` of the requests, which the daemon logs and
+    /// which proxies auditing the traffic to it attribute the requests with.
+    /// Fails with a `RequestPrepareError` if it has a line break.
+    pub fn with_user_agent(
+        self,
+        user_agent: &str,
+    ) -> Result<DockerClient, DockerApiError> {
+        self.with_header("User-Agent", user_agent)
+    }
+
+    /// Adds a header to every request made with the client and its clones,
+    /// like an `X-Client-Id` identifying the service using it. A header of
+    /// the same name set before is replaced. Fails with a
+    /// `RequestPrepareError` if the header would break the requests.
+    ///
+    /// ```rust,ignore
+    /// let client = DockerClient::new("unix:///var/run/docker.sock")?
+    ///     .with_user_agent("deployer/2.1")?
+    ///     .with_header("X-Client-Id", "ci-runner-7")?;
+    /// ```
+    pub fn with_header(
+        mut self,
+        name: &str,
+        value: &str,
+    ) -> Result<DockerClient, DockerApiError> {
+        if !is_valid_header(name, value) {
+            return Err(DockerApiError::RequestPrepareError(
+                "Invalid request header",
+            ));
+        }
+
+        self.headers
+            .retain(|(existing, _)| !existing.eq_ignore_ascii_case(name));
+        self.headers.push((name.to_string(), value.to_string()));
+        Ok(self)
+    }
+
+    /// Calls `callback` with every request sent by the client and its
+    /// clones and the response to it, with the credentials redacted, see
+    /// the `debug` module.
+    pub fn with_debug<F>(mut self, callback: F) -> DockerClient
+    where
+        F: FnMut(&DebugExchange) + Send + 'static,
+    {
+        self.debug = Some(Arc::new(Mutex::new(Box::new(callback))));
+        self
+    }
+
+    /// Writes the dumps of `with_debug` to `writer`, like stderr.
+    pub fn with_debug_writer<W>(self, mut writer: W) -> DockerClient
+    where
+        W: Write + Send + 'static,
+    {
+        self.with_debug(move |exchange| {
+            let _ = write!(writer, "{}", exchange);
+        })
+    }
+
+    fn dump(&self, request: &str, response: Option<&[u8]>) {
+        if let Some(ref debug) = self.debug {
+            let exchange = DebugExchange::new(request, response);
+            if let Ok(mut callback) = debug.lock() {
+                callback(&exchange);
+            }
+        }
+    }
+
+    /// Writes the identification headers of the client into the request,
+    /// right after its request line.
+    fn identify(&self, request: &str) -> String {
+        let (request_line, rest) = match request.split_once("\r\n") {
+            Some(parts) => parts,
+            None => return request.to_string(),
+        };
+
+        let mut identified = format!("{}\r\n", request_line);
+        for (name, value) in &self.headers {
+            identified.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        identified.push_str(rest);
+        identified
+    }
+
+    /// Asks the daemon for the API version it supports and checks the
+    /// requests against it, see `with_api_version`.
+    pub fn negotiate_api_version(self) -> Result<DockerClient, DockerApiError> {
+        let version = self.get_version()?;
+        Ok(self.with_api_version(version.api_version))
+    }
+
+    /// Opens a new connection to the daemon.
+    fn connect(&self) -> Option<Connection> {
+        match self.protocol {
+            ConnectionProtocol::Unix => {
+                UnixStream::connect(self.address).map(Connection::Unix).ok()
+            }
+            ConnectionProtocol::Tcp => {
+                connect_tcp(self.address, &self.proxy, self.tls.as_ref()).ok()
+            }
+        }
+    }
+
+    /// Opens a new connection to the daemon for a streaming request and
+    /// writes the request to it. The handle shuts the connection down.
+    fn connect_stream(
+        &self,
+        request: &str,
+        handle: &StreamHandle,
+    ) -> Option<Connection> {
+        let mut client = self.connect()?;
+
+        match client.try_clone() {
+            Ok(sock) => handle.set_shutdown(move || {
+                let _ = sock.shutdown(Shutdown::Both);
+            }),
+            Err(_) => return None,
+        }
+
+        let request = self.identify(request);
+        self.dump(&request, None);
+        match client.write_all(request.as_bytes()) {
+            Ok(_) => Some(client),
+            Err(_) => None,
+        }
+    }
+}
+
+/// Connects to the daemon at `address`, a `host:port` address, with TLS if
+/// a config is given.
+fn connect_tcp(
+    address: &'static str,
+    proxy: &ProxyConfig,
+    tls: Option<&TlsConfig>,
+) -> Result<Connection, DockerClientError> {
+    let socket = match proxy::connect(address, proxy, tls.is_some()) {
+        Ok(sock) => sock,
+        Err(_err) => {
+            return Err(DockerClientError::SocketConnectionError(address))
+        }
+    };
+
+    let config = match tls {
+        Some(config) => config,
+        None => return Ok(Connection::Tcp(socket)),
+    };
+
+    let host = match address.rsplit_once(':') {
+        Some((host, _)) => host.trim_start_matches('[').trim_end_matches(']'),
+        None => address,
+    };
+    let sock = match socket.try_clone() {
+        Ok(sock) => sock,
+        Err(_err) => {
+            return Err(DockerClientError::SocketConnectionError(address))
+        }
+    };
+    let session = tls::connect(config, host, socket)?;
+
+    Ok(Connection::Tls(Arc::new(Mutex::new(session)), sock))
 }
 
 /// Implement clone for the DockerClient structure.
-/// The clone here is not true clone, the unix_socket cloned
+/// The clone here is not true clone, the socket cloned
 /// still refers to the stream and change to one of the two will
 /// propogate the changes to other.
 impl Clone for DockerClient {
     fn clone(&self) -> DockerClient {
-        let sock = self.socket
-            .try_clone()
-            .expect("Error while trying to clone the socket");
-
-        let protocol = match self.protocol {
-            ConnectionProtocol::UNIX => ConnectionProtocol::UNIX,
-        };
-
-        let docker_client_clone = DockerClient {
-            socket: sock,
-            protocol: protocol,
-        };
-
-        return docker_client_clone;
+        DockerClient {
+            socket: self.socket.clone(),
+            address: self.address,
+            protocol: self.protocol,
+            proxy: self.proxy.clone(),
+            tls: self.tls.clone(),
+            rate_limiter: self.rate_limiter.clone(),
+            api_version: self.api_version,
+            strict: self.strict,
+            owner_label: self.owner_label.clone(),
+            resource_check: self.resource_check,
+            headers: self.headers.clone(),
+            debug: self.debug.clone(),
+        }
     }
 }
 
 impl DockerApiClient for DockerClient {
     fn request(&self, request: &str) -> Option<Vec<u8>> {
-        let mut client = self.socket.try_clone().unwrap();
-
-        let buf = request.as_bytes();
-        match client.write_all(buf) {
-            Ok(_) => println!("Wrote all data to socket"),
-            Err(_) => return None,
+        // The client can be shared by threads, a request made while the
+        // connection is in use goes on a new one.
+        let mut guard;
+        let mut fresh;
+        let client: &mut Connection = match self.socket.try_lock() {
+            Ok(sock) => {
+                guard = sock;
+                &mut guard
+            }
+            Err(_) => {
+                fresh = self.connect()?;
+                &mut fresh
+            }
         };
 
-        // Can't figure out why read_to_end was not working here. :/
-        const BUFFER_SIZE: usize = 1024;
+        let request = self.identify(request);
+        if client.write_all(request.as_bytes()).is_err() {
+            return None;
+        }
+
+        // The daemon keeps the connection alive, so read_to_end would block
+        // here. Keep reading until we have the complete response instead.
+        const BUFFER_SIZE: usize = 16 * 1024;
         let mut buffer: [u8; BUFFER_SIZE] = [0; BUFFER_SIZE];
         let mut raw_resp: Vec<u8> = Vec::new();
+        let mut parser = ResponseParser::new();
         loop {
             let len = match client.read(&mut buffer) {
                 Ok(len) => len,
                 Err(_) => return None,
             };
 
-            for i in 0..len {
-                raw_resp.push(buffer[i]);
-            }
+            raw_resp.extend_from_slice(&buffer[..len]);
 
-            if len < BUFFER_SIZE {
+            if len == 0 || parser.is_complete(&raw_resp) {
                 break;
             }
         }
 
+        self.dump(&request, Some(&raw_resp));
+
         Some(raw_resp)
     }
+
+    fn stream_request(
+        &self,
+        request: &str,
+        handle: &StreamHandle,
+    ) -> Option<Box<dyn Read + Send>> {
+        self.connect_stream(request, handle)
+            .map(|sock| Box::new(sock) as Box<dyn Read + Send>)
+    }
+
+    fn upload_request(
+        &self,
+        request: &str,
+        body: &mut dyn Read,
+        handle: &StreamHandle,
+    ) -> Option<Box<dyn Read + Send>> {
+        let mut sock = self.connect_stream(request, handle)?;
+        match utils::write_chunked(&mut sock, body) {
+            Ok(_) => Some(Box::new(sock)),
+            Err(_) => None,
+        }
+    }
+
+    fn rate_limiter(&self) -> Option<&RateLimiter> {
+        self.rate_limiter.as_deref()
+    }
+
+    fn api_version(&self) -> Option<ApiVersion> {
+        self.api_version
+    }
+
+    fn is_strict(&self) -> bool {
+        self.strict
+    }
+
+    fn owner_label(&self) -> Option<(&str, &str)> {
+        self.owner_label
+            .as_ref()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+
+    fn resource_check(&self) -> ResourceCheck {
+        self.resource_check
+    }
+
+    fn hijack_request(
+        &self,
+        request: &str,
+        handle: &StreamHandle,
+    ) -> Option<(Box<dyn Read + Send>, Box<dyn CloseWrite>)> {
+        let reader = self.connect_stream(request, handle)?;
+        // Reading and writing would wait for each other on the shared
+        // session of a TLS connection.
+        if let Connection::Tls(..) = reader {
+            return None;
+        }
+        match reader.try_clone() {
+            Ok(writer) => Some((Box::new(reader), Box::new(writer))),
+            Err(_) => None,
+        }
+    }
 }
 
 impl Version for DockerClient {}
+#[cfg(feature = "build")]
+impl Build for DockerClient {}
+#[cfg(feature = "containers")]
 impl Containers for DockerClient {}
+#[cfg(feature = "events")]
+impl Events for DockerClient {}
+#[cfg(feature = "exec")]
+impl Exec for DockerClient {}
+#[cfg(feature = "images")]
 impl Images for DockerClient {}
+#[cfg(feature = "logs")]
+impl Logs for DockerClient {}
+#[cfg(feature = "networks")]
+impl Networks for DockerClient {}
+#[cfg(feature = "stats")]
+impl Stats for DockerClient {}
+#[cfg(feature = "swarm")]
+impl Swarm for DockerClient {}
+#[cfg(feature = "secrets")]
+impl Secrets for DockerClient {}
+#[cfg(feature = "services")]
+impl Services for DockerClient {}
+#[cfg(feature = "volumes")]
+impl Volumes for DockerClient {}
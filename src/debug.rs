@@ -0,0 +1,153 @@
+//! Dumps of the requests sent to the daemon with its responses, to find out
+//! why it rejects a request, like a create payload with a field it does not
+//! expect.
+//!
+//! The credentials are redacted, both the headers carrying them and the
+//! fields of the JSON bodies, and the JSON bodies are pretty printed.
+//!
+//! ```rust,ignore
+//! let client = DockerClient::new("unix:///var/run/docker.sock")?
+//!     .with_debug_writer(io::stderr());
+//! ```
+use std::fmt;
+
+use serde_json;
+use serde_json::Value;
+
+use utils::Response;
+
+/// Headers whose value is replaced by `REDACTED`.
+pub const REDACTED_HEADERS: [&str; 4] = [
+    "Authorization",
+    "Proxy-Authorization",
+    "X-Registry-Auth",
+    "X-Registry-Config",
+];
+
+/// Fields of the JSON bodies whose value is replaced by `REDACTED`, like
+/// the password of a registry login.
+pub const REDACTED_FIELDS: [&str; 4] =
+    ["password", "auth", "identitytoken", "registrytoken"];
+
+pub const REDACTED: &str = "<redacted>";
+
+/// A request sent to the daemon with its response, both sanitized.
+///
+/// * response: None for the streaming requests, whose response is read
+///   by the caller as it arrives.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DebugExchange {
+    pub request: String,
+    pub response: Option<String>,
+}
+
+impl DebugExchange {
+    pub fn new(request: &str, response: Option<&[u8]>) -> DebugExchange {
+        DebugExchange {
+            request: sanitize_request(request),
+            response: response.map(sanitize_response),
+        }
+    }
+}
+
+impl fmt::Display for DebugExchange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, ">>> request\n{}", self.request)?;
+        match self.response {
+            Some(ref response) => writeln!(f, "<<< response\n{}", response),
+            None => writeln!(f, "<<< response streamed"),
+        }
+    }
+}
+
+/// Redacts the credentials of a raw request and pretty prints its JSON
+/// body, the lines of the head being separated with `\n`.
+pub fn sanitize_request(request: &str) -> String {
+    let (head, body) = match request.split_once("\r\n\r\n") {
+        Some(parts) => parts,
+        None => (request.trim_end(), ""),
+    };
+
+    let head: Vec<String> = head.split("\r\n").map(redact_header).collect();
+
+    with_body(head.join("\n"), body)
+}
+
+/// Redacts the values of the `REDACTED_HEADERS` of a raw request, which is
+/// otherwise kept as it is, its body included.
+pub fn redact_headers(request: &str) -> String {
+    let (head, body) = match request.split_once("\r\n\r\n") {
+        Some(parts) => parts,
+        None => return request.to_string(),
+    };
+
+    let head: Vec<String> = head.split("\r\n").map(redact_header).collect();
+
+    format!("{}\r\n\r\n{}", head.join("\r\n"), body)
+}
+
+fn redact_header(line: &str) -> String {
+    match line.split_once(':') {
+        Some((name, _))
+            if REDACTED_HEADERS
+                .iter()
+                .any(|header| header.eq_ignore_ascii_case(name.trim())) =>
+        {
+            format!("{}: {}", name, REDACTED)
+        }
+        _ => line.to_string(),
+    }
+}
+
+/// Parses a raw response and pretty prints its JSON body, the response is
+/// given as it is if it can not be parsed.
+pub fn sanitize_response(response: &[u8]) -> String {
+    let parsed = match Response::parse_http_response(response.to_vec()) {
+        Ok(parsed) => parsed,
+        Err(_) => return String::from_utf8_lossy(response).into_owned(),
+    };
+
+    let mut head = format!("HTTP/1.1 {}", parsed.status_code);
+    let mut headers: Vec<(&String, &String)> = parsed.headers.iter().collect();
+    headers.sort();
+    for (name, value) in headers {
+        head.push_str(&format!("\n{}: {}", name, value));
+    }
+
+    with_body(head, &parsed.body)
+}
+
+fn with_body(head: String, body: &str) -> String {
+    if body.trim().is_empty() {
+        return head;
+    }
+
+    let body = match serde_json::from_str::<Value>(body) {
+        Ok(mut json) => {
+            redact_fields(&mut json);
+            serde_json::to_string_pretty(&json)
+                .unwrap_or_else(|_| body.to_string())
+        }
+        Err(_) => body.to_string(),
+    };
+    format!("{}\n\n{}", head, body)
+}
+
+fn redact_fields(json: &mut Value) {
+    match json {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                if REDACTED_FIELDS
+                    .iter()
+                    .any(|field| field.eq_ignore_ascii_case(key))
+                {
+                    *value = Value::String(REDACTED.to_string());
+                } else {
+                    redact_fields(value);
+                }
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(redact_fields),
+        _ => (),
+    }
+}
@@ -0,0 +1,326 @@
+//! The `.dockerignore` file of a build context, listing the files which are
+//! not sent to the daemon.
+//!
+//! Every line is a pattern matched against the paths relative to the root
+//! of the context, the way the docker CLI does it:
+//!
+//! * `*` matches any part of a name and `?` one character, neither matching
+//!   `/`.
+//! * `**` matches any number of directories, none included, so `**/*.go`
+//!   matches `main.go` and `cmd/app/main.go`.
+//! * `[a-z]` and `[^0-9]` match a character in or out of the ranges, `\`
+//!   escapes the next character.
+//! * A pattern starting with `!` is an exception, re-including what the
+//!   patterns before it excluded. The last pattern matching a path decides.
+//! * A directory which matches is excluded with all its content, unless an
+//!   exception re-includes some of it.
+//!
+//! The lines starting with `#` are comments.
+//!
+//! ```rust
+//! extern crate rust_docker;
+//!
+//! use rust_docker::dockerignore::DockerIgnore;
+//!
+//! let ignore = DockerIgnore::parse("# build output\ntarget\n**/*.log\n!keep.log\n");
+//! assert!(ignore.is_excluded("target/debug/app"));
+//! assert!(ignore.is_excluded("logs/server.log"));
+//! assert!(!ignore.is_excluded("keep.log"));
+//! assert!(!ignore.is_excluded("src/main.rs"));
+//! ```
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use errors::DockerApiError;
+
+/// A piece of a pattern.
+///
+/// * AnyDirs: `**` followed by more of the pattern, any number of whole
+///   directories.
+/// * AnyPath: `**` ending the pattern, anything.
+/// * Class: `[...]`, the character is in the inclusive ranges, or is not
+///   when negated.
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Literal(u8),
+    AnyName,
+    AnyChar,
+    AnyDirs,
+    AnyPath,
+    Class(bool, Vec<(u8, u8)>),
+}
+
+/// A line of a `.dockerignore` file.
+///
+/// * exception: The line starts with `!`, what it matches is re-included.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IgnorePattern {
+    pub pattern: String,
+    pub exception: bool,
+    tokens: Vec<Token>,
+}
+
+impl IgnorePattern {
+    /// Parses a line, None if it is not a valid pattern, like a line with an
+    /// unterminated `[`. The CLI refuses those, they are skipped here.
+    pub fn parse(line: &str) -> Option<IgnorePattern> {
+        let (exception, line) = match line.strip_prefix('!') {
+            Some(rest) => (true, rest.trim()),
+            None => (false, line),
+        };
+        let pattern = clean_path(line);
+        if pattern.is_empty() {
+            return None;
+        }
+
+        Some(IgnorePattern {
+            tokens: tokenize(pattern.as_bytes())?,
+            pattern,
+            exception,
+        })
+    }
+
+    /// Checks if the pattern matches the path itself, not one of its parent
+    /// directories.
+    pub fn matches(&self, path: &str) -> bool {
+        match_tokens(&self.tokens, path.as_bytes())
+    }
+
+    /// Checks if the pattern matches the path or one of its parent
+    /// directories.
+    fn matches_or_parent(&self, path: &str) -> bool {
+        let mut prefix = path;
+        loop {
+            if self.matches(prefix) {
+                return true;
+            }
+            match prefix.rfind('/') {
+                Some(idx) => prefix = &prefix[..idx],
+                None => return false,
+            }
+        }
+    }
+}
+
+/// The patterns of a `.dockerignore` file.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DockerIgnore {
+    patterns: Vec<IgnorePattern>,
+}
+
+impl DockerIgnore {
+    /// Parses the content of a `.dockerignore` file. The patterns are
+    /// cleaned like the CLI does, a leading `/` or `./` is meaningless as
+    /// the paths are relative to the root of the context anyway.
+    pub fn parse(content: &str) -> DockerIgnore {
+        let patterns = content
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(IgnorePattern::parse)
+            .collect();
+
+        DockerIgnore { patterns }
+    }
+
+    /// Reads the `.dockerignore` file at the root of `dir`, a directory
+    /// without one excludes nothing.
+    pub fn from_dir(dir: &Path) -> Result<DockerIgnore, DockerApiError> {
+        match fs::read_to_string(dir.join(".dockerignore")) {
+            Ok(content) => Ok(DockerIgnore::parse(&content)),
+            Err(ref err) if err.kind() == io::ErrorKind::NotFound => {
+                Ok(DockerIgnore::default())
+            }
+            Err(err) => Err(DockerApiError::IoError(err)),
+        }
+    }
+
+    /// The patterns, in the order of the file.
+    pub fn patterns(&self) -> &[IgnorePattern] {
+        &self.patterns
+    }
+
+    /// Adds an exception for `path`, so that it is sent whatever the file
+    /// says, like the CLI does for the Dockerfile and the `.dockerignore`.
+    pub fn keep(mut self, path: &str) -> DockerIgnore {
+        let line = format!("!{}", escape(path));
+        if let Some(pattern) = IgnorePattern::parse(&line) {
+            self.patterns.push(pattern);
+        }
+        self
+    }
+
+    /// Whether some patterns are exceptions, in which case the content of
+    /// an excluded directory may still be partly included.
+    pub fn has_exceptions(&self) -> bool {
+        self.patterns.iter().any(|pattern| pattern.exception)
+    }
+
+    /// Checks if the file or directory at `path`, relative to the root of
+    /// the context and separated with `/`, is left out of the context.
+    pub fn is_excluded(&self, path: &str) -> bool {
+        let path = clean_path(path);
+        let mut excluded = false;
+        for pattern in &self.patterns {
+            // Only an exception can change an exclusion, and the reverse.
+            if pattern.exception == excluded && pattern.matches_or_parent(&path)
+            {
+                excluded = !pattern.exception;
+            }
+        }
+        excluded
+    }
+}
+
+/// Removes the empty and `.` components of a path, resolves its `..` ones
+/// and removes its leading `/`.
+fn clean_path(path: &str) -> String {
+    let mut parts: Vec<&str> = Vec::new();
+    for part in path.split('/') {
+        match part {
+            "" | "." => (),
+            ".." => {
+                parts.pop();
+            }
+            _ => parts.push(part),
+        }
+    }
+    parts.join("/")
+}
+
+/// Escapes the characters of a path which have a meaning in a pattern.
+fn escape(path: &str) -> String {
+    let mut escaped = String::new();
+    for c in path.chars() {
+        if "*?[]\\".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn tokenize(pattern: &[u8]) -> Option<Vec<Token>> {
+    let mut tokens: Vec<Token> = Vec::new();
+    let mut idx = 0;
+    while idx < pattern.len() {
+        match pattern[idx] {
+            b'*' if pattern.get(idx + 1) == Some(&b'*') => {
+                idx += 2;
+                if pattern.get(idx) == Some(&b'/') {
+                    idx += 1;
+                }
+                tokens.push(if idx == pattern.len() {
+                    Token::AnyPath
+                } else {
+                    Token::AnyDirs
+                });
+                continue;
+            }
+            b'*' => tokens.push(Token::AnyName),
+            b'?' => tokens.push(Token::AnyChar),
+            b'\\' => {
+                idx += 1;
+                tokens.push(Token::Literal(*pattern.get(idx)?));
+            }
+            b'[' => {
+                let (class, len) = parse_class(&pattern[idx + 1..])?;
+                tokens.push(class);
+                idx += len + 1;
+                continue;
+            }
+            c => tokens.push(Token::Literal(c)),
+        }
+        idx += 1;
+    }
+    Some(tokens)
+}
+
+/// Parses a character class after its `[`, giving the number of bytes it
+/// takes with its `]`.
+fn parse_class(pattern: &[u8]) -> Option<(Token, usize)> {
+    let mut idx = 0;
+    let negated = pattern.first() == Some(&b'^');
+    if negated {
+        idx += 1;
+    }
+
+    let mut ranges: Vec<(u8, u8)> = Vec::new();
+    loop {
+        let low = match *pattern.get(idx)? {
+            b']' if !ranges.is_empty() => {
+                return Some((Token::Class(negated, ranges), idx + 1))
+            }
+            b'\\' => {
+                idx += 1;
+                *pattern.get(idx)?
+            }
+            c => c,
+        };
+        idx += 1;
+
+        let high = if pattern.get(idx) == Some(&b'-')
+            && pattern.get(idx + 1).is_some_and(|c| *c != b']')
+        {
+            idx += 1;
+            let high = match *pattern.get(idx)? {
+                b'\\' => {
+                    idx += 1;
+                    *pattern.get(idx)?
+                }
+                c => c,
+            };
+            idx += 1;
+            high
+        } else {
+            low
+        };
+        ranges.push((low, high));
+    }
+}
+
+fn match_tokens(tokens: &[Token], name: &[u8]) -> bool {
+    let token = match tokens.first() {
+        Some(token) => token,
+        None => return name.is_empty(),
+    };
+    let rest = &tokens[1..];
+
+    match *token {
+        Token::AnyPath => true,
+        Token::AnyDirs => {
+            match_tokens(rest, name)
+                || name.iter().enumerate().any(|(idx, c)| {
+                    *c == b'/' && match_tokens(rest, &name[idx + 1..])
+                })
+        }
+        Token::AnyName => {
+            let mut idx = 0;
+            loop {
+                if match_tokens(rest, &name[idx..]) {
+                    return true;
+                }
+                if idx == name.len() || name[idx] == b'/' {
+                    return false;
+                }
+                idx += 1;
+            }
+        }
+        Token::AnyChar => match name.first() {
+            Some(c) if *c != b'/' => match_tokens(rest, &name[1..]),
+            _ => false,
+        },
+        Token::Class(negated, ref ranges) => match name.first() {
+            Some(c) if *c != b'/' => {
+                let inside =
+                    ranges.iter().any(|&(low, high)| low <= *c && *c <= high);
+                inside != negated && match_tokens(rest, &name[1..])
+            }
+            _ => false,
+        },
+        Token::Literal(c) => {
+            name.first() == Some(&c) && match_tokens(rest, &name[1..])
+        }
+    }
+}
@@ -1,5 +1,10 @@
+use std::fmt;
+use std::time::Duration;
+
 use serde_json;
 
+use api::version::ApiVersion;
+
 quick_error! {
     #[derive(Debug)]
     pub enum DockerClientError {
@@ -12,6 +17,11 @@ quick_error! {
             description("Could not connect to docker socket.")
             display("Error while connection to docker socket at {}", addr)
         }
+
+        TlsError(msg: String) {
+            description("Could not establish the TLS session.")
+            display("TLS error : {}", msg)
+        }
     }
 }
 
@@ -62,5 +72,227 @@ quick_error! {
             description("The container in context faced some error")
             display("ContainerError : {}", msg)
         }
+
+        ImageError(msg: String) {
+            description("The image in context faced some error")
+            display("ImageError : {}", msg)
+        }
+
+        VolumeError(msg: String) {
+            description("The volume in context faced some error")
+            display("VolumeError : {}", msg)
+        }
+
+        ServiceError(msg: String) {
+            description("The swarm service in context faced some error")
+            display("ServiceError : {}", msg)
+        }
+
+        IoError(err: ::std::io::Error) {
+            description("Error while reading or writing a file")
+            display("IO error : {}", err)
+        }
+
+        TooManyRequestsError(retry_after: Option<Duration>, msg: String) {
+            description("The registry rate limit was reached")
+            display("TooManyRequestsError : {}", msg)
+        }
+
+        InvalidIdError(msg: String) {
+            description("The provided ID is not valid")
+            display("InvalidIdError : {}", msg)
+        }
+
+        NotModified(msg: String) {
+            description("The object was already in the requested state")
+            display("NotModified : {}", msg)
+        }
+
+        Conflict(msg: String) {
+            description("The request conflicts with the state of the object")
+            display("Conflict : {}", msg)
+        }
+
+        NoSuchContainer(msg: String) {
+            description("The container does not exist")
+            display("NoSuchContainer : {}", msg)
+        }
+
+        NoSuchImage(msg: String) {
+            description("The image does not exist")
+            display("NoSuchImage : {}", msg)
+        }
+
+        NoSuchNetwork(msg: String) {
+            description("The network does not exist")
+            display("NoSuchNetwork : {}", msg)
+        }
+
+        NoSuchVolume(msg: String) {
+            description("The volume does not exist")
+            display("NoSuchVolume : {}", msg)
+        }
+
+        NoSuchExec(msg: String) {
+            description("The exec instance does not exist")
+            display("NoSuchExec : {}", msg)
+        }
+
+        NoSuchService(msg: String) {
+            description("The swarm service does not exist")
+            display("NoSuchService : {}", msg)
+        }
+
+        NoSuchSecret(msg: String) {
+            description("The swarm secret does not exist")
+            display("NoSuchSecret : {}", msg)
+        }
+
+        NotInSwarm(msg: String) {
+            description("The daemon is not a manager of a swarm")
+            display("NotInSwarm : {}", msg)
+        }
+
+        UnsupportedApiVersion(field: &'static str, required: ApiVersion, actual: ApiVersion) {
+            description("A field of the request needs a newer API version")
+            display("UnsupportedApiVersion : {} needs API {}, the daemon has {}", field, required, actual)
+        }
+
+        InsufficientResources(shortfalls: Vec<ResourceShortfall>) {
+            description("The daemon does not have the resources the container asks for")
+            display("InsufficientResources : {}", shortfalls.iter().map(|s| s.to_string()).collect::<Vec<_>>().join(", "))
+        }
+    }
+}
+
+/// * Memory: In bytes.
+/// * Cpus: In billionths of a CPU, like `HostConfig::nano_cpus`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resource {
+    Memory,
+    Cpus,
+}
+
+/// A resource a container asks for which the daemon does not have left.
+///
+/// * requested: What the container asks for.
+/// * reserved: What the running containers already have.
+/// * total: What the daemon has, from `/info`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ResourceShortfall {
+    pub resource: Resource,
+    pub requested: i64,
+    pub reserved: i64,
+    pub total: i64,
+}
+
+impl fmt::Display for ResourceShortfall {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.resource {
+            Resource::Memory => write!(
+                f,
+                "{} bytes of memory requested, {} of {} reserved",
+                self.requested, self.reserved, self.total
+            ),
+            Resource::Cpus => write!(
+                f,
+                "{:.2} CPUs requested, {:.2} of {:.2} reserved",
+                self.requested as f64 / 1e9,
+                self.reserved as f64 / 1e9,
+                self.total as f64 / 1e9
+            ),
+        }
+    }
+}
+
+/// The kind of object an endpoint works on, deciding which `NoSuch*` error a
+/// 404 of the daemon is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiObject {
+    Container,
+    Image,
+    Network,
+    Volume,
+    Exec,
+    Service,
+    Secret,
+    Swarm,
+}
+
+impl DockerApiError {
+    /// Maps an unexpected status of an endpoint working on `object` to an
+    /// error, so that the callers match on the variant instead of the
+    /// message:
+    ///
+    /// * 304: `NotModified`, like starting a running container.
+    /// * 404: The `NoSuch*` variant of the object, `NotInSwarm` for the
+    ///   swarm.
+    /// * 409: `Conflict`, like creating a container with a name in use.
+    /// * 503: `NotInSwarm` for the swarm, its services and its secrets.
+    ///
+    /// The other statuses are an `InvalidApiResponseError`. The message is
+    /// the one the daemon gives in the body, the body itself if it has none.
+    ///
+    /// ```rust
+    /// extern crate rust_docker;
+    ///
+    /// use rust_docker::errors::{ApiObject, DockerApiError};
+    ///
+    /// let body = r#"{"message":"No such container: web"}"#.to_string();
+    /// match DockerApiError::from_response(ApiObject::Container, 404, body) {
+    ///     DockerApiError::NoSuchContainer(msg) => assert_eq!(msg, "No such container: web"),
+    ///     err => panic!("unexpected error {}", err),
+    /// }
+    /// ```
+    pub fn from_response(
+        object: ApiObject,
+        status: usize,
+        body: String,
+    ) -> DockerApiError {
+        let message = || match serde_json::from_str::<serde_json::Value>(&body)
+        {
+            Ok(ref value) if value["message"].is_string() => {
+                value["message"].as_str().unwrap_or_default().to_string()
+            }
+            _ => body.trim().to_string(),
+        };
+
+        match (status, object) {
+            (304, _) => DockerApiError::NotModified(message()),
+            (409, _) => DockerApiError::Conflict(message()),
+            (404, ApiObject::Container) => {
+                DockerApiError::NoSuchContainer(message())
+            }
+            (404, ApiObject::Image) => DockerApiError::NoSuchImage(message()),
+            (404, ApiObject::Network) => {
+                DockerApiError::NoSuchNetwork(message())
+            }
+            (404, ApiObject::Volume) => DockerApiError::NoSuchVolume(message()),
+            (404, ApiObject::Exec) => DockerApiError::NoSuchExec(message()),
+            (404, ApiObject::Service) => {
+                DockerApiError::NoSuchService(message())
+            }
+            (404, ApiObject::Secret) => DockerApiError::NoSuchSecret(message()),
+            (404, ApiObject::Swarm)
+            | (503, ApiObject::Swarm)
+            | (503, ApiObject::Service)
+            | (503, ApiObject::Secret) => DockerApiError::NotInSwarm(message()),
+            _ => DockerApiError::InvalidApiResponseError(status, body),
+        }
+    }
+
+    /// Whether the object the request was about does not exist.
+    pub fn is_not_found(&self) -> bool {
+        match *self {
+            DockerApiError::NoSuchContainer(_)
+            | DockerApiError::NoSuchImage(_)
+            | DockerApiError::NoSuchNetwork(_)
+            | DockerApiError::NoSuchVolume(_)
+            | DockerApiError::NoSuchExec(_)
+            | DockerApiError::NoSuchService(_)
+            | DockerApiError::NoSuchSecret(_) => true,
+            DockerApiError::InvalidApiResponseError(status, _) => status == 404,
+            _ => false,
+        }
     }
 }
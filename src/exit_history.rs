@@ -0,0 +1,230 @@
+//! The last exits of some containers, from the `die` events of the daemon,
+//! to build restart policies above the ones of docker.
+//!
+//! ```rust,ignore
+//! let tracker = track_exits(&client, ExitHistory::new(10).watch("worker"));
+//! loop {
+//!     thread::sleep(Duration::from_secs(10));
+//!     if tracker.is_crashlooping("worker", Duration::from_secs(300)) {
+//!         client.stop_container("worker", None)?;
+//!         alert("worker is crashlooping");
+//!     }
+//! }
+//! ```
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::thread::JoinHandle;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use api::events::{Backoff, Event, EventFilters, EventType, Events};
+use errors::DockerApiError;
+use utils::StreamHandle;
+
+/// An exit of a container.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExitRecord {
+    pub exit_code: i64,
+    pub time: SystemTime,
+}
+
+impl ExitRecord {
+    pub fn is_failure(&self) -> bool {
+        self.exit_code != 0
+    }
+}
+
+/// The last exits of the watched containers, by name, the oldest first.
+///
+/// * capacity: The number of exits kept per container.
+/// * crashloop_failures: The failed exits in the window making a
+///   container crashlooping, 3 by default.
+#[derive(Debug, Clone)]
+pub struct ExitHistory {
+    capacity: usize,
+    crashloop_failures: usize,
+    exits: HashMap<String, VecDeque<ExitRecord>>,
+}
+
+impl ExitHistory {
+    /// Keeps the last `capacity` exits of every watched container.
+    pub fn new(capacity: usize) -> ExitHistory {
+        ExitHistory {
+            capacity,
+            crashloop_failures: 3,
+            exits: HashMap::new(),
+        }
+    }
+
+    /// Records the exits of the container with this name, without the
+    /// leading slash.
+    pub fn watch(mut self, name: &str) -> ExitHistory {
+        self.start_watching(name);
+        self
+    }
+
+    pub fn crashloop_failures(mut self, failures: usize) -> ExitHistory {
+        self.crashloop_failures = failures;
+        self
+    }
+
+    /// Same as `watch`, on a history already in use.
+    pub fn start_watching(&mut self, name: &str) {
+        self.exits.entry(name.to_string()).or_default();
+    }
+
+    /// Stops recording the exits of the container, forgetting them.
+    pub fn stop_watching(&mut self, name: &str) {
+        self.exits.remove(name);
+    }
+
+    /// The filters of the events recorded, to stream only them.
+    pub fn filters() -> EventFilters {
+        EventFilters::new()
+            .event_type(EventType::Container)
+            .event("die")
+    }
+
+    /// Records the event if it is the `die` of a watched container, giving
+    /// whether it was.
+    pub fn record(&mut self, event: &Event) -> bool {
+        if event.typ != "container" || event.action != "die" {
+            return false;
+        }
+        let capacity = self.capacity;
+        let attributes = &event.actor.attributes;
+        let exits = match attributes
+            .get("name")
+            .and_then(|name| self.exits.get_mut(name))
+        {
+            Some(exits) => exits,
+            None => return false,
+        };
+        let exit_code = attributes
+            .get("exitCode")
+            .and_then(|code| code.parse().ok())
+            .unwrap_or(-1);
+        let time = if event.time_nano > 0 {
+            UNIX_EPOCH + Duration::from_nanos(event.time_nano as u64)
+        } else {
+            event.time.unwrap_or_else(SystemTime::now)
+        };
+
+        exits.push_back(ExitRecord { exit_code, time });
+        while exits.len() > capacity {
+            exits.pop_front();
+        }
+        true
+    }
+
+    /// The exits of the container, the oldest first, empty if it is not
+    /// watched.
+    pub fn exits(&self, name: &str) -> Vec<ExitRecord> {
+        self.exits
+            .get(name)
+            .map(|exits| exits.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn last_exit(&self, name: &str) -> Option<ExitRecord> {
+        self.exits.get(name).and_then(|exits| exits.back().cloned())
+    }
+
+    /// Whether the container failed at least `crashloop_failures` times
+    /// during the last `window`.
+    pub fn is_crashlooping(&self, name: &str, window: Duration) -> bool {
+        let since = SystemTime::now().checked_sub(window).unwrap_or(UNIX_EPOCH);
+        let failures = match self.exits.get(name) {
+            Some(exits) => exits
+                .iter()
+                .filter(|exit| exit.is_failure() && exit.time >= since)
+                .count(),
+            None => 0,
+        };
+        failures > 0 && failures >= self.crashloop_failures
+    }
+}
+
+/// The background thread of `track_exits`, recording the exits.
+pub struct ExitTracker {
+    history: Arc<Mutex<ExitHistory>>,
+    handle: StreamHandle,
+    thread: JoinHandle<Result<(), DockerApiError>>,
+}
+
+impl ExitTracker {
+    /// The history, locked until the guard is dropped, to watch other
+    /// containers or query several of them at once.
+    pub fn history(&self) -> MutexGuard<'_, ExitHistory> {
+        match self.history.lock() {
+            Ok(history) => history,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    pub fn exits(&self, name: &str) -> Vec<ExitRecord> {
+        self.history().exits(name)
+    }
+
+    pub fn is_crashlooping(&self, name: &str, window: Duration) -> bool {
+        self.history().is_crashlooping(name, window)
+    }
+
+    /// Handle to stop the tracking from another thread.
+    pub fn handle(&self) -> StreamHandle {
+        self.handle.clone()
+    }
+
+    /// Stops the tracking and waits for the thread to end, giving the
+    /// history, or the error which ended the thread before.
+    pub fn stop(self) -> Result<ExitHistory, DockerApiError> {
+        self.handle.cancel();
+        let history = self.history;
+        match self.thread.join() {
+            Ok(result) => result.map(|_| match history.lock() {
+                Ok(history) => history.clone(),
+                Err(poisoned) => poisoned.into_inner().clone(),
+            }),
+            Err(_) => {
+                Err(DockerApiError::RequestError("The events thread panicked"))
+            }
+        }
+    }
+}
+
+/// Records the exits of the containers watched by `history` from a thread
+/// of its own, streaming the events like `Events::events_into`. Only the
+/// exits from now are recorded.
+pub fn track_exits<C>(client: &C, history: ExitHistory) -> ExitTracker
+where
+    C: Events + Clone + Send + 'static,
+{
+    let client = client.clone();
+    let history = Arc::new(Mutex::new(history));
+    let recorded = history.clone();
+    let handle = StreamHandle::new();
+    let stop = handle.clone();
+
+    let thread = thread::spawn(move || {
+        let events = client.get_events_reconnecting(
+            Some(ExitHistory::filters()),
+            Backoff::default(),
+        );
+        let inner = events.handle();
+        stop.set_shutdown(move || inner.cancel());
+
+        for event in events {
+            let event = event?;
+            if let Ok(mut history) = recorded.lock() {
+                history.record(&event);
+            }
+        }
+        Ok(())
+    });
+
+    ExitTracker {
+        history,
+        handle,
+        thread,
+    }
+}
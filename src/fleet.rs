@@ -0,0 +1,238 @@
+//! A few named daemons managed together, for the small setups which do not
+//! run a swarm.
+//!
+//! The operations of the fleet run on all the hosts at once, each host gets
+//! its own result so that an unreachable daemon does not hide the others.
+//! The requests in flight are limited per host and overall by the
+//! `ConcurrencyPolicy` of the fleet.
+//!
+//! ```rust,ignore
+//! let fleet = DockerFleet::connect(&[
+//!     ("web-1", "tcp://10.0.0.11:2375"),
+//!     ("web-2", "tcp://10.0.0.12:2375"),
+//! ])?;
+//!
+//! for host in fleet.pull_on_all("nginx:1.25", None) {
+//!     if let Err(err) = host.result {
+//!         println!("{} could not pull : {}", host.host, err);
+//!     }
+//! }
+//! ```
+use std::thread;
+
+#[cfg(feature = "containers")]
+use api::containers::{Container, Containers};
+#[cfg(feature = "images")]
+use api::images::{ImageProgress, Images, RegistryAuth};
+use bulk::{run_bulk_limited, BulkResult, ConcurrencyPolicy, InFlightLimit};
+use client::DockerClient;
+use errors::{DockerApiError, DockerClientError};
+
+/// The result of an operation on a single host of the fleet.
+#[derive(Debug)]
+pub struct HostResult<T> {
+    pub host: String,
+    pub result: Result<T, DockerApiError>,
+}
+
+/// Clients of several daemons, by host name, in the order they were added.
+pub struct DockerFleet<C = DockerClient> {
+    hosts: Vec<(String, C)>,
+    policy: ConcurrencyPolicy,
+}
+
+impl DockerFleet<DockerClient> {
+    /// Connects to the daemons at the addresses, named by the first item of
+    /// the pairs, see `DockerClient::new`.
+    pub fn connect(
+        hosts: &[(&str, &'static str)],
+    ) -> Result<DockerFleet<DockerClient>, DockerClientError> {
+        let mut fleet = DockerFleet::new();
+        for &(name, address) in hosts {
+            fleet = fleet.host(name, DockerClient::new(address)?);
+        }
+        Ok(fleet)
+    }
+}
+
+impl<C> DockerFleet<C> {
+    pub fn new() -> DockerFleet<C> {
+        DockerFleet {
+            hosts: Vec::new(),
+            policy: ConcurrencyPolicy::default(),
+        }
+    }
+
+    /// Limits the requests in flight to each host and to all of them,
+    /// `ConcurrencyPolicy::default()` if not set.
+    pub fn concurrency<P>(mut self, policy: P) -> DockerFleet<C>
+    where
+        P: Into<ConcurrencyPolicy>,
+    {
+        self.policy = policy.into();
+        self
+    }
+
+    /// Adds the client of a host, replacing the client of the host with the
+    /// same name.
+    pub fn host(mut self, name: &str, client: C) -> DockerFleet<C> {
+        match self.hosts.iter_mut().find(|host| host.0 == name) {
+            Some(host) => host.1 = client,
+            None => self.hosts.push((name.to_string(), client)),
+        }
+        self
+    }
+
+    /// The client of the host with this name.
+    pub fn get(&self, name: &str) -> Option<&C> {
+        self.hosts
+            .iter()
+            .find(|host| host.0 == name)
+            .map(|host| &host.1)
+    }
+
+    /// The names of the hosts.
+    pub fn hosts(&self) -> Vec<&str> {
+        self.hosts.iter().map(|host| host.0.as_str()).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.hosts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hosts.is_empty()
+    }
+
+    /// Runs `operation` on all the hosts at once, one thread per host, as
+    /// many of them as the total limit of the policy allows. The results
+    /// are in the order of the hosts.
+    pub fn run_on_all<T, F>(&self, operation: F) -> Vec<HostResult<T>>
+    where
+        C: Sync,
+        T: Send,
+        F: Fn(&C) -> Result<T, DockerApiError> + Sync,
+    {
+        let operation = &operation;
+        let limit = self.total_limit();
+        let limit = limit.as_ref();
+        thread::scope(|scope| {
+            let handles: Vec<_> = self
+                .hosts
+                .iter()
+                .map(|(_, client)| {
+                    scope.spawn(move || {
+                        let _guard = limit.map(|limit| limit.acquire());
+                        operation(client)
+                    })
+                })
+                .collect();
+
+            self.hosts
+                .iter()
+                .zip(handles)
+                .map(|((name, _), handle)| HostResult {
+                    host: name.clone(),
+                    result: handle.join().unwrap_or(Err(
+                        DockerApiError::RequestError(
+                            "The operation did not complete",
+                        ),
+                    )),
+                })
+                .collect()
+        })
+    }
+
+    /// Runs `operation` on every item on all the hosts, like
+    /// `bulk::run_bulk` does on one host, with the limits of the policy.
+    /// The results of a host are in the order of the items.
+    pub fn run_bulk_on_all<T, F>(
+        &self,
+        items: &[&str],
+        operation: F,
+    ) -> Vec<HostResult<Vec<BulkResult<T>>>>
+    where
+        C: Sync,
+        T: Send,
+        F: Fn(&C, &str) -> Result<T, DockerApiError> + Sync,
+    {
+        let operation = &operation;
+        let limit = self.total_limit();
+        let limit = limit.as_ref();
+        let per_host = self.policy.per_host;
+        thread::scope(|scope| {
+            let handles: Vec<_> = self
+                .hosts
+                .iter()
+                .map(|(_, client)| {
+                    scope.spawn(move || {
+                        run_bulk_limited(
+                            client, items, per_host, limit, operation,
+                        )
+                    })
+                })
+                .collect();
+
+            self.hosts
+                .iter()
+                .zip(handles)
+                .map(|((name, _), handle)| HostResult {
+                    host: name.clone(),
+                    result: handle.join().map_err(|_| {
+                        DockerApiError::RequestError(
+                            "The operation did not complete",
+                        )
+                    }),
+                })
+                .collect()
+        })
+    }
+
+    fn total_limit(&self) -> Option<InFlightLimit> {
+        self.policy.total.map(InFlightLimit::new)
+    }
+
+    /// Lists the containers of every host, running or stopped.
+    #[cfg(feature = "containers")]
+    pub fn list_containers_all_hosts(&self) -> Vec<HostResult<Vec<Container>>>
+    where
+        C: Containers + Sync,
+    {
+        self.run_on_all(|client| client.list_all_containers(None))
+    }
+
+    /// Pulls the image on every host, with the same credentials for all of
+    /// them.
+    #[cfg(feature = "images")]
+    pub fn pull_on_all(
+        &self,
+        image: &str,
+        auth: Option<&RegistryAuth>,
+    ) -> Vec<HostResult<Vec<ImageProgress>>>
+    where
+        C: Images + Sync,
+    {
+        self.run_on_all(|client| client.pull_image(image, auth))
+    }
+
+    /// Pulls the images on every host, see `bulk::pull_many`.
+    #[cfg(feature = "images")]
+    pub fn pull_many_on_all(
+        &self,
+        images: &[&str],
+        auth: Option<&RegistryAuth>,
+    ) -> Vec<HostResult<Vec<BulkResult<Vec<ImageProgress>>>>>
+    where
+        C: Images + Sync,
+    {
+        self.run_bulk_on_all(images, |client, image| {
+            client.pull_image(image, auth)
+        })
+    }
+}
+
+impl<C> Default for DockerFleet<C> {
+    fn default() -> DockerFleet<C> {
+        DockerFleet::new()
+    }
+}
@@ -0,0 +1,271 @@
+//! Garbage collection of the containers, images and volumes piling up on a
+//! host, like the ones of CI runners.
+//!
+//! The retention policies are declared with `GcPolicy` and `run_gc` applies
+//! all of them, returning a report of what was removed. In dry-run mode
+//! nothing is removed and the report tells what would have been.
+//!
+//! ```rust,ignore
+//! let policy = GcPolicy::new()
+//!     .remove_stopped_containers_older_than(Duration::from_secs(24 * 60 * 60))
+//!     .keep_recent_images(3)
+//!     .prune_dangling_volumes()
+//!     .dry_run(true);
+//!
+//! let report = run_gc(&client, &policy)?;
+//! println!("{:?}", report.images);
+//! ```
+//!
+//! The objects created with a client having an owner label are removed with
+//! `cleanup_owned`, whatever their age.
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime};
+
+use api::containers::Containers;
+use api::images::{ImageCompactInfo, Images};
+use api::networks::Networks;
+use api::reference::Reference;
+use api::volumes::Volumes;
+use errors::DockerApiError;
+use utils::encode_query_value;
+
+/// The retention policies to apply, none by default.
+///
+/// * stopped_containers_max_age: Exited containers which finished longer
+///   ago than this are removed, with their anonymous volumes.
+/// * images_per_repository: Only this number of the most recent images of
+///   every repository are kept, with all their tags in the repository.
+///   Images are removed by tag, so an image still tagged in another
+///   repository is kept.
+/// * prune_dangling_volumes: Volumes not used by any container are removed.
+/// * dry_run: Only report what would be removed.
+#[derive(Debug, Clone, Default)]
+pub struct GcPolicy {
+    pub stopped_containers_max_age: Option<Duration>,
+    pub images_per_repository: Option<usize>,
+    pub prune_dangling_volumes: bool,
+    pub dry_run: bool,
+}
+
+impl GcPolicy {
+    pub fn new() -> GcPolicy {
+        GcPolicy::default()
+    }
+
+    pub fn remove_stopped_containers_older_than(
+        mut self,
+        age: Duration,
+    ) -> GcPolicy {
+        self.stopped_containers_max_age = Some(age);
+        self
+    }
+
+    pub fn keep_recent_images(mut self, count: usize) -> GcPolicy {
+        self.images_per_repository = Some(count);
+        self
+    }
+
+    pub fn prune_dangling_volumes(mut self) -> GcPolicy {
+        self.prune_dangling_volumes = true;
+        self
+    }
+
+    pub fn dry_run(mut self, dry_run: bool) -> GcPolicy {
+        self.dry_run = dry_run;
+        self
+    }
+}
+
+/// What was removed by `run_gc`, or would have been in dry-run mode.
+///
+/// * containers: IDs of the containers.
+/// * images: Tags of the images.
+/// * volumes: Names of the volumes.
+/// * networks: Names of the networks.
+/// * errors: The objects which could not be removed with the error the
+///   daemon gave, for instance for an image still used by a container.
+#[derive(Debug, Default)]
+pub struct GcReport {
+    pub dry_run: bool,
+    pub containers: Vec<String>,
+    pub images: Vec<String>,
+    pub volumes: Vec<String>,
+    pub networks: Vec<String>,
+    pub errors: Vec<(String, DockerApiError)>,
+}
+
+impl GcReport {
+    /// Checks if nothing was removed and no error happened.
+    pub fn is_empty(&self) -> bool {
+        self.containers.is_empty()
+            && self.images.is_empty()
+            && self.volumes.is_empty()
+            && self.networks.is_empty()
+            && self.errors.is_empty()
+    }
+}
+
+/// The images of a repository, as the time they were created at with their
+/// ID, and the tags they have in the repository.
+type RepositoryImages<'a> =
+    HashMap<(Option<SystemTime>, &'a str), Vec<&'a str>>;
+
+/// Gives the tags to remove to keep only the `keep` most recent images of
+/// every repository, an image being counted once whatever the number of
+/// its tags in the repository.
+fn expired_tags(images: &[ImageCompactInfo], keep: usize) -> Vec<String> {
+    let mut repositories: HashMap<String, RepositoryImages> = HashMap::new();
+    for image in images {
+        for tag in &image.repo_tags {
+            // Untagged images are listed with `<none>:<none>`.
+            let reference = match Reference::parse(tag) {
+                Ok(reference) => reference,
+                Err(_) => continue,
+            };
+            repositories
+                .entry(reference.full_name())
+                .or_default()
+                .entry((image.created, image.id.as_str()))
+                .or_default()
+                .push(tag);
+        }
+    }
+
+    let mut expired = Vec::new();
+    for repository in repositories.values() {
+        let mut images: Vec<_> = repository.iter().collect();
+        images.sort_by(|a, b| b.0.cmp(a.0));
+        for (_, tags) in images.into_iter().skip(keep) {
+            expired.extend(tags.iter().map(|tag| tag.to_string()));
+        }
+    }
+    expired.sort();
+
+    expired
+}
+
+/// Applies the retention policies of `policy`. Listing the objects to
+/// remove fails the whole run, while failing to remove one of them is only
+/// recorded in the report.
+///
+/// The containers are removed first, so that the images and volumes they
+/// used can be removed in the same run.
+pub fn run_gc<C>(
+    client: &C,
+    policy: &GcPolicy,
+) -> Result<GcReport, DockerApiError>
+where
+    C: Containers + Images + Volumes,
+{
+    let mut report = GcReport {
+        dry_run: policy.dry_run,
+        ..Default::default()
+    };
+
+    if let Some(age) = policy.stopped_containers_max_age {
+        for container in client.list_exited_containers_older_than(age)? {
+            let id = container.id.to_string();
+            if policy.dry_run {
+                report.containers.push(id);
+                continue;
+            }
+            match client.remove_container(&id, false, true) {
+                Ok(()) => report.containers.push(id),
+                Err(DockerApiError::NoSuchContainer(_)) => (),
+                Err(err) => report.errors.push((id, err)),
+            }
+        }
+    }
+
+    if let Some(keep) = policy.images_per_repository {
+        let images = client.list_images(None)?;
+        for tag in expired_tags(&images, keep) {
+            if policy.dry_run {
+                report.images.push(tag);
+                continue;
+            }
+            match client.remove_image(&tag, false) {
+                Ok(()) => report.images.push(tag),
+                Err(err) => report.errors.push((tag, err)),
+            }
+        }
+    }
+
+    if policy.prune_dangling_volumes {
+        for volume in client.list_dangling_volumes()? {
+            if policy.dry_run {
+                report.volumes.push(volume.name);
+                continue;
+            }
+            match client.remove_volume(&volume.name, false) {
+                Ok(()) => report.volumes.push(volume.name),
+                Err(err) => report.errors.push((volume.name, err)),
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+/// Removes the containers, networks and volumes carrying the owner label of
+/// the client, see `DockerClient::with_owner_label`. The containers are
+/// killed first if they run, and removed before the networks and volumes
+/// they use. Fails if the client has no owner label.
+pub fn cleanup_owned<C>(client: &C) -> Result<GcReport, DockerApiError>
+where
+    C: Containers + Networks + Volumes,
+{
+    let (key, value) = match client.owner_label() {
+        Some(label) => label,
+        None => {
+            return Err(DockerApiError::MismatchedParametersError(
+                "The client has no owner label",
+            ))
+        }
+    };
+    let owned = |labels: Option<&HashMap<String, String>>| {
+        labels
+            .and_then(|labels| labels.get(key))
+            .map(|v| v.as_str())
+            == Some(value)
+    };
+    let mut report = GcReport::default();
+
+    let filters = json!({ "label": [format!("{}={}", key, value)] });
+    let query_params = format!(
+        "?all=true&filters={}",
+        encode_query_value(&filters.to_string())
+    );
+    for container in
+        client.get_containers("/containers/json", "GET", &query_params)?
+    {
+        let id = container.id.to_string();
+        match client.remove_container(&id, true, true) {
+            Ok(()) => report.containers.push(id),
+            Err(DockerApiError::NoSuchContainer(_)) => (),
+            Err(err) => report.errors.push((id, err)),
+        }
+    }
+
+    for network in client.list_networks()? {
+        if !owned(network.labels.as_ref()) {
+            continue;
+        }
+        match client.remove_network(&network.id) {
+            Ok(()) => report.networks.push(network.name),
+            Err(err) => report.errors.push((network.name, err)),
+        }
+    }
+
+    for volume in client.list_volumes()? {
+        if !owned(volume.labels.as_ref()) {
+            continue;
+        }
+        match client.remove_volume(&volume.name, false) {
+            Ok(()) => report.volumes.push(volume.name),
+            Err(err) => report.errors.push((volume.name, err)),
+        }
+    }
+
+    Ok(report)
+}
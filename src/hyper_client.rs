@@ -0,0 +1,565 @@
+//! A client for the docker daemon built on hyper, behind the `hyper`
+//! feature.
+//!
+//! `HyperClient` talks to the daemon's unix socket with hyper and
+//! hyperlocal, on a tokio runtime of its own, and is used like
+//! `DockerClient`: all the API traits work on it and its methods block.
+//! The rate limiter, the owner label, the resource check and the
+//! identification headers are set like on `DockerClient`. Hijacked
+//! connections, used by the interactive execs, and the debug dumps are not
+//! supported.
+//!
+//! ```rust,ignore
+//! let client = HyperClient::new("unix:///var/run/docker.sock")?;
+//! for container in client.list_running_containers(None)? {
+//!     println!("{}", container.Id);
+//! }
+//! ```
+use std::future::Future;
+use std::io;
+use std::io::Read;
+use std::os::unix::net::UnixStream;
+use std::pin::Pin;
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use http_body::{Body, Frame, SizeHint};
+use hyper::body::{Bytes, Incoming};
+use hyper::header::TRANSFER_ENCODING;
+use hyper_util::client::legacy::{Client, ResponseFuture};
+use hyperlocal::{UnixClientExt, UnixConnector};
+use tokio::runtime::{Builder, Runtime};
+use tokio::sync::mpsc as async_mpsc;
+
+use api::api_utils::is_valid_header;
+#[cfg(feature = "build")]
+use api::build::Build;
+#[cfg(feature = "containers")]
+use api::containers::Containers;
+#[cfg(feature = "events")]
+use api::events::Events;
+#[cfg(feature = "exec")]
+use api::exec::Exec;
+#[cfg(feature = "images")]
+use api::images::Images;
+#[cfg(feature = "logs")]
+use api::logs::Logs;
+#[cfg(feature = "networks")]
+use api::networks::Networks;
+#[cfg(feature = "secrets")]
+use api::secrets::Secrets;
+#[cfg(feature = "services")]
+use api::services::Services;
+#[cfg(feature = "stats")]
+use api::stats::Stats;
+#[cfg(feature = "swarm")]
+use api::swarm::Swarm;
+use api::version::{ApiVersion, Version};
+#[cfg(feature = "volumes")]
+use api::volumes::Volumes;
+use api::{DockerApiClient, ResourceCheck};
+
+use client::DEFAULT_USER_AGENT;
+use errors::{DockerApiError, DockerClientError};
+use ratelimit::RateLimiter;
+use utils;
+use utils::StreamHandle;
+
+/// Most headers expected in a request built by `RequestBuilder`.
+const MAX_HEADERS: usize = 64;
+
+/// Size of the pieces the body of an upload is sent in.
+const UPLOAD_CHUNK_SIZE: usize = 64 * 1024;
+
+/// A client of the docker daemon using hyper for HTTP.
+///
+/// * runtime: Runtime running the requests, shared by the clones of the
+///   client.
+/// * client: The hyper client, pooling the connections to the socket.
+/// * socket: Path to the docker socket.
+/// * headers: Headers identifying the client, added to every request which
+///   does not set them itself, see `DockerClient::with_header`.
+///
+/// The other fields are the ones of `DockerClient`.
+#[derive(Clone)]
+pub struct HyperClient {
+    runtime: Arc<Runtime>,
+    client: Client<UnixConnector, RequestBody>,
+    socket: &'static str,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    api_version: Option<ApiVersion>,
+    strict: bool,
+    owner_label: Option<(String, String)>,
+    resource_check: ResourceCheck,
+    headers: Vec<(String, String)>,
+}
+
+impl HyperClient {
+    /// Creates a client of the daemon listening on the unix socket at
+    /// `connection_addr`, like `unix:///var/run/docker.sock`.
+    pub fn new(
+        connection_addr: &'static str,
+    ) -> Result<HyperClient, DockerClientError> {
+        let socket = match utils::validate_unix_socket_address(connection_addr)
+        {
+            Some(ref components) if components[0] == "unix" => components[1],
+            _ => {
+                return Err(DockerClientError::InvalidTargetAddress(
+                    connection_addr,
+                ))
+            }
+        };
+
+        // Fail early like `DockerClient` if nothing listens on the socket.
+        if UnixStream::connect(socket).is_err() {
+            return Err(DockerClientError::SocketConnectionError(socket));
+        }
+
+        let runtime = match Builder::new_multi_thread()
+            .worker_threads(1)
+            .enable_io()
+            .build()
+        {
+            Ok(runtime) => runtime,
+            Err(_err) => {
+                return Err(DockerClientError::SocketConnectionError(socket))
+            }
+        };
+
+        Ok(HyperClient {
+            runtime: Arc::new(runtime),
+            client: Client::unix(),
+            socket,
+            rate_limiter: None,
+            api_version: None,
+            strict: false,
+            owner_label: None,
+            resource_check: ResourceCheck::Off,
+            headers: vec![(
+                "User-Agent".to_string(),
+                DEFAULT_USER_AGENT.to_string(),
+            )],
+        })
+    }
+
+    /// Limits the pulls, pushes and searches, see
+    /// `DockerClient::with_rate_limiter`.
+    pub fn with_rate_limiter(mut self, limiter: RateLimiter) -> HyperClient {
+        self.rate_limiter = Some(Arc::new(limiter));
+        self
+    }
+
+    /// Checks the requests against the API version, see
+    /// `DockerClient::with_api_version`.
+    pub fn with_api_version(mut self, version: ApiVersion) -> HyperClient {
+        self.api_version = Some(version);
+        self
+    }
+
+    /// Deserializes the responses strictly, see
+    /// `DockerClient::strict_deserialization`.
+    pub fn strict_deserialization(mut self, strict: bool) -> HyperClient {
+        self.strict = strict;
+        self
+    }
+
+    /// Labels the objects created with the client, see
+    /// `DockerClient::with_owner_label`.
+    pub fn with_owner_label(mut self, key: &str, value: &str) -> HyperClient {
+        self.owner_label = Some((key.to_string(), value.to_string()));
+        self
+    }
+
+    /// Checks the resources a container asks for before creating it, see
+    /// `DockerClient::with_resource_check`.
+    pub fn with_resource_check(mut self, check: ResourceCheck) -> HyperClient {
+        self.resource_check = check;
+        self
+    }
+
+    /// Sets the `User-Agent` of the requests, see
+    /// `DockerClient::with_user_agent`.
+    pub fn with_user_agent(
+        self,
+        user_agent: &str,
+    ) -> Result<HyperClient, DockerApiError> {
+        self.with_header("User-Agent", user_agent)
+    }
+
+    /// Adds a header to every request, see `DockerClient::with_header`.
+    pub fn with_header(
+        mut self,
+        name: &str,
+        value: &str,
+    ) -> Result<HyperClient, DockerApiError> {
+        if !is_valid_header(name, value) {
+            return Err(DockerApiError::RequestPrepareError(
+                "Invalid request header",
+            ));
+        }
+
+        self.headers
+            .retain(|(existing, _)| !existing.eq_ignore_ascii_case(name));
+        self.headers.push((name.to_string(), value.to_string()));
+        Ok(self)
+    }
+
+    /// Asks the daemon for the API version it supports and checks the
+    /// requests against it, see `with_api_version`.
+    pub fn negotiate_api_version(self) -> Result<HyperClient, DockerApiError> {
+        let version = self.get_version()?;
+        Ok(self.with_api_version(version.api_version))
+    }
+
+    /// Turns a raw request, as built by `RequestBuilder`, into a hyper
+    /// request, the body is given separately. hyper frames the body itself,
+    /// so the length headers of the raw request are left out.
+    fn prepare(
+        &self,
+        request: &str,
+    ) -> Option<(hyper::http::request::Builder, Bytes)> {
+        let mut raw_headers = [httparse::EMPTY_HEADER; MAX_HEADERS];
+        let mut parsed = httparse::Request::new(&mut raw_headers);
+        let head_len = match parsed.parse(request.as_bytes()) {
+            Ok(httparse::Status::Complete(len)) => len,
+            _ => return None,
+        };
+
+        let path = parsed.path?;
+        if path.parse::<hyper::Uri>().is_err() {
+            return None;
+        }
+        let uri: hyper::Uri = hyperlocal::Uri::new(self.socket, path).into();
+
+        let mut builder =
+            hyper::Request::builder().method(parsed.method?).uri(uri);
+        for header in parsed.headers.iter() {
+            let name = header.name;
+            if name.eq_ignore_ascii_case("content-length")
+                || name.eq_ignore_ascii_case("transfer-encoding")
+            {
+                continue;
+            }
+            builder = builder.header(name, header.value);
+        }
+        for (name, value) in &self.headers {
+            if !parsed
+                .headers
+                .iter()
+                .any(|header| header.name.eq_ignore_ascii_case(name))
+            {
+                builder = builder.header(name.as_str(), value.as_str());
+            }
+        }
+
+        let body = Bytes::copy_from_slice(&request.as_bytes()[head_len..]);
+        Some((builder, body))
+    }
+
+    /// Sends the request from the runtime, the response comes out of the
+    /// returned stream as it arrives. The handle stops the request.
+    fn send(
+        &self,
+        request: hyper::Request<RequestBody>,
+        handle: &StreamHandle,
+    ) -> HyperStream {
+        let (sender, receiver) = mpsc::channel();
+        let task = self.runtime.spawn(Exchange {
+            state: ExchangeState::Requesting(self.client.request(request)),
+            sender,
+        });
+
+        let abort = task.abort_handle();
+        handle.set_shutdown(move || abort.abort());
+
+        HyperStream {
+            receiver,
+            data: Bytes::new(),
+        }
+    }
+}
+
+/// Where an `Exchange` is at.
+enum ExchangeState {
+    Requesting(ResponseFuture),
+    Reading(Incoming),
+}
+
+/// The task of a request on the runtime, it waits for the response and
+/// sends its head and then its body down the channel as they arrive. It
+/// ends early if the receiving end is dropped.
+struct Exchange {
+    state: ExchangeState,
+    sender: mpsc::Sender<io::Result<Bytes>>,
+}
+
+impl Future for Exchange {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<()> {
+        loop {
+            let next = match self.state {
+                ExchangeState::Requesting(ref mut response) => {
+                    match Pin::new(response).poll(cx) {
+                        Poll::Ready(Ok(response)) => {
+                            if self
+                                .sender
+                                .send(Ok(response_head(&response)))
+                                .is_err()
+                            {
+                                return Poll::Ready(());
+                            }
+                            ExchangeState::Reading(response.into_body())
+                        }
+                        Poll::Ready(Err(_)) => return Poll::Ready(()),
+                        Poll::Pending => return Poll::Pending,
+                    }
+                }
+                ExchangeState::Reading(ref mut body) => {
+                    let data = match Pin::new(body).poll_frame(cx) {
+                        Poll::Ready(Some(Ok(frame))) => match frame.into_data()
+                        {
+                            Ok(data) => Ok(data),
+                            // Trailers.
+                            Err(_) => continue,
+                        },
+                        Poll::Ready(Some(Err(err))) => {
+                            Err(io::Error::other(err))
+                        }
+                        Poll::Ready(None) => return Poll::Ready(()),
+                        Poll::Pending => return Poll::Pending,
+                    };
+                    if self.sender.send(data).is_err() {
+                        return Poll::Ready(());
+                    }
+                    continue;
+                }
+            };
+            self.state = next;
+        }
+    }
+}
+
+/// The head of the response, written back as raw HTTP for the parsers of
+/// the crate. hyper has removed the chunked encoding of the body already.
+fn response_head(response: &hyper::Response<Incoming>) -> Bytes {
+    let status = response.status();
+    let mut head = format!(
+        "HTTP/1.1 {} {}\r\n",
+        status.as_u16(),
+        status.canonical_reason().unwrap_or("")
+    );
+    for (name, value) in response.headers() {
+        if name == TRANSFER_ENCODING {
+            continue;
+        }
+        head.push_str(&format!(
+            "{}: {}\r\n",
+            name,
+            String::from_utf8_lossy(value.as_bytes())
+        ));
+    }
+    head.push_str("\r\n");
+    Bytes::from(head)
+}
+
+/// Body of the requests, read from a channel so that uploads are sent while
+/// they are read.
+struct RequestBody {
+    receiver: async_mpsc::Receiver<Bytes>,
+    length: Option<u64>,
+}
+
+impl RequestBody {
+    /// A body known in full, sent with its length.
+    fn full(data: Bytes) -> RequestBody {
+        let (sender, receiver) = async_mpsc::channel(1);
+        let length = data.len() as u64;
+        if !data.is_empty() {
+            let _ = sender.try_send(data);
+        }
+        RequestBody {
+            receiver,
+            length: Some(length),
+        }
+    }
+
+    /// A body sent as it is written to the sender, with the chunked
+    /// transfer encoding. It ends when the sender is dropped.
+    fn channel() -> (async_mpsc::Sender<Bytes>, RequestBody) {
+        let (sender, receiver) = async_mpsc::channel(4);
+        let body = RequestBody {
+            receiver,
+            length: None,
+        };
+        (sender, body)
+    }
+}
+
+impl Body for RequestBody {
+    type Data = Bytes;
+    type Error = io::Error;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
+    ) -> Poll<Option<Result<Frame<Bytes>, io::Error>>> {
+        self.receiver
+            .poll_recv(cx)
+            .map(|data| data.map(|data| Ok(Frame::data(data))))
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        match self.length {
+            Some(length) => SizeHint::with_exact(length),
+            None => SizeHint::default(),
+        }
+    }
+}
+
+/// The raw response to a request sent by the runtime, its head followed by
+/// its body. It ends when the body does or when the request is stopped.
+struct HyperStream {
+    receiver: mpsc::Receiver<io::Result<Bytes>>,
+    data: Bytes,
+}
+
+impl HyperStream {
+    /// Waits for the head of the response, None if the request failed.
+    fn open(mut self) -> Option<HyperStream> {
+        match self.receiver.recv() {
+            Ok(Ok(head)) => {
+                self.data = head;
+                Some(self)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Read for HyperStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.data.is_empty() {
+            match self.receiver.recv() {
+                Ok(Ok(data)) => self.data = data,
+                Ok(Err(err)) => return Err(err),
+                Err(_) => return Ok(0),
+            }
+        }
+
+        let len = buf.len().min(self.data.len());
+        buf[..len].copy_from_slice(&self.data.split_to(len));
+        Ok(len)
+    }
+}
+
+impl DockerApiClient for HyperClient {
+    fn request(&self, request: &str) -> Option<Vec<u8>> {
+        let (builder, body) = self.prepare(request)?;
+        let request = builder.body(RequestBody::full(body)).ok()?;
+
+        let mut stream = self.send(request, &StreamHandle::new()).open()?;
+        let mut response = Vec::new();
+        match stream.read_to_end(&mut response) {
+            Ok(_) => Some(response),
+            Err(_) => None,
+        }
+    }
+
+    fn stream_request(
+        &self,
+        request: &str,
+        handle: &StreamHandle,
+    ) -> Option<Box<dyn Read + Send>> {
+        let (builder, body) = self.prepare(request)?;
+        let request = builder.body(RequestBody::full(body)).ok()?;
+
+        self.send(request, handle)
+            .open()
+            .map(|stream| Box::new(stream) as Box<dyn Read + Send>)
+    }
+
+    fn upload_request(
+        &self,
+        request: &str,
+        body: &mut dyn Read,
+        handle: &StreamHandle,
+    ) -> Option<Box<dyn Read + Send>> {
+        let (builder, _) = self.prepare(request)?;
+        let (sender, request_body) = RequestBody::channel();
+        let request = builder.body(request_body).ok()?;
+        let stream = self.send(request, handle);
+
+        let mut buffer = vec![0; UPLOAD_CHUNK_SIZE];
+        loop {
+            let len = match body.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(len) => len,
+                Err(_) => {
+                    handle.cancel();
+                    return None;
+                }
+            };
+            // The daemon can answer before the whole body is sent, like
+            // when the request is refused.
+            let data = Bytes::copy_from_slice(&buffer[..len]);
+            if sender.blocking_send(data).is_err() {
+                break;
+            }
+        }
+        drop(sender);
+
+        stream
+            .open()
+            .map(|stream| Box::new(stream) as Box<dyn Read + Send>)
+    }
+
+    fn api_version(&self) -> Option<ApiVersion> {
+        self.api_version
+    }
+
+    fn is_strict(&self) -> bool {
+        self.strict
+    }
+
+    fn rate_limiter(&self) -> Option<&RateLimiter> {
+        self.rate_limiter.as_deref()
+    }
+
+    fn owner_label(&self) -> Option<(&str, &str)> {
+        self.owner_label
+            .as_ref()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+
+    fn resource_check(&self) -> ResourceCheck {
+        self.resource_check
+    }
+}
+
+impl Version for HyperClient {}
+#[cfg(feature = "build")]
+impl Build for HyperClient {}
+#[cfg(feature = "containers")]
+impl Containers for HyperClient {}
+#[cfg(feature = "events")]
+impl Events for HyperClient {}
+#[cfg(feature = "exec")]
+impl Exec for HyperClient {}
+#[cfg(feature = "images")]
+impl Images for HyperClient {}
+#[cfg(feature = "logs")]
+impl Logs for HyperClient {}
+#[cfg(feature = "networks")]
+impl Networks for HyperClient {}
+#[cfg(feature = "stats")]
+impl Stats for HyperClient {}
+#[cfg(feature = "swarm")]
+impl Swarm for HyperClient {}
+#[cfg(feature = "secrets")]
+impl Secrets for HyperClient {}
+#[cfg(feature = "services")]
+impl Services for HyperClient {}
+#[cfg(feature = "volumes")]
+impl Volumes for HyperClient {}
@@ -1,19 +1,88 @@
 //! docker-rs is a rust library to interact with Docker API
 //!
-//! * Currently the only method to connect to docker is through unix
-//! socket.
+//! * The client connects to docker through its unix socket or over TCP,
+//!   optionally through an HTTP proxy. TLS needs the `rustls` or the
+//!   `native-tls` feature.
+//! * Every API module but `version` has a feature of the same name, all of
+//!   them enabled by default. Building with `default-features = false` and
+//!   only the needed ones leaves the others out, along with the helpers
+//!   using them like `bulk` or `stacks`.
+//! * The `hyper` feature adds `hyper_client::HyperClient`, a client built on
+//!   hyper and tokio, `DockerClient` stays the default.
 #[macro_use]
 extern crate quick_error;
 
 #[macro_use]
 extern crate serde_derive;
 
+extern crate base64;
+#[cfg(feature = "chrono")]
+extern crate chrono;
+#[cfg(feature = "hyper")]
+extern crate http_body;
+extern crate httparse;
+#[cfg(feature = "hyper")]
+extern crate hyper;
+#[cfg(feature = "hyper")]
+extern crate hyper_util;
+#[cfg(feature = "hyper")]
+extern crate hyperlocal;
+#[cfg(feature = "native-tls")]
+extern crate native_tls;
+#[cfg(feature = "regex")]
+extern crate regex;
+#[cfg(feature = "rustls")]
+extern crate rustls;
 extern crate serde;
+// `json!` is only used by some of the API modules.
+#[allow(unused_imports)]
+#[macro_use]
 extern crate serde_json;
+#[cfg(feature = "tar")]
+extern crate tar;
+#[cfg(feature = "hyper")]
+extern crate tokio;
 
 pub mod api;
+pub mod bulk;
 pub mod client;
+pub mod debug;
+pub mod dockerignore;
 pub mod errors;
+#[cfg(feature = "events")]
+pub mod exit_history;
+pub mod fleet;
+#[cfg(all(feature = "containers", feature = "images", feature = "volumes"))]
+pub mod gc;
+#[cfg(feature = "hyper")]
+pub mod hyper_client;
+#[cfg(all(feature = "containers", feature = "images", feature = "volumes"))]
+pub mod migrate;
+pub mod proxy;
+#[cfg(all(
+    feature = "build",
+    feature = "containers",
+    feature = "networks",
+    feature = "volumes"
+))]
+pub mod prune;
+pub mod ratelimit;
+pub mod replay;
+#[cfg(all(feature = "containers", feature = "exec"))]
+pub mod runner;
+#[cfg(feature = "images")]
+pub mod snapshot;
+#[cfg(all(feature = "networks", feature = "secrets", feature = "services"))]
+pub mod stacks;
+#[cfg(all(feature = "containers", feature = "logs"))]
+pub mod startup;
+#[cfg(all(feature = "tar", feature = "containers"))]
+pub mod tar_util;
+#[cfg(feature = "test-support")]
+pub mod test_support;
+pub mod tls;
+#[cfg(all(feature = "containers", feature = "images"))]
+pub mod updater;
 pub mod utils;
 
 pub use client::DockerClient;
@@ -0,0 +1,142 @@
+//! Moves a stopped container to another daemon, a cold migration.
+//!
+//! The container is created again on the other daemon with the config it
+//! was inspected with. Its image can be copied along, and the content of
+//! its named volumes is sent through the archive endpoints, so the
+//! container finds its data there.
+//!
+//! ```rust,ignore
+//! client.stop_container("db", None)?;
+//! let opts = MigrateOptions::new().copy_image(true).volumes(true);
+//! let id = migrate_container(&old_host, &new_host, "db", &opts)?;
+//! new_host.start_container(&id)?;
+//! ```
+use std::path::Path;
+
+use api::containers::{ContainerConfig, Containers};
+use api::ids::ContainerId;
+use api::images::{copy_image, Images};
+use api::volumes::{VolumeConfig, Volumes};
+use errors::DockerApiError;
+
+/// Options of `migrate_container`.
+///
+/// * name: Name of the container on the other daemon, the name it has now
+///   if not set.
+/// * copy_image: Copies the image of the container, see `copy_image`.
+///   Otherwise it must already be on the other daemon.
+/// * volumes: Creates the named volumes of the container on the other
+///   daemon and copies their content. Bind mounts are not copied, their
+///   host paths must exist there.
+/// * start: Starts the container once it is migrated.
+#[derive(Debug, Clone, Default)]
+pub struct MigrateOptions {
+    pub name: Option<String>,
+    pub copy_image: bool,
+    pub volumes: bool,
+    pub start: bool,
+}
+
+impl MigrateOptions {
+    pub fn new() -> MigrateOptions {
+        MigrateOptions::default()
+    }
+
+    pub fn name(mut self, name: &str) -> MigrateOptions {
+        self.name = Some(name.to_string());
+        self
+    }
+
+    pub fn copy_image(mut self, copy: bool) -> MigrateOptions {
+        self.copy_image = copy;
+        self
+    }
+
+    pub fn volumes(mut self, volumes: bool) -> MigrateOptions {
+        self.volumes = volumes;
+        self
+    }
+
+    pub fn start(mut self, start: bool) -> MigrateOptions {
+        self.start = start;
+        self
+    }
+}
+
+/// Creates the container `id` of `src` on `dst`, giving the ID of the new
+/// container. The container must be stopped, so that its volumes are not
+/// written to while they are copied. It is left on `src`, to be removed
+/// once the new one works.
+///
+/// The networks of the container must exist on `dst`. When a step fails
+/// what was created on `dst` before it is left there.
+pub fn migrate_container<S, D>(
+    src: &S,
+    dst: &D,
+    id: &str,
+    opts: &MigrateOptions,
+) -> Result<ContainerId, DockerApiError>
+where
+    S: Containers + Images + Volumes,
+    D: Containers + Images + Volumes,
+{
+    let details = src.inspect_container(id)?;
+    if details.state.running || details.state.restarting {
+        return Err(DockerApiError::ContainerError(format!(
+            "The container {} must be stopped to be migrated",
+            id
+        )));
+    }
+    let config = ContainerConfig::from_inspect(&details)?;
+
+    if opts.copy_image {
+        copy_image(src, dst, &config.image)?;
+    }
+
+    let volumes: Vec<_> = details
+        .mounts
+        .iter()
+        .filter_map(|mount| {
+            mount
+                .name
+                .as_ref()
+                .map(|name| (name.as_str(), mount.destination.as_str()))
+        })
+        .collect();
+    if opts.volumes {
+        for &(name, _) in &volumes {
+            let volume = src.inspect_volume(name)?;
+            dst.create_volume(VolumeConfig {
+                name: volume.name,
+                driver: volume.driver,
+                driver_opts: volume.options.unwrap_or_default(),
+                labels: volume.labels.unwrap_or_default(),
+            })?;
+        }
+    }
+
+    let name = match opts.name {
+        Some(ref name) => name.clone(),
+        None => details.name.trim_start_matches('/').to_string(),
+    };
+    let new_id = dst.create_container(&name, config)?.id;
+
+    if opts.volumes {
+        // The archive of a directory holds the directory itself, it is
+        // extracted in the parent of the mount point.
+        for &(_, destination) in &volumes {
+            let parent = Path::new(destination)
+                .parent()
+                .and_then(|parent| parent.to_str())
+                .unwrap_or("/");
+            let mut archive = src.get_archive(&details.id, destination)?;
+            dst.put_archive(&new_id, parent, &mut archive)?;
+        }
+    }
+
+    if opts.start {
+        dst.start_container(&new_id)?;
+    }
+
+    Ok(new_id)
+}
@@ -0,0 +1,128 @@
+//! Removes everything unused on a host at once, like `docker system prune`.
+//!
+//! ```rust,ignore
+//! let opts = SystemPruneOptions::new()
+//!     .until(Duration::from_secs(24 * 60 * 60))
+//!     .exclude_label("keep");
+//! let report = system_prune(&client, &opts)?;
+//! println!("{} bytes reclaimed", report.space_reclaimed());
+//! ```
+use std::time::Duration;
+
+use api::build::{Build, BuildCachePruneReport};
+use api::containers::{Containers, ContainersPruneReport};
+use api::images::{Images, ImagesPruneReport};
+use api::networks::{Networks, NetworksPruneReport};
+use api::volumes::{Volumes, VolumesPruneReport};
+use api::PruneFilters;
+use errors::DockerApiError;
+
+/// Options of `system_prune`.
+///
+/// * all: Removes all the images not used by a container, not only the
+///   dangling ones, and all the build cache.
+/// * volumes: Also removes the volumes not used by a container, which
+///   `docker system prune` only does with `--volumes`.
+/// * filters: Applied to every prune, see `PruneFilters`. The volumes
+///   have no creation date filter, `until` does not apply to them, and the
+///   build cache has no labels, only `until` applies to it.
+#[derive(Debug, Clone, Default)]
+pub struct SystemPruneOptions {
+    pub all: bool,
+    pub volumes: bool,
+    pub filters: PruneFilters,
+}
+
+impl SystemPruneOptions {
+    pub fn new() -> SystemPruneOptions {
+        SystemPruneOptions::default()
+    }
+
+    pub fn all(mut self, all: bool) -> SystemPruneOptions {
+        self.all = all;
+        self
+    }
+
+    pub fn volumes(mut self, volumes: bool) -> SystemPruneOptions {
+        self.volumes = volumes;
+        self
+    }
+
+    /// Only removes what was created longer ago than `age`.
+    pub fn until(mut self, age: Duration) -> SystemPruneOptions {
+        self.filters.until = Some(age);
+        self
+    }
+
+    /// Only removes the objects with this label, `key` or `key=value`.
+    pub fn label(mut self, label: &str) -> SystemPruneOptions {
+        self.filters.labels.push(label.to_string());
+        self
+    }
+
+    /// Keeps the objects with this label, `key` or `key=value`.
+    pub fn exclude_label(mut self, label: &str) -> SystemPruneOptions {
+        self.filters.exclude_labels.push(label.to_string());
+        self
+    }
+}
+
+/// What `system_prune` removed.
+///
+/// * volumes: None unless the volumes were pruned.
+#[derive(Debug, Default)]
+pub struct SystemPruneReport {
+    pub containers: ContainersPruneReport,
+    pub networks: NetworksPruneReport,
+    pub volumes: Option<VolumesPruneReport>,
+    pub images: ImagesPruneReport,
+    pub build_cache: BuildCachePruneReport,
+}
+
+impl SystemPruneReport {
+    /// The disk space freed by all the prunes, in bytes.
+    pub fn space_reclaimed(&self) -> u64 {
+        self.containers.space_reclaimed
+            + self
+                .volumes
+                .as_ref()
+                .map_or(0, |volumes| volumes.space_reclaimed)
+            + self.images.space_reclaimed
+            + self.build_cache.space_reclaimed
+    }
+}
+
+/// Prunes the stopped containers, the unused networks, optionally the
+/// unused volumes, then the images and the build cache, in this order so
+/// that what the removed containers used is removed too. Stops at the
+/// first prune failing.
+pub fn system_prune<C>(
+    client: &C,
+    opts: &SystemPruneOptions,
+) -> Result<SystemPruneReport, DockerApiError>
+where
+    C: Build + Containers + Images + Networks + Volumes,
+{
+    let filters = &opts.filters;
+    let containers = client.prune_containers(filters)?;
+    let networks = client.prune_networks(filters)?;
+    let volumes = if opts.volumes {
+        let filters = PruneFilters {
+            until: None,
+            ..filters.clone()
+        };
+        Some(client.prune_volumes(&filters, false)?)
+    } else {
+        None
+    };
+    let images = client.prune_images(filters, opts.all)?;
+    let build_cache = client.prune_build_cache(filters, opts.all)?;
+
+    Ok(SystemPruneReport {
+        containers,
+        networks,
+        volumes,
+        images,
+        build_cache,
+    })
+}
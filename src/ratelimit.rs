@@ -0,0 +1,71 @@
+//! Client side rate limiting of the operations which hit a registry, like
+//! pulls, pushes and searches, to stay under limits like the pull rate
+//! limit of Docker Hub in batch tooling.
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Token bucket, an operation takes a token and the tokens are refilled at
+/// a constant rate up to the capacity of the bucket. The bucket starts
+/// full, so bursts up to the capacity are allowed.
+///
+/// ```rust,ignore
+/// // 100 pulls every 6 hours, like anonymous pulls on Docker Hub.
+/// let limiter = RateLimiter::new(100, Duration::from_secs(6 * 60 * 60));
+/// let client = DockerClient::new("unix:///var/run/docker.sock")?
+///     .with_rate_limiter(limiter);
+/// ```
+#[derive(Debug)]
+pub struct RateLimiter {
+    capacity: f64,
+    tokens_per_sec: f64,
+    state: Mutex<(f64, Instant)>,
+}
+
+impl RateLimiter {
+    /// Allows `capacity` operations every `period`.
+    pub fn new(capacity: u32, period: Duration) -> RateLimiter {
+        let capacity = f64::from(capacity.max(1));
+        let period = period.as_secs_f64().max(f64::MIN_POSITIVE);
+
+        RateLimiter {
+            capacity,
+            tokens_per_sec: capacity / period,
+            state: Mutex::new((capacity, Instant::now())),
+        }
+    }
+
+    /// Takes a token if one is available, gives the time to wait for the
+    /// next one otherwise.
+    fn take(&self) -> Result<(), Duration> {
+        let mut state = match self.state.lock() {
+            Ok(state) => state,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+
+        let now = Instant::now();
+        let elapsed = now.duration_since(state.1).as_secs_f64();
+        state.0 = (state.0 + elapsed * self.tokens_per_sec).min(self.capacity);
+        state.1 = now;
+
+        if state.0 >= 1.0 {
+            state.0 -= 1.0;
+            return Ok(());
+        }
+
+        let missing = 1.0 - state.0;
+        Err(Duration::from_secs_f64(missing / self.tokens_per_sec))
+    }
+
+    /// Takes a token if one is available, without waiting.
+    pub fn try_acquire(&self) -> bool {
+        self.take().is_ok()
+    }
+
+    /// Takes a token, waiting for one to be available.
+    pub fn acquire(&self) {
+        while let Err(wait) = self.take() {
+            thread::sleep(wait);
+        }
+    }
+}
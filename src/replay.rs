@@ -0,0 +1,367 @@
+//! Record and replay of the exchanges with the daemon, to write fast and
+//! deterministic tests for code using the client.
+//!
+//! `RecordingClient` wraps a client talking to a real daemon and records
+//! every request with the raw response it got, `save` writes them to a
+//! JSON fixture. `ReplayClient` loads the fixture and answers the same
+//! requests with the recorded responses, without any daemon.
+//!
+//! ```rust,ignore
+//! // Once, against a real daemon.
+//! let client = RecordingClient::new(DockerClient::new("unix:///var/run/docker.sock")?);
+//! let containers = client.list_all_containers(None)?;
+//! client.save(Path::new("tests/fixtures/list.json"))?;
+//!
+//! // In the tests.
+//! let client = ReplayClient::load(Path::new("tests/fixtures/list.json"))?;
+//! let containers = client.list_all_containers(None)?;
+//! ```
+//!
+//! Streamed responses are recorded as far as they were read. The stdin sent
+//! on hijacked connections is not recorded, the replay client does not
+//! support them.
+//!
+//! The credentials sent in the headers, like `X-Registry-Auth`, are
+//! redacted from the recorded requests, see `debug::REDACTED_HEADERS`, and
+//! the requests are replayed whatever the values of these headers.
+use std::cell::RefCell;
+use std::fs::File;
+use std::io;
+use std::io::{Cursor, Read};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde_json;
+
+use debug::redact_headers;
+
+#[cfg(feature = "build")]
+use api::build::Build;
+#[cfg(feature = "containers")]
+use api::containers::Containers;
+#[cfg(feature = "events")]
+use api::events::Events;
+#[cfg(feature = "exec")]
+use api::exec::Exec;
+#[cfg(feature = "images")]
+use api::images::Images;
+#[cfg(feature = "logs")]
+use api::logs::Logs;
+#[cfg(feature = "networks")]
+use api::networks::Networks;
+#[cfg(feature = "secrets")]
+use api::secrets::Secrets;
+#[cfg(feature = "services")]
+use api::services::Services;
+#[cfg(feature = "stats")]
+use api::stats::Stats;
+#[cfg(feature = "swarm")]
+use api::swarm::Swarm;
+use api::version::{ApiVersion, Version};
+#[cfg(feature = "volumes")]
+use api::volumes::Volumes;
+use api::{DockerApiClient, ResourceCheck};
+use errors::DockerApiError;
+use ratelimit::RateLimiter;
+use utils::{CloseWrite, StreamHandle};
+
+/// How the raw response is stored in the fixture, responses which are not
+/// valid UTF-8, like multiplexed logs, are base64 encoded.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Encoding {
+    Utf8,
+    Base64,
+}
+
+/// A request and the raw HTTP response the daemon gave to it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Interaction {
+    pub request: String,
+    pub response: String,
+    pub encoding: Encoding,
+}
+
+impl Interaction {
+    fn new(request: &str, response: &[u8]) -> Interaction {
+        match String::from_utf8(response.to_vec()) {
+            Ok(response) => Interaction {
+                request: request.to_string(),
+                response,
+                encoding: Encoding::Utf8,
+            },
+            Err(_) => Interaction {
+                request: request.to_string(),
+                response: STANDARD.encode(response),
+                encoding: Encoding::Base64,
+            },
+        }
+    }
+
+    fn response_bytes(&self) -> Option<Vec<u8>> {
+        match self.encoding {
+            Encoding::Utf8 => Some(self.response.clone().into_bytes()),
+            Encoding::Base64 => STANDARD.decode(&self.response).ok(),
+        }
+    }
+}
+
+/// The content of a fixture file.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct Fixture {
+    pub interactions: Vec<Interaction>,
+}
+
+/// Reader passing the response of a streaming request through while
+/// recording what was read.
+struct TeeReader {
+    inner: Box<dyn Read + Send>,
+    recorded: Arc<Mutex<Vec<u8>>>,
+}
+
+impl Read for TeeReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.inner.read(buf)?;
+        if let Ok(mut recorded) = self.recorded.lock() {
+            recorded.extend_from_slice(&buf[..len]);
+        }
+        Ok(len)
+    }
+}
+
+/// A recorded request with the response read so far, shared with the
+/// reader of streamed responses.
+type Recorded = (String, Arc<Mutex<Vec<u8>>>);
+
+/// A client recording the requests made through it with their responses.
+pub struct RecordingClient<C: DockerApiClient> {
+    inner: C,
+    interactions: RefCell<Vec<Recorded>>,
+}
+
+impl<C: DockerApiClient> RecordingClient<C> {
+    pub fn new(inner: C) -> RecordingClient<C> {
+        RecordingClient {
+            inner,
+            interactions: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Gives the interactions recorded so far.
+    pub fn fixture(&self) -> Fixture {
+        let interactions = self
+            .interactions
+            .borrow()
+            .iter()
+            .map(|(request, response)| {
+                let response = match response.lock() {
+                    Ok(response) => response.clone(),
+                    Err(_) => Vec::new(),
+                };
+                Interaction::new(request, &response)
+            })
+            .collect();
+
+        Fixture { interactions }
+    }
+
+    /// Writes the interactions recorded so far to the fixture at `path`.
+    pub fn save(&self, path: &Path) -> Result<(), DockerApiError> {
+        let file = File::create(path).map_err(DockerApiError::IoError)?;
+        serde_json::to_writer_pretty(file, &self.fixture())
+            .map_err(DockerApiError::JsonSerializationError)
+    }
+
+    fn record(&self, request: &str, response: Vec<u8>) -> Arc<Mutex<Vec<u8>>> {
+        let response = Arc::new(Mutex::new(response));
+        self.interactions
+            .borrow_mut()
+            .push((redact_headers(request), response.clone()));
+        response
+    }
+}
+
+impl<C: DockerApiClient> DockerApiClient for RecordingClient<C> {
+    fn request(&self, request: &str) -> Option<Vec<u8>> {
+        let response = self.inner.request(request)?;
+        self.record(request, response.clone());
+        Some(response)
+    }
+
+    fn stream_request(
+        &self,
+        request: &str,
+        handle: &StreamHandle,
+    ) -> Option<Box<dyn Read + Send>> {
+        let inner = self.inner.stream_request(request, handle)?;
+        let recorded = self.record(request, Vec::new());
+        Some(Box::new(TeeReader { inner, recorded }))
+    }
+
+    /// The uploaded body is not recorded, only the request and the
+    /// response.
+    fn upload_request(
+        &self,
+        request: &str,
+        body: &mut dyn Read,
+        handle: &StreamHandle,
+    ) -> Option<Box<dyn Read + Send>> {
+        let inner = self.inner.upload_request(request, body, handle)?;
+        let recorded = self.record(request, Vec::new());
+        Some(Box::new(TeeReader { inner, recorded }))
+    }
+
+    fn rate_limiter(&self) -> Option<&RateLimiter> {
+        self.inner.rate_limiter()
+    }
+
+    fn api_version(&self) -> Option<ApiVersion> {
+        self.inner.api_version()
+    }
+
+    fn is_strict(&self) -> bool {
+        self.inner.is_strict()
+    }
+
+    fn owner_label(&self) -> Option<(&str, &str)> {
+        self.inner.owner_label()
+    }
+
+    fn resource_check(&self) -> ResourceCheck {
+        self.inner.resource_check()
+    }
+
+    fn hijack_request(
+        &self,
+        request: &str,
+        handle: &StreamHandle,
+    ) -> Option<(Box<dyn Read + Send>, Box<dyn CloseWrite>)> {
+        let (inner, writer) = self.inner.hijack_request(request, handle)?;
+        let recorded = self.record(request, Vec::new());
+        Some((Box::new(TeeReader { inner, recorded }), writer))
+    }
+}
+
+/// A client answering the requests with the responses of a fixture.
+///
+/// A request gets the response of the first interaction with the same
+/// request which was not replayed yet, so the same request can get
+/// different responses in the order they were recorded. The values of the
+/// redacted headers are not compared.
+pub struct ReplayClient {
+    interactions: Vec<Interaction>,
+    replayed: RefCell<Vec<bool>>,
+}
+
+impl ReplayClient {
+    pub fn new(fixture: Fixture) -> ReplayClient {
+        let replayed = vec![false; fixture.interactions.len()];
+        ReplayClient {
+            interactions: fixture.interactions,
+            replayed: RefCell::new(replayed),
+        }
+    }
+
+    /// Loads the fixture at `path`, as written by `RecordingClient::save`.
+    pub fn load(path: &Path) -> Result<ReplayClient, DockerApiError> {
+        let file = File::open(path).map_err(DockerApiError::IoError)?;
+        let fixture: Fixture = serde_json::from_reader(file)
+            .map_err(DockerApiError::JsonDeserializationError)?;
+        Ok(ReplayClient::new(fixture))
+    }
+
+    /// Checks if all the interactions of the fixture were replayed.
+    pub fn is_exhausted(&self) -> bool {
+        self.replayed.borrow().iter().all(|replayed| *replayed)
+    }
+}
+
+impl DockerApiClient for ReplayClient {
+    fn request(&self, request: &str) -> Option<Vec<u8>> {
+        let request = redact_headers(request);
+        let mut replayed = self.replayed.borrow_mut();
+        let pos = self.interactions.iter().enumerate().position(
+            |(i, interaction)| {
+                !replayed[i] && redact_headers(&interaction.request) == request
+            },
+        )?;
+
+        replayed[pos] = true;
+        self.interactions[pos].response_bytes()
+    }
+
+    fn stream_request(
+        &self,
+        request: &str,
+        _handle: &StreamHandle,
+    ) -> Option<Box<dyn Read + Send>> {
+        self.request(request)
+            .map(|resp| Box::new(Cursor::new(resp)) as Box<dyn Read + Send>)
+    }
+
+    fn upload_request(
+        &self,
+        request: &str,
+        body: &mut dyn Read,
+        handle: &StreamHandle,
+    ) -> Option<Box<dyn Read + Send>> {
+        if io::copy(body, &mut io::sink()).is_err() {
+            return None;
+        }
+        self.stream_request(request, handle)
+    }
+}
+
+impl Version for ReplayClient {}
+#[cfg(feature = "build")]
+impl Build for ReplayClient {}
+#[cfg(feature = "containers")]
+impl Containers for ReplayClient {}
+#[cfg(feature = "events")]
+impl Events for ReplayClient {}
+#[cfg(feature = "exec")]
+impl Exec for ReplayClient {}
+#[cfg(feature = "images")]
+impl Images for ReplayClient {}
+#[cfg(feature = "logs")]
+impl Logs for ReplayClient {}
+#[cfg(feature = "networks")]
+impl Networks for ReplayClient {}
+#[cfg(feature = "stats")]
+impl Stats for ReplayClient {}
+#[cfg(feature = "swarm")]
+impl Swarm for ReplayClient {}
+#[cfg(feature = "secrets")]
+impl Secrets for ReplayClient {}
+#[cfg(feature = "services")]
+impl Services for ReplayClient {}
+#[cfg(feature = "volumes")]
+impl Volumes for ReplayClient {}
+
+impl<C: DockerApiClient> Version for RecordingClient<C> {}
+#[cfg(feature = "build")]
+impl<C: DockerApiClient> Build for RecordingClient<C> {}
+#[cfg(feature = "containers")]
+impl<C: DockerApiClient> Containers for RecordingClient<C> {}
+#[cfg(feature = "events")]
+impl<C: DockerApiClient> Events for RecordingClient<C> {}
+#[cfg(feature = "exec")]
+impl<C: DockerApiClient> Exec for RecordingClient<C> {}
+#[cfg(feature = "images")]
+impl<C: DockerApiClient> Images for RecordingClient<C> {}
+#[cfg(feature = "logs")]
+impl<C: DockerApiClient> Logs for RecordingClient<C> {}
+#[cfg(feature = "networks")]
+impl<C: DockerApiClient> Networks for RecordingClient<C> {}
+#[cfg(feature = "stats")]
+impl<C: DockerApiClient> Stats for RecordingClient<C> {}
+#[cfg(feature = "swarm")]
+impl<C: DockerApiClient> Swarm for RecordingClient<C> {}
+#[cfg(feature = "secrets")]
+impl<C: DockerApiClient> Secrets for RecordingClient<C> {}
+#[cfg(feature = "services")]
+impl<C: DockerApiClient> Services for RecordingClient<C> {}
+#[cfg(feature = "volumes")]
+impl<C: DockerApiClient> Volumes for RecordingClient<C> {}
@@ -0,0 +1,283 @@
+//! Runs a sequence of commands in a fresh container of an image, like the
+//! steps of a CI job.
+//!
+//! The container is kept alive while the steps run one after the other as
+//! execs, so the files written by a step are seen by the next ones. The
+//! output of the steps is streamed to callbacks as it is written.
+//!
+//! ```rust,ignore
+//! let spec = RunSpec::new("rust:1.70")
+//!     .step("build", &["cargo", "build"])
+//!     .step("test", &["cargo", "test"])
+//!     .working_dir("/src");
+//!
+//! let result = Runner::new(&spec)
+//!     .on_output(|step, _, output| print!("[{}] {}", step, String::from_utf8_lossy(output)))
+//!     .run(&client)?;
+//! println!("success: {}", result.success());
+//! ```
+use std::time::{Duration, Instant};
+
+use api::containers::{ContainerConfig, Containers};
+use api::exec::{Exec, ExecOptions};
+use api::ids::ContainerId;
+use api::logs::StreamType;
+use errors::DockerApiError;
+
+/// A command run by the runner, `name` is given to the callbacks.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunStep {
+    pub name: String,
+    pub cmd: Vec<String>,
+}
+
+/// The image and the steps to run in a container of it.
+///
+/// * env: Environment variables of the steps, as `KEY=value`.
+/// * working_dir: Directory the steps run in.
+/// * continue_on_failure: Runs the next steps after a step failed, they are
+///   skipped by default.
+/// * keep_container: Leaves the container once the steps are done, it is
+///   removed by default.
+#[derive(Debug, Clone, Default)]
+pub struct RunSpec {
+    pub image: String,
+    pub steps: Vec<RunStep>,
+    pub env: Vec<String>,
+    pub working_dir: Option<String>,
+    pub user: Option<String>,
+    pub continue_on_failure: bool,
+    pub keep_container: bool,
+}
+
+impl RunSpec {
+    pub fn new(image: &str) -> RunSpec {
+        RunSpec {
+            image: image.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn step(mut self, name: &str, cmd: &[&str]) -> RunSpec {
+        self.steps.push(RunStep {
+            name: name.to_string(),
+            cmd: cmd.iter().map(|arg| arg.to_string()).collect(),
+        });
+        self
+    }
+
+    pub fn env(mut self, key: &str, value: &str) -> RunSpec {
+        self.env.push(format!("{}={}", key, value));
+        self
+    }
+
+    pub fn working_dir(mut self, dir: &str) -> RunSpec {
+        self.working_dir = Some(dir.to_string());
+        self
+    }
+
+    pub fn user(mut self, user: &str) -> RunSpec {
+        self.user = Some(user.to_string());
+        self
+    }
+
+    pub fn continue_on_failure(mut self, continue_on_failure: bool) -> RunSpec {
+        self.continue_on_failure = continue_on_failure;
+        self
+    }
+
+    pub fn keep_container(mut self, keep: bool) -> RunSpec {
+        self.keep_container = keep;
+        self
+    }
+
+    /// Config of the container the steps run in. Its entrypoint is replaced
+    /// by a command which waits forever, so that it runs until it is removed
+    /// whatever the image would run.
+    fn to_config(&self) -> ContainerConfig {
+        ContainerConfig {
+            image: self.image.clone(),
+            entrypoint: vec!["tail".to_string()],
+            cmd: vec!["-f".to_string(), "/dev/null".to_string()],
+            env: self.env.clone(),
+            working_dir: self.working_dir.clone().unwrap_or_default(),
+            ..Default::default()
+        }
+    }
+
+    fn exec_options(&self) -> ExecOptions {
+        ExecOptions {
+            env: self.env.clone(),
+            user: self.user.clone(),
+            working_dir: self.working_dir.clone(),
+            privileged: false,
+        }
+    }
+}
+
+/// What happened to a step.
+///
+/// * exit_code: Exit code of the command, None if the step was skipped
+///   because a previous step failed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StepResult {
+    pub name: String,
+    pub exit_code: Option<i64>,
+    pub duration: Duration,
+}
+
+impl StepResult {
+    pub fn success(&self) -> bool {
+        self.exit_code == Some(0)
+    }
+}
+
+/// The steps of a run, in order, with the container they ran in.
+#[derive(Debug, Clone)]
+pub struct RunResult {
+    pub container_id: ContainerId,
+    pub steps: Vec<StepResult>,
+}
+
+impl RunResult {
+    /// All the steps ran and exited with 0.
+    pub fn success(&self) -> bool {
+        self.steps.iter().all(|step| step.success())
+    }
+}
+
+type OutputCallback<'a> = Box<dyn FnMut(&str, StreamType, &[u8]) + 'a>;
+type StepCallback<'a> = Box<dyn FnMut(&StepResult) + 'a>;
+
+/// Runs a `RunSpec`, with the callbacks getting the output of the steps.
+pub struct Runner<'a> {
+    spec: &'a RunSpec,
+    on_output: Option<OutputCallback<'a>>,
+    on_step: Option<StepCallback<'a>>,
+}
+
+impl<'a> Runner<'a> {
+    pub fn new(spec: &'a RunSpec) -> Runner<'a> {
+        Runner {
+            spec,
+            on_output: None,
+            on_step: None,
+        }
+    }
+
+    /// Calls `callback` with the name of the step and every piece of output
+    /// it writes, as it is written.
+    pub fn on_output<F>(mut self, callback: F) -> Runner<'a>
+    where
+        F: FnMut(&str, StreamType, &[u8]) + 'a,
+    {
+        self.on_output = Some(Box::new(callback));
+        self
+    }
+
+    /// Calls `callback` once a step is done or skipped.
+    pub fn on_step<F>(mut self, callback: F) -> Runner<'a>
+    where
+        F: FnMut(&StepResult) + 'a,
+    {
+        self.on_step = Some(Box::new(callback));
+        self
+    }
+
+    /// Creates the container, runs the steps in it and removes it unless
+    /// `keep_container` is set. A failing step is not an error, its exit
+    /// code is in the result. The container is removed even if running a
+    /// step fails with an error.
+    pub fn run<C>(mut self, client: &C) -> Result<RunResult, DockerApiError>
+    where
+        C: Containers + Exec,
+    {
+        let id = client.create_container("", self.spec.to_config())?.id;
+
+        let steps = match client.start_container(&id) {
+            Ok(_) => self.run_steps(client, id.as_str()),
+            Err(err) => Err(err),
+        };
+
+        if !self.spec.keep_container {
+            let removed = client.remove_container(&id, true, true);
+            if steps.is_ok() {
+                removed?;
+            }
+        }
+
+        Ok(RunResult {
+            container_id: id,
+            steps: steps?,
+        })
+    }
+
+    fn run_steps<C>(
+        &mut self,
+        client: &C,
+        id: &str,
+    ) -> Result<Vec<StepResult>, DockerApiError>
+    where
+        C: Containers + Exec,
+    {
+        let opts = self.spec.exec_options();
+        let mut results: Vec<StepResult> = Vec::new();
+        let mut failed = false;
+
+        for step in &self.spec.steps {
+            let result = if failed && !self.spec.continue_on_failure {
+                StepResult {
+                    name: step.name.clone(),
+                    exit_code: None,
+                    duration: Duration::from_secs(0),
+                }
+            } else {
+                let started = Instant::now();
+                let exit_code = self.run_step(client, id, step, &opts)?;
+                StepResult {
+                    name: step.name.clone(),
+                    exit_code: Some(exit_code),
+                    duration: started.elapsed(),
+                }
+            };
+
+            failed = failed || !result.success();
+            if let Some(ref mut callback) = self.on_step {
+                callback(&result);
+            }
+            results.push(result);
+        }
+
+        Ok(results)
+    }
+
+    fn run_step<C>(
+        &mut self,
+        client: &C,
+        id: &str,
+        step: &RunStep,
+        opts: &ExecOptions,
+    ) -> Result<i64, DockerApiError>
+    where
+        C: Containers + Exec,
+    {
+        let cmd: Vec<&str> = step.cmd.iter().map(|arg| arg.as_str()).collect();
+        let exec_id = client.create_exec(id, &opts.to_config(&cmd))?;
+
+        let mut frames = client.start_exec(&exec_id, false)?;
+        while let Some(frame) = frames.next_frame()? {
+            if let Some(ref mut callback) = self.on_output {
+                callback(&step.name, frame.stream, frame.payload);
+            }
+        }
+
+        let details = client.inspect_exec(&exec_id)?;
+        match details.exit_code {
+            Some(exit_code) if !details.running => Ok(exit_code),
+            _ => Err(DockerApiError::ContainerError(format!(
+                "Step {} is still running after its output was closed",
+                step.name
+            ))),
+        }
+    }
+}
@@ -0,0 +1,121 @@
+//! Bakes the state of a container into an image and publishes it, like
+//! `docker commit` followed by `docker push`.
+//!
+//! ```rust,ignore
+//! let opts = SnapshotOptions::new().comment("golden image").remove_local(true);
+//! let snapshot = snapshot_and_push(
+//!     &client,
+//!     "builder",
+//!     "registry.example.com/app:golden",
+//!     Some(&auth),
+//!     &opts,
+//! )?;
+//! println!("pushed {}", snapshot.digest.unwrap_or_default());
+//! ```
+use api::ids::ImageId;
+use api::images::{CommitOptions, ImageProgress, Images, RegistryAuth};
+use errors::DockerApiError;
+
+/// Options of `snapshot_and_push`.
+///
+/// * pause: Pauses the container while it is committed, see
+///   `CommitOptions`.
+/// * remove_local: Removes the image once it is pushed, the registry then
+///   has the only copy.
+#[derive(Debug, Clone, Default)]
+pub struct SnapshotOptions {
+    pub comment: Option<String>,
+    pub author: Option<String>,
+    pub pause: Option<bool>,
+    pub changes: Vec<String>,
+    pub remove_local: bool,
+}
+
+impl SnapshotOptions {
+    pub fn new() -> SnapshotOptions {
+        SnapshotOptions::default()
+    }
+
+    pub fn comment(mut self, comment: &str) -> SnapshotOptions {
+        self.comment = Some(comment.to_string());
+        self
+    }
+
+    pub fn author(mut self, author: &str) -> SnapshotOptions {
+        self.author = Some(author.to_string());
+        self
+    }
+
+    pub fn pause(mut self, pause: bool) -> SnapshotOptions {
+        self.pause = Some(pause);
+        self
+    }
+
+    /// Applies a Dockerfile instruction to the image, like `CMD ["app"]`.
+    pub fn change(mut self, change: &str) -> SnapshotOptions {
+        self.changes.push(change.to_string());
+        self
+    }
+
+    pub fn remove_local(mut self, remove: bool) -> SnapshotOptions {
+        self.remove_local = remove;
+        self
+    }
+}
+
+/// The image made by `snapshot_and_push`.
+///
+/// * image_id: ID of the committed image.
+/// * digest: Digest of the manifest in the registry, None if the registry
+///   did not report it.
+/// * progress: The progress messages of the push.
+#[derive(Debug)]
+pub struct Snapshot {
+    pub image_id: ImageId,
+    pub reference: String,
+    pub digest: Option<String>,
+    pub progress: Vec<ImageProgress>,
+}
+
+/// Commits the container, tags the image as `reference` and pushes it.
+///
+/// When the push fails the image is left tagged locally, so that it can
+/// be pushed again without committing the container another time.
+pub fn snapshot_and_push<C>(
+    client: &C,
+    id: &str,
+    reference: &str,
+    auth: Option<&RegistryAuth>,
+    opts: &SnapshotOptions,
+) -> Result<Snapshot, DockerApiError>
+where
+    C: Images,
+{
+    let commit = CommitOptions {
+        reference: None,
+        comment: opts.comment.clone(),
+        author: opts.author.clone(),
+        pause: opts.pause,
+        changes: opts.changes.clone(),
+    };
+    let image_id = client.commit_container(id, &commit)?;
+
+    client.tag_image(image_id.as_str(), reference)?;
+    let progress = client.push_image(reference, auth)?;
+    let digest = progress
+        .iter()
+        .rev()
+        .find_map(|msg| msg.pushed_digest())
+        .map(|digest| digest.to_string());
+
+    if opts.remove_local {
+        client.remove_image(reference, false)?;
+    }
+
+    Ok(Snapshot {
+        image_id,
+        reference: reference.to_string(),
+        digest,
+        progress,
+    })
+}
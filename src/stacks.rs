@@ -0,0 +1,242 @@
+//! Deployment of a set of services with their networks and secrets as a
+//! unit, the way `docker stack deploy` does it.
+//!
+//! Everything of a stack is named after its namespace, `web` of the stack
+//! `shop` being `shop_web`, and labelled with `com.docker.stack.namespace`.
+//! The services refer to the networks and secrets of the stack by their
+//! short names. Deploying the stack again updates the services, and removes
+//! the ones which are no longer in it.
+//!
+//! ```rust,ignore
+//! let stack = Stack::new("shop")
+//!     .network("frontend")
+//!     .secret(SecretSpec::new("db_password", b"hunter2"))
+//!     .service(
+//!         ServiceSpec::new("web", "nginx:1.25")
+//!             .replicas(2)
+//!             .network("frontend")
+//!             .secret("db_password"),
+//!     );
+//!
+//! let report = deploy_stack(&client, &stack)?;
+//! println!("created {:?}, updated {:?}", report.created, report.updated);
+//! ```
+use std::collections::HashMap;
+
+use api::networks::{NetworkConfig, NetworkDriver, Networks};
+use api::secrets::{SecretSpec, Secrets};
+use api::services::{ServiceSpec, Services};
+use errors::DockerApiError;
+
+/// Label telling the stack an object belongs to.
+pub const NAMESPACE_LABEL: &str = "com.docker.stack.namespace";
+
+/// The services, networks and secrets of a stack, with their short names.
+#[derive(Debug, Clone, Default)]
+pub struct Stack {
+    pub namespace: String,
+    pub services: Vec<ServiceSpec>,
+    pub networks: Vec<NetworkConfig>,
+    pub secrets: Vec<SecretSpec>,
+}
+
+impl Stack {
+    pub fn new(namespace: &str) -> Stack {
+        Stack {
+            namespace: namespace.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn service(mut self, spec: ServiceSpec) -> Stack {
+        self.services.push(spec);
+        self
+    }
+
+    /// Adds an attachable overlay network.
+    pub fn network(self, name: &str) -> Stack {
+        self.network_config(NetworkConfig {
+            name: name.to_string(),
+            driver: NetworkDriver::Overlay,
+            attachable: true,
+            ..Default::default()
+        })
+    }
+
+    pub fn network_config(mut self, config: NetworkConfig) -> Stack {
+        self.networks.push(config);
+        self
+    }
+
+    pub fn secret(mut self, spec: SecretSpec) -> Stack {
+        self.secrets.push(spec);
+        self
+    }
+
+    /// Name of an object of the stack, `<namespace>_<name>`.
+    pub fn scoped_name(&self, name: &str) -> String {
+        format!("{}_{}", self.namespace, name)
+    }
+
+    fn label_filter(&self) -> String {
+        format!("{}={}", NAMESPACE_LABEL, self.namespace)
+    }
+}
+
+/// What was done by `deploy_stack` or `remove_stack`, with the full names
+/// of the objects.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StackReport {
+    pub networks_created: Vec<String>,
+    pub secrets_created: Vec<String>,
+    pub created: Vec<String>,
+    pub updated: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+/// Creates the networks and secrets of the stack which do not exist yet,
+/// then creates or updates its services, and removes the services of the
+/// namespace which are not in the stack.
+///
+/// Secrets can not be changed once created, an existing secret is used as
+/// it is. The services can refer to secrets created outside of the stack by
+/// their full name.
+pub fn deploy_stack<C>(
+    client: &C,
+    stack: &Stack,
+) -> Result<StackReport, DockerApiError>
+where
+    C: Services + Networks + Secrets,
+{
+    let mut report = StackReport::default();
+    let mut network_names: HashMap<&str, String> = HashMap::new();
+    let mut secret_names: HashMap<&str, String> = HashMap::new();
+
+    let existing_networks = client.list_networks()?;
+    for network in &stack.networks {
+        let name = stack.scoped_name(&network.name);
+        if !existing_networks
+            .iter()
+            .any(|existing| existing.name == name)
+        {
+            let mut config = network.clone();
+            config.name = name.clone();
+            config
+                .labels
+                .get_or_insert_with(Default::default)
+                .insert(NAMESPACE_LABEL.to_string(), stack.namespace.clone());
+            client.create_network(config)?;
+            report.networks_created.push(name.clone());
+        }
+        network_names.insert(network.name.as_str(), name);
+    }
+
+    let mut secret_ids: HashMap<String, String> = client
+        .list_secrets(None)?
+        .into_iter()
+        .map(|secret| (secret.spec.name, secret.id))
+        .collect();
+    for secret in &stack.secrets {
+        let name = stack.scoped_name(&secret.name);
+        if !secret_ids.contains_key(&name) {
+            let mut spec =
+                secret.clone().label(NAMESPACE_LABEL, &stack.namespace);
+            spec.name = name.clone();
+            let id = client.create_secret(&spec)?.id;
+            secret_ids.insert(name.clone(), id);
+            report.secrets_created.push(name.clone());
+        }
+        secret_names.insert(secret.name.as_str(), name);
+    }
+
+    let existing_services =
+        client.list_services(Some(&stack.label_filter()))?;
+    for service in &stack.services {
+        let mut spec = service.clone().label(NAMESPACE_LABEL, &stack.namespace);
+        spec.name = stack.scoped_name(&service.name);
+
+        let container = &mut spec.task_template.container_spec;
+        container
+            .labels
+            .insert(NAMESPACE_LABEL.to_string(), stack.namespace.clone());
+        for secret in &mut container.secrets {
+            if let Some(name) = secret_names.get(secret.secret_name.as_str()) {
+                secret.secret_name = name.clone();
+            }
+            match secret_ids.get(&secret.secret_name) {
+                Some(id) => secret.secret_id = id.clone(),
+                None => {
+                    return Err(DockerApiError::ServiceError(format!(
+                        "{} uses the secret {} which does not exist",
+                        service.name, secret.secret_name
+                    )))
+                }
+            }
+        }
+        for network in &mut spec.task_template.networks {
+            if let Some(name) = network_names.get(network.target.as_str()) {
+                network.target = name.clone();
+            }
+        }
+
+        match existing_services
+            .iter()
+            .find(|existing| existing.spec.name == spec.name)
+        {
+            Some(existing) => {
+                client.update_service(&existing.id, existing.version, &spec)?;
+                report.updated.push(spec.name);
+            }
+            None => {
+                client.create_service(&spec)?;
+                report.created.push(spec.name);
+            }
+        }
+    }
+
+    for existing in &existing_services {
+        if !stack.services.iter().any(|service| {
+            stack.scoped_name(&service.name) == existing.spec.name
+        }) {
+            client.remove_service(&existing.id)?;
+            report.removed.push(existing.spec.name.clone());
+        }
+    }
+
+    Ok(report)
+}
+
+/// Removes the services, networks and secrets labelled with the namespace,
+/// the services first as they use the others.
+pub fn remove_stack<C>(
+    client: &C,
+    namespace: &str,
+) -> Result<StackReport, DockerApiError>
+where
+    C: Services + Networks + Secrets,
+{
+    let stack = Stack::new(namespace);
+    let mut report = StackReport::default();
+
+    for service in client.list_services(Some(&stack.label_filter()))? {
+        client.remove_service(&service.id)?;
+        report.removed.push(service.spec.name);
+    }
+
+    for network in client.list_networks()? {
+        let in_stack = network.labels.as_ref().is_some_and(|labels| {
+            labels.get(NAMESPACE_LABEL).map(String::as_str) == Some(namespace)
+        });
+        if in_stack {
+            client.remove_network(&network.id)?;
+            report.removed.push(network.name);
+        }
+    }
+
+    for secret in client.list_secrets(Some(&stack.label_filter()))? {
+        client.remove_secret(&secret.id)?;
+        report.removed.push(secret.spec.name);
+    }
+
+    Ok(report)
+}
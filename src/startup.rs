@@ -0,0 +1,413 @@
+//! Startup of a set of containers depending on each other, like a database
+//! which has to accept connections before the application using it starts.
+//!
+//! The containers are started in the order of their dependencies, the ones
+//! whose dependencies are ready being started together, and each of them
+//! is waited for until its readiness condition holds.
+//!
+//! ```rust,ignore
+//! let plan = StartupPlan::new()
+//!     .node("db", Readiness::Healthy, &[])
+//!     .node("cache", Readiness::PortOpen(6379), &[])
+//!     .node("web", Readiness::LogContains("Listening".to_string()), &["db", "cache"])
+//!     .parallelism(2)
+//!     .timeout(Duration::from_secs(60));
+//!
+//! for node in start_in_order(&client, &plan)? {
+//!     println!("{} : {:?}", node.name, node.outcome);
+//! }
+//! ```
+use std::collections::{HashMap, HashSet};
+use std::net::TcpStream;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use api::containers::Containers;
+use api::logs::{Logs, LogsOptions};
+use bulk::run_bulk;
+use errors::DockerApiError;
+
+/// When a started container is considered ready.
+///
+/// * Running: As soon as it runs.
+/// * Healthy: Once its healthcheck passes.
+/// * PortOpen: Once the host port the container port is published on
+///   accepts TCP connections.
+/// * LogContains: Once a line of its logs contains the text, only the
+///   lines written since it was started are looked at.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Readiness {
+    Running,
+    Healthy,
+    PortOpen(u16),
+    LogContains(String),
+}
+
+/// A container of the plan, with the containers it depends on.
+#[derive(Debug, Clone)]
+pub struct StartupNode {
+    pub name: String,
+    pub readiness: Readiness,
+    pub depends_on: Vec<String>,
+}
+
+/// The containers to start, created beforehand, with their dependencies.
+///
+/// * parallelism: Containers started at the same time at most, 4 by default.
+/// * timeout: Time for a container to get ready, 60 seconds by default.
+/// * poll_interval: Time between the readiness checks.
+#[derive(Debug, Clone)]
+pub struct StartupPlan {
+    pub nodes: Vec<StartupNode>,
+    pub parallelism: usize,
+    pub timeout: Duration,
+    pub poll_interval: Duration,
+}
+
+impl Default for StartupPlan {
+    fn default() -> StartupPlan {
+        StartupPlan {
+            nodes: Vec::new(),
+            parallelism: 4,
+            timeout: Duration::from_secs(60),
+            poll_interval: Duration::from_millis(250),
+        }
+    }
+}
+
+impl StartupPlan {
+    pub fn new() -> StartupPlan {
+        StartupPlan::default()
+    }
+
+    /// Adds the container `name`, a name or an ID, started once all the
+    /// containers of `depends_on` are ready.
+    pub fn node(
+        mut self,
+        name: &str,
+        readiness: Readiness,
+        depends_on: &[&str],
+    ) -> StartupPlan {
+        self.nodes.push(StartupNode {
+            name: name.to_string(),
+            readiness,
+            depends_on: depends_on.iter().map(|dep| dep.to_string()).collect(),
+        });
+        self
+    }
+
+    pub fn parallelism(mut self, parallelism: usize) -> StartupPlan {
+        self.parallelism = parallelism;
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> StartupPlan {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn poll_interval(mut self, interval: Duration) -> StartupPlan {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Checks that the dependencies are containers of the plan and that
+    /// they have no cycle, giving the containers in a valid startup order.
+    pub fn order(&self) -> Result<Vec<&str>, DockerApiError> {
+        let names: HashSet<&str> =
+            self.nodes.iter().map(|node| node.name.as_str()).collect();
+        if names.len() != self.nodes.len() {
+            return Err(DockerApiError::ContainerError(
+                "A container is in the startup plan twice".to_string(),
+            ));
+        }
+
+        let mut order: Vec<&str> = Vec::new();
+        let mut pending: Vec<&StartupNode> = self.nodes.iter().collect();
+        for node in &pending {
+            for dep in &node.depends_on {
+                if !names.contains(dep.as_str()) {
+                    return Err(DockerApiError::ContainerError(format!(
+                        "{} depends on {} which is not in the startup plan",
+                        node.name, dep
+                    )));
+                }
+            }
+        }
+
+        while !pending.is_empty() {
+            let (ready, blocked): (Vec<&StartupNode>, Vec<&StartupNode>) =
+                pending.into_iter().partition(|node| {
+                    node.depends_on
+                        .iter()
+                        .all(|dep| order.contains(&dep.as_str()))
+                });
+            if ready.is_empty() {
+                let cycle: Vec<&str> =
+                    blocked.iter().map(|node| node.name.as_str()).collect();
+                return Err(DockerApiError::ContainerError(format!(
+                    "The dependencies of {} form a cycle",
+                    cycle.join(", ")
+                )));
+            }
+
+            order.extend(ready.iter().map(|node| node.name.as_str()));
+            pending = blocked;
+        }
+
+        Ok(order)
+    }
+}
+
+/// What happened to a container of the plan.
+///
+/// * Ready: It was started and got ready after the duration.
+/// * Failed: It could not be started or did not get ready in time.
+/// * Skipped: It was not started because a dependency did not get ready.
+#[derive(Debug)]
+pub enum StartupOutcome {
+    Ready(Duration),
+    Failed(DockerApiError),
+    Skipped,
+}
+
+/// The outcome for a container of the plan.
+#[derive(Debug)]
+pub struct NodeResult {
+    pub name: String,
+    pub outcome: StartupOutcome,
+}
+
+/// Checks once if the container is ready, an error means it will never
+/// be, like a container which exited.
+/// The logs are followed by `wait_for_log`, only the state of the container
+/// is checked here for `LogContains`.
+fn is_ready<C>(
+    client: &C,
+    name: &str,
+    readiness: &Readiness,
+) -> Result<bool, DockerApiError>
+where
+    C: Containers + Logs,
+{
+    let details = client.inspect_container(name)?;
+    if !details.state.running {
+        return Err(DockerApiError::ContainerError(format!(
+            "{} is not running : {}",
+            name, details.state.status
+        )));
+    }
+
+    match *readiness {
+        Readiness::Running | Readiness::LogContains(_) => Ok(true),
+        Readiness::Healthy => match details.state.health {
+            Some(ref health) if health.status == "unhealthy" => {
+                Err(DockerApiError::ContainerError(format!(
+                    "{} is unhealthy",
+                    name
+                )))
+            }
+            Some(ref health) => Ok(health.status == "healthy"),
+            None => Err(DockerApiError::ContainerError(format!(
+                "{} has no healthcheck",
+                name
+            ))),
+        },
+        Readiness::PortOpen(port) => {
+            match details.network_settings.published_addr(port) {
+                Some(addr) => Ok(TcpStream::connect_timeout(
+                    &addr,
+                    Duration::from_secs(1),
+                )
+                .is_ok()),
+                None => Err(DockerApiError::ContainerError(format!(
+                    "Port {} of {} is not published",
+                    port, name
+                ))),
+            }
+        }
+    }
+}
+
+/// Seconds since the unix epoch, as taken by the `since` of the logs.
+fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// Follows the logs of the container written since `since` until a line
+/// contains the text. The stream is cancelled once `timeout` is over.
+/// Gives false when the stream ends without such a line.
+fn follow_logs<C>(
+    client: &C,
+    name: &str,
+    text: &str,
+    since: i64,
+    timeout: Duration,
+) -> Result<bool, DockerApiError>
+where
+    C: Logs,
+{
+    let options = LogsOptions {
+        follow: true,
+        since: Some(since),
+        ..Default::default()
+    };
+    let logs = client.get_container_logs(name, &options)?;
+
+    // Dropping the sender on return stops the watchdog.
+    let handle = logs.handle();
+    let (_done, done_rx) = mpsc::channel::<()>();
+    thread::spawn(move || {
+        if let Err(mpsc::RecvTimeoutError::Timeout) =
+            done_rx.recv_timeout(timeout)
+        {
+            handle.cancel();
+        }
+    });
+
+    for line in logs {
+        if line?.message.contains(text) {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Waits for a line of the logs of the container to contain the text.
+/// The logs are followed from the time the container was started, and
+/// followed again from where they ended if the stream ends while the
+/// container still runs.
+fn wait_for_log<C>(
+    client: &C,
+    node: &StartupNode,
+    text: &str,
+    mut since: i64,
+    plan: &StartupPlan,
+    started: Instant,
+) -> Result<(), DockerApiError>
+where
+    C: Containers + Logs,
+{
+    loop {
+        let followed = unix_now();
+        let remaining = plan.timeout.checked_sub(started.elapsed());
+        if let Some(remaining) = remaining {
+            if follow_logs(client, &node.name, text, since, remaining)? {
+                return Ok(());
+            }
+        }
+
+        is_ready(client, &node.name, &node.readiness)?;
+        if started.elapsed() >= plan.timeout {
+            return Err(DockerApiError::ContainerError(format!(
+                "{} was not ready after {:?}",
+                node.name, plan.timeout
+            )));
+        }
+        since = followed;
+        thread::sleep(plan.poll_interval);
+    }
+}
+
+/// Starts the container and waits for it to be ready.
+fn start_node<C>(
+    client: &C,
+    node: &StartupNode,
+    plan: &StartupPlan,
+) -> Result<Duration, DockerApiError>
+where
+    C: Containers + Logs,
+{
+    let started = Instant::now();
+    let since = unix_now();
+    client.ensure_started(&node.name)?;
+
+    if let Readiness::LogContains(ref text) = node.readiness {
+        wait_for_log(client, node, text, since, plan, started)?;
+        return Ok(started.elapsed());
+    }
+
+    loop {
+        if is_ready(client, &node.name, &node.readiness)? {
+            return Ok(started.elapsed());
+        }
+        if started.elapsed() >= plan.timeout {
+            return Err(DockerApiError::ContainerError(format!(
+                "{} was not ready after {:?}",
+                node.name, plan.timeout
+            )));
+        }
+        thread::sleep(plan.poll_interval);
+    }
+}
+
+/// Starts the containers of the plan in the order of their dependencies.
+/// Fails without starting anything if the dependencies are not valid,
+/// otherwise gives the outcome for every container in the order of the
+/// plan.
+pub fn start_in_order<C>(
+    client: &C,
+    plan: &StartupPlan,
+) -> Result<Vec<NodeResult>, DockerApiError>
+where
+    C: Containers + Logs + Sync,
+{
+    plan.order()?;
+
+    let nodes: HashMap<&str, &StartupNode> = plan
+        .nodes
+        .iter()
+        .map(|node| (node.name.as_str(), node))
+        .collect();
+    let mut outcomes: HashMap<String, StartupOutcome> = HashMap::new();
+
+    while outcomes.len() < plan.nodes.len() {
+        let mut wave: Vec<&str> = Vec::new();
+        for node in &plan.nodes {
+            if outcomes.contains_key(&node.name) {
+                continue;
+            }
+
+            let deps: Vec<Option<&StartupOutcome>> = node
+                .depends_on
+                .iter()
+                .map(|dep| outcomes.get(dep))
+                .collect();
+            if deps.iter().any(|dep| match *dep {
+                Some(&StartupOutcome::Ready(_)) | None => false,
+                Some(_) => true,
+            }) {
+                outcomes.insert(node.name.clone(), StartupOutcome::Skipped);
+            } else if deps.iter().all(|dep| dep.is_some()) {
+                wave.push(node.name.as_str());
+            }
+        }
+
+        let results =
+            run_bulk(client, &wave, plan.parallelism, |client, name| {
+                start_node(client, nodes[name], plan)
+            });
+        for result in results {
+            let outcome = match result.result {
+                Ok(elapsed) => StartupOutcome::Ready(elapsed),
+                Err(err) => StartupOutcome::Failed(err),
+            };
+            outcomes.insert(result.item, outcome);
+        }
+    }
+
+    Ok(plan
+        .nodes
+        .iter()
+        .map(|node| NodeResult {
+            name: node.name.clone(),
+            outcome: outcomes
+                .remove(&node.name)
+                .unwrap_or(StartupOutcome::Skipped),
+        })
+        .collect())
+}
@@ -0,0 +1,156 @@
+//! Tar archives for the endpoints taking or giving them, needs the `tar`
+//! feature.
+//!
+//! `context_from_dir` packs a directory as a build context, leaving out the
+//! files of its `.dockerignore` like the CLI does, and `extract_archive`
+//! unpacks an archive like the ones of `Containers::get_archive` without
+//! writing anywhere outside of the target directory.
+//!
+//! ```rust,ignore
+//! let context = context_from_dir(Path::new("./app"))?;
+//! client.build_image(&mut context.as_slice(), &BuildOptions::new().tag("app"))?;
+//!
+//! let archive = client.get_archive("web", "/var/log/nginx")?;
+//! extract_archive(archive, Path::new("./logs"))?;
+//! ```
+use std::fs;
+use std::io;
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+
+use tar::{Archive, Builder, EntryType};
+
+use api::containers::Containers;
+use dockerignore::DockerIgnore;
+use errors::DockerApiError;
+
+/// Packs the content of `dir` as a build context, without the files
+/// excluded by its `.dockerignore`. Like with the CLI the `Dockerfile` and
+/// the `.dockerignore` are sent even if the file excludes them. The entries
+/// are sorted so that the same directory always gives the same archive.
+/// Symbolic links are kept as links.
+pub fn context_from_dir(dir: &Path) -> Result<Vec<u8>, DockerApiError> {
+    write_context(dir, Vec::new())
+}
+
+/// Same as `context_from_dir`, writing the archive to `out` and giving it
+/// back, for contexts too large to be kept in memory.
+pub fn write_context<W: Write>(
+    dir: &Path,
+    out: W,
+) -> Result<W, DockerApiError> {
+    let ignore = DockerIgnore::from_dir(dir)?
+        .keep("Dockerfile")
+        .keep(".dockerignore");
+
+    let mut builder = Builder::new(out);
+    builder.follow_symlinks(false);
+    append_dir(&mut builder, dir, "", &ignore)
+        .map_err(DockerApiError::IoError)?;
+
+    builder.into_inner().map_err(DockerApiError::IoError)
+}
+
+fn append_dir<W: Write>(
+    builder: &mut Builder<W>,
+    dir: &Path,
+    prefix: &str,
+    ignore: &DockerIgnore,
+) -> io::Result<()> {
+    let mut entries: Vec<fs::DirEntry> =
+        fs::read_dir(dir)?.collect::<io::Result<Vec<fs::DirEntry>>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+
+    for entry in entries {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let rel = if prefix.is_empty() {
+            name
+        } else {
+            format!("{}/{}", prefix, name)
+        };
+        let excluded = ignore.is_excluded(&rel);
+        let path = entry.path();
+        if entry.file_type()?.is_dir() {
+            if !excluded {
+                builder.append_dir(&rel, &path)?;
+            }
+            // An exception may re-include a part of an excluded directory.
+            if !excluded || ignore.has_exceptions() {
+                append_dir(builder, &path, &rel, ignore)?;
+            }
+        } else if !excluded {
+            builder.append_path_with_name(&path, &rel)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Unpacks the archive into `dest`, created if missing, giving the paths of
+/// the entries unpacked.
+///
+/// Fails on an entry which would be written outside of `dest`, like a path
+/// with `..` or a file under a link pointing elsewhere, before writing it.
+/// The entries before it are left unpacked.
+pub fn extract_archive<R: Read>(
+    archive: R,
+    dest: &Path,
+) -> Result<Vec<PathBuf>, DockerApiError> {
+    fs::create_dir_all(dest).map_err(DockerApiError::IoError)?;
+
+    let mut archive = Archive::new(archive);
+    let mut unpacked: Vec<PathBuf> = Vec::new();
+    for entry in archive.entries().map_err(DockerApiError::IoError)? {
+        let mut entry = entry.map_err(DockerApiError::IoError)?;
+        let path = entry.path().map_err(DockerApiError::IoError)?.into_owned();
+
+        let escapes = !is_contained(&path)
+            || match entry.link_name().map_err(DockerApiError::IoError)? {
+                Some(ref target)
+                    if entry.header().entry_type() == EntryType::Link =>
+                {
+                    !is_contained(target)
+                }
+                _ => false,
+            };
+        if escapes || !entry.unpack_in(dest).map_err(DockerApiError::IoError)? {
+            return Err(DockerApiError::IoError(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "The entry {} of the archive is outside of {}",
+                    path.display(),
+                    dest.display()
+                ),
+            )));
+        }
+        unpacked.push(dest.join(&path));
+    }
+
+    Ok(unpacked)
+}
+
+/// Checks that a relative path stays under the directory it is relative
+/// to, following its `..` components.
+fn is_contained(path: &Path) -> bool {
+    let mut depth = 0;
+    for component in path.components() {
+        match component {
+            Component::Normal(_) => depth += 1,
+            Component::CurDir => (),
+            Component::ParentDir if depth > 0 => depth -= 1,
+            _ => return false,
+        }
+    }
+    true
+}
+
+/// Copies the file or directory at `path` in the container to `dest`, like
+/// `docker cp`. A directory is copied with its name, as `dest/<name>`.
+pub fn copy_from_container<C: Containers>(
+    client: &C,
+    id: &str,
+    path: &str,
+    dest: &Path,
+) -> Result<Vec<PathBuf>, DockerApiError> {
+    extract_archive(client.get_archive(id, path)?, dest)
+}
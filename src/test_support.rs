@@ -0,0 +1,159 @@
+//! Helpers to test against a real docker daemon, enabled with the
+//! `test-support` feature.
+//!
+//! The containers, networks and volumes are created with unique names and
+//! removed when their guard is dropped, like the ones of
+//! `Containers::create_container_owned`, which also happens when the test
+//! panics, so a failing test does not leave anything behind on the daemon.
+//!
+//! The integration tests of the crate use these helpers, run them with
+//! `cargo test --features test-support --test it`.
+use std::env;
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use api::containers::{
+    ContainerConfig, ContainerFsChange, Containers, OwnedContainer,
+};
+use api::networks::{NetworkConfig, Networks, OwnedNetwork};
+use api::volumes::{OwnedVolume, VolumeCreateBuilder, Volumes};
+use client::DockerClient;
+use errors::DockerApiError;
+
+/// Socket used when `DOCKER_HOST` is not set.
+const DEFAULT_DOCKER_HOST: &str = "unix:///var/run/docker.sock";
+
+/// Prefix of the names given by `unique_name`.
+pub const NAME_PREFIX: &str = "docker-rs-test";
+
+static NAME_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Connects to the daemon at `DOCKER_HOST`, or at the default socket.
+/// Panics if the daemon can not be reached, as the tests can not run.
+pub fn connect() -> DockerClient {
+    let host = match env::var("DOCKER_HOST") {
+        Ok(host) => host,
+        Err(_) => DEFAULT_DOCKER_HOST.to_string(),
+    };
+
+    // The client keeps the address for the lifetime of the program.
+    let host: &'static str = Box::leak(host.into_boxed_str());
+    match DockerClient::new(host) {
+        Ok(client) => client,
+        Err(err) => {
+            panic!("Can not connect to the daemon at {} : {}", host, err)
+        }
+    }
+}
+
+/// Gives a name which is unique across the tests and the test runs, made
+/// of `NAME_PREFIX`, `prefix`, the process ID, the time and a counter.
+pub fn unique_name(prefix: &str) -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or(0);
+
+    format!(
+        "{}-{}-{}-{}-{}",
+        NAME_PREFIX,
+        prefix,
+        process::id(),
+        nanos,
+        NAME_COUNTER.fetch_add(1, Ordering::SeqCst)
+    )
+}
+
+/// Creates a container from the config with a unique name, removed with
+/// its volumes when the guard is dropped. The image must exist locally.
+pub fn unique_container<C: Containers>(
+    client: &C,
+    config: ContainerConfig,
+) -> Result<OwnedContainer<'_, C>, DockerApiError> {
+    client.create_container_owned(&unique_name("container"), config)
+}
+
+/// Creates a network from the config, with a unique name in place of the
+/// name of the config, removed when the guard is dropped.
+pub fn unique_network<C: Networks>(
+    client: &C,
+    config: NetworkConfig,
+) -> Result<OwnedNetwork<'_, C>, DockerApiError> {
+    client.create_network_owned(NetworkConfig {
+        name: unique_name("network"),
+        ..config
+    })
+}
+
+/// Creates a volume of the `local` driver with a unique name, removed when
+/// the guard is dropped.
+pub fn unique_volume<C: Volumes>(
+    client: &C,
+) -> Result<OwnedVolume<'_, C>, DockerApiError> {
+    let config = VolumeCreateBuilder::new(&unique_name("volume")).build()?;
+    client.create_volume_owned(config)
+}
+
+/// Checks if `path` is `dir` or is under it.
+fn is_within(path: &str, dir: &str) -> bool {
+    let dir = dir.trim_end_matches('/');
+    path == dir || dir.is_empty() || path.starts_with(&format!("{}/", dir))
+}
+
+/// Gives the changes done to the filesystem of the container outside of
+/// the allowed paths, a path allowing everything under it. The directories
+/// above an allowed path are reported as modified whenever something is
+/// written under it, those modifications are allowed too.
+pub fn changes_outside<C: Containers>(
+    client: &C,
+    id: &str,
+    allowed_paths: &[&str],
+) -> Result<Vec<ContainerFsChange>, DockerApiError> {
+    let changes = client.get_container_filesystem_changes(id)?;
+
+    Ok(changes
+        .into_iter()
+        .filter(|change| {
+            !allowed_paths.iter().any(|allowed| {
+                is_within(&change.path, allowed)
+                    || (change.kind == 0 && is_within(allowed, &change.path))
+            })
+        })
+        .collect())
+}
+
+/// Panics, listing the changes like `docker diff`, if the container changed
+/// its filesystem outside of the allowed paths, like an image which should
+/// only write to its volumes.
+///
+/// ```rust,ignore
+/// let container = TestContainer::create(&client, config)?;
+/// client.start_container(&container.id)?;
+/// // ... exercise the container ...
+/// assert_no_changes_outside(&client, &container.id, &["/tmp", "/var/run"]);
+/// ```
+pub fn assert_no_changes_outside<C: Containers>(
+    client: &C,
+    id: &str,
+    allowed_paths: &[&str],
+) {
+    let changes = match changes_outside(client, id, allowed_paths) {
+        Ok(changes) => changes,
+        Err(err) => panic!("Can not get the changes of {} : {}", id, err),
+    };
+    if changes.is_empty() {
+        return;
+    }
+
+    let diff: Vec<String> = changes
+        .iter()
+        .map(|change| format!("  {}", change))
+        .collect();
+    panic!(
+        "{} changed its filesystem outside of {} :\n{}",
+        id,
+        allowed_paths.join(", "),
+        diff.join("\n")
+    );
+}
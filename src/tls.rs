@@ -0,0 +1,162 @@
+//! TLS for the daemons reached over TCP, with either the `rustls` or the
+//! `native-tls` backend selected by the cargo feature of the same name.
+//!
+//! The files are the ones `docker --tlsverify` uses, usually found in
+//! `DOCKER_CERT_PATH`:
+//!
+//! * `ca.pem`: The CA the certificate of the daemon is checked against.
+//! * `cert.pem`, `key.pem`: The client certificate and its key, when the
+//!   daemon authenticates the clients. With `native-tls` the key must be in
+//!   the PKCS#8 format (`BEGIN PRIVATE KEY`).
+#[cfg(all(feature = "rustls", feature = "native-tls"))]
+compile_error!("The `rustls` and `native-tls` features are mutually exclusive");
+
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::path::{Path, PathBuf};
+
+use errors::DockerClientError;
+
+/// Certificates to connect to the daemon with.
+///
+/// * ca_cert: CA to verify the daemon against. Without it `native-tls`
+///   uses the roots of the system, while `rustls` fails to connect.
+/// * client_cert: Client certificate and key, both PEM encoded.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TlsConfig {
+    pub ca_cert: Option<PathBuf>,
+    pub client_cert: Option<(PathBuf, PathBuf)>,
+}
+
+impl TlsConfig {
+    /// Uses `ca.pem`, `cert.pem` and `key.pem` from the directory, like
+    /// the docker CLI does with `DOCKER_CERT_PATH`. The client certificate
+    /// is only used if both the certificate and the key exist.
+    pub fn from_cert_path(dir: &Path) -> TlsConfig {
+        let cert = dir.join("cert.pem");
+        let key = dir.join("key.pem");
+
+        TlsConfig {
+            ca_cert: Some(dir.join("ca.pem")),
+            client_cert: if cert.exists() && key.exists() {
+                Some((cert, key))
+            } else {
+                None
+            },
+        }
+    }
+}
+
+/// An established TLS session.
+pub trait TlsStream: Read + Write + Send {}
+
+impl<S: Read + Write + Send> TlsStream for S {}
+
+#[cfg(any(feature = "rustls", feature = "native-tls"))]
+fn tls_error<E: ::std::fmt::Display>(err: E) -> DockerClientError {
+    DockerClientError::TlsError(err.to_string())
+}
+
+/// Runs the TLS handshake with the daemon at `host` over `stream`.
+#[cfg(feature = "rustls")]
+pub fn connect(
+    config: &TlsConfig,
+    host: &str,
+    stream: TcpStream,
+) -> Result<Box<dyn TlsStream>, DockerClientError> {
+    use rustls::crypto::ring;
+    use rustls::pki_types::pem::PemObject;
+    use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName};
+    use rustls::{ClientConfig, ClientConnection, RootCertStore, StreamOwned};
+    use std::convert::TryFrom;
+    use std::sync::Arc;
+
+    let ca_cert = match config.ca_cert {
+        Some(ref ca_cert) => ca_cert,
+        None => {
+            return Err(DockerClientError::TlsError(
+                "A CA certificate is needed with rustls".to_string(),
+            ))
+        }
+    };
+
+    let mut roots = RootCertStore::empty();
+    for cert in CertificateDer::pem_file_iter(ca_cert).map_err(tls_error)? {
+        roots.add(cert.map_err(tls_error)?).map_err(tls_error)?;
+    }
+
+    let builder =
+        ClientConfig::builder_with_provider(Arc::new(ring::default_provider()))
+            .with_safe_default_protocol_versions()
+            .map_err(tls_error)?
+            .with_root_certificates(roots);
+    let client_config = match config.client_cert {
+        Some((ref cert, ref key)) => {
+            let certs = CertificateDer::pem_file_iter(cert)
+                .map_err(tls_error)?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(tls_error)?;
+            let key = PrivateKeyDer::from_pem_file(key).map_err(tls_error)?;
+            builder
+                .with_client_auth_cert(certs, key)
+                .map_err(tls_error)?
+        }
+        None => builder.with_no_client_auth(),
+    };
+
+    let name = ServerName::try_from(host.to_string()).map_err(tls_error)?;
+    let mut session = ClientConnection::new(Arc::new(client_config), name)
+        .map_err(tls_error)?;
+    let mut stream = stream;
+    // Handshake now, so that a bad certificate fails the connection
+    // instead of the first request.
+    while session.is_handshaking() {
+        session.complete_io(&mut stream).map_err(tls_error)?;
+    }
+
+    Ok(Box::new(StreamOwned::new(session, stream)))
+}
+
+/// Runs the TLS handshake with the daemon at `host` over `stream`.
+#[cfg(all(feature = "native-tls", not(feature = "rustls")))]
+pub fn connect(
+    config: &TlsConfig,
+    host: &str,
+    stream: TcpStream,
+) -> Result<Box<dyn TlsStream>, DockerClientError> {
+    use native_tls::{Certificate, Identity, TlsConnector};
+    use std::fs;
+
+    let mut builder = TlsConnector::builder();
+    if let Some(ref ca_cert) = config.ca_cert {
+        let pem = fs::read(ca_cert).map_err(tls_error)?;
+        builder.add_root_certificate(
+            Certificate::from_pem(&pem).map_err(tls_error)?,
+        );
+    }
+    if let Some((ref cert, ref key)) = config.client_cert {
+        let cert = fs::read(cert).map_err(tls_error)?;
+        let key = fs::read(key).map_err(tls_error)?;
+        builder.identity(Identity::from_pkcs8(&cert, &key).map_err(tls_error)?);
+    }
+
+    let connector = builder.build().map_err(tls_error)?;
+    match connector.connect(host, stream) {
+        Ok(stream) => Ok(Box::new(stream)),
+        Err(err) => Err(tls_error(err)),
+    }
+}
+
+/// Without a TLS backend the connections fail, enable the `rustls` or the
+/// `native-tls` feature.
+#[cfg(not(any(feature = "rustls", feature = "native-tls")))]
+pub fn connect(
+    _config: &TlsConfig,
+    _host: &str,
+    _stream: TcpStream,
+) -> Result<Box<dyn TlsStream>, DockerClientError> {
+    Err(DockerClientError::TlsError(
+        "No TLS backend, enable the rustls or the native-tls feature"
+            .to_string(),
+    ))
+}
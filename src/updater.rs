@@ -0,0 +1,269 @@
+//! Keeps containers on the latest image of their tag, like watchtower.
+//!
+//! The containers labelled with `ENABLE_LABEL=true` are checked
+//! periodically: the digest the registry has for the image of a container
+//! is compared with the local one, and when the tag was pushed again the
+//! image is pulled and the container recreated with it, keeping its config,
+//! volumes and networks.
+//!
+//! ```rust,ignore
+//! let stop = AtomicBool::new(false);
+//! Updater::new()
+//!     .interval(Duration::from_secs(300))
+//!     .before_update(|update| update.container != "db")
+//!     .after_update(|result| println!("{} : {:?}", result.container, result.outcome))
+//!     .run(&client, &stop)?;
+//! ```
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use api::containers::{Container, Containers};
+use api::ids::ContainerId;
+use api::images::{Images, RegistryAuth};
+use api::reference::Reference;
+use errors::DockerApiError;
+use utils::encode_query_value;
+
+/// Label enabling the updates of a container, with the value `true`.
+pub const ENABLE_LABEL: &str = "com.docker-rs.updater.enable";
+
+/// A container whose image has a new digest in the registry.
+///
+/// * digest: The digest the registry has now.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PendingUpdate {
+    pub container: String,
+    pub id: ContainerId,
+    pub image: String,
+    pub digest: String,
+}
+
+/// What happened to a container during a check.
+///
+/// * Skipped: The image is an ID or a digest, or `before_update` refused
+///   the update.
+/// * Updated: The container was recreated as `id` with the image of
+///   `digest`.
+#[derive(Debug)]
+pub enum UpdateOutcome {
+    UpToDate,
+    Skipped,
+    Updated { id: ContainerId, digest: String },
+    Failed(DockerApiError),
+}
+
+#[derive(Debug)]
+pub struct UpdateResult {
+    pub container: String,
+    pub image: String,
+    pub outcome: UpdateOutcome,
+}
+
+type BeforeUpdate<'a> = Box<dyn FnMut(&PendingUpdate) -> bool + 'a>;
+type AfterUpdate<'a> = Box<dyn FnMut(&UpdateResult) + 'a>;
+
+/// Checks and updates the labelled containers.
+pub struct Updater<'a> {
+    label: String,
+    auth: Option<RegistryAuth>,
+    interval: Duration,
+    before_update: Option<BeforeUpdate<'a>>,
+    after_update: Option<AfterUpdate<'a>>,
+}
+
+impl<'a> Default for Updater<'a> {
+    fn default() -> Updater<'a> {
+        Updater::new()
+    }
+}
+
+impl<'a> Updater<'a> {
+    /// Updates the containers with `ENABLE_LABEL=true`, checking every 5
+    /// minutes.
+    pub fn new() -> Updater<'a> {
+        Updater {
+            label: format!("{}=true", ENABLE_LABEL),
+            auth: None,
+            interval: Duration::from_secs(300),
+            before_update: None,
+            after_update: None,
+        }
+    }
+
+    /// Updates the containers with this label instead, `key` or
+    /// `key=value`.
+    pub fn label(mut self, label: &str) -> Updater<'a> {
+        self.label = label.to_string();
+        self
+    }
+
+    /// Credentials for the registry, for the checks and the pulls.
+    pub fn auth(mut self, auth: RegistryAuth) -> Updater<'a> {
+        self.auth = Some(auth);
+        self
+    }
+
+    pub fn interval(mut self, interval: Duration) -> Updater<'a> {
+        self.interval = interval;
+        self
+    }
+
+    /// Calls `callback` once the new image is pulled, before the container
+    /// is recreated. The container is left as it is if it returns false.
+    pub fn before_update<F>(mut self, callback: F) -> Updater<'a>
+    where
+        F: FnMut(&PendingUpdate) -> bool + 'a,
+    {
+        self.before_update = Some(Box::new(callback));
+        self
+    }
+
+    /// Calls `callback` with the result of every container checked.
+    pub fn after_update<F>(mut self, callback: F) -> Updater<'a>
+    where
+        F: FnMut(&UpdateResult) + 'a,
+    {
+        self.after_update = Some(Box::new(callback));
+        self
+    }
+
+    /// Checks the labelled containers once, updating the ones whose image
+    /// changed. A container failing to update does not stop the others,
+    /// its error is in its result.
+    pub fn check<C>(
+        &mut self,
+        client: &C,
+    ) -> Result<Vec<UpdateResult>, DockerApiError>
+    where
+        C: Containers + Images,
+    {
+        let filters = json!({ "label": [self.label] });
+        let query_params = format!(
+            "?all=true&filters={}",
+            encode_query_value(&filters.to_string())
+        );
+        let containers =
+            client.get_containers("/containers/json", "GET", &query_params)?;
+
+        let mut results = Vec::new();
+        for container in containers {
+            let result = UpdateResult {
+                container: container.name().unwrap_or_default().to_string(),
+                image: container.image.clone(),
+                outcome: self.update(client, &container),
+            };
+            if let Some(ref mut callback) = self.after_update {
+                callback(&result);
+            }
+            results.push(result);
+        }
+        Ok(results)
+    }
+
+    /// Checks the containers every `interval` until `stop` is set. Only a
+    /// failure to list the containers ends it with an error.
+    pub fn run<C>(
+        &mut self,
+        client: &C,
+        stop: &AtomicBool,
+    ) -> Result<(), DockerApiError>
+    where
+        C: Containers + Images,
+    {
+        while !stop.load(Ordering::SeqCst) {
+            let started = Instant::now();
+            self.check(client)?;
+            while !stop.load(Ordering::SeqCst)
+                && started.elapsed() < self.interval
+            {
+                thread::sleep(Duration::from_millis(100));
+            }
+        }
+        Ok(())
+    }
+
+    fn update<C>(&mut self, client: &C, container: &Container) -> UpdateOutcome
+    where
+        C: Containers + Images,
+    {
+        // The list gives the ID of the image instead of its name once the
+        // tag points to another image, the config keeps the name.
+        let image = if container.image.starts_with("sha256:") {
+            match client.inspect_container(&container.id) {
+                Ok(details) => details.config.image,
+                Err(err) => return UpdateOutcome::Failed(err),
+            }
+        } else {
+            container.image.clone()
+        };
+        // An image ID or a pinned digest never changes.
+        let pinned = match Reference::parse(&image) {
+            Ok(reference) => reference.digest.is_some(),
+            Err(_) => true,
+        };
+        if pinned || image.starts_with("sha256:") {
+            return UpdateOutcome::Skipped;
+        }
+
+        let digest = match self.new_digest(client, &image) {
+            Ok(Some(digest)) => digest,
+            Ok(None) => return UpdateOutcome::UpToDate,
+            Err(err) => return UpdateOutcome::Failed(err),
+        };
+        if let Err(err) = client.pull_image(&image, self.auth.as_ref()) {
+            return UpdateOutcome::Failed(err);
+        }
+
+        let pending = PendingUpdate {
+            container: container.name().unwrap_or_default().to_string(),
+            id: container.id.clone(),
+            image,
+            digest,
+        };
+        if let Some(ref mut callback) = self.before_update {
+            if !callback(&pending) {
+                return UpdateOutcome::Skipped;
+            }
+        }
+
+        match client.recreate_container(&container.id, |config| config) {
+            Ok(id) => UpdateOutcome::Updated {
+                id,
+                digest: pending.digest,
+            },
+            Err(err) => UpdateOutcome::Failed(err),
+        }
+    }
+
+    /// The digest the registry has for the reference, None if the local
+    /// image already has it.
+    fn new_digest<C>(
+        &self,
+        client: &C,
+        reference: &str,
+    ) -> Result<Option<String>, DockerApiError>
+    where
+        C: Images,
+    {
+        let remote = client
+            .inspect_distribution(reference, self.auth.as_ref())?
+            .descriptor
+            .digest;
+        let repository = Reference::parse(reference)?.full_name();
+
+        let local = client.inspect_image(reference)?;
+        let up_to_date =
+            local.repo_digests.iter().any(
+                |repo_digest| match Reference::parse(repo_digest) {
+                    Ok(parsed) => {
+                        parsed.full_name() == repository
+                            && parsed.digest.as_ref() == Some(&remote)
+                    }
+                    Err(_) => false,
+                },
+            );
+
+        Ok(if up_to_date { None } else { Some(remote) })
+    }
+}
@@ -1,55 +1,123 @@
+use std::cell::Cell;
 use std::collections::HashMap;
+use std::io;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::marker::PhantomData;
+use std::net::Shutdown;
+use std::os::unix::net::UnixStream;
 use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use std::str;
-use std::usize;
 
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serializer};
 use serde_json;
 
 use errors::DockerApiError;
 
-// This implementation of HTTP response parsing is mostly taken from
-// https://github.com/p00s/minihttpse
-// with minor changes.
 const CR: u8 = b'\r';
 const LF: u8 = b'\n';
 
+/// Response headers worth showing to the callers of the API methods, the
+/// daemon version information and the warnings it sends.
+pub const EXPOSED_HEADERS: &[&str] = &[
+    "api-version",
+    "docker-experimental",
+    "ostype",
+    "server",
+    "warning",
+];
+
 #[derive(Debug)]
 pub struct Response {
     pub status_code: usize,
+    pub headers: HashMap<String, String>,
     pub body: String,
 }
 
-/// Response represent a minimal HTTP response that we are concerned with
-/// for docker API response parsing.
+/// How the end of the body of a response is found.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BodyFraming {
+    Chunked,
+    Length(usize),
+    // Responses like 204 and 304 come without a body and without a length
+    // marker.
+    Empty,
+}
+
+/// The status line and the headers of a response, parsed from the bytes
+/// read so far.
 ///
-/// The implementation is pretty straight forward and does not do something
-/// awfully bad.
-impl Response {
-    /// Public function to parse the HTTP response provided as an argument.
-    pub fn parse_http_response(
-        res: Vec<u8>,
-    ) -> Result<Response, DockerApiError> {
-        let mut pos: usize = 0;
-        for i in 0..(res.len() - 1) {
-            if res[i] == CR && res[i + 1] == LF && res[i + 2] == CR
-                && res[i + 3] == LF
-            {
-                pos = i + 3;
-                break;
-            }
-        }
+/// * body_start: Offset of the body in the bytes of the response.
+#[derive(Debug)]
+struct ResponseHead {
+    status_code: usize,
+    headers: HashMap<String, String>,
+    body_start: usize,
+    framing: BodyFraming,
+}
+
+/// Position of the `\r\n` ending the line which starts at `from`.
+fn find_crlf(buf: &[u8], from: usize) -> Option<usize> {
+    if from >= buf.len() {
+        return None;
+    }
+    buf[from..]
+        .windows(2)
+        .position(|w| w[0] == CR && w[1] == LF)
+        .map(|pos| from + pos)
+}
+
+/// Most headers expected in a response of the daemon, it sends about ten.
+const MAX_HEADERS: usize = 64;
 
-        if pos == 0 {
+/// Parses the head of the response, None if it has not been fully read yet.
+/// Header lines without a colon are skipped.
+fn parse_head(res: &[u8]) -> Result<Option<ResponseHead>, DockerApiError> {
+    let mut raw_headers = [httparse::EMPTY_HEADER; MAX_HEADERS];
+    let mut parsed = httparse::Response::new(&mut raw_headers);
+    let status = httparse::ParserConfig::default()
+        .ignore_invalid_headers_in_responses(true)
+        .parse_response(&mut parsed, res);
+    let body_start = match status {
+        Ok(httparse::Status::Complete(len)) => len,
+        Ok(httparse::Status::Partial) => return Ok(None),
+        Err(httparse::Error::Status) => {
+            return Err(DockerApiError::HTTPResponseParseError(
+                "Error while parsing HTTP status code",
+            ))
+        }
+        Err(httparse::Error::Version) => {
             return Err(DockerApiError::HTTPResponseParseError(
                 "Not a valid HTTP response",
-            ));
+            ))
         }
+        Err(_) => {
+            return Err(DockerApiError::HTTPResponseParseError(
+                "Error while parsing HTTP header",
+            ))
+        }
+    };
+    let status_code = match parsed.code {
+        Some(code) => usize::from(code),
+        None => {
+            return Err(DockerApiError::HTTPResponseParseError(
+                "Error while parsing HTTP status code",
+            ))
+        }
+    };
 
-        let (resp_header, resp_body): (&[u8], &[u8]) = res.split_at(pos);
-
-        let header_info = match String::from_utf8(resp_header.to_vec()) {
-            Ok(h) => h,
+    let mut headers: HashMap<String, String> = HashMap::new();
+    let mut framing = BodyFraming::Empty;
+    for header in parsed.headers.iter() {
+        let value = match str::from_utf8(header.value) {
+            Ok(value) => value.trim(),
             Err(_) => {
                 return Err(DockerApiError::HTTPResponseParseError(
                     "Error while parsing HTTP header",
@@ -57,107 +125,1084 @@ impl Response {
             }
         };
 
-        let body = resp_body[1..].to_owned();
+        if header.name.eq_ignore_ascii_case("transfer-encoding")
+            && value.eq_ignore_ascii_case("chunked")
+        {
+            framing = BodyFraming::Chunked;
+        } else if header.name.eq_ignore_ascii_case("content-length")
+            && framing != BodyFraming::Chunked
+        {
+            match value.parse() {
+                Ok(len) => framing = BodyFraming::Length(len),
+                Err(_) => {
+                    return Err(DockerApiError::HTTPResponseParseError(
+                        "Invalid Content-Length header",
+                    ))
+                }
+            }
+        }
+        headers.insert(header.name.to_owned(), value.to_owned());
+    }
 
-        let mut header_vec: Vec<&str> = header_info.split("\r\n").collect();
-        let status = header_vec[0].to_owned();
-        let status_vec: Vec<&str> = status.splitn(3, " ").collect();
+    Ok(Some(ResponseHead {
+        status_code,
+        headers,
+        body_start,
+        framing,
+    }))
+}
 
-        let status_code: usize = match status_vec[1].parse() {
-            Ok(s) => s,
-            Err(_) => {
-                return Err(DockerApiError::HTTPResponseParseError(
-                    "Error while parsing HTTP status code",
-                ))
+/// One step of the walk over a chunked body.
+enum Chunk {
+    /// A chunk of data, with its range in the body and the offset of the
+    /// next chunk.
+    Data(usize, usize, usize),
+    /// The terminating zero sized chunk, with its trailers.
+    Last,
+    /// The chunk has not been fully read yet.
+    Incomplete,
+}
+
+/// Reads the chunk starting at `pos` of a chunked body.
+fn next_chunk(body: &[u8], pos: usize) -> Result<Chunk, DockerApiError> {
+    let line_end = match find_crlf(body, pos) {
+        Some(end) => end,
+        None => return Ok(Chunk::Incomplete),
+    };
+
+    let size = str::from_utf8(&body[pos..line_end])
+        .ok()
+        .and_then(|line| line.split(';').next())
+        .and_then(|size| usize::from_str_radix(size.trim(), 16).ok());
+    let size = match size {
+        Some(size) => size,
+        None => {
+            return Err(DockerApiError::HTTPResponseParseError(
+                "Invalid chunks",
+            ))
+        }
+    };
+
+    let data_start = line_end + 2;
+    if size == 0 {
+        // Skip the trailers, up to the empty line.
+        let mut line = data_start;
+        loop {
+            match find_crlf(body, line) {
+                Some(end) if end == line => return Ok(Chunk::Last),
+                Some(end) => line = end + 2,
+                None => return Ok(Chunk::Incomplete),
             }
-        };
+        }
+    }
+
+    let data_end = data_start + size;
+    if body.len() < data_end + 2 {
+        return Ok(Chunk::Incomplete);
+    }
+    if &body[data_end..data_end + 2] != b"\r\n" {
+        return Err(DockerApiError::HTTPResponseParseError(
+            "Chunk without a line break after its data",
+        ));
+    }
+    Ok(Chunk::Data(data_start, data_end, data_end + 2))
+}
 
-        header_vec.remove(0);
-        let len = header_vec.len();
-        header_vec.remove(len - 1);
+/// Keeps track of a response as it is read from the connection, so that
+/// finding out whether it is complete does not go over the bytes already
+/// checked again.
+#[derive(Debug, Default)]
+pub struct ResponseParser {
+    head: Option<ResponseHead>,
+    // Offset in the body of the next chunk to check, for chunked bodies.
+    next_chunk: usize,
+}
+
+impl ResponseParser {
+    pub fn new() -> ResponseParser {
+        ResponseParser::default()
+    }
 
-        let mut headers: HashMap<String, String> = HashMap::new();
-        for header in header_vec {
-            let item = header.to_owned();
-            let item_vec: Vec<&str> = item.splitn(2, ": ").collect();
-            headers.insert(item_vec[0].to_owned(), item_vec[1].to_owned());
+    /// Checks if `res`, all the bytes read so far, make up a complete
+    /// response. The bytes given to successive calls must start the same.
+    /// A malformed response is complete, the error comes out of parsing it.
+    pub fn is_complete(&mut self, res: &[u8]) -> bool {
+        if self.head.is_none() {
+            match parse_head(res) {
+                Ok(Some(head)) => self.head = Some(head),
+                Ok(None) => return false,
+                Err(_) => return true,
+            }
         }
 
-        let body = match headers.get("Transfer-Encoding") {
-            Some(enc) => {
-                if enc == "chunked" {
-                    Response::parse_chunk(body)?
-                } else {
-                    body
+        let head = match self.head {
+            Some(ref head) => head,
+            None => return false,
+        };
+        let body = &res[head.body_start..];
+        match head.framing {
+            BodyFraming::Empty => true,
+            BodyFraming::Length(len) => body.len() >= len,
+            BodyFraming::Chunked => loop {
+                match next_chunk(body, self.next_chunk) {
+                    Ok(Chunk::Data(_, _, next)) => self.next_chunk = next,
+                    Ok(Chunk::Last) | Err(_) => return true,
+                    Ok(Chunk::Incomplete) => return false,
                 }
+            },
+        }
+    }
+}
+
+/// Response represent a minimal HTTP response that we are concerned with
+/// for docker API response parsing.
+///
+/// The response is parsed in a single pass over its bytes, without copying
+/// the head, and a malformed response is an error rather than a panic.
+impl Response {
+    /// Public function to parse the HTTP response provided as an argument.
+    pub fn parse_http_response(
+        res: Vec<u8>,
+    ) -> Result<Response, DockerApiError> {
+        let head = match parse_head(&res)? {
+            Some(head) => head,
+            None => {
+                return Err(DockerApiError::HTTPResponseParseError(
+                    "Not a valid HTTP response",
+                ))
             }
-            None => body,
         };
 
-        let response = match String::from_utf8(body) {
-            Ok(s) => s.trim().to_owned(),
+        let body = &res[head.body_start..];
+        let body = match head.framing {
+            BodyFraming::Chunked => Response::decode_chunks(body)?,
+            BodyFraming::Length(len) if len < body.len() => {
+                body[..len].to_vec()
+            }
+            _ => body.to_vec(),
+        };
+
+        let body = match String::from_utf8(body) {
+            Ok(body) => body,
             Err(_) => {
                 return Err(DockerApiError::HTTPResponseParseError(
                     "Error while parsing response body",
                 ))
             }
         };
+        let trimmed = body.trim();
+        let body = if trimmed.len() == body.len() {
+            body
+        } else {
+            trimmed.to_owned()
+        };
 
         Ok(Response {
-            status_code: status_code,
-            body: response,
+            status_code: head.status_code,
+            headers: head.headers,
+            body,
         })
     }
 
+    /// Gives the value of the header with the provided name, the lookup is
+    /// not case sensitive.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|&(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Gives the headers of `EXPOSED_HEADERS` present in the response, with
+    /// lowercased names.
+    pub fn exposed_headers(&self) -> HashMap<String, String> {
+        EXPOSED_HEADERS
+            .iter()
+            .filter_map(|name| {
+                self.header(name)
+                    .map(|value| (name.to_string(), value.to_string()))
+            })
+            .collect()
+    }
+
     /// A helper function to parse_http_reseponse, when the Header Transfer-Encoding
     /// `chunked` is present in the response.
     pub fn parse_chunk(body: Vec<u8>) -> Result<Vec<u8>, DockerApiError> {
-        let mut buf: Vec<u8> = Vec::new();
-        let mut count: usize = 0;
+        Response::decode_chunks(&body)
+    }
+
+    fn decode_chunks(body: &[u8]) -> Result<Vec<u8>, DockerApiError> {
+        let mut buf: Vec<u8> = Vec::with_capacity(body.len());
+        let mut pos: usize = 0;
 
         loop {
-            let mut pos: usize = 0;
-            for i in count..body.len() - 1 {
-                if body[i] == CR && body[i + 1] == LF {
-                    pos = i;
-                    break;
+            match next_chunk(body, pos)? {
+                Chunk::Data(start, end, next) => {
+                    buf.extend_from_slice(&body[start..end]);
+                    pos = next;
+                }
+                Chunk::Last => return Ok(buf),
+                Chunk::Incomplete => {
+                    return Err(DockerApiError::HTTPResponseParseError(
+                        "Chuncked response without length marker",
+                    ))
                 }
             }
-            if pos == 0 {
+        }
+    }
+
+    /// Checks if the raw bytes read so far from the socket make up a complete
+    /// HTTP response. The length of the body is taken from the
+    /// `Content-Length` header, or from the terminating chunk when the body
+    /// is `chunked`. Use a `ResponseParser` to check a response while it is
+    /// being read.
+    pub fn is_complete(res: &[u8]) -> bool {
+        ResponseParser::new().is_complete(res)
+    }
+}
+
+/// A HTTP response whose body is read from the connection as it arrives,
+/// used for the streaming endpoints like events, logs and stats.
+///
+/// The body is available through the `Read` and `BufRead` implementations,
+/// with the chunked transfer encoding already removed.
+pub struct StreamResponse {
+    pub status_code: usize,
+    pub headers: HashMap<String, String>,
+    body: Box<dyn BufRead + Send>,
+    handle: StreamHandle,
+}
+
+impl StreamResponse {
+    /// Reads the status line and the headers of the response from the reader
+    /// and leaves the body to be read later.
+    /// Header names are lowercased.
+    pub fn from_reader(
+        reader: Box<dyn Read + Send>,
+        handle: StreamHandle,
+    ) -> Result<StreamResponse, DockerApiError> {
+        let mut reader = BufReader::new(reader);
+
+        // The head ends with the first empty line, the rest is left in the
+        // reader for the body.
+        let mut raw_head: Vec<u8> = Vec::new();
+        loop {
+            let start = raw_head.len();
+            match reader.read_until(LF, &mut raw_head) {
+                Ok(0) | Err(_) => {
+                    return Err(DockerApiError::HTTPResponseParseError(
+                        "Error while parsing HTTP header",
+                    ))
+                }
+                Ok(_) => {}
+            }
+            let line = &raw_head[start..];
+            if start > 0 && (line == b"\r\n" || line == b"\n") {
+                break;
+            }
+        }
+
+        let head = match parse_head(&raw_head)? {
+            Some(head) => head,
+            None => {
                 return Err(DockerApiError::HTTPResponseParseError(
-                    "Chuncked response without length marker",
-                ));
+                    "Error while parsing HTTP header",
+                ))
             }
+        };
+        let status_code = head.status_code;
+        let headers: HashMap<String, String> = head
+            .headers
+            .into_iter()
+            .map(|(name, value)| (name.to_lowercase(), value))
+            .collect();
+
+        let body: Box<dyn BufRead + Send> = match head.framing {
+            BodyFraming::Chunked => {
+                Box::new(BufReader::new(ChunkedReader::new(reader)))
+            }
+            BodyFraming::Length(len) => Box::new(reader.take(len as u64)),
+            // A streamed body without a length marker lasts until the
+            // connection is closed.
+            BodyFraming::Empty => Box::new(reader),
+        };
+
+        Ok(StreamResponse {
+            status_code,
+            headers,
+            body,
+            handle,
+        })
+    }
+
+    /// Handle to cancel the response from another thread.
+    pub fn handle(&self) -> StreamHandle {
+        self.handle.clone()
+    }
 
-            let size_s = match str::from_utf8(&body[count..pos]) {
-                Ok(s) => s,
+    /// Reads the rest of the body into a string, mostly useful to get the
+    /// error message out of a response with an unexpected status code.
+    pub fn body_to_string(mut self) -> Result<String, DockerApiError> {
+        let mut body = String::new();
+        match self.body.read_to_string(&mut body) {
+            Ok(_) => Ok(body.trim().to_owned()),
+            Err(_) => Err(DockerApiError::HTTPResponseParseError(
+                "Error while parsing response body",
+            )),
+        }
+    }
+}
+
+impl Read for StreamResponse {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.handle.is_cancelled() {
+            return Ok(0);
+        }
+        self.body.read(buf)
+    }
+}
+
+impl BufRead for StreamResponse {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.handle.is_cancelled() {
+            return Ok(&[]);
+        }
+        self.body.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.body.consume(amt)
+    }
+}
+
+/// Iterator over the JSON objects, one per line, of a streaming response
+/// like the events or the stats. It blocks until the next object arrives and
+/// ends when the daemon closes the stream.
+///
+/// The lines are read into a buffer which is reused for every object.
+pub struct JsonStream<T> {
+    response: StreamResponse,
+    line: Vec<u8>,
+    item: PhantomData<T>,
+}
+
+impl<T: DeserializeOwned> JsonStream<T> {
+    pub fn new(response: StreamResponse) -> JsonStream<T> {
+        JsonStream {
+            response,
+            line: Vec::new(),
+            item: PhantomData,
+        }
+    }
+
+    /// Handle to stop the stream from another thread.
+    pub fn handle(&self) -> StreamHandle {
+        self.response.handle()
+    }
+}
+
+impl<T: DeserializeOwned> Iterator for JsonStream<T> {
+    type Item = Result<T, DockerApiError>;
+
+    fn next(&mut self) -> Option<Result<T, DockerApiError>> {
+        loop {
+            self.line.clear();
+            match self.response.read_until(LF, &mut self.line) {
+                Ok(0) => return None,
+                Ok(_) => {}
+                Err(_) => {
+                    if self.response.handle().is_cancelled() {
+                        return None;
+                    }
+                    return Some(Err(DockerApiError::HTTPResponseParseError(
+                        "Error while reading stream",
+                    )));
+                }
+            }
+
+            if self.line.iter().all(|b| b.is_ascii_whitespace()) {
+                continue;
+            }
+
+            // Checking the line as a whole is faster than letting serde_json
+            // check each of its strings.
+            let line = match str::from_utf8(&self.line) {
+                Ok(line) => line,
                 Err(_) => {
+                    return Some(Err(DockerApiError::HTTPResponseParseError(
+                        "Error while reading stream",
+                    )))
+                }
+            };
+            return Some(match serde_json::from_str(line) {
+                Ok(item) => Ok(item),
+                Err(err) => Err(DockerApiError::JsonDeserializationError(err)),
+            });
+        }
+    }
+}
+
+/// Iterator over the elements of a JSON array in the body of a response,
+/// like the container list. Each element is deserialized as soon as it is
+/// read, so the whole array is never held in memory.
+pub struct JsonArrayStream<T> {
+    response: StreamResponse,
+    strict: bool,
+    started: bool,
+    done: bool,
+    element: Vec<u8>,
+    item: PhantomData<T>,
+}
+
+impl<T: DeserializeOwned> JsonArrayStream<T> {
+    /// Reads the array from the response, `strict` is given to
+    /// `deserialize_body` for every element.
+    pub fn new(response: StreamResponse, strict: bool) -> JsonArrayStream<T> {
+        JsonArrayStream {
+            response,
+            strict,
+            started: false,
+            done: false,
+            element: Vec::new(),
+            item: PhantomData,
+        }
+    }
+
+    /// Handle to stop the stream from another thread.
+    pub fn handle(&self) -> StreamHandle {
+        self.response.handle()
+    }
+
+    /// Reads the bytes of the next element into `element`, false once the
+    /// array ends.
+    fn read_element(&mut self) -> Result<bool, DockerApiError> {
+        let mut depth = 0;
+        let mut in_string = false;
+        let mut escaped = false;
+        self.element.clear();
+
+        loop {
+            let (used, end) = {
+                let buf = match self.response.fill_buf() {
+                    Ok(buf) => buf,
+                    Err(_) => {
+                        return Err(DockerApiError::HTTPResponseParseError(
+                            "Error while reading stream",
+                        ))
+                    }
+                };
+                if buf.is_empty() {
                     return Err(DockerApiError::HTTPResponseParseError(
-                        "Invlid chunks",
-                    ))
+                        "The JSON array ended early",
+                    ));
                 }
+
+                let mut end = None;
+                let mut used = buf.len();
+                for (i, &byte) in buf.iter().enumerate() {
+                    if !self.started {
+                        match byte {
+                            b'[' => self.started = true,
+                            b if b.is_ascii_whitespace() => (),
+                            _ => {
+                                return Err(
+                                    DockerApiError::HTTPResponseParseError(
+                                        "The response is not a JSON array",
+                                    ),
+                                )
+                            }
+                        }
+                        continue;
+                    }
+
+                    if in_string {
+                        if escaped {
+                            escaped = false;
+                        } else if byte == b'\\' {
+                            escaped = true;
+                        } else if byte == b'"' {
+                            in_string = false;
+                        }
+                    } else {
+                        match byte {
+                            b'"' => in_string = true,
+                            b'{' | b'[' => depth += 1,
+                            b'}' | b']' if depth > 0 => depth -= 1,
+                            b',' | b']' if depth == 0 => {
+                                end = Some(byte == b']');
+                                used = i + 1;
+                                break;
+                            }
+                            _ => (),
+                        }
+                    }
+                    self.element.push(byte);
+                }
+                (used, end)
             };
+            self.response.consume(used);
 
-            count = pos + 2;
-            let size: usize = match usize::from_str_radix(size_s, 16) {
-                Ok(s) => s,
-                Err(_) => {
+            match end {
+                Some(last) => {
+                    if last {
+                        self.done = true;
+                    }
+                    let empty =
+                        self.element.iter().all(|b| b.is_ascii_whitespace());
+                    if !empty {
+                        return Ok(true);
+                    }
+                    if last {
+                        return Ok(false);
+                    }
                     return Err(DockerApiError::HTTPResponseParseError(
-                        "Invalid chunks",
-                    ))
+                        "Empty element in the JSON array",
+                    ));
                 }
+                None => continue,
+            }
+        }
+    }
+}
+
+impl<T: DeserializeOwned> Iterator for JsonArrayStream<T> {
+    type Item = Result<T, DockerApiError>;
+
+    fn next(&mut self) -> Option<Result<T, DockerApiError>> {
+        if self.done && self.element.is_empty() {
+            return None;
+        }
+
+        match self.read_element() {
+            Ok(true) => (),
+            Ok(false) => return None,
+            Err(_) if self.response.handle().is_cancelled() => return None,
+            Err(err) => {
+                self.done = true;
+                self.element.clear();
+                return Some(Err(err));
+            }
+        }
+
+        let item = match str::from_utf8(&self.element) {
+            Ok(element) => deserialize_body(element, self.strict),
+            Err(_) => Err(DockerApiError::HTTPResponseParseError(
+                "The JSON array is not valid UTF-8",
+            )),
+        };
+        if self.done {
+            self.element.clear();
+        }
+        Some(item)
+    }
+}
+
+type ShutdownFn = Box<dyn Fn() + Send>;
+
+/// Handle to cancel a streaming call, like following the logs or the events,
+/// from another thread.
+///
+/// Cancelling shuts the connection down, which unblocks the thread reading
+/// from the stream. The stream then ends as if the daemon had closed it.
+#[derive(Clone, Default)]
+pub struct StreamHandle {
+    cancelled: Arc<AtomicBool>,
+    shutdown: Arc<Mutex<Option<ShutdownFn>>>,
+}
+
+impl StreamHandle {
+    pub fn new() -> StreamHandle {
+        StreamHandle::default()
+    }
+
+    /// Cancels the stream, this can be called more than once.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+        if let Ok(shutdown) = self.shutdown.lock() {
+            if let Some(ref shutdown) = *shutdown {
+                shutdown();
+            }
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Sets the function which shuts down the underlying connection, used by
+    /// the `DockerApiClient` implementations in `stream_request`.
+    /// If the handle is already cancelled the function is called right away.
+    pub fn set_shutdown<F>(&self, shutdown: F)
+    where
+        F: Fn() + Send + 'static,
+    {
+        if let Ok(mut current) = self.shutdown.lock() {
+            *current = Some(Box::new(shutdown));
+            if self.is_cancelled() {
+                if let Some(ref shutdown) = *current {
+                    shutdown();
+                }
+            }
+        }
+    }
+}
+
+/// Copies `body` to `writer` with the chunked transfer encoding, ending
+/// with the last empty chunk. Gives the number of bytes of the body.
+pub fn write_chunked<W: Write + ?Sized>(
+    writer: &mut W,
+    body: &mut dyn Read,
+) -> io::Result<u64> {
+    let mut buffer = [0; 32 * 1024];
+    let mut written = 0;
+    loop {
+        let len = match body.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(len) => len,
+            Err(ref err) if err.kind() == io::ErrorKind::Interrupted => {
+                continue
+            }
+            Err(err) => return Err(err),
+        };
+        write!(writer, "{:x}\r\n", len)?;
+        writer.write_all(&buffer[..len])?;
+        writer.write_all(b"\r\n")?;
+        written += len as u64;
+    }
+    writer.write_all(b"0\r\n\r\n")?;
+    writer.flush()?;
+    Ok(written)
+}
+
+/// Write half of a hijacked connection, as used by the interactive exec
+/// sessions to send the stdin of the command.
+pub trait CloseWrite: Write + Send {
+    /// Shuts the write half of the connection down, so that the other side
+    /// reads EOF while the output can still be read.
+    fn close_write(&mut self) -> io::Result<()>;
+}
+
+impl CloseWrite for UnixStream {
+    fn close_write(&mut self) -> io::Result<()> {
+        self.shutdown(Shutdown::Write)
+    }
+}
+
+/// Reader decoding a body sent with the chunked transfer encoding, it
+/// returns EOF once the terminating zero sized chunk is read.
+pub struct ChunkedReader<R: BufRead> {
+    inner: R,
+    remaining: usize,
+    done: bool,
+    // Reused for the size lines and the trailers.
+    line: Vec<u8>,
+}
+
+impl<R: BufRead> ChunkedReader<R> {
+    pub fn new(inner: R) -> ChunkedReader<R> {
+        ChunkedReader {
+            inner,
+            remaining: 0,
+            done: false,
+            line: Vec::new(),
+        }
+    }
+
+    fn read_chunk_size(&mut self) -> io::Result<usize> {
+        self.line.clear();
+        if self.inner.read_until(LF, &mut self.line)? == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "Connection closed inside chunked body",
+            ));
+        }
+
+        str::from_utf8(&self.line)
+            .ok()
+            .and_then(|line| line.split(';').next())
+            .and_then(|size| usize::from_str_radix(size.trim(), 16).ok())
+            .ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidData, "Invalid chunks")
+            })
+    }
+}
+
+impl<R: BufRead> Read for ChunkedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.done || buf.is_empty() {
+            return Ok(0);
+        }
+
+        if self.remaining == 0 {
+            let size = self.read_chunk_size()?;
+            if size == 0 {
+                // Skip the trailers till the final empty line.
+                self.line.clear();
+                while self.inner.read_until(LF, &mut self.line)? > 2 {
+                    self.line.clear();
+                }
+                self.done = true;
+                return Ok(0);
+            }
+            self.remaining = size;
+        }
+
+        let max = buf.len().min(self.remaining);
+        let len = self.inner.read(&mut buf[..max])?;
+        if len == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "Connection closed inside chunked body",
+            ));
+        }
+
+        self.remaining -= len;
+        if self.remaining == 0 {
+            let mut crlf = [0; 2];
+            self.inner.read_exact(&mut crlf)?;
+        }
+
+        Ok(len)
+    }
+}
+
+/// Percent encodes a value to be used in the query string of a request,
+/// like the JSON encoded `filters`.
+pub fn encode_query_value(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z'
+            | b'a'..=b'z'
+            | b'0'..=b'9'
+            | b'-'
+            | b'_'
+            | b'.'
+            | b'~' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+
+    encoded
+}
+
+/// Converts a duration to the whole seconds the API expects for timeouts,
+/// rounding up so that a timeout is never shortened.
+pub fn duration_to_secs(duration: Duration) -> u64 {
+    if duration.subsec_nanos() > 0 {
+        duration.as_secs() + 1
+    } else {
+        duration.as_secs()
+    }
+}
+
+/// Converts a duration to the nanoseconds the API expects for intervals,
+/// saturating at `i64::MAX`.
+pub fn duration_to_nanos(duration: Duration) -> i64 {
+    if duration.as_nanos() > i64::MAX as u128 {
+        i64::MAX
+    } else {
+        duration.as_nanos() as i64
+    }
+}
+
+/// Deserializes a `null` value as the default of the type, for the fields
+/// the daemon sends as `null` instead of leaving them out, like empty lists.
+pub fn deserialize_null_default<'de, D, T>(
+    deserializer: D,
+) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Default + Deserialize<'de>,
+{
+    Ok(Option::deserialize(deserializer)?.unwrap_or_default())
+}
+
+/// Deserializes a command like the `Entrypoint` of a container, which the
+/// API takes as a list or as a single string, and which is `null` when it is
+/// not set.
+pub fn deserialize_string_or_list<'de, D>(
+    deserializer: D,
+) -> Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StringOrList {
+        String(String),
+        List(Vec<String>),
+    }
+
+    match Option::<StringOrList>::deserialize(deserializer)? {
+        Some(StringOrList::String(string)) => Ok(vec![string]),
+        Some(StringOrList::List(list)) => Ok(list),
+        None => Ok(Vec::new()),
+    }
+}
+
+thread_local! {
+    static STRICT: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Whether the body being deserialized by `deserialize_body` on this thread
+/// is deserialized strictly, for the lenient parts of the structs.
+pub fn is_strict_deserialization() -> bool {
+    STRICT.with(|strict| strict.get())
+}
+
+/// Deserializes the body of a response. In strict mode the unknown values of
+/// the enums and the optional blocks which cannot be read fail the whole
+/// body, otherwise they are kept as `Other` and None.
+pub fn deserialize_body<T: DeserializeOwned>(
+    body: &str,
+    strict: bool,
+) -> Result<T, DockerApiError> {
+    let previous = STRICT.with(|cell| cell.replace(strict));
+    let result = serde_json::from_str(body);
+    STRICT.with(|cell| cell.set(previous));
+
+    match result {
+        Ok(value) => Ok(value),
+        Err(err) => Err(DockerApiError::JsonDeserializationError(err)),
+    }
+}
+
+/// Deserializes an optional block of a response, which is None when it is
+/// missing or, unless deserializing strictly, when it cannot be read, like a
+/// block whose shape changed in another API version.
+pub fn deserialize_lenient<'de, D, T>(
+    deserializer: D,
+) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: DeserializeOwned,
+{
+    let value = match Option::<serde_json::Value>::deserialize(deserializer)? {
+        Some(value) => value,
+        None => return Ok(None),
+    };
+
+    match serde_json::from_value(value) {
+        Ok(block) => Ok(Some(block)),
+        Err(err) if is_strict_deserialization() => Err(D::Error::custom(err)),
+        Err(_) => Ok(None),
+    }
+}
+
+/// Parses a RFC3339 timestamp like `2018-05-01T10:00:01.123456789Z`, the
+/// format used by the daemon for dates, into a `SystemTime`. Returns None if
+/// the timestamp is malformed.
+pub fn parse_rfc3339(timestamp: &str) -> Option<SystemTime> {
+    let bytes = timestamp.as_bytes();
+    if bytes.len() < 20
+        || bytes[4] != b'-'
+        || bytes[7] != b'-'
+        || (bytes[10] != b'T' && bytes[10] != b't')
+        || bytes[13] != b':'
+        || bytes[16] != b':'
+    {
+        return None;
+    }
+
+    let number = |start: usize, end: usize| -> Option<i64> {
+        let digits = timestamp.get(start..end)?;
+        if !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        digits.parse().ok()
+    };
+
+    let (year, month, day) = (number(0, 4)?, number(5, 7)?, number(8, 10)?);
+    let (hour, min, sec) = (number(11, 13)?, number(14, 16)?, number(17, 19)?);
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    if hour > 23 || min > 59 || sec > 60 {
+        return None;
+    }
+
+    let mut rest = &timestamp[19..];
+    let mut nanos = 0;
+    if rest.starts_with('.') {
+        let digits = rest[1..].bytes().take_while(|b| b.is_ascii_digit());
+        let len = digits.count();
+        if len == 0 {
+            return None;
+        }
+        for (i, b) in rest[1..=len].bytes().enumerate() {
+            if i < 9 {
+                nanos += u32::from(b - b'0') * 10u32.pow(8 - i as u32);
+            }
+        }
+        rest = &rest[len + 1..];
+    }
+
+    let offset = match rest {
+        "Z" | "z" => 0,
+        _ if rest.len() == 6 && rest.as_bytes()[3] == b':' => {
+            let sign = match rest.as_bytes()[0] {
+                b'+' => 1,
+                b'-' => -1,
+                _ => return None,
             };
+            let hours: i64 = rest.get(1..3)?.parse().ok()?;
+            let mins: i64 = rest.get(4..6)?.parse().ok()?;
+            sign * (hours * 3600 + mins * 60)
+        }
+        _ => return None,
+    };
+
+    // Days since the epoch of the civil date, from Howard Hinnant's
+    // `days_from_civil` algorithm.
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146_097 + doe - 719_468;
+
+    let secs = days * 86400 + hour * 3600 + min * 60 + sec - offset;
+    if secs >= 0 {
+        Some(UNIX_EPOCH + Duration::new(secs as u64, nanos))
+    } else {
+        Some(
+            UNIX_EPOCH - Duration::from_secs((-secs) as u64)
+                + Duration::new(0, nanos),
+        )
+    }
+}
 
-            if size == 0 && count + 2 == body.len() {
-                return Ok(buf);
+/// Formats a `SystemTime` as a RFC3339 timestamp in UTC, with nanoseconds
+/// when the time has any, like the daemon does.
+pub fn format_rfc3339(time: SystemTime) -> String {
+    let (secs, nanos) = match time.duration_since(UNIX_EPOCH) {
+        Ok(since) => (since.as_secs() as i64, since.subsec_nanos()),
+        Err(err) => {
+            let before = err.duration();
+            match before.subsec_nanos() {
+                0 => (-(before.as_secs() as i64), 0),
+                n => (-(before.as_secs() as i64) - 1, 1_000_000_000 - n),
             }
+        }
+    };
 
-            buf.extend_from_slice(&body[pos + 2..pos + 2 + size]);
-            count = count + size + 2;
+    // Civil date of the days since the epoch, from Howard Hinnant's
+    // `civil_from_days` algorithm.
+    let days = secs.div_euclid(86400);
+    let secs_of_day = secs.rem_euclid(86400);
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    let mut formatted = format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        secs_of_day % 3600 / 60,
+        secs_of_day % 60
+    );
+    if nanos > 0 {
+        formatted.push_str(&format!(".{:09}", nanos));
+    }
+    formatted.push('Z');
+    formatted
+}
+
+/// The zero time of Go, which the daemon sends for the dates which are
+/// not set, like the end of a running container.
+const ZERO_TIME: &str = "0001-01-01T00:00:00Z";
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawTimestamp {
+    Secs(i64),
+    Float(f64),
+    Text(String),
+}
+
+/// Deserializes a date the daemon sends either as a unix timestamp or as a
+/// RFC3339 string. The dates which are not set, `null`, `0`, an empty
+/// string or the zero time, are None.
+pub fn deserialize_timestamp<'de, D>(
+    deserializer: D,
+) -> Result<Option<SystemTime>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let time = match Option::<RawTimestamp>::deserialize(deserializer)? {
+        None | Some(RawTimestamp::Secs(0)) => None,
+        Some(RawTimestamp::Secs(secs)) if secs > 0 => {
+            Some(UNIX_EPOCH + Duration::from_secs(secs as u64))
+        }
+        Some(RawTimestamp::Secs(secs)) => {
+            Some(UNIX_EPOCH - Duration::from_secs(secs.unsigned_abs()))
+        }
+        Some(RawTimestamp::Float(secs)) if secs > 0.0 && secs.is_finite() => {
+            Some(UNIX_EPOCH + Duration::from_secs_f64(secs))
+        }
+        Some(RawTimestamp::Float(_)) => None,
+        Some(RawTimestamp::Text(ref text))
+            if text.is_empty() || text.as_str() == ZERO_TIME =>
+        {
+            None
         }
+        Some(RawTimestamp::Text(text)) => match parse_rfc3339(&text) {
+            Some(time) => Some(time),
+            None => {
+                return Err(D::Error::custom(format!(
+                    "invalid timestamp {}",
+                    text
+                )))
+            }
+        },
+    };
+
+    Ok(time)
+}
+
+/// Serializes a date as a unix timestamp in seconds, 0 when it is not set.
+pub fn serialize_unix_timestamp<S: Serializer>(
+    time: &Option<SystemTime>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let secs = match *time {
+        Some(time) => match time.duration_since(UNIX_EPOCH) {
+            Ok(since) => since.as_secs() as i64,
+            Err(err) => -(err.duration().as_secs() as i64),
+        },
+        None => 0,
+    };
+    serializer.serialize_i64(secs)
+}
+
+/// Serializes a date as a RFC3339 timestamp, the zero time when it is not
+/// set.
+pub fn serialize_rfc3339_timestamp<S: Serializer>(
+    time: &Option<SystemTime>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match *time {
+        Some(time) => serializer.serialize_str(&format_rfc3339(time)),
+        None => serializer.serialize_str(ZERO_TIME),
     }
 }
 
+/// A date of the daemon as a chrono `DateTime`.
+#[cfg(feature = "chrono")]
+pub fn to_datetime(time: Option<SystemTime>) -> Option<DateTime<Utc>> {
+    time.map(DateTime::<Utc>::from)
+}
+
 /// This function validates a given unix domain socket address, it can be either
 /// of an absolute socket path or unix domain socket address.
 ///
@@ -185,7 +1230,7 @@ pub fn validate_unix_socket_address(address: &str) -> Option<Vec<&str>> {
         return None;
     }
 
-    return Some(addr_comp);
+    Some(addr_comp)
 }
 
 /// Checks if the JSON string provided is valid or not and returns
@@ -0,0 +1,179 @@
+#![allow(dead_code)]
+
+use std::cell::RefCell;
+use std::io;
+use std::io::{Cursor, Read, Write};
+use std::sync::{Arc, Mutex};
+
+use rust_docker::api::version::ApiVersion;
+use rust_docker::api::{DockerApiClient, ResourceCheck};
+use rust_docker::ratelimit::RateLimiter;
+use rust_docker::utils::{CloseWrite, StreamHandle};
+use serde_json;
+
+/// A client answering requests with canned HTTP responses, so that the API
+/// traits can be tested without a docker daemon.
+pub struct MockClient {
+    responses: RefCell<Vec<String>>,
+    pub requests: RefCell<Vec<String>>,
+    pub stdin: StdinRecorder,
+    pub rate_limiter: Option<RateLimiter>,
+    pub api_version: Option<ApiVersion>,
+    pub strict: bool,
+    pub owner_label: Option<(String, String)>,
+    pub resource_check: ResourceCheck,
+}
+
+/// Records what is written to the hijacked connections of the client.
+#[derive(Clone, Default)]
+pub struct StdinRecorder {
+    pub written: Arc<Mutex<Vec<u8>>>,
+    pub closed: Arc<Mutex<bool>>,
+}
+
+impl Write for StdinRecorder {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.written.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl CloseWrite for StdinRecorder {
+    fn close_write(&mut self) -> io::Result<()> {
+        *self.closed.lock().unwrap() = true;
+        Ok(())
+    }
+}
+
+impl MockClient {
+    /// Creates a client which replies to the requests with the provided
+    /// responses in order.
+    pub fn new(responses: Vec<String>) -> MockClient {
+        MockClient {
+            responses: RefCell::new(responses),
+            requests: RefCell::new(Vec::new()),
+            stdin: StdinRecorder::default(),
+            rate_limiter: None,
+            api_version: None,
+            strict: false,
+            owner_label: None,
+            resource_check: ResourceCheck::Off,
+        }
+    }
+
+    /// Returns the request line of the nth request sent to the client.
+    pub fn request_line(&self, n: usize) -> String {
+        let requests = self.requests.borrow();
+        requests[n].lines().next().unwrap_or("").to_string()
+    }
+
+    /// Returns the JSON body of the nth request sent to the client.
+    pub fn request_body(&self, n: usize) -> serde_json::Value {
+        let requests = self.requests.borrow();
+        let body = requests[n].split_once("\r\n\r\n").map_or("", |x| x.1);
+        serde_json::from_str(body.trim()).unwrap_or(serde_json::Value::Null)
+    }
+}
+
+impl DockerApiClient for MockClient {
+    fn rate_limiter(&self) -> Option<&RateLimiter> {
+        self.rate_limiter.as_ref()
+    }
+
+    fn api_version(&self) -> Option<ApiVersion> {
+        self.api_version
+    }
+
+    fn is_strict(&self) -> bool {
+        self.strict
+    }
+
+    fn owner_label(&self) -> Option<(&str, &str)> {
+        self.owner_label
+            .as_ref()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+
+    fn resource_check(&self) -> ResourceCheck {
+        self.resource_check
+    }
+
+    fn request(&self, request: &str) -> Option<Vec<u8>> {
+        self.requests.borrow_mut().push(request.to_string());
+
+        let mut responses = self.responses.borrow_mut();
+        if responses.is_empty() {
+            return None;
+        }
+        Some(responses.remove(0).into_bytes())
+    }
+
+    fn stream_request(
+        &self,
+        request: &str,
+        _handle: &StreamHandle,
+    ) -> Option<Box<dyn Read + Send>> {
+        self.request(request)
+            .map(|resp| Box::new(Cursor::new(resp)) as Box<dyn Read + Send>)
+    }
+
+    /// The uploaded body is recorded after the request, without the chunked
+    /// encoding.
+    fn upload_request(
+        &self,
+        request: &str,
+        body: &mut dyn Read,
+        handle: &StreamHandle,
+    ) -> Option<Box<dyn Read + Send>> {
+        let mut uploaded = String::new();
+        body.read_to_string(&mut uploaded).ok()?;
+        self.stream_request(&format!("{}{}", request, uploaded), handle)
+    }
+
+    fn hijack_request(
+        &self,
+        request: &str,
+        handle: &StreamHandle,
+    ) -> Option<(Box<dyn Read + Send>, Box<dyn CloseWrite>)> {
+        let reader = self.stream_request(request, handle)?;
+        Some((reader, Box::new(self.stdin.clone())))
+    }
+}
+
+/// Formats a HTTP response with the provided status and the body sent in
+/// chunks, the way the daemon answers on the streaming endpoints.
+pub fn chunked_http_response(status: usize, chunks: &[&str]) -> String {
+    let mut resp = format!(
+        "HTTP/1.1 {} Status\r\nTransfer-Encoding: chunked\r\n\r\n",
+        status
+    );
+    for chunk in chunks {
+        resp.push_str(&format!("{:x}\r\n{}\r\n", chunk.len(), chunk));
+    }
+    resp.push_str("0\r\n\r\n");
+    resp
+}
+
+/// Formats a minimal HTTP response with the provided status and body.
+pub fn http_response(status: usize, body: &str) -> String {
+    format!(
+        "HTTP/1.1 {} Status\r\nContent-Length: {}\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )
+}
+
+/// Formats a frame of the multiplexed output of the logs and the execs,
+/// with the header giving its stream and its length. The payload must be
+/// shorter than 128 bytes for the frame to be a valid string.
+pub fn frame(stream: u8, payload: &str) -> String {
+    let mut frame = vec![stream, 0, 0, 0];
+    frame.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+    frame.extend_from_slice(payload.as_bytes());
+    String::from_utf8(frame).unwrap()
+}
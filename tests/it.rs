@@ -0,0 +1,73 @@
+//! Tests against a real docker daemon, run them with
+//! `cargo test --features test-support --test it`.
+extern crate rust_docker;
+
+use rust_docker::api::containers::{ContainerConfig, Containers};
+use rust_docker::api::exec::{Exec, ExecOptions};
+use rust_docker::api::images::Images;
+use rust_docker::api::networks::{NetworkConfig, Networks};
+use rust_docker::api::volumes::Volumes;
+use rust_docker::test_support::{
+    connect, unique_container, unique_network, unique_volume, NAME_PREFIX,
+};
+
+const IMAGE: &str = "busybox:latest";
+
+#[test]
+fn test_container_exec() {
+    let client = connect();
+    client.ensure_image(IMAGE, None).unwrap();
+
+    let config = ContainerConfig {
+        image: IMAGE.to_string(),
+        cmd: vec!["sleep".to_string(), "60".to_string()],
+        ..Default::default()
+    };
+    let container = unique_container(&client, config).unwrap();
+    client.start_container(container.id()).unwrap();
+
+    let output = client
+        .exec_output(
+            container.id(),
+            &["echo", "hello"],
+            &ExecOptions::default(),
+        )
+        .unwrap();
+    assert_eq!(output.stdout, b"hello\n");
+    assert_eq!(output.exit_code, 0);
+}
+
+#[test]
+fn test_container_removed_on_drop() {
+    let client = connect();
+    client.ensure_image(IMAGE, None).unwrap();
+
+    let config = ContainerConfig {
+        image: IMAGE.to_string(),
+        ..Default::default()
+    };
+    let id = {
+        let container = unique_container(&client, config).unwrap();
+        container.id().clone()
+    };
+
+    assert!(client.inspect_container(&id).is_err());
+}
+
+#[test]
+fn test_network_and_volume() {
+    let client = connect();
+
+    let network = unique_network(&client, NetworkConfig::default()).unwrap();
+    assert!(client
+        .inspect_network(network.id())
+        .unwrap()
+        .name
+        .starts_with(NAME_PREFIX));
+
+    let volume = unique_volume(&client).unwrap();
+    assert_eq!(
+        client.inspect_volume(volume.name()).unwrap().name,
+        volume.name()
+    );
+}
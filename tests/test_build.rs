@@ -0,0 +1,223 @@
+#![cfg(all(feature = "build", feature = "images"))]
+extern crate rust_docker;
+extern crate serde_json;
+
+mod common;
+
+use std::io::Cursor;
+use std::time::{Duration, Instant};
+
+use common::{chunked_http_response, http_response, MockClient};
+
+use rust_docker::api::build::{
+    Build, BuildLog, BuildMessage, BuildOptions, BuildStep,
+};
+use rust_docker::api::images::TransferProgress;
+use rust_docker::errors::DockerApiError;
+
+impl Build for MockClient {}
+
+#[test]
+fn test_build_options_query() {
+    assert_eq!(BuildOptions::new().to_query(), "");
+
+    let options = BuildOptions::new()
+        .tag("app:1.0")
+        .tag("app:latest")
+        .dockerfile("docker/Dockerfile")
+        .build_arg("VERSION", "1.0")
+        .build_arg("BASE", "alpine")
+        .label("team", "infra")
+        .target("release")
+        .cache_from("app:cache")
+        .network("host")
+        .platform("linux/arm64")
+        .squash(true)
+        .no_cache(true);
+
+    assert_eq!(
+        options.to_query(),
+        "?t=app%3A1.0&t=app%3Alatest\
+         &dockerfile=docker%2FDockerfile&target=release&networkmode=host\
+         &platform=linux%2Farm64\
+         &buildargs=%7B%22BASE%22%3A%22alpine%22%2C%22VERSION%22%3A%221.0%22%7D\
+         &labels=%7B%22team%22%3A%22infra%22%7D\
+         &cachefrom=%5B%22app%3Acache%22%5D&squash=true&nocache=true"
+    );
+}
+
+#[test]
+fn test_build_image() {
+    let client = MockClient::new(vec![chunked_http_response(
+        200,
+        &[
+            "{\"stream\":\"Step 1/2 : FROM alpine\\n\"}\r\n",
+            "{\"stream\":\"Step 2/2 : RUN true\\n\"}\r\n",
+            "{\"aux\":{\"ID\":\"sha256:4ae1\"}}\r\n",
+        ],
+    )]);
+    let mut context = Cursor::new(b"context tar".to_vec());
+
+    let messages = client
+        .build_image(&mut context, &BuildOptions::new().tag("app"))
+        .unwrap();
+
+    assert_eq!(messages.len(), 3);
+    assert_eq!(
+        messages[0].stream,
+        Some("Step 1/2 : FROM alpine\n".to_string())
+    );
+    assert_eq!(messages[2].image_id(), Some("sha256:4ae1"));
+
+    let request = client.requests.borrow()[0].clone();
+    assert!(request.starts_with("POST /build?t=app HTTP/1.1\r\n"));
+    assert!(request.contains("Content-Type: application/x-tar\r\n"));
+    assert!(request.contains("Transfer-Encoding: chunked\r\n"));
+    assert!(!request.contains("Content-Length"));
+    assert!(request.ends_with("\r\n\r\ncontext tar"));
+}
+
+#[test]
+fn test_build_image_failures() {
+    let client = MockClient::new(vec![
+        chunked_http_response(
+            200,
+            &[
+                "{\"stream\":\"Step 1/2 : FROM alpine\\n\"}\r\n",
+                "{\"error\":\"The command '/bin/sh -c false' returned a non-zero code: 1\"}\r\n",
+            ],
+        ),
+        http_response(400, "{\"message\":\"dockerfile parse error\"}"),
+    ]);
+
+    match client.build_image(&mut Cursor::new(Vec::new()), &BuildOptions::new())
+    {
+        Err(DockerApiError::ImageError(msg)) => {
+            assert!(msg.contains("non-zero code"))
+        }
+        other => panic!("{:?}", other),
+    }
+    match client.build_image(&mut Cursor::new(Vec::new()), &BuildOptions::new())
+    {
+        Err(DockerApiError::InvalidApiResponseError(400, body)) => {
+            assert!(body.contains("parse error"))
+        }
+        other => panic!("{:?}", other),
+    }
+}
+
+fn stream(text: &str) -> BuildMessage {
+    BuildMessage {
+        stream: Some(text.to_string()),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_build_log_steps() {
+    let start = Instant::now();
+    let at = |secs| start + Duration::from_secs(secs);
+    let mut log = BuildLog::new();
+
+    log.record_at(&stream("Step 1/3 : FROM alpine\n"), at(0));
+    log.record_at(&stream(" ---> a24bb4013296\n"), at(0));
+    log.record_at(
+        &stream("Step 2/3 : COPY . /src\n ---> Using cache\n"),
+        at(1),
+    );
+    log.record_at(&stream(" ---> 3c8f20d1e6a4\n"), at(1));
+    log.record_at(
+        &stream("Step 3/3 : RUN make\n ---> Running in 1a2b3c\n"),
+        at(2),
+    );
+    log.record_at(&stream("cc -o app main"), at(3));
+    log.record_at(&stream(".c\nok\n"), at(5));
+    log.record_at(&stream("Removing intermediate container 1a2b3c\n"), at(9));
+    log.record_at(&stream(" ---> 9f3c4d5e6f70\n"), at(9));
+    assert_eq!(log.steps().len(), 3);
+    log.record_at(
+        &BuildMessage {
+            aux: Some(serde_json::json!({ "ID": "sha256:9f3c4d5e6f70" })),
+            ..Default::default()
+        },
+        at(10),
+    );
+    log.record_at(&stream("Successfully built 9f3c4d5e6f70\n"), at(10));
+    log.record_at(&stream("Successfully tagged app:1.0\n"), at(11));
+
+    let summary = log.finish();
+    assert_eq!(
+        summary.steps[2],
+        BuildStep {
+            number: 3,
+            total: 3,
+            instruction: "RUN make".to_string(),
+            cache_hit: false,
+            duration: Duration::from_secs(8),
+            output: "cc -o app main.c\nok\n".to_string(),
+            image_id: Some("9f3c4d5e6f70".to_string()),
+        }
+    );
+    assert_eq!(summary.steps[0].duration, Duration::from_secs(1));
+    assert!(summary.steps[1].cache_hit);
+    assert_eq!(summary.steps[1].image_id, Some("3c8f20d1e6a4".to_string()));
+    assert_eq!(summary.cached_steps(), 1);
+    assert_eq!(summary.image_id, Some("sha256:9f3c4d5e6f70".to_string()));
+    assert_eq!(summary.tags, vec!["app:1.0"]);
+    assert_eq!(summary.duration, Duration::from_secs(11));
+}
+
+#[test]
+fn test_build_image_steps() {
+    let client = MockClient::new(vec![chunked_http_response(
+        200,
+        &[
+            "{\"stream\":\"Step 1/2 : FROM alpine\\n\"}\r\n",
+            "{\"stream\":\"Step 2/2 : RUN echo hi\\n\"}\r\n",
+            "{\"stream\":\"hi\\n\"}\r\n",
+            "{\"aux\":{\"ID\":\"sha256:4ae1\"}}\r\n",
+        ],
+    )]);
+
+    let summary = client
+        .build_image_steps(&mut Cursor::new(Vec::new()), &BuildOptions::new())
+        .unwrap();
+
+    assert_eq!(summary.steps.len(), 2);
+    assert_eq!(summary.steps[1].instruction, "RUN echo hi");
+    assert_eq!(summary.steps[1].output, "hi\n");
+    assert_eq!(summary.image_id, Some("sha256:4ae1".to_string()));
+}
+
+#[test]
+fn test_build_image_upload_progress() {
+    let client = MockClient::new(vec![chunked_http_response(
+        200,
+        &["{\"aux\":{\"ID\":\"sha256:4ae1\"}}\r\n"],
+    )]);
+    let context = vec![7u8; 100_000];
+
+    let mut reports: Vec<TransferProgress> = Vec::new();
+    let messages = client
+        .build_image_with_upload_progress(
+            &mut Cursor::new(context),
+            Some(100_000),
+            &BuildOptions::new(),
+            &mut |progress| reports.push(*progress),
+        )
+        .unwrap();
+
+    assert_eq!(messages[0].image_id(), Some("sha256:4ae1"));
+    assert!(reports.len() > 1);
+    assert!(reports
+        .windows(2)
+        .all(|pair| pair[0].transferred < pair[1].transferred));
+    assert_eq!(
+        reports.last(),
+        Some(&TransferProgress {
+            transferred: 100_000,
+            total: Some(100_000),
+        })
+    );
+    assert!(client.requests.borrow()[0].len() > 100_000);
+}
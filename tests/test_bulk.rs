@@ -0,0 +1,164 @@
+#![cfg(all(feature = "containers", feature = "images"))]
+extern crate rust_docker;
+
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use rust_docker::api::containers::Containers;
+use rust_docker::api::images::Images;
+use rust_docker::api::DockerApiClient;
+use rust_docker::bulk;
+use rust_docker::bulk::ConcurrencyPolicy;
+
+/// A client which can be shared by threads, answering every request after
+/// a delay and keeping track of the requests in flight.
+#[derive(Default)]
+struct SlowClient {
+    requests: Mutex<Vec<String>>,
+    in_flight: Mutex<(usize, usize)>,
+}
+
+impl DockerApiClient for SlowClient {
+    fn request(&self, request: &str) -> Option<Vec<u8>> {
+        let line = request.lines().next().unwrap_or("").to_string();
+        {
+            let mut in_flight = self.in_flight.lock().unwrap();
+            in_flight.0 += 1;
+            in_flight.1 = in_flight.1.max(in_flight.0);
+        }
+        thread::sleep(Duration::from_millis(20));
+        self.in_flight.lock().unwrap().0 -= 1;
+
+        let resp = if line.contains("/containers/bad/") {
+            "HTTP/1.1 404 Not Found\r\nContent-Length: 2\r\n\r\n{}".to_string()
+        } else if line.starts_with("POST /images/create") {
+            "HTTP/1.1 200 OK\r\nContent-Length: 21\r\n\r\n{\"status\":\"Pulled\"}\r\n"
+                .to_string()
+        } else if line.starts_with("GET /containers/") {
+            let health = if line.contains("/sick/") {
+                "unhealthy"
+            } else {
+                "healthy"
+            };
+            let body = format!(
+                r#"{{"State": {{"Status": "running", "Running": true,
+                    "Health": {{"Status": "{}"}}}}}}"#,
+                health
+            );
+            format!(
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{}",
+                body.len(),
+                body
+            )
+        } else {
+            "HTTP/1.1 204 No Content\r\n\r\n".to_string()
+        };
+        self.requests.lock().unwrap().push(line);
+        Some(resp.into_bytes())
+    }
+}
+
+impl Containers for SlowClient {}
+impl Images for SlowClient {}
+
+#[test]
+fn test_stop_all_runs_concurrently() {
+    let client = SlowClient::default();
+    let ids = ["a", "b", "bad", "c", "d", "e"];
+
+    let results = bulk::stop_all(&client, &ids, None, 3);
+
+    assert_eq!(results.len(), 6);
+    for (result, id) in results.iter().zip(ids.iter()) {
+        assert_eq!(result.item, *id);
+        assert_eq!(result.result.is_ok(), *id != "bad");
+    }
+    assert_eq!(client.requests.lock().unwrap().len(), 6);
+    let max_in_flight = client.in_flight.lock().unwrap().1;
+    assert!(max_in_flight > 1 && max_in_flight <= 3);
+}
+
+#[test]
+fn test_pull_many() {
+    let client = SlowClient::default();
+
+    let results = bulk::pull_many(&client, &["debian", "redis:4"], None, 8);
+
+    assert_eq!(results[0].item, "debian");
+    assert_eq!(
+        results[1].result.as_ref().unwrap()[0].status,
+        Some("Pulled".to_string())
+    );
+    let mut requests = client.requests.lock().unwrap().clone();
+    requests.sort();
+    assert_eq!(
+        requests,
+        vec![
+            "POST /images/create?fromImage=debian&tag=latest HTTP/1.1",
+            "POST /images/create?fromImage=redis&tag=4 HTTP/1.1",
+        ]
+    );
+}
+
+#[test]
+fn test_total_limits_a_single_host() {
+    let client = SlowClient::default();
+    let ids = ["a", "b", "c", "d"];
+
+    let policy = ConcurrencyPolicy::per_host(4).total(2);
+    let results = bulk::start_all(&client, &ids, policy);
+
+    assert!(results.iter().all(|result| result.result.is_ok()));
+    assert_eq!(client.in_flight.lock().unwrap().1, 2);
+}
+
+#[test]
+fn test_rolling_restart() {
+    let client = SlowClient::default();
+    let ids = ["a", "b", "c", "d", "e"];
+
+    let report =
+        bulk::rolling_restart(&client, &ids, 2, Duration::from_secs(5));
+
+    assert!(report.is_complete());
+    assert_eq!(report.restarted, vec!["a", "b", "c", "d", "e"]);
+    let requests = client.requests.lock().unwrap();
+    assert_eq!(requests.len(), 10);
+    // The second batch is not restarted before the first one is healthy.
+    let restarted_c = requests
+        .iter()
+        .position(|line| line == "POST /containers/c/restart HTTP/1.1")
+        .unwrap();
+    let checked_b = requests
+        .iter()
+        .position(|line| line == "GET /containers/b/json HTTP/1.1")
+        .unwrap();
+    assert!(checked_b < restarted_c);
+}
+
+#[test]
+fn test_rolling_restart_stops_at_a_failing_batch() {
+    let client = SlowClient::default();
+    let ids = ["a", "sick", "bad", "d", "e"];
+
+    let report =
+        bulk::rolling_restart(&client, &ids, 3, Duration::from_secs(5));
+
+    assert!(!report.is_complete());
+    assert_eq!(report.failed_batch, Some(0));
+    assert_eq!(report.restarted, vec!["a"]);
+    let failed: Vec<&str> = report
+        .failures
+        .iter()
+        .map(|failure| failure.item.as_str())
+        .collect();
+    assert_eq!(failed, vec!["sick", "bad"]);
+    assert_eq!(report.not_restarted, vec!["d", "e"]);
+    assert!(!client
+        .requests
+        .lock()
+        .unwrap()
+        .iter()
+        .any(|line| line.contains("/containers/d/")));
+}
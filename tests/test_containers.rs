@@ -0,0 +1,1420 @@
+#![cfg(all(feature = "containers", feature = "networks"))]
+extern crate rust_docker;
+extern crate serde_json;
+
+mod common;
+
+use std::collections::HashMap;
+use std::net::TcpListener;
+use std::panic::{self, AssertUnwindSafe};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use common::{chunked_http_response, http_response, MockClient};
+
+use rust_docker::api::containers::{
+    expand_index, normalize_container_name, parse_env, ContainerConfig,
+    ContainerDetails, ContainerNetworkSettings, ContainerState,
+    ContainerTemplate, Containers, EnsureAction, HealthConfig, HostConfig,
+    MountSpec, PortBinding, Propagation, Resource, ResourceCheck,
+    ResourceShortfall, RestartPolicy, Signal, StateChange, UsernsMode,
+};
+use rust_docker::api::networks::EndpointSettings;
+use rust_docker::api::platform::Platform;
+use rust_docker::api::version::ApiVersion;
+use rust_docker::api::DockerApiClient;
+use rust_docker::errors::DockerApiError;
+use rust_docker::utils::parse_rfc3339;
+
+impl Containers for MockClient {}
+
+#[test]
+fn test_mount_spec_serialization() {
+    let mounts = vec![
+        MountSpec::bind("/srv/data", "/data", Some(Propagation::RShared))
+            .read_only(),
+        MountSpec::volume("cache", "/cache", true),
+        MountSpec::tmpfs("/run", Some(65536), Some(0o1777)),
+    ];
+
+    assert_eq!(
+        serde_json::to_value(&mounts).unwrap(),
+        serde_json::json!([
+            {
+                "Type": "bind",
+                "Source": "/srv/data",
+                "Target": "/data",
+                "ReadOnly": true,
+                "BindOptions": {"Propagation": "rshared"}
+            },
+            {
+                "Type": "volume",
+                "Source": "cache",
+                "Target": "/cache",
+                "ReadOnly": false,
+                "VolumeOptions": {"NoCopy": true}
+            },
+            {
+                "Type": "tmpfs",
+                "Target": "/run",
+                "TmpfsOptions": {"SizeBytes": 65536, "Mode": 1023}
+            }
+        ])
+    );
+}
+
+#[test]
+fn test_container_config_env() {
+    let mut vars = HashMap::new();
+    vars.insert("B".to_string(), "2".to_string());
+    vars.insert("A".to_string(), "x=1 y".to_string());
+
+    let config = ContainerConfig::default()
+        .env("A", "0")
+        .env_map(vars)
+        .env("PATH", "/bin");
+
+    assert_eq!(config.env, vec!["A=x=1 y", "B=2", "PATH=/bin"]);
+    assert_eq!(config.env_to_map()["A"], "x=1 y");
+
+    let env = parse_env(&["EMPTY".to_string(), "C=".to_string()]);
+    assert_eq!(env["EMPTY"], "");
+    assert_eq!(env["C"], "");
+}
+
+#[test]
+fn test_durations() {
+    let client = MockClient::new(vec![http_response(204, "")]);
+    client
+        .stop_container("f808ca", Some(Duration::from_millis(1500)))
+        .unwrap();
+    assert_eq!(
+        client.request_line(0),
+        "POST /containers/f808ca/stop?t=2 HTTP/1.1"
+    );
+
+    let health = HealthConfig::shell("curl -f localhost")
+        .interval(Duration::from_secs(30))
+        .timeout(Duration::from_millis(500))
+        .retries(3);
+    assert_eq!(
+        serde_json::to_value(&health).unwrap(),
+        serde_json::json!({
+            "Test": ["CMD-SHELL", "curl -f localhost"],
+            "Interval": 30_000_000_000i64,
+            "Timeout": 500_000_000,
+            "StartPeriod": 0,
+            "Retries": 3
+        })
+    );
+}
+
+#[test]
+fn test_state_changes_are_posted() {
+    let client = MockClient::new(vec![http_response(204, ""); 5]);
+    client.start_container("f808ca").unwrap();
+    client.stop_container("f808ca", None).unwrap();
+    client
+        .restart_container("f808ca", Some(Duration::from_secs(3)))
+        .unwrap();
+    client.pause_container("f808ca").unwrap();
+    client.unpause_container("f808ca").unwrap();
+
+    let lines: Vec<String> = (0..5).map(|n| client.request_line(n)).collect();
+    assert_eq!(
+        lines,
+        vec![
+            "POST /containers/f808ca/start HTTP/1.1",
+            "POST /containers/f808ca/stop HTTP/1.1",
+            "POST /containers/f808ca/restart?t=3 HTTP/1.1",
+            "POST /containers/f808ca/pause HTTP/1.1",
+            "POST /containers/f808ca/unpause HTTP/1.1",
+        ]
+    );
+}
+
+#[test]
+fn test_stop_signal_and_timeout() {
+    let config = ContainerConfig {
+        image: "nginx".to_string(),
+        ..Default::default()
+    }
+    .stop_signal(Signal::Quit)
+    .stop_timeout(Duration::from_millis(2500));
+
+    let value = serde_json::to_value(&config).unwrap();
+    assert_eq!(value["StopSignal"], "SIGQUIT");
+    assert_eq!(value["StopTimeout"], 3);
+
+    let signals: Vec<Signal> =
+        serde_json::from_str(r#"["SIGTERM", "kill", "SIGRTMIN+3"]"#).unwrap();
+    assert_eq!(
+        signals,
+        vec![
+            Signal::Term,
+            Signal::Kill,
+            Signal::Raw("SIGRTMIN+3".to_string())
+        ]
+    );
+    assert!(serde_json::to_value(ContainerConfig::default())
+        .unwrap()
+        .get("StopSignal")
+        .is_none());
+}
+
+#[test]
+fn test_kill_container_signal() {
+    let client = MockClient::new(vec![
+        http_response(204, ""),
+        http_response(204, ""),
+        http_response(204, ""),
+    ]);
+
+    client.kill_container("web", None).unwrap();
+    client.kill_container("web", Some(Signal::Hup)).unwrap();
+    let signal: Signal = "sigrtmin+3".parse().unwrap();
+    client.kill_container("web", Some(signal)).unwrap();
+
+    assert_eq!(client.request_line(0), "POST /containers/web/kill HTTP/1.1");
+    assert_eq!(
+        client.request_line(1),
+        "POST /containers/web/kill?signal=SIGHUP HTTP/1.1"
+    );
+    assert_eq!(
+        client.request_line(2),
+        "POST /containers/web/kill?signal=SIGRTMIN%2B3 HTTP/1.1"
+    );
+
+    assert_eq!("usr1".parse::<Signal>().unwrap(), Signal::Usr1);
+    assert_eq!("9".parse::<Signal>().unwrap(), Signal::number(9));
+    assert_eq!(
+        "stop".parse::<Signal>().unwrap(),
+        Signal::Raw("SIGSTOP".to_string())
+    );
+    assert!("SIGTREM".parse::<Signal>().is_err());
+    assert!("0".parse::<Signal>().is_err());
+    assert!("SIGRTMIN+x".parse::<Signal>().is_err());
+}
+
+#[test]
+fn test_user_mapping() {
+    let config = ContainerConfig {
+        image: "nginx".to_string(),
+        host_config: Some(
+            HostConfig::default()
+                .group_add("docker")
+                .group_add("1001")
+                .userns_mode(UsernsMode::Host),
+        ),
+        ..Default::default()
+    }
+    .user_id(1000, 1000);
+
+    let value = serde_json::to_value(&config).unwrap();
+    assert_eq!(value["User"], "1000:1000");
+    assert_eq!(
+        value["HostConfig"]["GroupAdd"],
+        serde_json::json!(["docker", "1001"])
+    );
+    assert_eq!(value["HostConfig"]["UsernsMode"], "host");
+
+    let config = ContainerConfig::default().user("nginx", Some("www-data"));
+    assert_eq!(config.user, "nginx:www-data");
+
+    let host: HostConfig =
+        serde_json::from_str(r#"{"GroupAdd": null, "UsernsMode": ""}"#)
+            .unwrap();
+    assert!(host.group_add.is_empty());
+    assert_eq!(host.userns_mode, Some(UsernsMode::Private));
+}
+
+#[test]
+fn test_process_limits() {
+    let host = HostConfig::default()
+        .init(true)
+        .oom_kill_disable(false)
+        .oom_score_adj(-2000)
+        .pids_limit(Some(256));
+
+    let value = serde_json::to_value(&host).unwrap();
+    assert_eq!(value["Init"], true);
+    assert_eq!(value["OomKillDisable"], false);
+    assert_eq!(value["OomScoreAdj"], -1000);
+    assert_eq!(value["PidsLimit"], 256);
+
+    let unlimited = HostConfig::default().pids_limit(None);
+    assert_eq!(serde_json::to_value(&unlimited).unwrap()["PidsLimit"], -1);
+    assert!(serde_json::to_value(HostConfig::default())
+        .unwrap()
+        .get("Init")
+        .is_none());
+}
+
+#[test]
+fn test_cpu_options() {
+    let host = HostConfig::default()
+        .cpus(1.5)
+        .cpuset_cpus("0-3")
+        .cpuset_mems("0")
+        .cpu_shares(512);
+
+    let value = serde_json::to_value(&host).unwrap();
+    assert_eq!(value["NanoCpus"], 1_500_000_000i64);
+    assert_eq!(value["CpusetCpus"], "0-3");
+    assert_eq!(value["CpusetMems"], "0");
+    assert_eq!(value["CpuShares"], 512);
+    assert!(value.get("CpuQuota").is_none());
+
+    let host = HostConfig::default()
+        .cpu_quota(Duration::from_millis(100), Duration::from_millis(50));
+    assert_eq!(host.cpu_period, Some(100_000));
+    assert_eq!(host.cpu_quota, Some(50_000));
+    assert_eq!(HostConfig::default().cpus(0.1).nano_cpus, Some(100_000_000));
+}
+
+#[test]
+fn test_blkio_options() {
+    let host = HostConfig::default()
+        .blkio_weight(300)
+        .blkio_weight_device("/dev/sda", 200)
+        .device_read_bps("/dev/sda", 1024)
+        .device_read_bps("/dev/sda", 2048)
+        .device_write_iops("/dev/sdb", 100);
+
+    let value = serde_json::to_value(&host).unwrap();
+    assert_eq!(value["BlkioWeight"], 300);
+    assert_eq!(
+        value["BlkioWeightDevice"],
+        serde_json::json!([{"Path": "/dev/sda", "Weight": 200}])
+    );
+    assert_eq!(
+        value["BlkioDeviceReadBps"],
+        serde_json::json!([{"Path": "/dev/sda", "Rate": 2048}])
+    );
+    assert_eq!(
+        value["BlkioDeviceWriteIOps"],
+        serde_json::json!([{"Path": "/dev/sdb", "Rate": 100}])
+    );
+    assert!(value.get("BlkioDeviceWriteBps").is_none());
+
+    let host: HostConfig = serde_json::from_str(
+        r#"{"BlkioDeviceReadIOps": [{"Path": "/dev/sda", "Rate": 10}],
+            "BlkioDeviceWriteBps": null}"#,
+    )
+    .unwrap();
+    assert_eq!(host.blkio_device_read_iops[0].rate, 10);
+    assert!(host.blkio_device_write_bps.is_empty());
+}
+
+#[test]
+fn test_dns_options() {
+    let host = HostConfig::default()
+        .dns("10.0.0.53")
+        .dns("8.8.8.8")
+        .dns_search("corp.example.com")
+        .dns_option("ndots:2")
+        .add_host("db", "10.0.0.5")
+        .add_host("host.docker.internal", "host-gateway")
+        .add_host("v6", "::1");
+
+    let value = serde_json::to_value(&host).unwrap();
+    assert_eq!(value["Dns"], serde_json::json!(["10.0.0.53", "8.8.8.8"]));
+    assert_eq!(value["DnsSearch"], serde_json::json!(["corp.example.com"]));
+    assert_eq!(value["DnsOptions"], serde_json::json!(["ndots:2"]));
+    assert_eq!(
+        value["ExtraHosts"],
+        serde_json::json!([
+            "db:10.0.0.5",
+            "host.docker.internal:host-gateway",
+            "v6:::1"
+        ])
+    );
+}
+
+#[test]
+fn test_create_container_on_network() {
+    let config = ContainerConfig {
+        image: "redis".to_string(),
+        ..Default::default()
+    }
+    .network("backend", EndpointSettings::default().alias("cache"));
+    let client = MockClient::new(vec![http_response(
+        201,
+        r#"{"Id": "f808ca", "Warnings": []}"#,
+    )]);
+
+    client.create_container("cache", config).unwrap();
+
+    let body = client.request_body(0);
+    assert_eq!(body["HostConfig"]["NetworkMode"], "backend");
+    assert_eq!(
+        body["NetworkingConfig"],
+        serde_json::json!({
+            "EndpointsConfig": {"backend": {"Aliases": ["cache"]}}
+        })
+    );
+
+    let config = ContainerConfig {
+        host_config: Some(HostConfig {
+            network_mode: "host".to_string(),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+    .network("backend", EndpointSettings::default());
+    assert_eq!(config.host_config.unwrap().network_mode, "host");
+}
+
+#[test]
+fn test_create_container_for_platform() {
+    let client = MockClient::new(vec![http_response(
+        201,
+        r#"{"Id": "f808ca", "Warnings": []}"#,
+    )]);
+    client
+        .create_container_for_platform(
+            "cache",
+            ContainerConfig::default(),
+            Some(&Platform::new("linux", "arm64")),
+        )
+        .unwrap();
+    assert_eq!(
+        client.request_line(0),
+        "POST /containers/create?name=cache&platform=linux%2Farm64 HTTP/1.1"
+    );
+}
+
+#[test]
+fn test_create_container_checks_api_version() {
+    let config = || ContainerConfig {
+        image: "cuda".to_string(),
+        host_config: Some(HostConfig::default().gpus(None).init(true)),
+        ..Default::default()
+    };
+    let mut client = MockClient::new(vec![http_response(
+        201,
+        r#"{"Id": "f808ca", "Warnings": []}"#,
+    )]);
+    client.api_version = Some(ApiVersion::new(1, 39));
+
+    match client.create_container("gpu", config()) {
+        Err(DockerApiError::UnsupportedApiVersion(field, required, actual)) => {
+            assert_eq!(field, "HostConfig.DeviceRequests");
+            assert_eq!(required, ApiVersion::new(1, 40));
+            assert_eq!(actual, ApiVersion::new(1, 39));
+        }
+        other => panic!("{:?}", other),
+    }
+    assert!(client.requests.borrow().is_empty());
+
+    let mut stripped = config();
+    assert_eq!(
+        stripped.strip_unsupported(ApiVersion::new(1, 24)),
+        vec!["HostConfig.Init", "HostConfig.DeviceRequests"]
+    );
+    assert!(stripped
+        .unsupported_fields(ApiVersion::new(1, 24))
+        .is_empty());
+    client.create_container("gpu", stripped).unwrap();
+    assert!(client.request_body(0)["HostConfig"]
+        .get("DeviceRequests")
+        .is_none());
+
+    client.api_version = Some(ApiVersion::new(1, 40));
+    assert!(config().check_api_version(ApiVersion::new(1, 40)).is_ok());
+    assert!(client
+        .create_container_for_platform(
+            "gpu",
+            config(),
+            Some(&Platform::new("linux", "arm64"))
+        )
+        .is_err());
+}
+
+#[test]
+fn test_list_containers_field_names() {
+    let body = r#"[{
+        "Id": "8dfafdbc3a40",
+        "Names": ["/boring_feynman"],
+        "Image": "ubuntu:latest",
+        "ImageID": "d74508fb6632",
+        "Command": "echo 1",
+        "State": "Exited",
+        "Status": "Exit 0",
+        "Ports": [{"PrivatePort": 2222, "PublicPort": 3333, "Type": "tcp"}],
+        "Labels": {},
+        "SizeRootFs": 0,
+        "Created": 1367854155,
+        "HostConfig": {"NetworkMode": "default"},
+        "NetworkSettings": {
+            "Networks": {
+                "bridge": {
+                    "NetworkID": "7ea29fc1412292a2d7bba362f9253545",
+                    "Gateway": "172.17.0.1",
+                    "IPAddress": "172.17.0.2",
+                    "IPPrefixLen": 16
+                }
+            }
+        },
+        "Mounts": []
+    }]"#;
+    let client = MockClient::new(vec![http_response(200, body)]);
+
+    let containers = client.list_all_containers(None).unwrap();
+
+    assert_eq!(containers[0].id, "8dfafdbc3a40");
+    assert_eq!(containers[0].image_id, "d74508fb6632");
+    assert_eq!(containers[0].ports[0].typ, "tcp");
+    assert_eq!(containers[0].host_config.network_mode, "default");
+    assert_eq!(
+        containers[0].created,
+        Some(UNIX_EPOCH + Duration::from_secs(1367854155))
+    );
+    assert_eq!(
+        containers[0].network_settings.networks["bridge"].ip_address,
+        "172.17.0.2"
+    );
+}
+
+#[test]
+fn test_create_container_warnings_and_headers() {
+    let body = r#"{"Id":"e90e34656806","Warnings":["Memory limit ignored"]}"#;
+    let client = MockClient::new(vec![
+        format!(
+            "HTTP/1.1 201 Created\r\nApi-Version: 1.37\r\nContent-Length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        ),
+        http_response(201, r#"{"Id":"e90e34656806","Warnings":null}"#),
+    ]);
+
+    let resp = client
+        .create_container_minimal("web", "nginx", Vec::new())
+        .unwrap();
+    assert_eq!(resp.id, "e90e34656806");
+    assert_eq!(resp.warnings, vec!["Memory limit ignored"]);
+    assert_eq!(resp.headers["api-version"], "1.37");
+
+    let resp = client
+        .create_container_minimal("web", "nginx", Vec::new())
+        .unwrap();
+    assert!(resp.warnings.is_empty());
+}
+
+fn exited_container(id: &str, created: i64) -> String {
+    format!(
+        r#"{{"Id":"{}","Names":["/{}"],"Image":"busybox","ImageID":"d74508fb6632",
+        "Command":"true","State":"exited","Status":"Exited (0)","Ports":[],
+        "Labels":{{}},"SizeRootFs":0,"Created":{},"HostConfig":{{}},
+        "NetworkSettings":{{"Networks":{{}}}},"Mounts":[]}}"#,
+        id, id, created
+    )
+}
+
+fn inspect_response(finished_at: &str) -> String {
+    let details = ContainerDetails {
+        state: ContainerState {
+            status: "exited".to_string(),
+            finished_at: parse_rfc3339(finished_at),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    http_response(200, &serde_json::to_string(&details).unwrap())
+}
+
+#[test]
+fn test_remove_exited_containers_older_than() {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64;
+    let list = format!(
+        "[{},{},{}]",
+        exited_container("aaa", now - 7200),
+        exited_container("bbb", now - 7200),
+        exited_container("ccc", now - 60)
+    );
+    let client = MockClient::new(vec![
+        http_response(200, &list),
+        inspect_response("2018-05-01T10:00:01.123456789Z"),
+        // Created long ago but exited recently.
+        inspect_response("2999-01-01T00:00:00Z"),
+        http_response(204, ""),
+    ]);
+
+    let removed = client
+        .remove_exited_containers_older_than(Duration::from_secs(3600))
+        .unwrap();
+
+    assert_eq!(removed.len(), 1);
+    assert_eq!(removed[0].id, "aaa");
+    assert_eq!(
+        client.request_line(0),
+        "GET /containers/json?all=true&filters=%7B%22status%22%3A%5B%22exited%22%5D%7D HTTP/1.1"
+    );
+    assert_eq!(client.request_line(1), "GET /containers/aaa/json HTTP/1.1");
+    assert_eq!(client.request_line(2), "GET /containers/bbb/json HTTP/1.1");
+    assert_eq!(
+        client.request_line(3),
+        "DELETE /containers/aaa?force=false&v=true HTTP/1.1"
+    );
+    assert_eq!(client.requests.borrow().len(), 4);
+}
+
+fn running_response(bindings: Option<Vec<PortBinding>>) -> String {
+    let mut ports = HashMap::new();
+    ports.insert("5432/tcp".to_string(), bindings);
+    let details = ContainerDetails {
+        state: ContainerState {
+            status: "running".to_string(),
+            running: true,
+            ..Default::default()
+        },
+        network_settings: ContainerNetworkSettings {
+            ports,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    http_response(200, &serde_json::to_string(&details).unwrap())
+}
+
+#[test]
+fn test_wait_for_port() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let client = MockClient::new(vec![
+        // Not published yet.
+        running_response(None),
+        running_response(Some(vec![PortBinding {
+            host_ip: "0.0.0.0".to_string(),
+            host_port: port.to_string(),
+        }])),
+    ]);
+
+    let addr = client
+        .wait_for_port("f808ca", 5432, Duration::from_secs(5))
+        .unwrap();
+
+    assert_eq!(addr, listener.local_addr().unwrap());
+    assert_eq!(
+        client.request_line(1),
+        "GET /containers/f808ca/json HTTP/1.1"
+    );
+}
+
+#[test]
+fn test_wait_for_port_of_stopped_container() {
+    let client =
+        MockClient::new(vec![inspect_response("2018-05-01T10:00:01Z")]);
+
+    assert!(client
+        .wait_for_port("f808ca", 5432, Duration::from_secs(5))
+        .is_err());
+}
+
+#[test]
+fn test_mapped_port() {
+    let binding = |ip: &str| PortBinding {
+        host_ip: ip.to_string(),
+        host_port: "32768".to_string(),
+    };
+    let client = MockClient::new(vec![
+        running_response(Some(vec![binding("::"), binding("0.0.0.0")])),
+        running_response(Some(vec![binding("::")])),
+        running_response(Some(vec![binding("10.0.0.2")])),
+        running_response(None),
+    ]);
+
+    let port =
+        |client: &MockClient| client.mapped_port("f808ca", 5432).unwrap();
+    assert_eq!(port(&client), Some("127.0.0.1:32768".parse().unwrap()));
+    assert_eq!(port(&client), Some("[::1]:32768".parse().unwrap()));
+    assert_eq!(port(&client), Some("10.0.0.2:32768".parse().unwrap()));
+    assert_eq!(port(&client), None);
+}
+
+#[test]
+fn test_lenient_and_strict_deserialization() {
+    let body = r#"{
+        "Id": "4fa6e0f0c678",
+        "Name": "/web",
+        "ExecIDs": ["e90e34656806"],
+        "State": {"Status": "running", "Running": true,
+                  "Health": {"Status": "healthy", "Log": "unexpected"},
+                  "StartedAt": "2018-05-01T10:00:01Z"},
+        "Config": {"Image": "nginx", "Cmd": null, "Env": null,
+                   "Entrypoint": ["/docker-entrypoint.sh"],
+                   "Healthcheck": {"Test": "CMD true"}},
+        "HostConfig": {"Mounts": [{"Type": "bind", "Source": "/srv",
+                                   "Target": "/srv",
+                                   "BindOptions": {"Propagation": "rprivate-ish"}}]}
+    }"#;
+    let mut client = MockClient::new(vec![
+        http_response(200, body),
+        http_response(200, body),
+        http_response(
+            200,
+            "[{\"Id\": \"4fa6e0f0c678\", \"Names\": null, \"Ports\": \
+             [{\"PrivatePort\": 80, \"Type\": \"tcp\"}]}]",
+        ),
+    ]);
+
+    let details = client.inspect_container("web").unwrap();
+    assert_eq!(details.name, "/web");
+    assert_eq!(details.exec_ids, vec!["e90e34656806"]);
+    assert!(details.state.running);
+    assert_eq!(details.state.health.unwrap().status, "healthy");
+    assert!(details.config.cmd.is_empty());
+    assert_eq!(details.config.entrypoint, vec!["/docker-entrypoint.sh"]);
+    assert!(details.config.healthcheck.is_none());
+
+    client.strict = true;
+    match client.inspect_container("web") {
+        Err(DockerApiError::JsonDeserializationError(_)) => (),
+        res => panic!("{:?}", res),
+    }
+
+    client.strict = false;
+    let containers = client.list_all_containers(None).unwrap();
+    assert!(containers[0].names.is_empty());
+    assert_eq!(containers[0].ports[0].private_port, 80);
+    assert_eq!(containers[0].ports[0].public_port, 0);
+}
+
+#[test]
+fn test_unknown_propagation() {
+    let mount: MountSpec = serde_json::from_str(
+        r#"{"Type": "bind", "Source": "/srv", "Target": "/srv",
+            "BindOptions": {"Propagation": "rprivate-ish"}}"#,
+    )
+    .unwrap();
+    let json = serde_json::to_value(&mount).unwrap();
+    assert_eq!(json["BindOptions"]["Propagation"], "rprivate-ish");
+
+    let known: Propagation = serde_json::from_str("\"rshared\"").unwrap();
+    assert_eq!(known, Propagation::RShared);
+    assert_eq!(
+        serde_json::from_str::<Propagation>("\"rslave2\"").unwrap(),
+        Propagation::Other("rslave2".to_string())
+    );
+}
+
+#[test]
+fn test_get_json() {
+    let client = MockClient::new(vec![
+        http_response(
+            200,
+            "{\"Id\": \"4fa6e0f0c678\", \"State\": {\"Health\": \
+             {\"Log\": [{\"ExitCode\": 0}]}}}",
+        ),
+        http_response(404, "{\"message\": \"No such container: db\"}"),
+    ]);
+
+    let info: serde_json::Value =
+        client.get_json("/containers/web/json?size=1").unwrap();
+    assert_eq!(info["State"]["Health"]["Log"][0]["ExitCode"], 0);
+    assert_eq!(
+        client.request_line(0),
+        "GET /containers/web/json?size=1 HTTP/1.1"
+    );
+
+    match client.get_json::<serde_json::Value>("/containers/db/json") {
+        Err(DockerApiError::InvalidApiResponseError(404, _)) => (),
+        res => panic!("{:?}", res),
+    }
+}
+
+#[test]
+fn test_iter_containers() {
+    let client = MockClient::new(vec![
+        chunked_http_response(
+            200,
+            &[
+                " [{\"Id\": \"8dfafdbc3a40\", \"Names\": [\"/a\"], ",
+                "\"Command\": \"echo \\\"],{\\\"\", \"Labels\": {\"k\": \"[\"}},",
+                "\n {\"Id\": \"9cd87474be90\", \"Ports\": [{\"PrivatePort\": 80}]}",
+                "]",
+            ],
+        ),
+        chunked_http_response(200, &["[]"]),
+        chunked_http_response(200, &["[{\"Id\": \"8dfafdbc3a40\"}, {\"Id\""]),
+    ]);
+
+    let containers: Vec<_> = client
+        .iter_containers(true, false)
+        .unwrap()
+        .map(|container| container.unwrap())
+        .collect();
+    assert_eq!(
+        client.request_line(0),
+        "GET /containers/json?all=true&size=false HTTP/1.1"
+    );
+    assert_eq!(containers.len(), 2);
+    assert_eq!(containers[0].command, "echo \"],{\"");
+    assert_eq!(containers[1].id, "9cd87474be90");
+    assert_eq!(containers[1].ports[0].private_port, 80);
+
+    assert_eq!(client.iter_containers(false, false).unwrap().count(), 0);
+
+    let mut truncated = client.iter_containers(true, false).unwrap();
+    assert_eq!(truncated.next().unwrap().unwrap().id, "8dfafdbc3a40");
+    assert!(truncated.next().unwrap().is_err());
+    assert!(truncated.next().is_none());
+}
+
+#[test]
+fn test_create_containers_from_template() {
+    assert_eq!(expand_index("web-{i}", 2), "web-2");
+    assert_eq!(expand_index("{i+8080}:{i}", 1), "8081:1");
+    assert_eq!(expand_index("{id}-{i", 3), "{id}-{i");
+
+    let config = ContainerConfig {
+        image: "nginx".to_string(),
+        ..Default::default()
+    }
+    .env("REPLICA", "{i}")
+    .publish(80, "{i+8080}");
+    let template = ContainerTemplate::new("web-{i}", config);
+
+    let client = MockClient::new(vec![
+        http_response(201, "{\"Id\": \"8dfafdbc3a40\"}"),
+        http_response(201, "{\"Id\": \"9cd87474be90\"}"),
+    ]);
+    let ids = client
+        .create_containers_from_template(&template, 2)
+        .unwrap();
+    assert_eq!(ids.len(), 2);
+    assert_eq!(ids[1].to_string(), "9cd87474be90");
+    assert_eq!(
+        client.request_line(1),
+        "POST /containers/create?name=web-1 HTTP/1.1"
+    );
+    let body = client.request_body(1);
+    assert_eq!(body["Env"], serde_json::json!(["REPLICA=1"]));
+    assert_eq!(body["ExposedPorts"], serde_json::json!({"80/tcp": {}}));
+    assert_eq!(
+        body["HostConfig"]["PortBindings"],
+        serde_json::json!({"80/tcp": [{"HostIp": "", "HostPort": "8081"}]})
+    );
+
+    let client = MockClient::new(vec![
+        http_response(201, "{\"Id\": \"8dfafdbc3a40\"}"),
+        http_response(409, "{\"message\": \"Conflict\"}"),
+        http_response(204, ""),
+    ]);
+    assert!(client
+        .create_containers_from_template(&template, 3)
+        .is_err());
+    assert_eq!(client.requests.borrow().len(), 3);
+    assert_eq!(
+        client.request_line(2),
+        "DELETE /containers/8dfafdbc3a40?force=true&v=true HTTP/1.1"
+    );
+}
+
+fn running_container(image: &str, running: bool) -> String {
+    let details = serde_json::json!({
+        "Id": "8dfafdbc3a40",
+        "State": {"Running": running},
+        "Config": {
+            "Image": image,
+            "Env": ["PATH=/usr/bin", "MODE=cache"],
+            "Labels": {"team": "core", "maintainer": "redis"},
+            "ExposedPorts": {"6379/tcp": {}}
+        },
+        "HostConfig": {
+            "NetworkMode": "default",
+            "PortBindings": {"6379/tcp": [{"HostIp": "", "HostPort": "6379"}]}
+        }
+    });
+    http_response(200, &details.to_string())
+}
+
+#[test]
+fn test_ensure_container() {
+    let desired = || {
+        let mut labels = HashMap::new();
+        labels.insert("team".to_string(), "core".to_string());
+        ContainerConfig {
+            image: "redis:7".to_string(),
+            labels: Some(labels),
+            ..Default::default()
+        }
+        .env("MODE", "cache")
+        .publish(6379, "6379")
+    };
+
+    let client = MockClient::new(vec![
+        http_response(404, "{\"message\": \"No such container: cache\"}"),
+        http_response(201, "{\"Id\": \"9cd87474be90\"}"),
+        http_response(204, ""),
+    ]);
+    let ensured = client.ensure_container("cache", desired()).unwrap();
+    assert_eq!(ensured.action, EnsureAction::Created);
+    assert_eq!(ensured.id.to_string(), "9cd87474be90");
+    assert_eq!(
+        client.request_line(2),
+        "POST /containers/9cd87474be90/start HTTP/1.1"
+    );
+
+    let client = MockClient::new(vec![running_container("redis:7", true)]);
+    let ensured = client.ensure_container("cache", desired()).unwrap();
+    assert_eq!(ensured.action, EnsureAction::Unchanged);
+    assert_eq!(client.requests.borrow().len(), 1);
+
+    let client = MockClient::new(vec![
+        running_container("redis:7", false),
+        http_response(204, ""),
+    ]);
+    let ensured = client.ensure_container("cache", desired()).unwrap();
+    assert_eq!(ensured.action, EnsureAction::Started);
+
+    let client = MockClient::new(vec![
+        running_container("redis:6", true),
+        http_response(204, ""),
+        http_response(204, ""),
+        http_response(201, "{\"Id\": \"9cd87474be90\"}"),
+        http_response(204, ""),
+        http_response(204, ""),
+    ]);
+    let ensured = client
+        .ensure_container("cache", desired().publish(6379, "6380"))
+        .unwrap();
+    assert_eq!(
+        ensured.action,
+        EnsureAction::Recreated(vec![
+            "Image".to_string(),
+            "HostConfig.PortBindings".to_string()
+        ])
+    );
+    assert_eq!(ensured.id.to_string(), "9cd87474be90");
+    let lines: Vec<String> = (1..6).map(|n| client.request_line(n)).collect();
+    assert_eq!(
+        lines,
+        vec![
+            "POST /containers/8dfafdbc3a40/stop HTTP/1.1",
+            "POST /containers/8dfafdbc3a40/rename?name=cache_old_8dfafdbc3a40 HTTP/1.1",
+            "POST /containers/create?name=cache HTTP/1.1",
+            "POST /containers/9cd87474be90/start HTTP/1.1",
+            "DELETE /containers/8dfafdbc3a40?force=true&v=false HTTP/1.1",
+        ]
+    );
+
+    let client = MockClient::new(vec![
+        running_container("redis:6", true),
+        http_response(204, ""),
+        http_response(204, ""),
+        http_response(201, "{\"Id\": \"9cd87474be90\"}"),
+        http_response(500, "{\"message\": \"port is already allocated\"}"),
+        http_response(204, ""),
+        http_response(204, ""),
+        http_response(204, ""),
+    ]);
+    assert!(client.ensure_container("cache", desired()).is_err());
+    let lines: Vec<String> = (4..8).map(|n| client.request_line(n)).collect();
+    assert_eq!(
+        lines,
+        vec![
+            "POST /containers/9cd87474be90/start HTTP/1.1",
+            "DELETE /containers/9cd87474be90?force=true&v=false HTTP/1.1",
+            "POST /containers/8dfafdbc3a40/rename?name=cache HTTP/1.1",
+            "POST /containers/8dfafdbc3a40/start HTTP/1.1",
+        ]
+    );
+    assert_eq!(client.requests.borrow().len(), 8);
+}
+
+#[cfg(feature = "test-support")]
+#[test]
+fn test_assert_no_changes_outside() {
+    use std::panic;
+
+    use rust_docker::test_support::{
+        assert_no_changes_outside, changes_outside,
+    };
+
+    let changes = r#"[{"Path":"/var","Kind":0},{"Path":"/var/log","Kind":0},
+        {"Path":"/var/log/app.log","Kind":1},{"Path":"/tmp/cache","Kind":1},
+        {"Path":"/etc/hosts.allow","Kind":2},{"Path":"/usr","Kind":0}]"#;
+    let client = MockClient::new(vec![
+        http_response(200, changes),
+        http_response(200, changes),
+        http_response(200, "null"),
+    ]);
+
+    let outside =
+        changes_outside(&client, "web", &["/var/log", "/tmp/"]).unwrap();
+    assert_eq!(
+        client.request_line(0),
+        "GET /containers/web/changes HTTP/1.1"
+    );
+    let outside: Vec<String> = outside.iter().map(|c| c.to_string()).collect();
+    assert_eq!(outside, vec!["D /etc/hosts.allow", "C /usr"]);
+
+    let err = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+        assert_no_changes_outside(&client, "web", &["/var/log", "/tmp"])
+    }))
+    .unwrap_err();
+    let msg = err.downcast_ref::<String>().unwrap();
+    assert_eq!(
+        msg,
+        "web changed its filesystem outside of /var/log, /tmp :\n  D /etc/hosts.allow\n  C /usr"
+    );
+
+    assert_no_changes_outside(&client, "web", &[]);
+}
+
+#[test]
+fn test_status_code_errors() {
+    let client = MockClient::new(vec![
+        http_response(304, ""),
+        http_response(
+            409,
+            r#"{"message":"Conflict. The container name \"/web\" is already in use"}"#,
+        ),
+        http_response(404, r#"{"message":"No such image: app:2.0"}"#),
+        http_response(404, r#"{"message":"No such container: db"}"#),
+        http_response(500, "boom"),
+    ]);
+
+    match client.start_container("web") {
+        Err(DockerApiError::NotModified(msg)) => {
+            assert_eq!(msg, "The start of container web changed nothing")
+        }
+        res => panic!("{:?}", res),
+    }
+
+    let config = ContainerConfig {
+        image: "app:2.0".to_string(),
+        ..Default::default()
+    };
+    match client.create_container("web", config.clone()) {
+        Err(DockerApiError::Conflict(msg)) => assert_eq!(
+            msg,
+            "Conflict. The container name \"/web\" is already in use"
+        ),
+        res => panic!("{:?}", res),
+    }
+    match client.create_container("web", config) {
+        Err(DockerApiError::NoSuchImage(msg)) => {
+            assert_eq!(msg, "No such image: app:2.0")
+        }
+        res => panic!("{:?}", res),
+    }
+
+    let err = client.remove_container("db", true, false).unwrap_err();
+    assert!(err.is_not_found());
+    assert_eq!(err.to_string(), "NoSuchContainer : No such container: db");
+
+    match client.stop_container("web", None) {
+        Err(DockerApiError::InvalidApiResponseError(500, body)) => {
+            assert_eq!(body, "boom")
+        }
+        res => panic!("{:?}", res),
+    }
+}
+
+#[test]
+fn test_ensure_started_and_stopped() {
+    let client = MockClient::new(vec![
+        http_response(204, ""),
+        http_response(304, ""),
+        http_response(304, ""),
+        http_response(204, ""),
+        http_response(404, r#"{"message":"No such container: db"}"#),
+    ]);
+
+    assert_eq!(client.ensure_started("web").unwrap(), StateChange::Changed);
+    assert_eq!(
+        client.request_line(0),
+        "POST /containers/web/start HTTP/1.1"
+    );
+    assert_eq!(
+        client.ensure_started("web").unwrap(),
+        StateChange::Unchanged
+    );
+
+    let timeout = Some(Duration::from_secs(5));
+    assert_eq!(
+        client.ensure_stopped("web", timeout).unwrap(),
+        StateChange::Unchanged
+    );
+    assert_eq!(
+        client.request_line(2),
+        "POST /containers/web/stop?t=5 HTTP/1.1"
+    );
+    assert_eq!(
+        client.ensure_stopped("web", None).unwrap(),
+        StateChange::Changed
+    );
+
+    match client.ensure_stopped("db", None) {
+        Err(DockerApiError::NoSuchContainer(_)) => (),
+        res => panic!("{:?}", res),
+    }
+}
+
+#[test]
+fn test_container_names() {
+    assert_eq!(normalize_container_name("/web").unwrap(), "web");
+    assert_eq!(
+        normalize_container_name("shop_web.1-a").unwrap(),
+        "shop_web.1-a"
+    );
+    for name in &["", "/", "w", "_web", "-web", "web app", "/app/db", "wéb"] {
+        match normalize_container_name(name) {
+            Err(DockerApiError::ContainerError(_)) => (),
+            res => panic!("{} : {:?}", name, res),
+        }
+    }
+
+    let client = MockClient::new(vec![
+        http_response(201, r#"{"Id":"4fa6e0f0c678","Warnings":[]}"#),
+        http_response(
+            200,
+            r#"[{"Id":"4fa6e0f0c678","Names":["/app/db","/db"]},
+                {"Id":"e90e34656806","Names":null}]"#,
+        ),
+    ]);
+    client
+        .create_container("/db", ContainerConfig::default())
+        .unwrap();
+    assert_eq!(
+        client.request_line(0),
+        "POST /containers/create?name=db HTTP/1.1"
+    );
+    match client.create_container("db 2", ContainerConfig::default()) {
+        Err(DockerApiError::ContainerError(_)) => (),
+        res => panic!("{:?}", res),
+    }
+    assert_eq!(client.requests.borrow().len(), 1);
+
+    let containers = client.list_all_containers(None).unwrap();
+    assert_eq!(containers[0].name(), Some("db"));
+    assert_eq!(containers[1].name(), None);
+}
+
+#[test]
+fn test_container_config_from_inspect() {
+    let details: ContainerDetails = serde_json::from_str(
+        r#"{"Id": "4fa6e0f0c6786287e131c3852c58a2e01cc697a68231826813597e4994f1d6e2",
+            "Name": "/web",
+            "Config": {"Hostname": "4fa6e0f0c678", "Image": "nginx:1.25",
+                "Cmd": ["nginx", "-g", "daemon off;"], "Env": ["MODE=prod"],
+                "Labels": {"app": "web"}},
+            "HostConfig": {"NetworkMode": "front", "Binds": ["/srv/www:/usr/share/nginx/html:ro"],
+                "RestartPolicy": {"Name": "on-failure", "MaximumRetryCount": 3},
+                "PortBindings": {"80/tcp": [{"HostIp": "", "HostPort": "8080"}]}},
+            "NetworkSettings": {"Networks": {"front": {
+                "IPAMConfig": {"IPv4Address": "172.20.0.10"},
+                "Aliases": ["4fa6e0f0c678", "www"],
+                "NetworkID": "5f1b", "EndpointID": "c1a9", "Gateway": "172.20.0.1",
+                "IPAddress": "172.20.0.10", "IPPrefixLen": 16,
+                "MacAddress": "02:42:ac:14:00:0a"}}}}"#,
+    )
+    .unwrap();
+
+    let config = ContainerConfig::from_inspect(&details)
+        .unwrap()
+        .env("MODE", "canary");
+
+    assert_eq!(config.image, "nginx:1.25");
+    assert_eq!(config.hostname, "");
+    assert_eq!(config.env, vec!["MODE=canary"]);
+    let host_config = config.host_config.as_ref().unwrap();
+    assert_eq!(host_config.binds, vec!["/srv/www:/usr/share/nginx/html:ro"]);
+    assert_eq!(
+        host_config.restart_policy,
+        Some(RestartPolicy {
+            name: "on-failure".to_string(),
+            maximum_retry_count: 3,
+        })
+    );
+
+    let json = serde_json::to_value(&config).unwrap();
+    assert_eq!(json["Labels"]["app"], "web");
+    assert_eq!(
+        json["HostConfig"]["PortBindings"]["80/tcp"][0]["HostPort"],
+        "8080"
+    );
+    assert_eq!(
+        json["NetworkingConfig"]["EndpointsConfig"]["front"],
+        serde_json::json!({
+            "IPAMConfig": {"IPv4Address": "172.20.0.10"},
+            "Aliases": ["www"]
+        })
+    );
+
+    let mut details = details;
+    details.config.hostname = "web.example.com".to_string();
+    details.host_config = serde_json::Value::Null;
+    let config = ContainerConfig::from_inspect(&details).unwrap();
+    assert_eq!(config.hostname, "web.example.com");
+    assert!(config.host_config.is_none());
+
+    details.host_config = serde_json::json!({"Memory": "lots"});
+    match ContainerConfig::from_inspect(&details) {
+        Err(DockerApiError::JsonDeserializationError(_)) => (),
+        res => panic!("{:?}", res),
+    }
+}
+
+#[test]
+fn test_container_config_from_inspect_round_trip() {
+    let host_config = serde_json::json!({
+        "NetworkMode": "default",
+        "Privileged": true,
+        "CapAdd": ["NET_ADMIN", "SYS_TIME"],
+        "Devices": [{"PathOnHost": "/dev/fuse", "PathInContainer": "/dev/fuse",
+                     "CgroupPermissions": "rwm"}],
+        "LogConfig": {"Type": "json-file", "Config": {"max-size": "10m"}},
+        "SecurityOpt": ["apparmor=unconfined"],
+        "ShmSize": 268435456,
+        "Ulimits": [{"Name": "nofile", "Soft": 1024, "Hard": 2048}],
+        "Memory": 536870912
+    });
+    let details: ContainerDetails = serde_json::from_value(serde_json::json!({
+        "Id": "4fa6e0f0c6786287e131",
+        "Name": "/vpn",
+        "Config": {"Image": "vpn:2", "Entrypoint": ["/init", "--verbose"],
+                   "Cmd": null, "Volumes": {"/data": {}}, "Shell": ["/bin/bash", "-c"]},
+        "HostConfig": host_config.clone()
+    }))
+    .unwrap();
+
+    let config = ContainerConfig::from_inspect(&details).unwrap();
+    assert_eq!(config.entrypoint, vec!["/init", "--verbose"]);
+    assert_eq!(config.host_config.as_ref().unwrap().memory, Some(536870912));
+
+    let json = serde_json::to_value(&config).unwrap();
+    assert_eq!(
+        json["Entrypoint"],
+        serde_json::json!(["/init", "--verbose"])
+    );
+    assert_eq!(json["Volumes"], serde_json::json!({"/data": {}}));
+    assert_eq!(json["Shell"], serde_json::json!(["/bin/bash", "-c"]));
+    assert_eq!(json["HostConfig"], host_config);
+
+    // The API also takes the entrypoint as a single string.
+    let config: ContainerConfig =
+        serde_json::from_str(r#"{"Entrypoint": "/init"}"#).unwrap();
+    assert_eq!(config.entrypoint, vec!["/init"]);
+}
+
+const WEB_DETAILS: &str = r#"{"Id": "4fa6e0f0c6786287e131",
+    "Name": "/web", "State": {"Running": true},
+    "Config": {"Hostname": "4fa6e0f0c678", "Image": "nginx:1.25",
+        "Entrypoint": ["/docker-entrypoint.sh"]},
+    "HostConfig": {"Binds": ["/srv/www:/usr/share/nginx/html:ro"],
+        "Privileged": true, "CapAdd": ["NET_ADMIN"]},
+    "Mounts": [{"Type": "bind", "Source": "/srv/www",
+        "Destination": "/usr/share/nginx/html"},
+        {"Type": "volume", "Name": "9c2e1d0b", "Source": "/var/lib/docker/volumes/9c2e1d0b/_data",
+        "Destination": "/var/cache/nginx", "Driver": "local"}]}"#;
+
+#[test]
+fn test_recreate_container() {
+    let client = MockClient::new(vec![
+        http_response(200, WEB_DETAILS),
+        http_response(204, ""),
+        http_response(204, ""),
+        http_response(201, r#"{"Id": "e90e34656806", "Warnings": []}"#),
+        http_response(204, ""),
+        http_response(204, ""),
+    ]);
+
+    let id = client
+        .recreate_container("web", |config| ContainerConfig {
+            image: "nginx:1.26".to_string(),
+            ..config
+        })
+        .unwrap();
+
+    assert_eq!(id.as_str(), "e90e34656806");
+    assert_eq!(
+        client.request_line(1),
+        "POST /containers/4fa6e0f0c6786287e131/stop HTTP/1.1"
+    );
+    assert_eq!(
+        client.request_line(2),
+        "POST /containers/4fa6e0f0c6786287e131/rename?name=web_old_4fa6e0f0c678 HTTP/1.1"
+    );
+    assert_eq!(
+        client.request_line(3),
+        "POST /containers/create?name=web HTTP/1.1"
+    );
+    let body = client.request_body(3);
+    assert_eq!(body["Image"], "nginx:1.26");
+    assert_eq!(body["Hostname"], "");
+    assert_eq!(
+        body["Entrypoint"],
+        serde_json::json!(["/docker-entrypoint.sh"])
+    );
+    assert_eq!(body["HostConfig"]["Privileged"], true);
+    assert_eq!(
+        body["HostConfig"]["CapAdd"],
+        serde_json::json!(["NET_ADMIN"])
+    );
+    assert_eq!(
+        body["HostConfig"]["Binds"],
+        serde_json::json!([
+            "/srv/www:/usr/share/nginx/html:ro",
+            "9c2e1d0b:/var/cache/nginx"
+        ])
+    );
+    assert_eq!(
+        client.request_line(4),
+        "DELETE /containers/4fa6e0f0c6786287e131?force=false&v=false HTTP/1.1"
+    );
+    assert_eq!(
+        client.request_line(5),
+        "POST /containers/e90e34656806/start HTTP/1.1"
+    );
+}
+
+#[test]
+fn test_recreate_container_puts_the_old_one_back() {
+    let client = MockClient::new(vec![
+        http_response(200, WEB_DETAILS),
+        http_response(304, ""),
+        http_response(204, ""),
+        http_response(404, r#"{"message": "No such image: nginx:9"}"#),
+        http_response(204, ""),
+        http_response(204, ""),
+    ]);
+
+    match client.recreate_container("web", |config| ContainerConfig {
+        image: "nginx:9".to_string(),
+        ..config
+    }) {
+        Err(DockerApiError::NoSuchImage(_)) => (),
+        res => panic!("{:?}", res),
+    }
+    assert_eq!(
+        client.request_line(4),
+        "POST /containers/4fa6e0f0c6786287e131/rename?name=web HTTP/1.1"
+    );
+    assert_eq!(
+        client.request_line(5),
+        "POST /containers/4fa6e0f0c6786287e131/start HTTP/1.1"
+    );
+}
+
+const RESOURCE_RESPONSES: [&str; 3] = [
+    r#"{"NCPU": 2, "MemTotal": 2147483648}"#,
+    r#"[{"Id": "8dfafdbc3a40", "Names": ["/db"], "Image": "postgres:16",
+        "ImageID": "sha256:2ca7", "Command": "postgres", "State": "running",
+        "Status": "Up 2 hours", "HostConfig": {"NetworkMode": "default"}}]"#,
+    r#"{"Id": "8dfafdbc3a40", "State": {"Running": true},
+        "HostConfig": {"Memory": 1610612736, "NanoCpus": 500000000}}"#,
+];
+
+fn limited_config() -> ContainerConfig {
+    ContainerConfig {
+        image: "redis:7".to_string(),
+        host_config: Some(HostConfig::default().memory(1 << 30).cpus(1.0)),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_create_container_resource_check() {
+    let mut client = MockClient::new(
+        RESOURCE_RESPONSES
+            .iter()
+            .map(|body| http_response(200, body))
+            .collect(),
+    );
+    client.resource_check = ResourceCheck::Deny;
+
+    match client.create_container("cache", limited_config()) {
+        Err(DockerApiError::InsufficientResources(shortfalls)) => {
+            assert_eq!(
+                shortfalls,
+                vec![ResourceShortfall {
+                    resource: Resource::Memory,
+                    requested: 1 << 30,
+                    reserved: 1610612736,
+                    total: 2147483648,
+                }]
+            );
+        }
+        res => panic!("{:?}", res),
+    }
+    assert_eq!(client.request_line(0), "GET /info HTTP/1.1");
+    assert_eq!(client.request_line(1), "GET /containers/json HTTP/1.1");
+    assert_eq!(client.requests.borrow().len(), 3);
+
+    let mut responses: Vec<String> = RESOURCE_RESPONSES
+        .iter()
+        .map(|body| http_response(200, body))
+        .collect();
+    responses.push(http_response(201, r#"{"Id": "9cd87474be90"}"#));
+    let mut client = MockClient::new(responses);
+    client.resource_check = ResourceCheck::Warn;
+
+    let resp = client.create_container("cache", limited_config()).unwrap();
+    assert_eq!(resp.resource_warnings.len(), 1);
+    assert_eq!(resp.resource_warnings[0].resource, Resource::Memory);
+    assert_eq!(client.request_body(3)["HostConfig"]["Memory"], 1 << 30);
+}
+
+#[test]
+fn test_resource_check_skips_containers_without_limits() {
+    let mut client =
+        MockClient::new(vec![http_response(201, r#"{"Id": "9cd87474be90"}"#)]);
+    client.resource_check = ResourceCheck::Deny;
+
+    let config = ContainerConfig {
+        image: "redis:7".to_string(),
+        ..Default::default()
+    };
+    client.create_container("cache", config).unwrap();
+    assert_eq!(
+        client.request_line(0),
+        "POST /containers/create?name=cache HTTP/1.1"
+    );
+}
+
+#[test]
+fn test_owned_container_is_removed_on_panic() {
+    let client = MockClient::new(vec![
+        http_response(201, r#"{"Id": "9cd87474be90"}"#),
+        http_response(204, ""),
+    ]);
+    let config = ContainerConfig {
+        image: "redis:7".to_string(),
+        ..Default::default()
+    };
+
+    let res = panic::catch_unwind(AssertUnwindSafe(|| {
+        let container = client.create_container_owned("cache", config).unwrap();
+        assert_eq!(container.id().as_str(), "9cd87474be90");
+        panic!("the test failed");
+    }));
+
+    assert!(res.is_err());
+    assert_eq!(
+        client.request_line(1),
+        "DELETE /containers/9cd87474be90?force=true&v=true HTTP/1.1"
+    );
+}
+
+#[test]
+fn test_owned_container_kept() {
+    let client =
+        MockClient::new(vec![http_response(201, r#"{"Id": "9cd87474be90"}"#)]);
+    let config = ContainerConfig {
+        image: "redis:7".to_string(),
+        ..Default::default()
+    };
+
+    let id = client
+        .create_container_owned("cache", config)
+        .unwrap()
+        .keep();
+
+    assert_eq!(id.as_str(), "9cd87474be90");
+    assert_eq!(client.requests.borrow().len(), 1);
+}
@@ -0,0 +1,43 @@
+extern crate rust_docker;
+
+use rust_docker::debug::{sanitize_request, sanitize_response, DebugExchange};
+
+#[test]
+fn test_sanitize_request() {
+    let request = "POST /auth HTTP/1.1\r\nHost: v1.37\r\n\
+                   X-Registry-Auth: eyJ1c2VybmFtZSI6ImNpIn0=\r\n\
+                   Content-Length: 63\r\n\r\n\
+                   {\"username\":\"ci\",\"password\":\"hunter2\",\"serveraddress\":\"r.io\"}";
+
+    assert_eq!(
+        sanitize_request(request),
+        "POST /auth HTTP/1.1\nHost: v1.37\nX-Registry-Auth: <redacted>\n\
+         Content-Length: 63\n\n{\n  \"password\": \"<redacted>\",\n  \
+         \"serveraddress\": \"r.io\",\n  \"username\": \"ci\"\n}"
+    );
+    assert_eq!(
+        sanitize_request("GET /_ping HTTP/1.1\r\nHost: v1.37\r\n\r\n"),
+        "GET /_ping HTTP/1.1\nHost: v1.37"
+    );
+}
+
+#[test]
+fn test_sanitize_response() {
+    let response =
+        b"HTTP/1.1 400 Bad Request\r\nContent-Type: application/json\r\n\
+                     Transfer-Encoding: chunked\r\n\r\n\
+                     1f\r\n{\"message\":\"invalid port spec\"}\r\n0\r\n\r\n";
+
+    let exchange =
+        DebugExchange::new("GET /info HTTP/1.1\r\n\r\n", Some(response));
+    assert_eq!(
+        exchange.to_string(),
+        ">>> request\nGET /info HTTP/1.1\n<<< response\nHTTP/1.1 400\n\
+         Content-Type: application/json\nTransfer-Encoding: chunked\n\n\
+         {\n  \"message\": \"invalid port spec\"\n}\n"
+    );
+    assert_eq!(sanitize_response(b"garbage"), "garbage");
+    assert!(DebugExchange::new("GET /events HTTP/1.1\r\n\r\n", None)
+        .to_string()
+        .ends_with("<<< response streamed\n"));
+}
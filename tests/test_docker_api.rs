@@ -0,0 +1,107 @@
+#![cfg(all(
+    feature = "build",
+    feature = "containers",
+    feature = "events",
+    feature = "exec",
+    feature = "images",
+    feature = "logs",
+    feature = "networks",
+    feature = "secrets",
+    feature = "services",
+    feature = "stats",
+    feature = "swarm",
+    feature = "volumes"
+))]
+extern crate rust_docker;
+extern crate serde_json;
+
+mod common;
+
+use common::{http_response, MockClient};
+
+use rust_docker::api::build::Build;
+use rust_docker::api::containers::{Container, Containers};
+use rust_docker::api::events::Events;
+use rust_docker::api::exec::Exec;
+use rust_docker::api::images::Images;
+use rust_docker::api::logs::Logs;
+use rust_docker::api::networks::Networks;
+use rust_docker::api::secrets::Secrets;
+use rust_docker::api::services::Services;
+use rust_docker::api::stats::Stats;
+use rust_docker::api::swarm::Swarm;
+use rust_docker::api::version::Version;
+use rust_docker::api::volumes::Volumes;
+use rust_docker::api::{DockerApi, DockerApiClient};
+use rust_docker::client::DockerClient;
+use rust_docker::errors::DockerApiError;
+
+impl Build for MockClient {}
+impl Containers for MockClient {}
+impl Events for MockClient {}
+impl Exec for MockClient {}
+impl Images for MockClient {}
+impl Logs for MockClient {}
+impl Networks for MockClient {}
+impl Secrets for MockClient {}
+impl Services for MockClient {}
+impl Stats for MockClient {}
+impl Swarm for MockClient {}
+impl Version for MockClient {}
+impl Volumes for MockClient {}
+
+/// A mock overriding an API method instead of answering requests.
+struct NoContainers;
+
+impl DockerApiClient for NoContainers {
+    fn request(&self, _request: &str) -> Option<Vec<u8>> {
+        None
+    }
+}
+
+impl Build for NoContainers {}
+impl Containers for NoContainers {
+    fn list_running_containers(
+        &self,
+        _limit: Option<u32>,
+    ) -> Result<Vec<Container>, DockerApiError> {
+        Ok(Vec::new())
+    }
+}
+impl Events for NoContainers {}
+impl Exec for NoContainers {}
+impl Images for NoContainers {}
+impl Logs for NoContainers {}
+impl Networks for NoContainers {}
+impl Secrets for NoContainers {}
+impl Services for NoContainers {}
+impl Stats for NoContainers {}
+impl Swarm for NoContainers {}
+impl Version for NoContainers {}
+impl Volumes for NoContainers {}
+
+fn running(api: &dyn DockerApi) -> Result<usize, DockerApiError> {
+    Ok(api.list_running_containers(None)?.len())
+}
+
+fn is_shareable_api<C: DockerApi + Send + Sync>() {}
+
+#[test]
+fn test_clients_behind_trait_objects() {
+    is_shareable_api::<DockerClient>();
+
+    let apis: Vec<Box<dyn DockerApi>> = vec![
+        Box::new(MockClient::new(vec![http_response(
+            200,
+            r#"[{"Id": "19a4d5d687db", "Names": ["/db"],
+                "Image": "postgres:16", "ImageID": "sha256:2ca7",
+                "Command": "postgres", "State": "running",
+                "Status": "Up 2 hours", "HostConfig": {}}]"#,
+        )])),
+        Box::new(NoContainers),
+    ];
+
+    let counts: Vec<usize> =
+        apis.iter().map(|api| running(&**api).unwrap()).collect();
+    assert_eq!(counts, vec![1, 0]);
+}
@@ -1,10 +1,34 @@
+#![cfg(feature = "containers")]
 extern crate rust_docker;
 
 use rust_docker::api::containers::Containers;
 use rust_docker::api::version::Version;
-use rust_docker::client::DockerClient;
+use rust_docker::client::{DockerClient, DEFAULT_USER_AGENT};
+use rust_docker::proxy::ProxyConfig;
 
+use std::io::{Read, Write};
+use std::net::TcpListener;
 use std::process::exit;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// Starts a fake daemon over TCP answering one request with an empty JSON
+/// object, returns its address and the request it got.
+fn start_daemon() -> (&'static str, thread::JoinHandle<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = format!("tcp://{}", listener.local_addr().unwrap());
+
+    let handle = thread::spawn(move || {
+        let (mut conn, _) = listener.accept().unwrap();
+        let mut buf = [0; 1024];
+        let len = conn.read(&mut buf).unwrap();
+        conn.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\n{}")
+            .unwrap();
+        String::from_utf8_lossy(&buf[..len]).to_string()
+    });
+
+    (Box::leak(addr.into_boxed_str()), handle)
+}
 
 #[test]
 fn test() {
@@ -26,8 +50,7 @@ fn test() {
     let running_cont = client.list_running_containers(None).unwrap();
     println!("{:?}", running_cont);
 
-    let mut cmd: Vec<String> = Vec::new();
-    cmd.push("ls".to_string());
+    let cmd: Vec<String> = vec!["ls".to_string()];
     let res = client
         .create_container_minimal("kk", "debian:jessie", cmd)
         .unwrap();
@@ -49,10 +72,61 @@ fn test_error_when_image_does_not_exist_locally() {
         if let Err(e) = res {
             assert_eq!(
                 format!("{}", e),
-                "Invalid API response, status_code : 404, body: {\"message\":\"No such image: this-image:doesnt-exist\"}"
+                "NoSuchImage : No such image: this-image:doesnt-exist"
             );
         }
     } else {
-        assert!(false, "Could not create a new DockerClient object");
+        panic!("Could not create a new DockerClient object");
     }
 }
+
+#[test]
+fn test_identification_headers() {
+    let (addr, daemon) = start_daemon();
+    let client =
+        DockerClient::with_proxy(addr, ProxyConfig::default()).unwrap();
+    assert_eq!(client.get_version_info().unwrap(), "{}");
+    let request = daemon.join().unwrap();
+    assert!(request.starts_with(&format!(
+        "GET /info HTTP/1.1\r\nUser-Agent: {}\r\nHost: v1.37\r\n",
+        DEFAULT_USER_AGENT
+    )));
+    assert!(DEFAULT_USER_AGENT.starts_with("docker-rs/"));
+
+    let (addr, daemon) = start_daemon();
+    let client = DockerClient::with_proxy(addr, ProxyConfig::default())
+        .unwrap()
+        .with_user_agent("deployer/2.1")
+        .unwrap()
+        .with_header("X-Client-Id", "ci-runner-7")
+        .unwrap();
+    assert!(client.clone().with_header("X-Client-Id", "a\r\nb").is_err());
+    client.get_version_info().unwrap();
+    let request = daemon.join().unwrap();
+    assert!(request.starts_with(
+        "GET /info HTTP/1.1\r\nUser-Agent: deployer/2.1\r\n\
+         X-Client-Id: ci-runner-7\r\nHost: v1.37\r\n"
+    ));
+}
+
+#[test]
+fn test_debug_dump() {
+    let (addr, daemon) = start_daemon();
+    let dumps: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
+    let recorded = dumps.clone();
+    let client = DockerClient::with_proxy(addr, ProxyConfig::default())
+        .unwrap()
+        .with_debug(move |exchange| {
+            recorded.lock().unwrap().push(exchange.to_string())
+        });
+
+    client.get_version_info().unwrap();
+    daemon.join().unwrap();
+    let dumps = dumps.lock().unwrap();
+    assert_eq!(dumps.len(), 1);
+    assert!(
+        dumps[0].starts_with(">>> request\nGET /info HTTP/1.1\nUser-Agent: ")
+    );
+    assert!(dumps[0]
+        .ends_with("<<< response\nHTTP/1.1 200\nContent-Length: 2\n\n{}\n"));
+}
@@ -0,0 +1,76 @@
+extern crate rust_docker;
+
+use rust_docker::dockerignore::{DockerIgnore, IgnorePattern};
+
+#[test]
+fn test_dockerignore_patterns() {
+    let ignore = DockerIgnore::parse(
+        "# comment\n\n/target\n./docs/*.md\n*.sw?\nnode_modules/\n",
+    );
+    let patterns: Vec<&str> = ignore
+        .patterns()
+        .iter()
+        .map(|pattern| pattern.pattern.as_str())
+        .collect();
+    assert_eq!(
+        patterns,
+        vec!["target", "docs/*.md", "*.sw?", "node_modules"]
+    );
+
+    assert!(ignore.is_excluded("target"));
+    assert!(ignore.is_excluded("target/release/app"));
+    assert!(!ignore.is_excluded("src/target.rs"));
+    assert!(ignore.is_excluded("docs/intro.md"));
+    assert!(!ignore.is_excluded("docs/api/intro.md"));
+    assert!(ignore.is_excluded("main.swp"));
+    assert!(!ignore.is_excluded("src/main.swp"));
+    assert!(ignore.is_excluded("./node_modules/left-pad/index.js"));
+}
+
+#[test]
+fn test_dockerignore_double_star() {
+    let ignore = DockerIgnore::parse("**/*.log\nbuild/**\ndocs/**/draft-*\n");
+
+    assert!(ignore.is_excluded("app.log"));
+    assert!(ignore.is_excluded("var/log/app.log"));
+    assert!(ignore.is_excluded("build/out/app"));
+    assert!(!ignore.is_excluded("build.rs"));
+    assert!(ignore.is_excluded("docs/draft-intro.md"));
+    assert!(ignore.is_excluded("docs/guide/v2/draft-intro.md"));
+    assert!(!ignore.is_excluded("docs/guide/intro.md"));
+}
+
+#[test]
+fn test_dockerignore_exceptions() {
+    let ignore = DockerIgnore::parse("*.md\n!README*.md\nREADME-secret.md\n");
+    assert!(ignore.has_exceptions());
+    assert!(ignore.is_excluded("CHANGELOG.md"));
+    assert!(!ignore.is_excluded("README.md"));
+    assert!(ignore.is_excluded("README-secret.md"));
+
+    let ignore = DockerIgnore::parse("vendor\n!vendor/keep\n");
+    assert!(ignore.is_excluded("vendor/other/lib.rs"));
+    assert!(!ignore.is_excluded("vendor/keep"));
+    assert!(!ignore.is_excluded("vendor/keep/lib.rs"));
+
+    let ignore = DockerIgnore::parse("*\n").keep("Dockerfile");
+    assert!(ignore.is_excluded("src"));
+    assert!(!ignore.is_excluded("Dockerfile"));
+}
+
+#[test]
+fn test_dockerignore_classes_and_escapes() {
+    let ignore = DockerIgnore::parse("tmp[0-9]\nfile[^a-c].txt\nstar\\*\n");
+    assert!(ignore.is_excluded("tmp7"));
+    assert!(!ignore.is_excluded("tmpx"));
+    assert!(ignore.is_excluded("filed.txt"));
+    assert!(!ignore.is_excluded("fileb.txt"));
+    assert!(ignore.is_excluded("star*"));
+    assert!(!ignore.is_excluded("starry"));
+
+    assert!(IgnorePattern::parse("broken[").is_none());
+    assert!(IgnorePattern::parse("!").is_none());
+    let pattern = IgnorePattern::parse("!/logs/../*.tmp").unwrap();
+    assert!(pattern.exception);
+    assert_eq!(pattern.pattern, "*.tmp");
+}
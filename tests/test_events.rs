@@ -0,0 +1,246 @@
+#![cfg(feature = "events")]
+extern crate rust_docker;
+extern crate serde_json;
+
+mod common;
+
+use common::{chunked_http_response, MockClient};
+
+use std::io::Read;
+use std::sync::mpsc::channel;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, UNIX_EPOCH};
+
+use rust_docker::api::events::{
+    Backoff, Event, EventFilters, EventGap, EventStats, EventType, Events,
+};
+use rust_docker::api::DockerApiClient;
+use rust_docker::utils::StreamHandle;
+
+impl Events for MockClient {}
+
+/// A `MockClient` which can be moved to another thread.
+#[derive(Clone)]
+struct SharedClient(Arc<Mutex<MockClient>>);
+
+impl DockerApiClient for SharedClient {
+    fn request(&self, request: &str) -> Option<Vec<u8>> {
+        self.0.lock().unwrap().request(request)
+    }
+
+    fn stream_request(
+        &self,
+        request: &str,
+        handle: &StreamHandle,
+    ) -> Option<Box<dyn Read + Send>> {
+        self.0.lock().unwrap().stream_request(request, handle)
+    }
+}
+
+impl Events for SharedClient {}
+
+#[test]
+fn test_event_filters_to_json() {
+    let filters = EventFilters::new()
+        .event_type(EventType::Container)
+        .container("web")
+        .container("db")
+        .label("env", None)
+        .network("backend")
+        .volume("data")
+        .image("debian");
+
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(&filters.to_json()).unwrap(),
+        serde_json::json!({
+            "type": ["container"],
+            "container": ["web", "db"],
+            "label": ["env"],
+            "network": ["backend"],
+            "volume": ["data"],
+            "image": ["debian"]
+        })
+    );
+}
+
+#[test]
+fn test_get_events_streams_events() {
+    let client = MockClient::new(vec![chunked_http_response(
+        200,
+        &[
+            "{\"Type\":\"container\",\"Action\":\"start\",\"Actor\":{\"ID\":\"f808ca\",\"Attributes\":{\"name\":\"web\"}},\"time\":1530000000,\"timeNano\":1530000000000000000}\n",
+            "{\"Type\":\"container\",\"Action\":\"die\",\"Actor\":{\"ID\":\"f808ca\"},\"time\":1530000005}\n",
+        ],
+    )]);
+
+    let filters = EventFilters::new().event("start").event("die");
+    let events: Vec<_> = client
+        .get_events(Some(1530000000), None, Some(&filters))
+        .unwrap()
+        .map(|e| e.unwrap())
+        .collect();
+
+    assert_eq!(
+        client.request_line(0),
+        "GET /events?since=1530000000&filters=%7B%22event%22%3A%5B%22start%22%2C%22die%22%5D%7D HTTP/1.1"
+    );
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].actor.attributes["name"], "web");
+    assert_eq!(events[1].action, "die");
+    assert_eq!(
+        events[1].time,
+        Some(UNIX_EPOCH + Duration::from_secs(1530000005))
+    );
+}
+
+#[test]
+fn test_reconnecting_events_resume_since_last_event() {
+    let start = "{\"Type\":\"container\",\"Action\":\"start\",\"time\":4000000000,\"timeNano\":4000000000000000001}\n";
+    let die = "{\"Type\":\"container\",\"Action\":\"die\",\"time\":4000000001,\"timeNano\":4000000001000000000}\n";
+    let client = MockClient::new(vec![
+        chunked_http_response(200, &[start]),
+        chunked_http_response(200, &[start, die]),
+    ]);
+
+    let backoff = Backoff {
+        initial: Duration::from_millis(0),
+        max_retries: Some(1),
+        ..Default::default()
+    };
+    let mut gaps: Vec<EventGap> = Vec::new();
+    let actions: Vec<String> = client
+        .get_events_reconnecting(None, backoff)
+        .on_gap(|gap| gaps.push(gap.clone()))
+        .map(|e| e.map(|e| e.action).unwrap_or_else(|e| e.to_string()))
+        .collect();
+
+    assert_eq!(actions.len(), 3);
+    assert_eq!(actions[0], "start");
+    assert_eq!(actions[1], "die");
+    assert!(client
+        .request_line(1)
+        .starts_with("GET /events?since=4000000000.000000001 "));
+    assert_eq!(gaps[0].since, 4000000000000000001);
+    assert!(gaps[0].error.is_none());
+}
+
+#[test]
+fn test_reconnecting_events_keep_events_of_the_same_time() {
+    let start = "{\"Type\":\"container\",\"Action\":\"start\",\"Actor\":{\"ID\":\"a1\"},\"time\":4000000000,\"timeNano\":4000000000000000001}\n";
+    let other = "{\"Type\":\"container\",\"Action\":\"start\",\"Actor\":{\"ID\":\"b2\"},\"time\":4000000000,\"timeNano\":4000000000000000001}\n";
+    let client = MockClient::new(vec![
+        chunked_http_response(200, &[start]),
+        chunked_http_response(200, &[start, other]),
+    ]);
+
+    let backoff = Backoff {
+        initial: Duration::from_millis(0),
+        max_retries: Some(1),
+        ..Default::default()
+    };
+    let ids: Vec<String> = client
+        .get_events_reconnecting(None, backoff)
+        .filter_map(|e| e.ok())
+        .map(|e| e.actor.id)
+        .collect();
+
+    assert_eq!(ids, vec!["a1", "b2"]);
+}
+
+#[test]
+fn test_events_between() {
+    let client = MockClient::new(vec![chunked_http_response(
+        200,
+        &[
+            "{\"Type\":\"container\",\"Action\":\"create\",\"time\":1530000000}\n",
+            "{\"Type\":\"container\",\"Action\":\"start\",\"time\":1530000001}\n",
+        ],
+    )]);
+
+    // The future `until` is capped to the current time.
+    let events = client.events_between(1530000000, i64::MAX, None).unwrap();
+
+    let request_line = client.request_line(0);
+    assert!(request_line.starts_with("GET /events?since=1530000000&until="));
+    assert!(!request_line.contains(&i64::MAX.to_string()));
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[1].action, "start");
+
+    assert!(client.events_between(1530000001, 1530000000, None).is_err());
+}
+
+#[test]
+fn test_events_into_channel() {
+    let start = "{\"Type\":\"container\",\"Action\":\"start\",\"time\":4000000000,\"timeNano\":4000000000000000001}\n";
+    let die = "{\"Type\":\"container\",\"Action\":\"die\",\"time\":4000000001,\"timeNano\":4000000001000000000}\n";
+    let mock = MockClient::new(vec![
+        chunked_http_response(200, &[start]),
+        chunked_http_response(200, &[start, die]),
+    ]);
+    let client = SharedClient(Arc::new(Mutex::new(mock)));
+
+    let (sender, receiver) = channel();
+    let filters = EventFilters::new().event_type(EventType::Container);
+    let forwarder = client.events_into(sender, Some(filters));
+
+    let actions: Vec<String> =
+        receiver.iter().take(2).map(|event| event.action).collect();
+    assert_eq!(actions, vec!["start", "die"]);
+
+    forwarder.stop().unwrap();
+    let mock = client.0.lock().unwrap();
+    assert!(mock
+        .request_line(1)
+        .starts_with("GET /events?since=4000000000.000000001&filters="));
+}
+
+#[test]
+fn test_event_stats_record() {
+    let events: Vec<Event> = serde_json::from_str(
+        r#"[{"Type": "container", "Action": "start"},
+            {"Type": "container", "Action": "oom"},
+            {"Type": "container", "Action": "die",
+                "Actor": {"Attributes": {"exitCode": "137"}}},
+            {"Type": "container", "Action": "die",
+                "Actor": {"Attributes": {"exitCode": "0"}}},
+            {"Type": "container", "Action": "die",
+                "Actor": {"Attributes": {"exitCode": "137"}}},
+            {"Type": "image", "Action": "pull"},
+            {"Type": "network", "Action": "connect"}]"#,
+    )
+    .unwrap();
+
+    let mut stats = EventStats::new();
+    for event in &events {
+        stats.record(event);
+    }
+
+    assert_eq!(stats.starts, 1);
+    assert_eq!(stats.dies, 3);
+    assert_eq!(stats.exit_codes.get(&137), Some(&2));
+    assert_eq!(stats.exit_codes.get(&0), Some(&1));
+    assert_eq!(stats.oom_kills, 1);
+    assert_eq!(stats.pulls, 1);
+}
+
+#[test]
+fn test_event_stats_collector() {
+    let start = "{\"Type\":\"container\",\"Action\":\"start\",\"time\":4000000000,\"timeNano\":4000000000000000001}\n";
+    let pull = "{\"Type\":\"image\",\"Action\":\"pull\",\"time\":4000000001,\"timeNano\":4000000001000000000}\n";
+    let mock =
+        MockClient::new(vec![chunked_http_response(200, &[start, pull])]);
+    let client = SharedClient(Arc::new(Mutex::new(mock)));
+
+    let collector = client.event_stats();
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while collector.snapshot().pulls == 0 && Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    let stats = collector.stop().unwrap();
+    assert_eq!(stats.starts, 1);
+    assert_eq!(stats.pulls, 1);
+    let mock = client.0.lock().unwrap();
+    assert!(mock.request_line(0).contains("filters="));
+}
@@ -0,0 +1,121 @@
+#![cfg(feature = "exec")]
+extern crate rust_docker;
+extern crate serde_json;
+
+mod common;
+
+use std::io::Write;
+
+use common::{frame, http_response, MockClient};
+
+use rust_docker::api::exec::{Exec, ExecOptions, ExecOutput};
+use rust_docker::errors::DockerApiError;
+
+impl Exec for MockClient {}
+
+#[test]
+fn test_exec_output() {
+    let output = format!("{}{}", frame(1, "bin\netc\n"), frame(2, "oops\n"));
+    let client = MockClient::new(vec![
+        http_response(201, r#"{"Id":"e5f1"}"#),
+        format!("HTTP/1.1 200 OK\r\n\r\n{}", output),
+        http_response(200, r#"{"ID":"e5f1","Running":false,"ExitCode":2}"#),
+    ]);
+
+    let opts = ExecOptions {
+        user: Some("nobody".to_string()),
+        ..Default::default()
+    };
+    let output = client.exec_output("f808ca", &["ls", "/"], &opts).unwrap();
+
+    assert_eq!(
+        output,
+        ExecOutput {
+            stdout: b"bin\netc\n".to_vec(),
+            stderr: b"oops\n".to_vec(),
+            exit_code: 2,
+        }
+    );
+
+    assert_eq!(
+        client.request_line(0),
+        "POST /containers/f808ca/exec HTTP/1.1"
+    );
+    let body = client.request_body(0);
+    assert_eq!(body["Cmd"], serde_json::json!(["ls", "/"]));
+    assert_eq!(body["User"], "nobody");
+    assert_eq!(body["AttachStdout"], true);
+
+    assert_eq!(client.request_line(1), "POST /exec/e5f1/start HTTP/1.1");
+    assert_eq!(client.request_body(1)["Detach"], false);
+    assert_eq!(client.request_line(2), "GET /exec/e5f1/json HTTP/1.1");
+}
+
+#[test]
+fn test_exec_output_waits_for_exit_code() {
+    let client = MockClient::new(vec![
+        http_response(201, r#"{"Id":"e5f1"}"#),
+        format!("HTTP/1.1 200 OK\r\n\r\n{}", frame(1, "done\n")),
+        http_response(200, r#"{"ID":"e5f1","Running":true,"ExitCode":null}"#),
+        http_response(200, r#"{"ID":"e5f1","Running":false,"ExitCode":0}"#),
+    ]);
+
+    let output = client
+        .exec_output("f808ca", &["true"], &ExecOptions::default())
+        .unwrap();
+    assert_eq!(output.stdout, b"done\n".to_vec());
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(client.request_line(3), "GET /exec/e5f1/json HTTP/1.1");
+}
+
+#[test]
+fn test_exec_errors() {
+    let client = MockClient::new(vec![
+        http_response(404, r#"{"message":"No such container: gone"}"#),
+        http_response(409, r#"{"message":"Container f808ca is not running"}"#),
+        http_response(404, r#"{"message":"No such exec instance: e5f1"}"#),
+    ]);
+    let opts = ExecOptions::default();
+
+    match client.exec_output("gone", &["true"], &opts) {
+        Err(DockerApiError::NoSuchContainer(msg)) => {
+            assert_eq!(msg, "No such container: gone")
+        }
+        res => panic!("{:?}", res),
+    }
+    match client.exec_output("f808ca", &["true"], &opts) {
+        Err(DockerApiError::Conflict(msg)) => {
+            assert_eq!(msg, "Container f808ca is not running")
+        }
+        res => panic!("{:?}", res),
+    }
+    let err = client.inspect_exec("e5f1").unwrap_err();
+    assert!(err.is_not_found());
+    assert_eq!(err.to_string(), "NoSuchExec : No such exec instance: e5f1");
+}
+
+#[test]
+fn test_exec_interactive() {
+    let client = MockClient::new(vec![
+        http_response(201, r#"{"Id":"e5f1"}"#),
+        format!(
+            "HTTP/1.1 101 UPGRADED\r\nConnection: Upgrade\r\n\r\n{}",
+            frame(1, "hello\n")
+        ),
+    ]);
+
+    let session = client
+        .exec_interactive("f808ca", &["sh"], &ExecOptions::default(), false)
+        .unwrap();
+    let (mut stdin, mut output) = (session.stdin, session.output);
+    stdin.write_all(b"echo hello\n").unwrap();
+    stdin.close().unwrap();
+
+    assert_eq!(output.next_frame().unwrap().unwrap().payload, b"hello\n");
+    assert!(output.next_frame().unwrap().is_none());
+
+    assert_eq!(client.request_body(0)["AttachStdin"], true);
+    assert!(client.requests.borrow()[1].contains("Upgrade: tcp\r\n"));
+    assert_eq!(*client.stdin.written.lock().unwrap(), b"echo hello\n");
+    assert!(*client.stdin.closed.lock().unwrap());
+}
@@ -0,0 +1,62 @@
+#![cfg(feature = "events")]
+extern crate rust_docker;
+extern crate serde_json;
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use rust_docker::api::events::Event;
+use rust_docker::exit_history::ExitHistory;
+
+fn die(name: &str, exit_code: i64, ago: Duration) -> Event {
+    let time = SystemTime::now() - ago;
+    let time_nano = time.duration_since(UNIX_EPOCH).unwrap().as_nanos();
+    serde_json::from_value(serde_json::json!({
+        "Type": "container",
+        "Action": "die",
+        "Actor": {
+            "ID": "4fa6e0f0c678",
+            "Attributes": {"name": name, "exitCode": exit_code.to_string()}
+        },
+        "timeNano": time_nano as i64
+    }))
+    .unwrap()
+}
+
+#[test]
+fn test_exit_history_keeps_the_last_exits_of_watched_containers() {
+    let mut history = ExitHistory::new(2).watch("worker");
+
+    assert!(history.record(&die("worker", 1, Duration::from_secs(30))));
+    assert!(history.record(&die("worker", 2, Duration::from_secs(20))));
+    assert!(history.record(&die("worker", 0, Duration::from_secs(10))));
+    assert!(!history.record(&die("web", 1, Duration::from_secs(10))));
+
+    let codes: Vec<i64> = history
+        .exits("worker")
+        .iter()
+        .map(|exit| exit.exit_code)
+        .collect();
+    assert_eq!(codes, vec![2, 0]);
+    assert_eq!(history.last_exit("worker").unwrap().exit_code, 0);
+    assert!(history.exits("web").is_empty());
+}
+
+#[test]
+fn test_exit_history_is_crashlooping() {
+    let mut history = ExitHistory::new(10).watch("worker").watch("cron");
+    for ago in &[50, 40, 30] {
+        history.record(&die("worker", 1, Duration::from_secs(*ago)));
+    }
+    for ago in &[3000, 40, 30] {
+        history.record(&die("cron", 1, Duration::from_secs(*ago)));
+    }
+    history.record(&die("cron", 0, Duration::from_secs(20)));
+
+    assert!(history.is_crashlooping("worker", Duration::from_secs(60)));
+    assert!(!history.is_crashlooping("worker", Duration::from_secs(45)));
+    assert!(!history.is_crashlooping("cron", Duration::from_secs(60)));
+    assert!(!history.is_crashlooping("web", Duration::from_secs(60)));
+
+    let history = history.crashloop_failures(2);
+    assert!(history.is_crashlooping("cron", Duration::from_secs(60)));
+}
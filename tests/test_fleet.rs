@@ -0,0 +1,181 @@
+#![cfg(all(feature = "containers", feature = "images"))]
+extern crate rust_docker;
+
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use rust_docker::api::containers::Containers;
+use rust_docker::api::images::Images;
+use rust_docker::api::DockerApiClient;
+use rust_docker::bulk::ConcurrencyPolicy;
+use rust_docker::errors::DockerApiError;
+use rust_docker::fleet::DockerFleet;
+use rust_docker::DockerClient;
+
+/// A client which can be shared by threads, giving the same response to
+/// every request.
+struct HostClient {
+    response: &'static str,
+    requests: Mutex<Vec<String>>,
+}
+
+impl HostClient {
+    fn new(response: &'static str) -> HostClient {
+        HostClient {
+            response,
+            requests: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+impl DockerApiClient for HostClient {
+    fn request(&self, request: &str) -> Option<Vec<u8>> {
+        let line = request.lines().next().unwrap_or("").to_string();
+        self.requests.lock().unwrap().push(line);
+        Some(self.response.as_bytes().to_vec())
+    }
+}
+
+impl Containers for HostClient {}
+impl Images for HostClient {}
+
+#[test]
+fn test_fleet_fan_out() {
+    let fleet = DockerFleet::new()
+        .host(
+            "web-1",
+            HostClient::new(
+                "HTTP/1.1 200 OK\r\nContent-Length: 19\r\n\r\n[{\"Id\":\"4fa6e0f0\"}]",
+            ),
+        )
+        .host(
+            "web-2",
+            HostClient::new("HTTP/1.1 500 Internal Server Error\r\nContent-Length: 4\r\n\r\nboom"),
+        )
+        .host(
+            "web-3",
+            HostClient::new(
+                "HTTP/1.1 200 OK\r\nContent-Length: 21\r\n\r\n{\"status\":\"Pulled\"}\r\n",
+            ),
+        );
+    assert_eq!(fleet.hosts(), vec!["web-1", "web-2", "web-3"]);
+    assert!(fleet.get("web-4").is_none());
+
+    let results = fleet.list_containers_all_hosts();
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0].host, "web-1");
+    assert_eq!(
+        results[0].result.as_ref().unwrap()[0].id.as_str(),
+        "4fa6e0f0"
+    );
+    match results[1].result {
+        Err(DockerApiError::InvalidApiResponseError(500, _)) => (),
+        ref res => panic!("{:?}", res),
+    }
+    assert_eq!(
+        fleet.get("web-2").unwrap().requests.lock().unwrap()[0],
+        "GET /containers/json?all=true&size=true HTTP/1.1"
+    );
+
+    let results = fleet.pull_on_all("nginx:1.25", None);
+    assert_eq!(
+        results[2].result.as_ref().unwrap()[0].status,
+        Some("Pulled".to_string())
+    );
+    assert_eq!(
+        fleet.get("web-3").unwrap().requests.lock().unwrap()[1],
+        "POST /images/create?fromImage=nginx&tag=1.25 HTTP/1.1"
+    );
+
+    let fleet =
+        fleet.host("web-1", HostClient::new("HTTP/1.1 204 No Content\r\n\r\n"));
+    assert_eq!(fleet.len(), 3);
+    assert!(fleet
+        .get("web-1")
+        .unwrap()
+        .requests
+        .lock()
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn test_fleet_of_docker_clients() {
+    let fleet: DockerFleet<DockerClient> = DockerFleet::default();
+    assert!(fleet.is_empty());
+    assert!(fleet.list_containers_all_hosts().is_empty());
+
+    assert!(DockerFleet::connect(&[(
+        "gone",
+        "unix:///nonexistent/docker.sock"
+    )])
+    .is_err());
+}
+
+/// Requests in flight now and at most.
+type InFlight = Arc<Mutex<(usize, usize)>>;
+
+/// A slow client keeping track of its requests in flight and of the ones of
+/// all the hosts.
+struct SlowHost {
+    own: InFlight,
+    all: InFlight,
+}
+
+fn enter(in_flight: &InFlight) {
+    let mut in_flight = in_flight.lock().unwrap();
+    in_flight.0 += 1;
+    in_flight.1 = in_flight.1.max(in_flight.0);
+}
+
+impl DockerApiClient for SlowHost {
+    fn request(&self, _request: &str) -> Option<Vec<u8>> {
+        enter(&self.own);
+        enter(&self.all);
+        thread::sleep(Duration::from_millis(20));
+        self.own.lock().unwrap().0 -= 1;
+        self.all.lock().unwrap().0 -= 1;
+        Some(b"HTTP/1.1 204 No Content\r\n\r\n".to_vec())
+    }
+}
+
+impl Containers for SlowHost {}
+
+#[test]
+fn test_fleet_concurrency_policy() {
+    let all = InFlight::default();
+    let hosts: Vec<InFlight> = (0..3).map(|_| InFlight::default()).collect();
+    let mut fleet =
+        DockerFleet::new().concurrency(ConcurrencyPolicy::per_host(3).total(4));
+    for (idx, own) in hosts.iter().enumerate() {
+        let client = SlowHost {
+            own: own.clone(),
+            all: all.clone(),
+        };
+        fleet = fleet.host(&format!("host-{}", idx), client);
+    }
+
+    let ids = ["a", "b", "c", "d", "e", "f"];
+    let results =
+        fleet.run_bulk_on_all(&ids, |client, id| client.start_container(id));
+
+    assert_eq!(results.len(), 3);
+    for host in &results {
+        let items = host.result.as_ref().unwrap();
+        assert_eq!(items.len(), 6);
+        assert_eq!(items[5].item, "f");
+        assert!(items.iter().all(|item| item.result.is_ok()));
+    }
+    for own in &hosts {
+        assert!(own.lock().unwrap().1 <= 3);
+    }
+    let max = all.lock().unwrap().1;
+    assert!(max > 1 && max <= 4);
+
+    let fleet = fleet.concurrency(ConcurrencyPolicy::per_host(8).total(1));
+    all.lock().unwrap().1 = 0;
+    let results = fleet.run_on_all(|client| client.start_container("a"));
+    assert!(results.iter().all(|host| host.result.is_ok()));
+    assert_eq!(all.lock().unwrap().1, 1);
+}
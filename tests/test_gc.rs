@@ -0,0 +1,179 @@
+#![cfg(all(
+    feature = "containers",
+    feature = "images",
+    feature = "networks",
+    feature = "volumes"
+))]
+extern crate rust_docker;
+extern crate serde_json;
+
+mod common;
+
+use common::{http_response, MockClient};
+
+use rust_docker::api::containers::{ContainerConfig, Containers};
+use rust_docker::api::images::Images;
+use rust_docker::api::networks::Networks;
+use rust_docker::api::volumes::{VolumeCreateBuilder, Volumes};
+use rust_docker::gc::{cleanup_owned, run_gc, GcPolicy};
+
+impl Containers for MockClient {}
+impl Images for MockClient {}
+impl Networks for MockClient {}
+impl Volumes for MockClient {}
+
+fn image(id: &str, created: u64, tags: &[&str]) -> String {
+    format!(
+        r#"{{"Id":"sha256:{}","ParentId":"","RepoTags":{},"RepoDigests":null,
+        "Created":{},"Size":0,"VirtualSize":0,"SharedSize":-1,"Labels":null,
+        "Containers":-1}}"#,
+        id,
+        serde_json::to_string(tags).unwrap(),
+        created
+    )
+}
+
+fn images_response() -> String {
+    let images = [
+        image("aa", 300, &["app:3", "mirror/app:latest"]),
+        image("bb", 200, &["app:2"]),
+        image("cc", 100, &["app:1", "<none>:<none>"]),
+    ];
+    http_response(200, &format!("[{}]", images.join(",")))
+}
+
+const VOLUMES: &str = r#"{"Volumes":[{"Name":"orphan","Driver":"local",
+    "Mountpoint":"/var/lib/docker/volumes/orphan","Labels":null,
+    "Scope":"local","Options":null}],"Warnings":null}"#;
+
+#[test]
+fn test_gc_dry_run() {
+    let client =
+        MockClient::new(vec![images_response(), http_response(200, VOLUMES)]);
+    let policy = GcPolicy::new()
+        .keep_recent_images(1)
+        .prune_dangling_volumes()
+        .dry_run(true);
+
+    let report = run_gc(&client, &policy).unwrap();
+
+    assert!(report.dry_run);
+    assert_eq!(report.images, vec!["app:1", "app:2"]);
+    assert_eq!(report.volumes, vec!["orphan"]);
+    assert_eq!(client.requests.borrow().len(), 2);
+    assert_eq!(
+        client.request_line(1),
+        "GET /volumes?filters=%7B%22dangling%22%3A%5B%22true%22%5D%7D HTTP/1.1"
+    );
+}
+
+#[test]
+fn test_gc_keeps_images_with_all_their_tags() {
+    let images = [
+        image("aa", 300, &["app:3", "app:latest"]),
+        image("bb", 200, &["app:2", "app:stable"]),
+        image("cc", 100, &["app:1"]),
+    ];
+    let client = MockClient::new(vec![http_response(
+        200,
+        &format!("[{}]", images.join(",")),
+    )]);
+    let policy = GcPolicy::new().keep_recent_images(2).dry_run(true);
+
+    let report = run_gc(&client, &policy).unwrap();
+
+    assert_eq!(report.images, vec!["app:1"]);
+
+    let client = MockClient::new(vec![http_response(
+        200,
+        &format!("[{}]", images.join(",")),
+    )]);
+    let policy = GcPolicy::new().keep_recent_images(1).dry_run(true);
+
+    let report = run_gc(&client, &policy).unwrap();
+
+    assert_eq!(report.images, vec!["app:1", "app:2", "app:stable"]);
+}
+
+#[test]
+fn test_gc_reports_errors() {
+    let client = MockClient::new(vec![
+        images_response(),
+        http_response(200, "[]"),
+        http_response(409, r#"{"message":"image is being used"}"#),
+    ]);
+    let policy = GcPolicy::new().keep_recent_images(1);
+
+    let report = run_gc(&client, &policy).unwrap();
+
+    assert!(!report.dry_run);
+    assert_eq!(report.images, vec!["app:1"]);
+    assert_eq!(report.errors.len(), 1);
+    assert_eq!(report.errors[0].0, "app:2");
+    assert_eq!(
+        client.request_line(1),
+        "DELETE /images/app:1?force=false HTTP/1.1"
+    );
+}
+
+#[test]
+fn test_owner_label() {
+    let mut client = MockClient::new(vec![
+        http_response(201, r#"{"Id":"8dfafdbc3a40"}"#),
+        http_response(201, r#"{"Name":"data","Driver":"local"}"#),
+    ]);
+    client.owner_label = Some(("test.run".to_string(), "42".to_string()));
+
+    client
+        .create_container("web", ContainerConfig::default())
+        .unwrap();
+    let volume = VolumeCreateBuilder::new("data").build().unwrap();
+    client.create_volume(volume).unwrap();
+
+    assert_eq!(
+        client.request_body(0)["Labels"],
+        serde_json::json!({"test.run": "42"})
+    );
+    assert_eq!(
+        client.request_body(1)["Labels"],
+        serde_json::json!({"test.run": "42"})
+    );
+}
+
+#[test]
+fn test_cleanup_owned() {
+    let mut client = MockClient::new(vec![
+        http_response(200, r#"[{"Id":"8dfafdbc3a40"}]"#),
+        http_response(204, ""),
+        http_response(
+            200,
+            r#"[{"Name":"test-net","Id":"7b3a","Labels":{"test.run":"42"}},
+            {"Name":"bridge","Id":"5e1f","Labels":{}}]"#,
+        ),
+        http_response(204, ""),
+        http_response(
+            200,
+            r#"{"Volumes":[{"Name":"data","Labels":{"test.run":"42"}},
+            {"Name":"other","Labels":{"test.run":"7"}}]}"#,
+        ),
+        http_response(409, r#"{"message":"volume is in use"}"#),
+    ]);
+
+    assert!(cleanup_owned(&client).is_err());
+    assert!(client.requests.borrow().is_empty());
+
+    client.owner_label = Some(("test.run".to_string(), "42".to_string()));
+    let report = cleanup_owned(&client).unwrap();
+
+    assert_eq!(
+        client.request_line(0),
+        "GET /containers/json?all=true&filters=\
+         %7B%22label%22%3A%5B%22test.run%3D42%22%5D%7D HTTP/1.1"
+    );
+    assert_eq!(report.containers, vec!["8dfafdbc3a40"]);
+    assert_eq!(report.networks, vec!["test-net"]);
+    assert_eq!(client.request_line(3), "DELETE /networks/7b3a HTTP/1.1");
+    assert!(report.volumes.is_empty());
+    assert_eq!(report.errors.len(), 1);
+    assert_eq!(report.errors[0].0, "data");
+}
@@ -0,0 +1,186 @@
+extern crate rust_docker;
+extern crate serde_json;
+
+use std::fs;
+use std::io::{Read, Write};
+use std::os::unix::net::UnixListener;
+use std::sync::mpsc::{channel, Receiver};
+use std::thread;
+use std::time::Duration;
+
+use rust_docker::api::api_utils::RequestBuilder;
+use rust_docker::api::events::Events;
+use rust_docker::api::{DockerApiClient, ResourceCheck};
+use rust_docker::hyper_client::HyperClient;
+use rust_docker::ratelimit::RateLimiter;
+
+/// Starts a fake daemon which sends back the requests it gets and answers
+/// each of them with `response`, keeping the connections alive.
+fn start_daemon(
+    name: &str,
+    response: &'static str,
+) -> (&'static str, Receiver<String>) {
+    let path =
+        format!("/tmp/docker-rs-hyper-{}-{}.sock", name, std::process::id());
+    let _ = fs::remove_file(&path);
+    let listener = UnixListener::bind(&path).unwrap();
+    let (sender, requests) = channel();
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut conn = stream.unwrap();
+            let sender = sender.clone();
+            thread::spawn(move || {
+                let mut buf = [0; 4096];
+                let mut request = Vec::new();
+                while let Ok(len) = conn.read(&mut buf) {
+                    if len == 0 {
+                        break;
+                    }
+                    request.extend_from_slice(&buf[..len]);
+                    if !is_complete(&request) {
+                        continue;
+                    }
+                    let _ = sender
+                        .send(String::from_utf8_lossy(&request).into_owned());
+                    request.clear();
+                    if conn.write_all(response.as_bytes()).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+    });
+
+    (Box::leak(path.into_boxed_str()), requests)
+}
+
+/// Whether the request has its head and the body of its `Content-Length`,
+/// or the last chunk of a chunked body.
+fn is_complete(request: &[u8]) -> bool {
+    let request = String::from_utf8_lossy(request);
+    let head_end = match request.find("\r\n\r\n") {
+        Some(end) => end,
+        None => return false,
+    };
+    if request[..head_end].contains("transfer-encoding: chunked") {
+        return request.ends_with("0\r\n\r\n");
+    }
+    let length = request[..head_end]
+        .lines()
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+        .map_or(0, |(_, value)| value.trim().parse().unwrap());
+    request.len() >= head_end + 4 + length
+}
+
+#[test]
+fn test_hyper_client_requests() {
+    let (path, requests) = start_daemon(
+        "requests",
+        "HTTP/1.1 200 OK\r\nApi-Version: 1.41\r\nTransfer-Encoding: chunked\r\n\r\n\
+         5\r\n{\"ID\"\r\n7\r\n:\"a1b2\"\r\n1\r\n}\r\n0\r\n\r\n",
+    );
+    let client = HyperClient::new(path).unwrap();
+
+    let info: serde_json::Value = client.get_json("/info").unwrap();
+    assert_eq!(info["ID"], "a1b2");
+    let request = requests.recv().unwrap();
+    assert!(request.starts_with("GET /info HTTP/1.1\r\n"));
+    assert!(request.contains("user-agent: docker-rs/"));
+    assert!(request.contains("host: v1."));
+
+    let resp = client
+        .get_response_from_api(
+            "/containers/create",
+            "POST",
+            "{\"Image\":\"alpine\"}",
+        )
+        .unwrap();
+    assert_eq!(resp.status_code, 200);
+    assert_eq!(resp.header("api-version"), Some("1.41"));
+    assert_eq!(resp.body, "{\"ID\":\"a1b2\"}");
+    let request = requests.recv().unwrap();
+    assert!(request.starts_with("POST /containers/create HTTP/1.1\r\n"));
+    assert!(request.contains("content-length: 18\r\n"));
+    assert!(request.ends_with("\r\n\r\n{\"Image\":\"alpine\"}"));
+}
+
+#[test]
+fn test_hyper_client_settings() {
+    let (path, requests) = start_daemon(
+        "settings",
+        "HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\n{}",
+    );
+    let client = HyperClient::new(path)
+        .unwrap()
+        .with_rate_limiter(RateLimiter::new(10, Duration::from_secs(60)))
+        .with_owner_label("test.run", "42")
+        .with_resource_check(ResourceCheck::Deny)
+        .with_user_agent("deployer/2.1")
+        .unwrap()
+        .with_header("X-Client-Id", "ci-runner-7")
+        .unwrap();
+
+    assert!(client.rate_limiter().is_some());
+    assert_eq!(client.owner_label(), Some(("test.run", "42")));
+    assert_eq!(client.resource_check(), ResourceCheck::Deny);
+    assert!(HyperClient::new(path)
+        .unwrap()
+        .with_header("X-Client-Id", "a\r\nb")
+        .is_err());
+
+    client.get_response_from_api("/info", "GET", "").unwrap();
+    let request = requests.recv().unwrap();
+    assert!(request.contains("user-agent: deployer/2.1\r\n"));
+    assert!(request.contains("x-client-id: ci-runner-7\r\n"));
+    assert!(!request.contains("docker-rs/"));
+}
+
+#[test]
+fn test_hyper_client_upload() {
+    let (path, requests) = start_daemon(
+        "upload",
+        "HTTP/1.1 200 OK\r\nContent-Length: 11\r\n\r\n{\"ok\":true}",
+    );
+    let client = HyperClient::new(path).unwrap();
+
+    let context = vec![b'x'; 100 * 1024];
+    let request = RequestBuilder::new("POST", "/build")
+        .header("Content-Type", "application/x-tar")
+        .chunked();
+    let resp = client.send_upload(&request, &mut &context[..]).unwrap();
+    assert_eq!(resp.status_code, 200);
+    assert_eq!(resp.body_to_string().unwrap(), "{\"ok\":true}");
+
+    let request = requests.recv().unwrap();
+    assert!(request.starts_with("POST /build HTTP/1.1\r\n"));
+    assert!(request.contains("content-type: application/x-tar\r\n"));
+    assert!(request.contains("transfer-encoding: chunked\r\n"));
+    let body = request.split("\r\n\r\n").nth(1).unwrap();
+    assert_eq!(body.matches('x').count(), 100 * 1024);
+}
+
+#[test]
+fn test_hyper_client_cancel_stream() {
+    let (path, _requests) = start_daemon(
+        "events",
+        "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n",
+    );
+    let client = HyperClient::new(path).unwrap();
+
+    let mut events = client.get_events(None, None, None).unwrap();
+    let handle = events.handle();
+
+    let reader = thread::spawn(move || events.next().is_none());
+    thread::sleep(Duration::from_millis(100));
+    handle.cancel();
+
+    assert!(reader.join().unwrap());
+}
+
+#[test]
+fn test_hyper_client_invalid_address() {
+    assert!(HyperClient::new("tcp://127.0.0.1:2375").is_err());
+    assert!(HyperClient::new("/tmp/docker-rs-hyper-missing.sock").is_err());
+}
@@ -0,0 +1,35 @@
+extern crate rust_docker;
+extern crate serde_json;
+
+use rust_docker::api::ids::{ContainerId, ContainerRef, ImageId};
+
+#[test]
+fn test_container_id() {
+    let id = ContainerId::new(
+        "8dfafdbc3a40a6bf7b8e6d4d8a3b4c3e1c1f3a2e9f0b1c2d3e4f5a6b7c8d9e0f",
+    )
+    .unwrap();
+
+    assert_eq!(id.short(), "8dfafdbc3a40");
+    assert!(id.matches("8dfa"));
+    assert!(!id.matches(""));
+    assert!(ContainerId::new("my_container").is_err());
+
+    // Passed to the API methods like the names of the containers.
+    assert_eq!(id.container_ref().len(), 64);
+    assert_eq!("web".container_ref(), "web");
+    assert_eq!("web".to_string().container_ref(), "web");
+}
+
+#[test]
+fn test_image_id() {
+    let id: ImageId =
+        serde_json::from_str("\"sha256:d74508fb6632491cea586a1fd7d748dfc5274cd6fdfedee309ecdcbc2bf5cb82\"")
+            .unwrap();
+
+    assert_eq!(id.short(), "d74508fb6632");
+    assert!(id.matches("sha256:d745"));
+    assert!(id.matches("d745"));
+    assert_eq!(format!("{}", id), id.as_str());
+    assert!(ImageId::new("sha256:").is_err());
+}
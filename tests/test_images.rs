@@ -0,0 +1,373 @@
+#![cfg(feature = "images")]
+extern crate rust_docker;
+extern crate serde_json;
+
+mod common;
+
+use std::time::{Duration, UNIX_EPOCH};
+
+use common::{chunked_http_response, http_response, MockClient};
+
+use rust_docker::api::images::{
+    copy_image, ImageFilters, Images, TransferProgress,
+};
+use rust_docker::api::platform::Platform;
+use rust_docker::errors::DockerApiError;
+use rust_docker::ratelimit::RateLimiter;
+
+impl Images for MockClient {}
+
+#[test]
+fn test_image_exists_maps_not_found_to_false() {
+    let client = MockClient::new(vec![
+        http_response(200, "{}"),
+        http_response(404, "{\"message\":\"No such image: foo:latest\"}"),
+    ]);
+
+    assert!(client.image_exists("debian:jessie").unwrap());
+    assert!(!client.image_exists("foo:latest").unwrap());
+    assert_eq!(
+        client.request_line(0),
+        "GET /images/debian:jessie/json HTTP/1.1"
+    );
+}
+
+#[test]
+fn test_ensure_image_pulls_only_when_missing() {
+    let client = MockClient::new(vec![
+        http_response(404, "{\"message\":\"No such image\"}"),
+        http_response(200, "{\"status\":\"Pulling from library/debian\"}\r\n{\"status\":\"Downloaded newer image\"}"),
+        http_response(200, "{}"),
+    ]);
+
+    assert!(client.ensure_image("debian", None).unwrap());
+    assert_eq!(
+        client.request_line(1),
+        "POST /images/create?fromImage=debian&tag=latest HTTP/1.1"
+    );
+
+    assert!(!client.ensure_image("debian", None).unwrap());
+    assert_eq!(client.requests.borrow().len(), 3);
+}
+
+#[test]
+fn test_pull_image_reports_stream_errors() {
+    let client = MockClient::new(vec![http_response(
+        200,
+        "{\"status\":\"Pulling\"}\r\n{\"error\":\"manifest unknown\"}",
+    )]);
+
+    let res = client.pull_image("localhost:5000/foo", None);
+    assert_eq!(
+        format!("{}", res.unwrap_err()),
+        "ImageError : manifest unknown"
+    );
+    assert_eq!(
+        client.request_line(0),
+        "POST /images/create?fromImage=localhost:5000/foo&tag=latest HTTP/1.1"
+    );
+}
+
+#[test]
+fn test_tag_and_push_image() {
+    let client = MockClient::new(vec![
+        http_response(201, ""),
+        http_response(200, "{\"status\":\"Pushed\"}"),
+    ]);
+
+    client
+        .tag_image("d74508fb6632", "registry.local/web:1.0")
+        .unwrap();
+    assert_eq!(
+        client.request_line(0),
+        "POST /images/d74508fb6632/tag?repo=registry.local/web&tag=1.0 HTTP/1.1"
+    );
+
+    client.push_image("registry.local/web:1.0", None).unwrap();
+    assert_eq!(
+        client.request_line(1),
+        "POST /images/registry.local/web/push?tag=1.0 HTTP/1.1"
+    );
+    assert!(client.requests.borrow()[1].contains("X-Registry-Auth: "));
+
+    assert!(client.push_image("registry.local/Web", None).is_err());
+    assert_eq!(client.requests.borrow().len(), 2);
+}
+
+#[test]
+fn test_list_image_tags() {
+    let body = r#"[
+        {"Id":"sha256:aa","ParentId":"","RepoTags":["nginx:1.15","mirror/nginx:1.15"],
+         "RepoDigests":null,"Created":200,"Size":1024,"VirtualSize":1024,
+         "SharedSize":-1,"Labels":null,"Containers":-1},
+        {"Id":"sha256:bb","ParentId":"","RepoTags":["docker.io/library/nginx:1.14","nginx:1.14"],
+         "RepoDigests":null,"Created":100,"Size":2048,"VirtualSize":2048,
+         "SharedSize":-1,"Labels":null,"Containers":-1}
+    ]"#;
+    let client = MockClient::new(vec![http_response(200, body)]);
+
+    let tags = client.list_image_tags("docker.io/library/nginx").unwrap();
+
+    assert_eq!(
+        client.request_line(0),
+        "GET /images/json?filters=%7B%22reference%22%3A%5B%22nginx%22%5D%7D HTTP/1.1"
+    );
+    assert_eq!(tags.len(), 2);
+    assert_eq!(tags[0].tag, "1.14");
+    assert_eq!(tags[0].id, "sha256:bb");
+    assert_eq!(tags[0].size, 2048);
+    assert_eq!(tags[1].tag, "1.15");
+    assert_eq!(tags[1].created, Some(UNIX_EPOCH + Duration::from_secs(200)));
+}
+
+#[test]
+fn test_registry_rate_limits() {
+    let mut client = MockClient::new(vec![
+        format!(
+            "HTTP/1.1 429 Too Many Requests\r\nRetry-After: 30\r\nContent-Length: {}\r\n\r\n{}",
+            2, "{}"
+        ),
+        http_response(
+            200,
+            "{\"status\":\"Pulling from library/debian\"}\n\
+             {\"error\":\"toomanyrequests: You have reached your pull rate limit.\"}\n",
+        ),
+        http_response(200, "[{\"name\":\"debian\",\"star_count\":4000,\"is_official\":true}]"),
+    ]);
+    client.rate_limiter = Some(RateLimiter::new(3, Duration::from_secs(3600)));
+
+    match client.pull_image("debian", None) {
+        Err(DockerApiError::TooManyRequestsError(retry_after, _)) => {
+            assert_eq!(retry_after, Some(Duration::from_secs(30)))
+        }
+        other => panic!("Unexpected result {:?}", other),
+    }
+    match client.pull_image("debian", None) {
+        Err(DockerApiError::TooManyRequestsError(None, msg)) => {
+            assert!(msg.starts_with("toomanyrequests"))
+        }
+        other => panic!("Unexpected result {:?}", other),
+    }
+
+    let results = client.search_images("debian", Some(1)).unwrap();
+    assert_eq!(
+        client.request_line(2),
+        "GET /images/search?term=debian&limit=1 HTTP/1.1"
+    );
+    assert!(results[0].is_official);
+
+    // The three operations took all the tokens.
+    assert!(!client.rate_limiter.as_ref().unwrap().try_acquire());
+}
+
+#[test]
+fn test_pull_image_for_platform() {
+    let client = MockClient::new(vec![http_response(
+        200,
+        "{\"status\":\"Pulled\"}\r\n",
+    )]);
+    let platform = Platform::parse("linux/arm/v7").unwrap();
+
+    client
+        .pull_image_for_platform("redis:4", Some(&platform), None)
+        .unwrap();
+
+    assert_eq!(
+        client.request_line(0),
+        "POST /images/create?fromImage=redis&tag=4&platform=linux%2Farm%2Fv7 HTTP/1.1"
+    );
+}
+
+#[test]
+fn test_inspect_image_config_and_layers() {
+    let body = r#"{
+        "Id": "sha256:2ca7",
+        "RepoTags": ["nginx:1.25"],
+        "RepoDigests": ["nginx@sha256:4c0f"],
+        "Parent": "",
+        "Comment": "",
+        "Created": "2023-08-16T09:50:55.765544033Z",
+        "DockerVersion": "20.10.23",
+        "Author": "",
+        "Architecture": "amd64",
+        "Os": "linux",
+        "Size": 187000000,
+        "Config": {
+            "User": "",
+            "Env": ["PATH=/usr/local/bin:/usr/bin", "NGINX_VERSION=1.25.2"],
+            "Entrypoint": ["/docker-entrypoint.sh"],
+            "Cmd": ["nginx", "-g", "daemon off;"],
+            "WorkingDir": "",
+            "ExposedPorts": {"80/tcp": {}, "443/tcp": {}},
+            "Volumes": null,
+            "Labels": {"maintainer": "NGINX"},
+            "StopSignal": "SIGQUIT"
+        },
+        "RootFS": {
+            "Type": "layers",
+            "Layers": ["sha256:511780f8", "sha256:e6a1b3d9"]
+        }
+    }"#;
+    let client = MockClient::new(vec![
+        http_response(200, body),
+        http_response(
+            200,
+            "{\"Id\":\"sha256:1\",\"RepoTags\":null,\"RepoDigests\":null,\
+             \"Parent\":\"\",\"Comment\":\"\",\"DockerVersion\":\"\",\
+             \"Author\":\"\",\"Architecture\":\"amd64\",\"Os\":\"linux\",\
+             \"Size\":0}",
+        ),
+    ]);
+
+    let image = client.inspect_image("nginx:1.25").unwrap();
+    assert_eq!(image.repo_digests, vec!["nginx@sha256:4c0f"]);
+    assert_eq!(image.config.entrypoint, vec!["/docker-entrypoint.sh"]);
+    assert_eq!(image.config.cmd, vec!["nginx", "-g", "daemon off;"]);
+    assert_eq!(image.config.env.len(), 2);
+    assert_eq!(image.config.labels["maintainer"], "NGINX");
+    assert_eq!(
+        image.config.ports(),
+        vec![(80, "tcp".to_string()), (443, "tcp".to_string())]
+    );
+    assert!(image.config.volumes.is_empty());
+    assert_eq!(image.root_fs.typ, "layers");
+    assert_eq!(image.root_fs.layers.len(), 2);
+
+    let untagged = client.inspect_image("sha256:1").unwrap();
+    assert!(untagged.repo_tags.is_empty());
+    assert!(untagged.repo_digests.is_empty());
+    assert!(untagged.root_fs.layers.is_empty());
+}
+
+#[test]
+fn test_export_image_with_progress() {
+    let client = MockClient::new(vec![
+        http_response(200, "layer.tar contents"),
+        chunked_http_response(200, &["layer", ".tar"]),
+        http_response(404, r#"{"message":"No such image: app:2.0"}"#),
+    ]);
+
+    let mut archive: Vec<u8> = Vec::new();
+    let mut reports: Vec<TransferProgress> = Vec::new();
+    let written = client
+        .export_image_with_progress("app:1.0", &mut archive, |progress| {
+            reports.push(*progress)
+        })
+        .unwrap();
+    assert_eq!(client.request_line(0), "GET /images/app:1.0/get HTTP/1.1");
+    assert_eq!(written, 18);
+    assert_eq!(archive, b"layer.tar contents");
+    assert_eq!(
+        reports.last(),
+        Some(&TransferProgress {
+            transferred: 18,
+            total: Some(18)
+        })
+    );
+    assert_eq!(reports.last().unwrap().fraction(), Some(1.0));
+
+    let mut archive: Vec<u8> = Vec::new();
+    assert_eq!(client.export_image("app:1.0", &mut archive).unwrap(), 9);
+    assert_eq!(archive, b"layer.tar");
+
+    match client.export_image("app:2.0", &mut Vec::new()) {
+        Err(DockerApiError::NoSuchImage(_)) => (),
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+#[test]
+fn test_copy_image() {
+    let src = MockClient::new(vec![chunked_http_response(
+        200,
+        &["layer", ".tar contents"],
+    )]);
+    let dst = MockClient::new(vec![chunked_http_response(
+        200,
+        &["{\"stream\":\"Loaded image: app:1.0\\n\"}\n"],
+    )]);
+
+    let progress = copy_image(&src, &dst, "app:1.0").unwrap();
+
+    assert_eq!(progress.len(), 1);
+    assert_eq!(progress[0].loaded_image(), Some("app:1.0"));
+    assert_eq!(src.request_line(0), "GET /images/app:1.0/get HTTP/1.1");
+    assert_eq!(dst.request_line(0), "POST /images/load HTTP/1.1");
+    assert!(dst.requests.borrow()[0].ends_with("\r\n\r\nlayer.tar contents"));
+}
+
+#[test]
+fn test_image_annotations() {
+    let body = r#"{
+        "Id": "sha256:2ca7",
+        "Config": {
+            "Labels": {
+                "org.opencontainers.image.source": "https://github.com/example/app",
+                "org.opencontainers.image.revision": "from-label",
+                "org.opencontainers.image.created": "2024-03-01T12:00:00Z",
+                "org.opencontainers.image.vendor": "",
+                "maintainer": "ops@example.com"
+            }
+        },
+        "Descriptor": {
+            "mediaType": "application/vnd.oci.image.index.v1+json",
+            "digest": "sha256:4c0f",
+            "size": 1609,
+            "annotations": {
+                "org.opencontainers.image.revision": "9f2c1e7",
+                "org.opencontainers.image.licenses": "(MIT OR Apache-2.0) AND BSD-3-Clause"
+            }
+        }
+    }"#;
+    let client = MockClient::new(vec![http_response(200, body)]);
+
+    let image = client.inspect_image("app:1.0").unwrap();
+    assert_eq!(image.descriptor.as_ref().unwrap().digest, "sha256:4c0f");
+
+    let annotations = image.annotations();
+    assert_eq!(annotations.source(), Some("https://github.com/example/app"));
+    assert_eq!(annotations.revision(), Some("9f2c1e7"));
+    assert_eq!(
+        annotations.created(),
+        Some(UNIX_EPOCH + Duration::from_secs(1_709_294_400))
+    );
+    assert_eq!(
+        annotations.license_ids(),
+        vec!["MIT", "Apache-2.0", "BSD-3-Clause"]
+    );
+    assert_eq!(annotations.vendor(), None);
+    assert_eq!(annotations.all()["maintainer"], "ops@example.com");
+}
+
+#[test]
+fn test_list_images_filtered() {
+    let client = MockClient::new(vec![
+        http_response(
+            200,
+            r#"[{"Id":"sha256:4fa6e0f0c678","RepoTags":["nginx:1.25"],"Size":10}]"#,
+        ),
+        http_response(200, "[]"),
+    ]);
+
+    let filters = ImageFilters::new()
+        .reference("nginx:1.*")
+        .reference("nginx:2.*")
+        .since("nginx:1.0")
+        .before("nginx:3.0")
+        .dangling(true)
+        .dangling(false);
+    let images = client.list_images_filtered(&filters).unwrap();
+    assert_eq!(images[0].repo_tags, vec!["nginx:1.25"]);
+    assert_eq!(
+        client.request_line(0),
+        "GET /images/json?filters=%7B%22before%22%3A%5B%22nginx%3A3.0%22%5D%2C\
+         %22dangling%22%3A%5B%22false%22%5D%2C%22reference%22%3A%5B%22nginx%3A1.%2A%22%2C\
+         %22nginx%3A2.%2A%22%5D%2C%22since%22%3A%5B%22nginx%3A1.0%22%5D%7D HTTP/1.1"
+    );
+
+    assert!(client
+        .list_images_filtered(&ImageFilters::new())
+        .unwrap()
+        .is_empty());
+    assert_eq!(client.request_line(1), "GET /images/json HTTP/1.1");
+}
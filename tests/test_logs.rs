@@ -0,0 +1,264 @@
+#![cfg(feature = "logs")]
+extern crate rust_docker;
+extern crate serde_json;
+
+mod common;
+
+use std::env;
+use std::fs;
+use std::process;
+use std::time::{Duration, UNIX_EPOCH};
+
+use common::{frame, http_response, MockClient};
+
+use rust_docker::api::logs::{
+    is_stack_trace_continuation, LogFrame, LogLine, Logs, LogsOptions,
+    RotationPolicy, StreamType,
+};
+
+use rust_docker::errors::DockerApiError;
+
+impl Logs for MockClient {}
+
+fn logs_response(body: String) -> String {
+    format!(
+        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{}",
+        body.len(),
+        body
+    )
+}
+
+fn line(stream: StreamType, message: &str) -> LogLine {
+    LogLine {
+        stream,
+        message: message.to_string(),
+        timestamp: None,
+    }
+}
+
+#[test]
+fn test_container_logs_demultiplexes_frames() {
+    let mut body = frame(1, "hello ");
+    body.push_str(&frame(2, "oops\n"));
+    body.push_str(&frame(1, "world\nbye"));
+    let client = MockClient::new(vec![logs_response(body)]);
+
+    let options = LogsOptions {
+        tail: Some(10),
+        ..Default::default()
+    };
+    let lines: Vec<LogLine> = client
+        .get_container_logs("f808ca", &options)
+        .unwrap()
+        .map(|l| l.unwrap())
+        .collect();
+
+    assert_eq!(
+        client.request_line(0),
+        "GET /containers/f808ca/logs?follow=false&stdout=true&stderr=true&timestamps=false&tail=10 HTTP/1.1"
+    );
+    assert_eq!(
+        lines,
+        vec![
+            line(StreamType::Stderr, "oops"),
+            line(StreamType::Stdout, "hello world"),
+            line(StreamType::Stdout, "bye"),
+        ]
+    );
+}
+
+#[test]
+fn test_container_logs_errors() {
+    let client = MockClient::new(vec![
+        http_response(404, r#"{"message":"No such container: gone"}"#),
+        http_response(409, r#"{"message":"Container web is restarting"}"#),
+    ]);
+    let options = LogsOptions::default();
+
+    match client.get_container_logs("gone", &options) {
+        Err(DockerApiError::NoSuchContainer(msg)) => {
+            assert_eq!(msg, "No such container: gone")
+        }
+        Err(err) => panic!("{:?}", err),
+        Ok(_) => panic!("the logs of a missing container were read"),
+    }
+    match client.get_container_logs("web", &options) {
+        Err(DockerApiError::Conflict(msg)) => {
+            assert_eq!(msg, "Container web is restarting")
+        }
+        Err(err) => panic!("{:?}", err),
+        Ok(_) => panic!("the logs of a restarting container were read"),
+    }
+}
+
+#[test]
+fn test_container_logs_with_tty() {
+    let body = "root@f808ca:/# ls\r\nbin  etc\r\n".to_string();
+    let client = MockClient::new(vec![logs_response(body)]);
+
+    let lines: Vec<LogLine> = client
+        .get_container_logs("f808ca", &LogsOptions::default())
+        .unwrap()
+        .map(|l| l.unwrap())
+        .collect();
+
+    assert_eq!(
+        lines,
+        vec![
+            line(StreamType::Stdout, "root@f808ca:/# ls"),
+            line(StreamType::Stdout, "bin  etc"),
+        ]
+    );
+}
+
+#[test]
+fn test_container_log_frames() {
+    let mut body = frame(1, "hello\n");
+    body.push_str(&frame(2, "oops"));
+    let client = MockClient::new(vec![logs_response(body)]);
+
+    let mut frames = client
+        .get_container_log_frames("f808ca", &LogsOptions::default())
+        .unwrap();
+
+    assert_eq!(
+        frames.next_frame().unwrap(),
+        Some(LogFrame {
+            stream: StreamType::Stdout,
+            payload: b"hello\n",
+        })
+    );
+    assert_eq!(
+        frames.next_frame().unwrap(),
+        Some(LogFrame {
+            stream: StreamType::Stderr,
+            payload: b"oops",
+        })
+    );
+    assert_eq!(frames.next_frame().unwrap(), None);
+}
+
+#[test]
+fn test_container_logs_with_timestamps() {
+    let mut body = frame(1, "2018-05-01T10:00:01.5Z hello world\n");
+    body.push_str(&frame(2, "not a timestamp\n"));
+    let client = MockClient::new(vec![logs_response(body)]);
+
+    let options = LogsOptions {
+        timestamps: true,
+        ..Default::default()
+    };
+    let lines: Vec<LogLine> = client
+        .get_container_logs("f808ca", &options)
+        .unwrap()
+        .map(|l| l.unwrap())
+        .collect();
+
+    assert_eq!(lines[0].message, "hello world");
+    assert_eq!(
+        lines[0].timestamp,
+        Some(UNIX_EPOCH + Duration::from_millis(1_525_168_801_500))
+    );
+    assert_eq!(lines[1], line(StreamType::Stderr, "not a timestamp"));
+}
+
+#[test]
+fn test_container_logs_joins_split_lines() {
+    let mut body = frame(1, "2018-05-01T10:00:01Z aaaa");
+    body.push_str(&frame(1, "2018-05-01T10:00:02Z bbbb\n"));
+    let client = MockClient::new(vec![logs_response(body)]);
+
+    let options = LogsOptions {
+        timestamps: true,
+        ..Default::default()
+    };
+    let lines: Vec<LogLine> = client
+        .get_container_logs("f808ca", &options)
+        .unwrap()
+        .map(|l| l.unwrap())
+        .collect();
+
+    assert_eq!(lines.len(), 1);
+    assert_eq!(lines[0].message, "aaaabbbb");
+    assert_eq!(
+        lines[0].timestamp,
+        Some(UNIX_EPOCH + Duration::from_secs(1_525_168_801))
+    );
+}
+
+#[test]
+fn test_container_log_records() {
+    let mut body = frame(
+        2,
+        "Exception in thread \"main\" java.lang.Error: boom\n\
+         \tat App.main(App.java:3)\n",
+    );
+    body.push_str(&frame(1, "  not a continuation of stderr\n"));
+    body.push_str(&frame(2, "Caused by: java.io.IOException\nready\n"));
+    let client = MockClient::new(vec![logs_response(body)]);
+
+    let records: Vec<LogLine> = client
+        .get_container_logs("f808ca", &LogsOptions::default())
+        .unwrap()
+        .records(is_stack_trace_continuation)
+        .map(|l| l.unwrap())
+        .collect();
+
+    assert_eq!(records.len(), 4);
+    assert_eq!(
+        records[0].message,
+        "Exception in thread \"main\" java.lang.Error: boom\n\
+         \tat App.main(App.java:3)"
+    );
+    assert_eq!(records[1].stream, StreamType::Stdout);
+    assert_eq!(records[2].message, "Caused by: java.io.IOException");
+    assert_eq!(records[3].message, "ready");
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn test_container_log_records_with_regex() {
+    extern crate regex;
+
+    use rust_docker::api::logs::continuation_regex;
+
+    let body = frame(1, "query failed\n| SELECT 1\n| FROM t\ndone\n");
+    let client = MockClient::new(vec![logs_response(body)]);
+
+    let regex = regex::Regex::new(r"^\| ").unwrap();
+    let records: Vec<String> = client
+        .get_container_logs("f808ca", &LogsOptions::default())
+        .unwrap()
+        .records(continuation_regex(regex))
+        .map(|l| l.unwrap().message)
+        .collect();
+
+    assert_eq!(records, vec!["query failed\n| SELECT 1\n| FROM t", "done"]);
+}
+
+#[test]
+fn test_pipe_logs_to_file_rotates() {
+    let dir = env::temp_dir().join(format!("docker-rs-logs-{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("web.log");
+
+    let mut body = frame(1, "one\ntwo\n");
+    body.push_str(&frame(2, "three\n"));
+    body.push_str(&frame(1, "four\nfive\n"));
+    let client = MockClient::new(vec![logs_response(body)]);
+
+    let rotation = RotationPolicy::new().max_size(9).max_files(2);
+    let written = client.pipe_logs_to_file("web", &path, rotation).unwrap();
+
+    let read = |name: &str| fs::read_to_string(dir.join(name)).unwrap();
+    assert_eq!(written, 5);
+    assert!(client
+        .request_line(0)
+        .starts_with("GET /containers/web/logs?follow=true&"));
+    assert_eq!(read("web.log"), "five\n");
+    assert_eq!(read("web.log.1"), "four\n");
+    assert_eq!(read("web.log.2"), "three\n");
+    assert!(!dir.join("web.log.3").exists());
+
+    let _ = fs::remove_dir_all(&dir);
+}
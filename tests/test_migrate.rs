@@ -0,0 +1,114 @@
+#![cfg(all(feature = "containers", feature = "images", feature = "volumes"))]
+extern crate rust_docker;
+extern crate serde_json;
+
+mod common;
+
+use common::{chunked_http_response, http_response, MockClient};
+
+use rust_docker::api::containers::Containers;
+use rust_docker::api::images::Images;
+use rust_docker::api::volumes::Volumes;
+use rust_docker::errors::DockerApiError;
+use rust_docker::migrate::{migrate_container, MigrateOptions};
+
+impl Containers for MockClient {}
+impl Images for MockClient {}
+impl Volumes for MockClient {}
+
+fn db_details(running: bool) -> String {
+    http_response(
+        200,
+        &format!(
+            r#"{{"Id": "8dfafdbc3a40", "Name": "/db",
+                "State": {{"Running": {}}},
+                "Config": {{"Hostname": "8dfafdbc3a40", "Image": "postgres:16",
+                    "Entrypoint": ["docker-entrypoint.sh"]}},
+                "HostConfig": {{"Binds": ["pgdata:/var/lib/postgresql/data"],
+                    "ShmSize": 268435456,
+                    "Ulimits": [{{"Name": "nofile", "Soft": 1024, "Hard": 2048}}]}},
+                "Mounts": [{{"Type": "volume", "Name": "pgdata",
+                    "Source": "/var/lib/docker/volumes/pgdata/_data",
+                    "Destination": "/var/lib/postgresql/data",
+                    "Driver": "local", "Mode": "z", "RW": true,
+                    "Propagation": ""}}]}}"#,
+            running
+        ),
+    )
+}
+
+#[test]
+fn test_migrate_container_with_volumes() {
+    let src = MockClient::new(vec![
+        db_details(false),
+        http_response(
+            200,
+            r#"{"Name": "pgdata", "Driver": "local",
+                "Mountpoint": "/var/lib/docker/volumes/pgdata/_data",
+                "Labels": {"tier": "db"}, "Scope": "local", "Options": null}"#,
+        ),
+        chunked_http_response(200, &["data/", "PG_VERSION"]),
+    ]);
+    let dst = MockClient::new(vec![
+        http_response(
+            201,
+            r#"{"Name": "pgdata", "Driver": "local",
+                "Mountpoint": "/var/lib/docker/volumes/pgdata/_data",
+                "Scope": "local"}"#,
+        ),
+        http_response(201, r#"{"Id": "e90e34656806", "Warnings": []}"#),
+        http_response(200, ""),
+        http_response(204, ""),
+    ]);
+    let opts = MigrateOptions::new()
+        .name("db-moved")
+        .volumes(true)
+        .start(true);
+
+    let id = migrate_container(&src, &dst, "db", &opts).unwrap();
+
+    assert_eq!(id, "e90e34656806");
+    assert_eq!(src.request_line(1), "GET /volumes/pgdata HTTP/1.1");
+    assert_eq!(
+        src.request_line(2),
+        "GET /containers/8dfafdbc3a40/archive?path=%2Fvar%2Flib%2Fpostgresql%2Fdata HTTP/1.1"
+    );
+    assert_eq!(dst.request_line(0), "POST /volumes/create HTTP/1.1");
+    assert_eq!(dst.request_body(0)["Labels"]["tier"], "db");
+    assert_eq!(
+        dst.request_line(1),
+        "POST /containers/create?name=db-moved HTTP/1.1"
+    );
+    let body = dst.request_body(1);
+    assert_eq!(body["Image"], "postgres:16");
+    assert_eq!(
+        body["Entrypoint"],
+        serde_json::json!(["docker-entrypoint.sh"])
+    );
+    assert_eq!(body["HostConfig"]["ShmSize"], 268435456);
+    assert_eq!(
+        body["HostConfig"]["Ulimits"],
+        serde_json::json!([{"Name": "nofile", "Soft": 1024, "Hard": 2048}])
+    );
+    assert_eq!(
+        dst.request_line(2),
+        "PUT /containers/e90e34656806/archive?path=%2Fvar%2Flib%2Fpostgresql HTTP/1.1"
+    );
+    assert!(dst.requests.borrow()[2].ends_with("\r\n\r\ndata/PG_VERSION"));
+    assert_eq!(
+        dst.request_line(3),
+        "POST /containers/e90e34656806/start HTTP/1.1"
+    );
+}
+
+#[test]
+fn test_migrate_container_refuses_running_containers() {
+    let src = MockClient::new(vec![db_details(true)]);
+    let dst = MockClient::new(vec![]);
+
+    match migrate_container(&src, &dst, "db", &MigrateOptions::new()) {
+        Err(DockerApiError::ContainerError(_)) => {}
+        other => panic!("Expected a ContainerError, got {:?}", other),
+    }
+    assert!(dst.requests.borrow().is_empty());
+}
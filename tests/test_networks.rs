@@ -0,0 +1,163 @@
+#![cfg(all(feature = "containers", feature = "networks"))]
+extern crate rust_docker;
+extern crate serde_json;
+
+mod common;
+
+use std::collections::HashMap;
+
+use common::{http_response, MockClient};
+
+use rust_docker::api::networks::{
+    EndpointSettings, Ipam, NetworkConfig, NetworkDriver, Networks,
+};
+
+impl Networks for MockClient {}
+
+#[test]
+fn test_create_network_with_subnet() {
+    let client = MockClient::new(vec![http_response(
+        201,
+        "{\"Id\":\"22be93d5babb\",\"Warning\":\"\"}",
+    )]);
+
+    let config = NetworkConfig {
+        name: "backend".to_string(),
+        driver: NetworkDriver::Bridge,
+        ipam: Some(Ipam::with_subnet("172.28.0.0/16", Some("172.28.0.1"))),
+        ..Default::default()
+    };
+
+    let resp = client.create_network(config).unwrap();
+    assert_eq!(resp.id, "22be93d5babb");
+
+    assert_eq!(
+        client.request_body(0)["IPAM"],
+        serde_json::json!({
+            "Driver": "default",
+            "Config": [{"Subnet": "172.28.0.0/16", "Gateway": "172.28.0.1"}]
+        })
+    );
+}
+
+#[test]
+fn test_connect_container_with_endpoint_settings() {
+    let client = MockClient::new(vec![http_response(200, "")]);
+
+    let endpoint = EndpointSettings::default()
+        .alias("db")
+        .link("cache:redis")
+        .ipv4_address("172.28.0.5");
+    client
+        .connect_container_to_network("backend", "f808ca", Some(endpoint))
+        .unwrap();
+
+    assert_eq!(
+        client.request_line(0),
+        "POST /networks/backend/connect HTTP/1.1"
+    );
+    assert_eq!(
+        client.request_body(0),
+        serde_json::json!({
+            "Container": "f808ca",
+            "EndpointConfig": {
+                "IPAMConfig": {"IPv4Address": "172.28.0.5"},
+                "Links": ["cache:redis"],
+                "Aliases": ["db"]
+            }
+        })
+    );
+}
+
+#[test]
+fn test_network_config_macvlan_options() {
+    let mut options = HashMap::new();
+    options.insert("parent".to_string(), "eth0.10".to_string());
+
+    let config = NetworkConfig {
+        name: "vlan10".to_string(),
+        driver: NetworkDriver::Macvlan,
+        internal: true,
+        options: Some(options),
+        ..Default::default()
+    };
+
+    let body = serde_json::to_value(&config).unwrap();
+    assert_eq!(body["Driver"], "macvlan");
+    assert_eq!(body["Internal"], true);
+    assert_eq!(body["Options"]["parent"], "eth0.10");
+
+    let driver: NetworkDriver = serde_json::from_str("\"weave\"").unwrap();
+    assert_eq!(driver, NetworkDriver::Custom("weave".to_string()));
+}
+
+#[test]
+fn test_inspect_network_containers() {
+    let client = MockClient::new(vec![http_response(
+        200,
+        r#"{"Name": "backend", "Id": "7d86d31b1478", "Driver": "bridge",
+            "Containers": {
+                "19a4d5d687db": {"Name": "db",
+                    "EndpointID": "628cadb8bcb9",
+                    "MacAddress": "02:42:ac:1c:00:05",
+                    "IPv4Address": "172.28.0.5/16", "IPv6Address": ""}
+            }}"#,
+    )]);
+
+    let network = client.inspect_network("backend").unwrap();
+
+    let db = &network.containers["19a4d5d687db"];
+    assert_eq!(db.name, "db");
+    assert_eq!(db.endpoint_id, "628cadb8bcb9");
+    assert_eq!(db.mac_address, "02:42:ac:1c:00:05");
+    assert_eq!(db.ipv4_address, "172.28.0.5/16");
+    assert_eq!(db.ipv6_address, "");
+}
+
+#[test]
+fn test_containers_on_network() {
+    let client = MockClient::new(vec![http_response(
+        200,
+        r#"[{"Id": "19a4d5d687db", "Names": ["/db"], "Image": "postgres:16",
+            "ImageID": "sha256:2ca7", "Command": "postgres",
+            "State": "running", "Status": "Up 2 hours",
+            "HostConfig": {"NetworkMode": "backend"},
+            "NetworkSettings": {"Networks": {"backend": {
+                "IPAddress": "172.28.0.5", "MacAddress": "02:42:ac:1c:00:05"}}}}]"#,
+    )]);
+
+    let containers = client.containers_on_network("backend").unwrap();
+
+    assert_eq!(containers.len(), 1);
+    assert_eq!(containers[0].name(), Some("db"));
+    assert!(containers[0]
+        .network_settings
+        .networks
+        .contains_key("backend"));
+    assert_eq!(
+        client.request_line(0),
+        "GET /containers/json?all=true&filters=%7B%22network%22%3A%5B%22backend%22%5D%7D HTTP/1.1"
+    );
+}
+
+#[test]
+fn test_owned_network_is_removed_when_dropped() {
+    let client = MockClient::new(vec![
+        http_response(201, r#"{"Id": "7d86d31b1478", "Warning": ""}"#),
+        http_response(204, ""),
+    ]);
+
+    {
+        let config = NetworkConfig {
+            name: "backend".to_string(),
+            ..Default::default()
+        };
+        let network = client.create_network_owned(config).unwrap();
+        assert_eq!(network.id(), "7d86d31b1478");
+    }
+
+    assert_eq!(
+        client.request_line(1),
+        "DELETE /networks/7d86d31b1478 HTTP/1.1"
+    );
+}
@@ -0,0 +1,26 @@
+extern crate rust_docker;
+
+use rust_docker::api::platform::Platform;
+
+#[test]
+fn test_parse_platform() {
+    let platform = Platform::parse("linux/arm64").unwrap();
+    assert_eq!(platform, Platform::new("linux", "arm64"));
+    assert_eq!(platform.variant, None);
+
+    let platform: Platform = "Linux/ARM/v7".parse().unwrap();
+    assert_eq!(platform, Platform::new("linux", "arm").variant("v7"));
+    assert_eq!(platform.to_string(), "linux/arm/v7");
+
+    assert_eq!(
+        Platform::parse("linux/x86_64").unwrap(),
+        Platform::parse("linux/amd64").unwrap()
+    );
+}
+
+#[test]
+fn test_parse_invalid_platform() {
+    for platform in &["linux", "", "linux/", "/amd64", "linux/arm/v7/extra"] {
+        assert!(Platform::parse(platform).is_err(), "{}", platform);
+    }
+}
@@ -0,0 +1,93 @@
+extern crate rust_docker;
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::thread;
+
+use rust_docker::api::version::Version;
+use rust_docker::client::DockerClient;
+use rust_docker::proxy::ProxyConfig;
+
+/// Starts a fake proxy which accepts one tunnel and answers the request
+/// sent through it like the daemon, returns its address and the CONNECT
+/// request it got.
+fn start_proxy() -> (String, thread::JoinHandle<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+
+    let handle = thread::spawn(move || {
+        let (conn, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(conn.try_clone().unwrap());
+        let mut connect = String::new();
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            connect.push_str(&line);
+            if line == "\r\n" {
+                break;
+            }
+        }
+        let mut conn = conn;
+        conn.write_all(b"HTTP/1.1 200 Connection established\r\n\r\n")
+            .unwrap();
+
+        // The tunnelled request.
+        let mut buf = [0; 1024];
+        let _ = reader.read(&mut buf).unwrap();
+        conn.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\n{}")
+            .unwrap();
+
+        connect
+    });
+
+    (addr, handle)
+}
+
+#[test]
+fn test_no_proxy_matching() {
+    let config = ProxyConfig {
+        http_proxy: Some("http://proxy:3128".to_string()),
+        https_proxy: None,
+        no_proxy: vec![
+            "localhost".to_string(),
+            ".internal.example.com".to_string(),
+            "10.0.0.5:2375".to_string(),
+        ],
+    };
+
+    assert_eq!(
+        config.proxy_for("docker.example.com", false),
+        Some("http://proxy:3128")
+    );
+    assert_eq!(config.proxy_for("docker.example.com", true), None);
+    assert_eq!(config.proxy_for("localhost", false), None);
+    assert_eq!(config.proxy_for("build.internal.example.com", false), None);
+    assert_eq!(config.proxy_for("10.0.0.5", false), None);
+
+    let config = ProxyConfig {
+        no_proxy: vec!["*".to_string()],
+        ..config
+    };
+    assert_eq!(config.proxy_for("docker.example.com", false), None);
+}
+
+#[test]
+fn test_client_connects_through_proxy() {
+    let (proxy_addr, proxy) = start_proxy();
+    let config = ProxyConfig {
+        http_proxy: Some(format!("http://user:secret@{}", proxy_addr)),
+        ..Default::default()
+    };
+
+    let client =
+        DockerClient::with_proxy("tcp://docker.example.com:2375", config)
+            .unwrap();
+
+    assert_eq!(client.get_version_info().unwrap(), "{}");
+    let connect = proxy.join().unwrap();
+    assert!(connect.starts_with(
+        "CONNECT docker.example.com:2375 HTTP/1.1\r\nHost: docker.example.com:2375\r\n"
+    ));
+    // base64 of user:secret
+    assert!(connect.contains("Proxy-Authorization: Basic dXNlcjpzZWNyZXQ=\r\n"));
+}
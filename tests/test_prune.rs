@@ -0,0 +1,121 @@
+#![cfg(all(
+    feature = "build",
+    feature = "containers",
+    feature = "images",
+    feature = "networks",
+    feature = "volumes"
+))]
+extern crate rust_docker;
+extern crate serde_json;
+
+mod common;
+
+use std::time::Duration;
+
+use common::{http_response, MockClient};
+
+use rust_docker::api::build::Build;
+use rust_docker::api::containers::Containers;
+use rust_docker::api::images::Images;
+use rust_docker::api::networks::Networks;
+use rust_docker::api::volumes::Volumes;
+use rust_docker::api::PruneFilters;
+use rust_docker::prune::{system_prune, SystemPruneOptions};
+
+impl Build for MockClient {}
+impl Containers for MockClient {}
+impl Images for MockClient {}
+impl Networks for MockClient {}
+impl Volumes for MockClient {}
+
+#[test]
+fn test_prune_filters_query() {
+    let client = MockClient::new(vec![
+        http_response(
+            200,
+            r#"{"ContainersDeleted": null, "SpaceReclaimed": 0}"#,
+        ),
+        http_response(200, r#"{"ImagesDeleted": null, "SpaceReclaimed": 0}"#),
+    ]);
+    let filters = PruneFilters::new()
+        .until(Duration::from_secs(3600))
+        .exclude_label("keep");
+
+    let report = client.prune_containers(&filters).unwrap();
+    client.prune_images(&PruneFilters::new(), true).unwrap();
+
+    assert!(report.containers_deleted.is_empty());
+    assert_eq!(
+        client.request_line(0),
+        "POST /containers/prune?filters=%7B%22label%21%22%3A%5B%22keep%22%5D%2C%22until%22%3A%5B%223600s%22%5D%7D HTTP/1.1"
+    );
+    assert_eq!(
+        client.request_line(1),
+        "POST /images/prune?filters=%7B%22dangling%22%3A%5B%22false%22%5D%7D HTTP/1.1"
+    );
+}
+
+#[test]
+fn test_system_prune() {
+    let client = MockClient::new(vec![
+        http_response(
+            200,
+            r#"{"ContainersDeleted": ["4fa6e0f0c678"], "SpaceReclaimed": 100}"#,
+        ),
+        http_response(200, r#"{"NetworksDeleted": ["ci_default"]}"#),
+        http_response(
+            200,
+            r#"{"VolumesDeleted": ["f5e1d2"], "SpaceReclaimed": 20}"#,
+        ),
+        http_response(
+            200,
+            r#"{"ImagesDeleted": [{"Untagged": "app:old"},
+                {"Deleted": "sha256:2ca7"}], "SpaceReclaimed": 3000}"#,
+        ),
+        http_response(
+            200,
+            r#"{"CachesDeleted": ["ybsmqt"], "SpaceReclaimed": 400}"#,
+        ),
+    ]);
+    let opts = SystemPruneOptions::new()
+        .volumes(true)
+        .until(Duration::from_secs(60));
+
+    let report = system_prune(&client, &opts).unwrap();
+
+    assert_eq!(report.containers.containers_deleted.len(), 1);
+    assert_eq!(report.networks.networks_deleted, vec!["ci_default"]);
+    assert_eq!(report.volumes.unwrap().volumes_deleted, vec!["f5e1d2"]);
+    assert_eq!(report.images.images_deleted.len(), 2);
+    assert_eq!(report.build_cache.caches_deleted, vec!["ybsmqt"]);
+    let until = "filters=%7B%22until%22%3A%5B%2260s%22%5D%7D";
+    assert_eq!(
+        client.request_line(1),
+        format!("POST /networks/prune?{} HTTP/1.1", until)
+    );
+    // The daemon refuses `until` for the volumes.
+    assert_eq!(client.request_line(2), "POST /volumes/prune HTTP/1.1");
+    assert_eq!(
+        client.request_line(4),
+        format!("POST /build/prune?all=false&{} HTTP/1.1", until)
+    );
+}
+
+#[test]
+fn test_system_prune_reclaimed_space() {
+    let client = MockClient::new(vec![
+        http_response(
+            200,
+            r#"{"ContainersDeleted": [], "SpaceReclaimed": 100}"#,
+        ),
+        http_response(200, r#"{"NetworksDeleted": null}"#),
+        http_response(200, r#"{"ImagesDeleted": [], "SpaceReclaimed": 3000}"#),
+        http_response(200, r#"{"CachesDeleted": [], "SpaceReclaimed": 400}"#),
+    ]);
+
+    let report = system_prune(&client, &SystemPruneOptions::new()).unwrap();
+
+    assert!(report.volumes.is_none());
+    assert_eq!(report.space_reclaimed(), 3500);
+    assert_eq!(client.request_line(2), "POST /images/prune HTTP/1.1");
+}
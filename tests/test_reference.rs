@@ -0,0 +1,42 @@
+extern crate rust_docker;
+
+use rust_docker::api::reference::Reference;
+
+#[test]
+fn test_reference_normalization() {
+    let reference = Reference::parse("nginx").unwrap();
+    assert_eq!(reference.registry, "docker.io");
+    assert_eq!(reference.namespace, "library");
+    assert_eq!(reference.repo, "nginx");
+    assert_eq!(reference.tag, Some("latest".to_string()));
+    assert_eq!(reference.to_string(), "docker.io/library/nginx:latest");
+
+    let reference: Reference =
+        "localhost:5000/team/app/web:1.2".parse().unwrap();
+    assert_eq!(reference.registry, "localhost:5000");
+    assert_eq!(reference.namespace, "team/app");
+    assert_eq!(reference.familiar_name(), "localhost:5000/team/app/web");
+
+    let digest =
+        "sha256:7cc4b5aefd1d0cadf8d97d4350462ba51c694ebca145b08d7d41b41acc8db5aa";
+    let reference =
+        Reference::parse(&format!("quay.io/coreos/etcd@{}", digest)).unwrap();
+    assert_eq!(reference.tag, None);
+    assert_eq!(reference.tag_or_digest(), digest);
+    assert_eq!(reference.familiar_name(), "quay.io/coreos/etcd");
+}
+
+#[test]
+fn test_invalid_references() {
+    for reference in &[
+        "",
+        "Nginx",
+        "nginx:",
+        "nginx:-tag",
+        "my..repo",
+        "repo@sha256:xyz",
+        "registry.io/",
+    ] {
+        assert!(Reference::parse(reference).is_err(), "{}", reference);
+    }
+}
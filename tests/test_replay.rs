@@ -0,0 +1,106 @@
+#![cfg(all(feature = "logs", feature = "volumes"))]
+extern crate rust_docker;
+extern crate serde_json;
+
+mod common;
+
+use std::env;
+use std::fs;
+use std::process;
+
+use common::{chunked_http_response, http_response, MockClient};
+
+use rust_docker::api::logs::{Logs, LogsOptions};
+use rust_docker::api::volumes::Volumes;
+use rust_docker::api::DockerApiClient;
+use rust_docker::replay::{
+    Encoding, Fixture, Interaction, RecordingClient, ReplayClient,
+};
+
+#[test]
+fn test_record_and_replay() {
+    let volumes = "{\"Volumes\":[{\"Name\":\"data\",\"Driver\":\"local\",\
+                   \"Mountpoint\":\"/var/lib/docker/volumes/data\",\
+                   \"Labels\":null,\"Scope\":\"local\",\"Options\":null}],\
+                   \"Warnings\":null}";
+    let client = RecordingClient::new(MockClient::new(vec![
+        http_response(200, volumes),
+        chunked_http_response(200, &["first\nsecond\n"]),
+    ]));
+
+    let recorded = client.list_volumes().unwrap();
+    let options = LogsOptions::default();
+    let recorded_logs: Vec<String> = client
+        .get_container_logs("web", &options)
+        .unwrap()
+        .map(|line| line.unwrap().message)
+        .collect();
+
+    let path = env::temp_dir()
+        .join(format!("docker-rs-replay-{}.json", process::id()));
+    client.save(&path).unwrap();
+
+    let replay = ReplayClient::load(&path).unwrap();
+    let _ = fs::remove_file(&path);
+
+    assert!(!replay.is_exhausted());
+    let replayed = replay.list_volumes().unwrap();
+    assert_eq!(replayed.len(), 1);
+    assert_eq!(replayed[0].name, recorded[0].name);
+    let replayed_logs: Vec<String> = replay
+        .get_container_logs("web", &options)
+        .unwrap()
+        .map(|line| line.unwrap().message)
+        .collect();
+    assert_eq!(replayed_logs, recorded_logs);
+    assert_eq!(replayed_logs, vec!["first", "second"]);
+    assert!(replay.is_exhausted());
+
+    // Nothing left to answer the same request with.
+    assert!(replay.list_volumes().is_err());
+}
+
+#[test]
+fn test_replay_base64_response() {
+    let request = "GET /containers/web/export HTTP/1.1\r\nHost: v1.37\r\n\r\n";
+    let replay = ReplayClient::new(Fixture {
+        interactions: vec![Interaction {
+            request: request.to_string(),
+            response: "SFRUUC8xLjEgMjAwIE9LDQpDb250ZW50LUxlbmd0aDogMQ0KDQr/"
+                .to_string(),
+            encoding: Encoding::Base64,
+        }],
+    });
+
+    assert!(replay.request("GET /volumes HTTP/1.1\r\n\r\n").is_none());
+    assert_eq!(
+        replay.request(request).unwrap(),
+        b"HTTP/1.1 200 OK\r\nContent-Length: 1\r\n\r\n\xff".to_vec()
+    );
+    assert!(replay.is_exhausted());
+}
+
+#[test]
+fn test_record_redacts_registry_headers() {
+    let push = |auth: &str| {
+        format!(
+            "POST /images/app/push HTTP/1.1\r\nHost: v1.37\r\n\
+             X-Registry-Auth: {}\r\nx-registry-config: {}\r\n\r\n",
+            auth, auth
+        )
+    };
+    let client =
+        RecordingClient::new(MockClient::new(vec![http_response(200, "{}")]));
+    client.request(&push("c2VjcmV0")).unwrap();
+
+    let fixture = client.fixture();
+    assert_eq!(
+        fixture.interactions[0].request,
+        "POST /images/app/push HTTP/1.1\r\nHost: v1.37\r\n\
+         X-Registry-Auth: <redacted>\r\nx-registry-config: <redacted>\r\n\r\n"
+    );
+
+    let replay = ReplayClient::new(fixture);
+    assert!(replay.request(&push("b3RoZXI=")).is_some());
+    assert!(replay.is_exhausted());
+}
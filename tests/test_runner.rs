@@ -0,0 +1,114 @@
+#![cfg(all(feature = "containers", feature = "exec", feature = "logs"))]
+extern crate rust_docker;
+extern crate serde_json;
+
+mod common;
+
+use common::{frame, http_response, MockClient};
+
+use rust_docker::api::containers::Containers;
+use rust_docker::api::exec::Exec;
+use rust_docker::api::logs::StreamType;
+use rust_docker::runner::{RunSpec, Runner};
+
+impl Containers for MockClient {}
+impl Exec for MockClient {}
+
+/// Responses to create an exec, stream its output and inspect it.
+fn step(id: &str, output: &str, exit_code: i64) -> Vec<String> {
+    vec![
+        http_response(201, &format!("{{\"Id\":\"{}\"}}", id)),
+        format!("HTTP/1.1 200 OK\r\n\r\n{}", output),
+        http_response(
+            200,
+            &format!(
+                "{{\"ID\":\"{}\",\"Running\":false,\"ExitCode\":{}}}",
+                id, exit_code
+            ),
+        ),
+    ]
+}
+
+#[test]
+fn test_runner_streams_steps() {
+    let mut responses = vec![
+        http_response(201, r#"{"Id":"4fa6e0f0c678","Warnings":[]}"#),
+        http_response(204, ""),
+    ];
+    responses.extend(step("e1", &frame(1, "compiled\n"), 0));
+    responses.extend(step("e2", &frame(2, "1 failed\n"), 101));
+    responses.push(http_response(204, ""));
+    let client = MockClient::new(responses);
+
+    let spec = RunSpec::new("rust:1.70")
+        .step("build", &["cargo", "build"])
+        .step("test", &["cargo", "test"])
+        .step("publish", &["cargo", "publish"])
+        .env("CI", "true")
+        .working_dir("/src");
+
+    let mut output: Vec<(String, StreamType, String)> = Vec::new();
+    let mut done: Vec<String> = Vec::new();
+    let result = Runner::new(&spec)
+        .on_output(|step, stream, payload| {
+            output.push((
+                step.to_string(),
+                stream,
+                String::from_utf8_lossy(payload).to_string(),
+            ))
+        })
+        .on_step(|step| done.push(step.name.clone()))
+        .run(&client)
+        .unwrap();
+
+    assert!(!result.success());
+    assert_eq!(result.container_id, "4fa6e0f0c678");
+    let exit_codes: Vec<Option<i64>> =
+        result.steps.iter().map(|step| step.exit_code).collect();
+    assert_eq!(exit_codes, vec![Some(0), Some(101), None]);
+    assert_eq!(done, vec!["build", "test", "publish"]);
+    assert_eq!(
+        output,
+        vec![
+            (
+                "build".to_string(),
+                StreamType::Stdout,
+                "compiled\n".to_string()
+            ),
+            (
+                "test".to_string(),
+                StreamType::Stderr,
+                "1 failed\n".to_string()
+            ),
+        ]
+    );
+
+    let create = client.request_body(0);
+    assert_eq!(create["Image"], "rust:1.70");
+    assert_eq!(create["Entrypoint"], serde_json::json!(["tail"]));
+    assert_eq!(create["WorkingDir"], "/src");
+    let exec = client.request_body(2);
+    assert_eq!(exec["Cmd"], serde_json::json!(["cargo", "build"]));
+    assert_eq!(exec["Env"], serde_json::json!(["CI=true"]));
+    assert_eq!(
+        client.request_line(8),
+        "DELETE /containers/4fa6e0f0c678?force=true&v=true HTTP/1.1"
+    );
+}
+
+#[test]
+fn test_runner_removes_container_on_error() {
+    let client = MockClient::new(vec![
+        http_response(201, r#"{"Id":"4fa6e0f0c678"}"#),
+        http_response(204, ""),
+        http_response(404, r#"{"message":"No such container"}"#),
+        http_response(204, ""),
+    ]);
+
+    let spec = RunSpec::new("alpine").step("ls", &["ls"]);
+    assert!(Runner::new(&spec).run(&client).is_err());
+    assert_eq!(
+        client.request_line(3),
+        "DELETE /containers/4fa6e0f0c678?force=true&v=true HTTP/1.1"
+    );
+}
@@ -0,0 +1,90 @@
+#![cfg(feature = "images")]
+extern crate rust_docker;
+extern crate serde_json;
+
+mod common;
+
+use common::{http_response, MockClient};
+
+use rust_docker::api::images::Images;
+use rust_docker::errors::DockerApiError;
+use rust_docker::snapshot::{snapshot_and_push, SnapshotOptions};
+
+impl Images for MockClient {}
+
+const PUSH_PROGRESS: &str = "{\"status\":\"Pushing\",\"id\":\"4fa6e0f0c678\"}\n\
+    {\"status\":\"golden: digest: sha256:9f3c size: 528\"}\n\
+    {\"progressDetail\":{},\"aux\":{\"Tag\":\"golden\",\"Digest\":\"sha256:9f3c\",\"Size\":528}}\n";
+
+#[test]
+fn test_snapshot_and_push() {
+    let client = MockClient::new(vec![
+        http_response(201, r#"{"Id":"sha256:4fa6e0f0c678"}"#),
+        http_response(201, ""),
+        http_response(200, PUSH_PROGRESS),
+        http_response(
+            200,
+            r#"[{"Untagged":"registry.example.com/app:golden"}]"#,
+        ),
+    ]);
+
+    let opts = SnapshotOptions::new()
+        .comment("golden image")
+        .pause(false)
+        .change("CMD [\"app\"]")
+        .remove_local(true);
+    let snapshot = snapshot_and_push(
+        &client,
+        "builder",
+        "registry.example.com/app:golden",
+        None,
+        &opts,
+    )
+    .unwrap();
+
+    assert_eq!(snapshot.image_id.as_str(), "sha256:4fa6e0f0c678");
+    assert_eq!(snapshot.digest, Some("sha256:9f3c".to_string()));
+    assert_eq!(snapshot.progress.len(), 3);
+    assert_eq!(
+        client.request_line(0),
+        "POST /commit?container=builder&comment=golden%20image&pause=false\
+         &changes=CMD%20%5B%22app%22%5D HTTP/1.1"
+    );
+    assert_eq!(
+        client.request_line(1),
+        "POST /images/sha256:4fa6e0f0c678/tag?repo=registry.example.com/app&tag=golden HTTP/1.1"
+    );
+    assert_eq!(
+        client.request_line(2),
+        "POST /images/registry.example.com/app/push?tag=golden HTTP/1.1"
+    );
+    assert_eq!(
+        client.request_line(3),
+        "DELETE /images/registry.example.com/app:golden?force=false HTTP/1.1"
+    );
+}
+
+#[test]
+fn test_snapshot_keeps_the_image_when_the_push_fails() {
+    let client = MockClient::new(vec![
+        http_response(201, r#"{"Id":"sha256:4fa6e0f0c678"}"#),
+        http_response(201, ""),
+        http_response(200, "{\"error\":\"denied: requested access to the resource is denied\"}\n"),
+    ]);
+
+    let opts = SnapshotOptions::new().remove_local(true);
+    match snapshot_and_push(&client, "builder", "app:golden", None, &opts) {
+        Err(DockerApiError::ImageError(msg)) => assert!(msg.contains("denied")),
+        res => panic!("{:?}", res),
+    }
+    assert_eq!(client.requests.borrow().len(), 3);
+
+    let client = MockClient::new(vec![http_response(
+        404,
+        r#"{"message":"No such container: builder"}"#,
+    )]);
+    match snapshot_and_push(&client, "builder", "app:golden", None, &opts) {
+        Err(DockerApiError::NoSuchContainer(_)) => (),
+        res => panic!("{:?}", res),
+    }
+}
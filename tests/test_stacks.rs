@@ -0,0 +1,159 @@
+#![cfg(all(feature = "networks", feature = "secrets", feature = "services"))]
+extern crate rust_docker;
+extern crate serde_json;
+
+mod common;
+
+use common::{http_response, MockClient};
+
+use rust_docker::api::networks::Networks;
+use rust_docker::api::secrets::{SecretSpec, Secrets};
+use rust_docker::api::services::{ServiceSpec, Services};
+use rust_docker::errors::DockerApiError;
+use rust_docker::stacks::{deploy_stack, remove_stack, Stack};
+
+impl Networks for MockClient {}
+impl Secrets for MockClient {}
+impl Services for MockClient {}
+
+fn shop_stack() -> Stack {
+    Stack::new("shop")
+        .network("frontend")
+        .secret(SecretSpec::new("db_password", b"hunter2"))
+        .service(
+            ServiceSpec::new("web", "nginx:1.25")
+                .replicas(2)
+                .network("frontend")
+                .secret("db_password"),
+        )
+        .service(ServiceSpec::new("worker", "shop/worker:1.0"))
+}
+
+#[test]
+fn test_deploy_stack() {
+    let client = MockClient::new(vec![
+        http_response(200, r#"[{"Name":"bridge","Id":"5e1f"}]"#),
+        http_response(201, r#"{"Id":"7b3a","Warning":""}"#),
+        http_response(200, "[]"),
+        http_response(201, r#"{"ID":"ktnbjxoalbkvbvedmg1urrz8h"}"#),
+        http_response(
+            200,
+            r#"[{"ID":"9mnpnzenvg8p8tdbtq4wvbkcz","Version":{"Index":19},
+            "Spec":{"Name":"shop_worker"}},
+            {"ID":"3hd8cjpgc1pd","Version":{"Index":7},
+            "Spec":{"Name":"shop_admin"}}]"#,
+        ),
+        http_response(201, r#"{"ID":"ak7w3gjqoa3kuz8xcpnyy0pvl"}"#),
+        http_response(200, "{}"),
+        http_response(200, ""),
+    ]);
+
+    let report = deploy_stack(&client, &shop_stack()).unwrap();
+
+    assert_eq!(report.networks_created, vec!["shop_frontend"]);
+    assert_eq!(report.secrets_created, vec!["shop_db_password"]);
+    assert_eq!(report.created, vec!["shop_web"]);
+    assert_eq!(report.updated, vec!["shop_worker"]);
+    assert_eq!(report.removed, vec!["shop_admin"]);
+
+    assert_eq!(client.request_line(1), "POST /networks/create HTTP/1.1");
+    let network = client.request_body(1);
+    assert_eq!(network["Name"], "shop_frontend");
+    assert_eq!(network["Driver"], "overlay");
+    assert_eq!(network["Labels"]["com.docker.stack.namespace"], "shop");
+
+    let secret = client.request_body(3);
+    assert_eq!(secret["Name"], "shop_db_password");
+    assert_eq!(secret["Data"], "aHVudGVyMg==");
+
+    assert_eq!(
+        client.request_line(4),
+        "GET /services?filters=%7B%22label%22%3A%5B%22\
+         com.docker.stack.namespace%3Dshop%22%5D%7D HTTP/1.1"
+    );
+
+    assert_eq!(client.request_line(5), "POST /services/create HTTP/1.1");
+    let web = client.request_body(5);
+    assert_eq!(web["Name"], "shop_web");
+    assert_eq!(web["Labels"]["com.docker.stack.namespace"], "shop");
+    assert_eq!(web["Mode"]["Replicated"]["Replicas"], 2);
+    assert_eq!(
+        web["TaskTemplate"]["Networks"][0]["Target"],
+        "shop_frontend"
+    );
+    let container = &web["TaskTemplate"]["ContainerSpec"];
+    assert_eq!(container["Labels"]["com.docker.stack.namespace"], "shop");
+    assert_eq!(container["Secrets"][0]["SecretName"], "shop_db_password");
+    assert_eq!(
+        container["Secrets"][0]["SecretID"],
+        "ktnbjxoalbkvbvedmg1urrz8h"
+    );
+
+    assert_eq!(
+        client.request_line(6),
+        "POST /services/9mnpnzenvg8p8tdbtq4wvbkcz/update?version=19 HTTP/1.1"
+    );
+    assert_eq!(client.request_body(6)["Name"], "shop_worker");
+    assert_eq!(
+        client.request_line(7),
+        "DELETE /services/3hd8cjpgc1pd HTTP/1.1"
+    );
+}
+
+#[test]
+fn test_deploy_stack_missing_secret() {
+    let client = MockClient::new(vec![
+        http_response(200, "[]"),
+        http_response(200, "[]"),
+        http_response(200, "[]"),
+    ]);
+    let stack = Stack::new("shop")
+        .service(ServiceSpec::new("web", "nginx").secret("tls_key"));
+
+    match deploy_stack(&client, &stack) {
+        Err(DockerApiError::ServiceError(msg)) => {
+            assert!(msg.contains("tls_key"))
+        }
+        other => panic!("unexpected result {:?}", other),
+    }
+    assert_eq!(client.requests.borrow().len(), 3);
+}
+
+#[test]
+fn test_remove_stack() {
+    let client = MockClient::new(vec![
+        http_response(
+            200,
+            r#"[{"ID":"9mnpnzenvg8p","Spec":{"Name":"shop_web"}}]"#,
+        ),
+        http_response(200, ""),
+        http_response(
+            200,
+            r#"[{"Name":"shop_frontend","Id":"7b3a",
+            "Labels":{"com.docker.stack.namespace":"shop"}},
+            {"Name":"bridge","Id":"5e1f","Labels":{}}]"#,
+        ),
+        http_response(204, ""),
+        http_response(
+            200,
+            r#"[{"ID":"ktnbjxoalbkv","Spec":{"Name":"shop_db_password"}}]"#,
+        ),
+        http_response(204, ""),
+    ]);
+
+    let report = remove_stack(&client, "shop").unwrap();
+
+    assert_eq!(
+        report.removed,
+        vec!["shop_web", "shop_frontend", "shop_db_password"]
+    );
+    assert_eq!(
+        client.request_line(1),
+        "DELETE /services/9mnpnzenvg8p HTTP/1.1"
+    );
+    assert_eq!(client.request_line(3), "DELETE /networks/7b3a HTTP/1.1");
+    assert_eq!(
+        client.request_line(5),
+        "DELETE /secrets/ktnbjxoalbkv HTTP/1.1"
+    );
+}
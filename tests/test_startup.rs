@@ -0,0 +1,242 @@
+#![cfg(all(feature = "containers", feature = "logs"))]
+extern crate rust_docker;
+extern crate serde_json;
+
+use std::io::{Cursor, Read};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use rust_docker::api::containers::{
+    ContainerDetails, ContainerState, Containers, HealthState,
+};
+use rust_docker::api::logs::Logs;
+use rust_docker::api::DockerApiClient;
+use rust_docker::startup::{
+    start_in_order, Readiness, StartupOutcome, StartupPlan,
+};
+use rust_docker::utils::StreamHandle;
+
+/// A client which can be shared by threads. The containers named `bad*`
+/// exit as soon as they are started, `sick*` become unhealthy and the
+/// others become healthy after being inspected once. The logs of every
+/// container are a single line with its name, until `quiet*` ones which
+/// write nothing.
+#[derive(Default)]
+struct StartupClient {
+    requests: Mutex<Vec<String>>,
+}
+
+impl StartupClient {
+    fn started(&self) -> Vec<String> {
+        self.requests
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|line| line.starts_with("POST"))
+            .map(|line| line.split('/').nth(2).unwrap().to_string())
+            .collect()
+    }
+}
+
+impl DockerApiClient for StartupClient {
+    fn request(&self, request: &str) -> Option<Vec<u8>> {
+        let line = request.lines().next().unwrap_or("").to_string();
+        let name = line.split('/').nth(2).unwrap_or("").to_string();
+        let inspections = {
+            let mut requests = self.requests.lock().unwrap();
+            requests.push(line.clone());
+            requests.iter().filter(|l| **l == line).count()
+        };
+
+        if line.starts_with("POST") {
+            return Some(b"HTTP/1.1 204 No Content\r\n\r\n".to_vec());
+        }
+
+        let health = if name.starts_with("sick") {
+            "unhealthy"
+        } else if inspections > 1 {
+            "healthy"
+        } else {
+            "starting"
+        };
+        let details = ContainerDetails {
+            state: ContainerState {
+                status: "running".to_string(),
+                running: !name.starts_with("bad"),
+                health: Some(HealthState {
+                    status: health.to_string(),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let body = serde_json::to_string(&details).unwrap();
+        Some(
+            format!(
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{}",
+                body.len(),
+                body
+            )
+            .into_bytes(),
+        )
+    }
+
+    fn stream_request(
+        &self,
+        request: &str,
+        _handle: &StreamHandle,
+    ) -> Option<Box<dyn Read + Send>> {
+        let line = request.lines().next().unwrap_or("").to_string();
+        let name = line.split('/').nth(2).unwrap_or("").to_string();
+        self.requests.lock().unwrap().push(line);
+
+        let mut body = Vec::new();
+        if !name.starts_with("quiet") {
+            let message = format!("Listening as {}\n", name);
+            body.extend_from_slice(&[1, 0, 0, 0]);
+            body.extend_from_slice(&(message.len() as u32).to_be_bytes());
+            body.extend_from_slice(message.as_bytes());
+        }
+        let mut response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n",
+            body.len()
+        )
+        .into_bytes();
+        response.extend(body);
+        Some(Box::new(Cursor::new(response)))
+    }
+}
+
+impl Containers for StartupClient {}
+impl Logs for StartupClient {}
+
+#[test]
+fn test_plan_order() {
+    let plan = StartupPlan::new()
+        .node("web", Readiness::Running, &["db", "cache"])
+        .node("cache", Readiness::Running, &[])
+        .node("db", Readiness::Running, &[]);
+
+    assert_eq!(plan.order().unwrap(), vec!["cache", "db", "web"]);
+
+    let unknown = StartupPlan::new().node("web", Readiness::Running, &["db"]);
+    assert!(unknown.order().is_err());
+
+    let cycle = StartupPlan::new()
+        .node("a", Readiness::Running, &["b"])
+        .node("b", Readiness::Running, &["a"])
+        .node("c", Readiness::Running, &[]);
+    let err = cycle.order().unwrap_err().to_string();
+    assert!(err.contains("a, b"));
+}
+
+#[test]
+fn test_start_in_order() {
+    let client = StartupClient::default();
+    let plan = StartupPlan::new()
+        .node("web", Readiness::Healthy, &["db", "cache"])
+        .node("db", Readiness::Healthy, &[])
+        .node("cache", Readiness::Running, &[])
+        .poll_interval(Duration::from_millis(1));
+
+    let results = start_in_order(&client, &plan).unwrap();
+
+    let names: Vec<&str> = results.iter().map(|r| r.name.as_str()).collect();
+    assert_eq!(names, vec!["web", "db", "cache"]);
+    for result in &results {
+        match result.outcome {
+            StartupOutcome::Ready(_) => (),
+            ref outcome => panic!("{} : {:?}", result.name, outcome),
+        }
+    }
+    let started = client.started();
+    assert_eq!(started.len(), 3);
+    assert_eq!(started[2], "web");
+}
+
+#[test]
+fn test_start_in_order_skips_dependents_of_failures() {
+    let client = StartupClient::default();
+    let plan = StartupPlan::new()
+        .node("bad-db", Readiness::Running, &[])
+        .node("sick-cache", Readiness::Healthy, &[])
+        .node("web", Readiness::Running, &["bad-db"])
+        .node("worker", Readiness::Running, &["web", "sick-cache"])
+        .node("metrics", Readiness::Running, &[])
+        .poll_interval(Duration::from_millis(1));
+
+    let results = start_in_order(&client, &plan).unwrap();
+
+    let outcome =
+        |name: &str| &results.iter().find(|r| r.name == name).unwrap().outcome;
+    assert!(matches!(*outcome("bad-db"), StartupOutcome::Failed(_)));
+    assert!(matches!(*outcome("sick-cache"), StartupOutcome::Failed(_)));
+    assert!(matches!(*outcome("web"), StartupOutcome::Skipped));
+    assert!(matches!(*outcome("worker"), StartupOutcome::Skipped));
+    assert!(matches!(*outcome("metrics"), StartupOutcome::Ready(_)));
+
+    let mut started = client.started();
+    started.sort();
+    assert_eq!(started, vec!["bad-db", "metrics", "sick-cache"]);
+}
+
+#[test]
+fn test_start_in_order_rejects_invalid_plan() {
+    let client = StartupClient::default();
+    let plan = StartupPlan::new().node("web", Readiness::Running, &["db"]);
+
+    assert!(start_in_order(&client, &plan).is_err());
+    assert!(client.requests.lock().unwrap().is_empty());
+}
+
+#[test]
+fn test_start_in_order_follows_logs() {
+    let client = StartupClient::default();
+    let plan = StartupPlan::new()
+        .node("web", Readiness::LogContains("Listening".to_string()), &[])
+        .node(
+            "quiet",
+            Readiness::LogContains("Listening".to_string()),
+            &[],
+        )
+        .poll_interval(Duration::from_millis(1))
+        .timeout(Duration::from_millis(50));
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+
+    let results = start_in_order(&client, &plan).unwrap();
+
+    assert!(matches!(results[0].outcome, StartupOutcome::Ready(_)));
+    match results[1].outcome {
+        StartupOutcome::Failed(ref err) => {
+            assert!(err.to_string().contains("quiet was not ready"))
+        }
+        ref outcome => panic!("{:?}", outcome),
+    }
+
+    let requests = client.requests.lock().unwrap();
+    let follows: Vec<&String> = requests
+        .iter()
+        .filter(|line| line.starts_with("GET /containers/web/logs"))
+        .collect();
+    assert_eq!(follows.len(), 1);
+    assert!(follows[0].contains("follow=true&"));
+    let since: u64 = follows[0]
+        .split("since=")
+        .nth(1)
+        .and_then(|rest| rest.split(['&', ' ']).next())
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert!(since >= now && since <= now + 1);
+    assert!(
+        requests
+            .iter()
+            .filter(|line| line.starts_with("GET /containers/quiet/logs"))
+            .count()
+            > 1
+    );
+}
@@ -0,0 +1,146 @@
+#![cfg(feature = "stats")]
+extern crate rust_docker;
+extern crate serde_json;
+
+mod common;
+
+use common::{chunked_http_response, http_response, MockClient};
+
+use rust_docker::api::stats::{Stats, UsageTracker};
+use rust_docker::api::version::ApiVersion;
+
+impl Stats for MockClient {}
+
+fn sample(id: &str, read: &str, usage: u64) -> String {
+    format!(
+        "{{\"id\":\"{}\",\"read\":\"{}\",\"memory_stats\":{{\"usage\":{}}}}}\n",
+        id, read, usage
+    )
+}
+
+#[test]
+fn test_stats_for_merges_samples() {
+    let client = MockClient::new(vec![
+        chunked_http_response(
+            200,
+            &[&sample("web", "2018-05-01T10:00:01Z", 1)],
+        ),
+        chunked_http_response(200, &[&sample("db", "2018-05-01T10:00:02Z", 2)]),
+    ]);
+
+    let snapshots: Vec<_> = client
+        .stats_for(&["web", "db"])
+        .unwrap()
+        .map(|s| s.unwrap())
+        .collect();
+
+    assert_eq!(
+        client.request_line(1),
+        "GET /containers/db/stats?stream=true HTTP/1.1"
+    );
+    assert_eq!(snapshots.len(), 1);
+    assert_eq!(snapshots[0].read, "2018-05-01T10:00:02Z");
+
+    let ids: Vec<&str> =
+        snapshots[0].stats.iter().map(|s| s.id.as_str()).collect();
+    assert_eq!(ids, vec!["web", "db"]);
+    assert_eq!(snapshots[0].stats[1].memory_stats.usage, 2);
+}
+
+fn usage_sample(read: &str, cpu: u64, system: u64, rx: u64) -> String {
+    let stats = serde_json::json!({
+        "read": read,
+        "cpu_stats": {
+            "cpu_usage": {"total_usage": cpu},
+            "system_cpu_usage": system,
+            "online_cpus": 2
+        },
+        "memory_stats": {
+            "usage": 500,
+            "limit": 1000,
+            "stats": {"inactive_file": 100}
+        },
+        "networks": {
+            "eth0": {"rx_bytes": rx, "tx_bytes": 10},
+            "eth1": {"rx_bytes": rx, "tx_bytes": 10}
+        }
+    });
+    http_response(200, &stats.to_string())
+}
+
+#[test]
+fn test_usage_snapshot() {
+    let client = MockClient::new(vec![
+        usage_sample("2018-05-01T10:00:00Z", 1000, 10000, 100),
+        usage_sample("2018-05-01T10:00:02Z", 1500, 12000, 300),
+        usage_sample("2018-05-01T10:00:03Z", 100, 13000, 10),
+    ]);
+    let mut tracker = UsageTracker::new();
+
+    let first = tracker.usage_snapshot(&client, "web").unwrap();
+    assert_eq!(
+        client.request_line(0),
+        "GET /containers/web/stats?stream=false HTTP/1.1"
+    );
+    assert_eq!(first.cpu_percent, None);
+    assert_eq!(first.rx_bytes_per_sec, None);
+    assert_eq!(first.memory_working_set, 400);
+    assert_eq!(first.memory_limit, 1000);
+
+    let second = tracker.usage_snapshot(&client, "web").unwrap();
+    assert_eq!(second.cpu_percent, Some(50.0));
+    assert_eq!(second.rx_bytes_per_sec, Some(200.0));
+    assert_eq!(second.tx_bytes_per_sec, Some(0.0));
+
+    // The container restarted, its counters start over.
+    let restarted = tracker.usage_snapshot(&client, "web").unwrap();
+    assert_eq!(restarted.cpu_percent, None);
+    assert_eq!(restarted.rx_bytes_per_sec, None);
+}
+
+#[test]
+fn test_stats_once_takes_a_second_sample_without_precpu() {
+    let mut client = MockClient::new(vec![
+        usage_sample("2018-05-01T10:00:00Z", 1000, 10000, 100),
+        usage_sample("2018-05-01T10:00:01Z", 1250, 11000, 100),
+    ]);
+    client.api_version = Some(ApiVersion::new(1, 41));
+
+    let stats = client.get_container_stats_once("web").unwrap();
+
+    assert_eq!(client.requests.borrow().len(), 2);
+    assert_eq!(
+        client.request_line(1),
+        "GET /containers/web/stats?stream=false&one-shot=true HTTP/1.1"
+    );
+    assert_eq!(stats.preread, "2018-05-01T10:00:00Z");
+    assert_eq!(stats.precpu_stats.cpu_usage.total_usage, 1000);
+    assert_eq!(stats.cpu_percent(), Some(50.0));
+}
+
+#[test]
+fn test_stats_once_with_precpu() {
+    let stats = serde_json::json!({
+        "read": "2018-05-01T10:00:01Z",
+        "preread": "2018-05-01T10:00:00Z",
+        "cpu_stats": {
+            "cpu_usage": {"total_usage": 1250},
+            "system_cpu_usage": 11000,
+            "online_cpus": 4
+        },
+        "precpu_stats": {
+            "cpu_usage": {"total_usage": 1000},
+            "system_cpu_usage": 10000
+        }
+    });
+    let client = MockClient::new(vec![http_response(200, &stats.to_string())]);
+
+    let stats = client.get_container_stats_once("web").unwrap();
+
+    assert_eq!(client.requests.borrow().len(), 1);
+    assert_eq!(
+        client.request_line(0),
+        "GET /containers/web/stats?stream=false HTTP/1.1"
+    );
+    assert_eq!(stats.cpu_percent(), Some(100.0));
+}
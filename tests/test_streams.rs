@@ -0,0 +1,60 @@
+#![cfg(feature = "events")]
+extern crate rust_docker;
+
+use std::fs;
+use std::io::{Read, Write};
+use std::os::unix::net::UnixListener;
+use std::thread;
+use std::time::Duration;
+
+use rust_docker::api::events::Events;
+use rust_docker::client::DockerClient;
+
+/// Starts a fake daemon which answers the streaming request with the
+/// headers only and then keeps the connection open.
+fn start_silent_daemon(name: &str) -> &'static str {
+    let path = format!("/tmp/docker-rs-{}-{}.sock", name, std::process::id());
+    let _ = fs::remove_file(&path);
+    let listener = UnixListener::bind(&path).unwrap();
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut conn = stream.unwrap();
+            thread::spawn(move || {
+                let mut buf = [0; 1024];
+                let mut answered = false;
+                // Answer the request and keep the connection open till the
+                // client shuts it down.
+                while let Ok(len) = conn.read(&mut buf) {
+                    if len == 0 {
+                        break;
+                    }
+                    if !answered {
+                        let _ = conn.write_all(
+                            b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n",
+                        );
+                        answered = true;
+                    }
+                }
+            });
+        }
+    });
+
+    Box::leak(path.into_boxed_str())
+}
+
+#[test]
+fn test_cancel_blocked_events_stream() {
+    let path = start_silent_daemon("events");
+    let client = DockerClient::new(path).unwrap();
+
+    let mut events = client.get_events(None, None, None).unwrap();
+    let handle = events.handle();
+
+    let reader = thread::spawn(move || events.next().is_none());
+    thread::sleep(Duration::from_millis(100));
+    handle.cancel();
+
+    assert!(reader.join().unwrap());
+    assert!(handle.is_cancelled());
+}
@@ -0,0 +1,63 @@
+#![cfg(feature = "swarm")]
+extern crate rust_docker;
+extern crate serde_json;
+
+mod common;
+
+use common::{http_response, MockClient};
+
+use rust_docker::api::swarm::Swarm;
+use rust_docker::errors::DockerApiError;
+
+impl Swarm for MockClient {}
+
+fn swarm_response(index: u64, worker: &str, manager: &str) -> String {
+    let swarm = serde_json::json!({
+        "ID": "abajmipo7b4xz5ip2nrla6b11",
+        "Version": {"Index": index},
+        "CreatedAt": "2016-08-18T10:44:24.496525531Z",
+        "Spec": {"Name": "default", "Raft": {"SnapshotInterval": 10000}},
+        "JoinTokens": {"Worker": worker, "Manager": manager}
+    });
+    http_response(200, &swarm.to_string())
+}
+
+#[test]
+fn test_join_tokens() {
+    let client = MockClient::new(vec![
+        swarm_response(11, "SWMTKN-1-w", "SWMTKN-1-m"),
+        swarm_response(11, "SWMTKN-1-w", "SWMTKN-1-m"),
+        http_response(
+            503,
+            r#"{"message":"This node is not a swarm manager."}"#,
+        ),
+    ]);
+
+    assert_eq!(client.worker_join_token().unwrap(), "SWMTKN-1-w");
+    assert_eq!(client.manager_join_token().unwrap(), "SWMTKN-1-m");
+    assert_eq!(client.request_line(0), "GET /swarm HTTP/1.1");
+    match client.inspect_swarm() {
+        Err(DockerApiError::NotInSwarm(_)) => (),
+        res => panic!("{:?}", res),
+    }
+}
+
+#[test]
+fn test_rotate_join_tokens() {
+    let client = MockClient::new(vec![
+        swarm_response(11, "SWMTKN-1-w", "SWMTKN-1-m"),
+        http_response(200, ""),
+        swarm_response(12, "SWMTKN-1-w2", "SWMTKN-1-m"),
+    ]);
+
+    let tokens = client.rotate_join_tokens(true, false).unwrap();
+
+    assert_eq!(tokens.worker, "SWMTKN-1-w2");
+    assert_eq!(tokens.manager, "SWMTKN-1-m");
+    assert_eq!(
+        client.request_line(1),
+        "POST /swarm/update?version=11&rotateWorkerToken=true&\
+         rotateManagerToken=false HTTP/1.1"
+    );
+    assert_eq!(client.request_body(1)["Raft"]["SnapshotInterval"], 10000);
+}
@@ -0,0 +1,123 @@
+#![cfg(all(feature = "containers", feature = "tar"))]
+extern crate rust_docker;
+extern crate serde_json;
+extern crate tar;
+
+mod common;
+
+use std::env;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+use std::process;
+
+use common::MockClient;
+
+use rust_docker::api::containers::Containers;
+use rust_docker::tar_util::{
+    context_from_dir, copy_from_container, extract_archive,
+};
+
+impl Containers for MockClient {}
+
+fn entries(archive: &[u8]) -> Vec<String> {
+    tar::Archive::new(archive)
+        .entries()
+        .unwrap()
+        .map(|entry| entry.unwrap().path().unwrap().display().to_string())
+        .collect()
+}
+
+fn archive_with(path: &str, content: &[u8]) -> Vec<u8> {
+    let mut builder = tar::Builder::new(Vec::new());
+    let mut header = tar::Header::new_gnu();
+    header.set_size(content.len() as u64);
+    header.set_mode(0o644);
+    // set_path refuses `..`, the raw name bytes do not.
+    header.as_old_mut().name[..path.len()].copy_from_slice(path.as_bytes());
+    header.set_cksum();
+    builder.append(&header, content).unwrap();
+    builder.into_inner().unwrap()
+}
+
+#[test]
+fn test_context_from_dir() {
+    let dir =
+        env::temp_dir().join(format!("docker-rs-context-{}", process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("src")).unwrap();
+    fs::create_dir_all(dir.join("target/debug")).unwrap();
+    fs::write(dir.join("Dockerfile"), "FROM scratch\n").unwrap();
+    fs::write(
+        dir.join(".dockerignore"),
+        "target\n!target/debug/app\n**/*.log\nDockerfile\n",
+    )
+    .unwrap();
+    fs::write(dir.join("src/main.rs"), "fn main() {}\n").unwrap();
+    fs::write(dir.join("src/build.log"), "").unwrap();
+    fs::write(dir.join("target/debug/app"), "").unwrap();
+
+    let context = context_from_dir(&dir).unwrap();
+    assert_eq!(
+        entries(&context),
+        vec![
+            ".dockerignore",
+            "Dockerfile",
+            "src",
+            "src/main.rs",
+            "target/debug/app"
+        ]
+    );
+    assert_eq!(context, context_from_dir(&dir).unwrap());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_extract_archive() {
+    let dest =
+        env::temp_dir().join(format!("docker-rs-extract-{}", process::id()));
+    let _ = fs::remove_dir_all(&dest);
+
+    let unpacked = extract_archive(
+        archive_with("nginx/access.log", b"GET /").as_slice(),
+        &dest,
+    )
+    .unwrap();
+    assert_eq!(unpacked, vec![dest.join("nginx/access.log")]);
+    assert_eq!(fs::read(dest.join("nginx/access.log")).unwrap(), b"GET /");
+
+    let evil = archive_with("../escaped", b"oops");
+    assert!(extract_archive(evil.as_slice(), &dest).is_err());
+    assert!(!dest.parent().unwrap().join("escaped").exists());
+
+    fs::remove_dir_all(&dest).unwrap();
+}
+
+#[test]
+fn test_copy_from_container() {
+    let archive = archive_with("hosts", b"127.0.0.1 localhost\n");
+    // The mock client answers with strings, the archive is ASCII here.
+    let client = MockClient::new(vec![format!(
+        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{}",
+        archive.len(),
+        String::from_utf8(archive).unwrap()
+    )]);
+
+    let dest = env::temp_dir().join(format!("docker-rs-cp-{}", process::id()));
+    let _ = fs::remove_dir_all(&dest);
+    copy_from_container(&client, "web", "/etc/hosts", &dest).unwrap();
+
+    assert_eq!(
+        client.request_line(0),
+        "GET /containers/web/archive?path=%2Fetc%2Fhosts HTTP/1.1"
+    );
+    let mut hosts = String::new();
+    fs::File::open(Path::new(&dest).join("hosts"))
+        .unwrap()
+        .read_to_string(&mut hosts)
+        .unwrap();
+    assert_eq!(hosts, "127.0.0.1 localhost\n");
+
+    fs::remove_dir_all(&dest).unwrap();
+}
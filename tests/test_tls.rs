@@ -0,0 +1,62 @@
+extern crate rust_docker;
+
+use std::env;
+use std::fs;
+use std::process;
+
+use rust_docker::client::DockerClient;
+use rust_docker::errors::DockerClientError;
+use rust_docker::proxy::ProxyConfig;
+use rust_docker::tls::TlsConfig;
+
+#[test]
+fn test_tls_config_from_cert_path() {
+    let dir =
+        env::temp_dir().join(format!("docker-rs-certs-{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("ca.pem"), "").unwrap();
+    fs::write(dir.join("cert.pem"), "").unwrap();
+
+    // Without the key the client certificate is not used.
+    let config = TlsConfig::from_cert_path(&dir);
+    assert_eq!(config.ca_cert, Some(dir.join("ca.pem")));
+    assert_eq!(config.client_cert, None);
+
+    fs::write(dir.join("key.pem"), "").unwrap();
+    let config = TlsConfig::from_cert_path(&dir);
+    assert_eq!(
+        config.client_cert,
+        Some((dir.join("cert.pem"), dir.join("key.pem")))
+    );
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_tls_needs_tcp_address() {
+    match DockerClient::with_tls(
+        "unix:///var/run/docker.sock",
+        TlsConfig::default(),
+        ProxyConfig::default(),
+    ) {
+        Err(DockerClientError::InvalidTargetAddress(_)) => (),
+        _ => panic!("Expected an invalid address"),
+    }
+}
+
+#[cfg(not(feature = "native-tls"))]
+#[test]
+fn test_tls_fails_without_ca_or_backend() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = format!("tcp://{}", listener.local_addr().unwrap());
+
+    // Without a backend, or with rustls and no CA, nothing is sent.
+    match DockerClient::with_tls(
+        Box::leak(addr.into_boxed_str()),
+        TlsConfig::default(),
+        ProxyConfig::default(),
+    ) {
+        Err(DockerClientError::TlsError(_)) => (),
+        _ => panic!("Expected a TLS error"),
+    }
+}
@@ -0,0 +1,167 @@
+#![cfg(all(feature = "containers", feature = "images"))]
+extern crate rust_docker;
+extern crate serde_json;
+
+mod common;
+
+use std::cell::RefCell;
+
+use common::{http_response, MockClient};
+
+use rust_docker::api::containers::Containers;
+use rust_docker::api::images::Images;
+use rust_docker::updater::{UpdateOutcome, Updater};
+
+impl Containers for MockClient {}
+impl Images for MockClient {}
+
+const WEB_LIST: &str = r#"[{"Id": "4fa6e0f0c6786287e131", "Names": ["/web"],
+    "Image": "nginx:1.25", "ImageID": "sha256:2ca7", "Command": "nginx",
+    "Created": 1700000000, "State": "running", "Status": "Up 2 hours",
+    "Ports": [], "Labels": {"com.docker-rs.updater.enable": "true"},
+    "HostConfig": {"NetworkMode": "default"},
+    "NetworkSettings": {"Networks": {}}, "Mounts": []}]"#;
+
+const WEB_DETAILS: &str = r#"{"Id": "4fa6e0f0c6786287e131",
+    "Name": "/web", "State": {"Running": true},
+    "Config": {"Hostname": "4fa6e0f0c678", "Image": "nginx:1.25",
+        "Entrypoint": ["/docker-entrypoint.sh"]},
+    "HostConfig": {"Privileged": true, "ShmSize": 268435456}}"#;
+
+const OLD_DIGEST: &str =
+    "sha256:4c0f5cbb4c9e4b1d3f6a1a2b8e0b9d3c7e6f5a4b3c2d1e0f9a8b7c6d5e4f3a2b";
+const NEW_DIGEST: &str =
+    "sha256:9f3c2b1a0e9d8c7b6a5f4e3d2c1b0a9f8e7d6c5b4a3f2e1d0c9b8a7f6e5d4c3b";
+
+fn distribution(digest: &str) -> String {
+    http_response(
+        200,
+        &format!(
+            r#"{{"Descriptor": {{"mediaType": "application/vnd.oci.image.index.v1+json",
+                "digest": "{}", "size": 1778}},
+                "Platforms": [{{"architecture": "amd64", "os": "linux"}}]}}"#,
+            digest
+        ),
+    )
+}
+
+fn local_image(digest: &str) -> String {
+    http_response(
+        200,
+        &format!(
+            r#"{{"Id": "sha256:2ca7", "RepoTags": ["nginx:1.25"],
+                "RepoDigests": ["nginx@{}"]}}"#,
+            digest
+        ),
+    )
+}
+
+#[test]
+fn test_updater_recreates_containers_with_a_new_digest() {
+    let client = MockClient::new(vec![
+        http_response(200, WEB_LIST),
+        distribution(NEW_DIGEST),
+        local_image(OLD_DIGEST),
+        http_response(
+            200,
+            "{\"status\":\"Downloaded newer image for nginx:1.25\"}\n",
+        ),
+        http_response(200, WEB_DETAILS),
+        http_response(204, ""),
+        http_response(204, ""),
+        http_response(201, r#"{"Id": "e90e34656806", "Warnings": []}"#),
+        http_response(204, ""),
+        http_response(204, ""),
+    ]);
+
+    let pending = RefCell::new(Vec::new());
+    let results = Updater::new()
+        .before_update(|update| {
+            pending.borrow_mut().push(update.clone());
+            true
+        })
+        .check(&client)
+        .unwrap();
+
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].container, "web");
+    match results[0].outcome {
+        UpdateOutcome::Updated { ref id, ref digest } => {
+            assert_eq!(id.as_str(), "e90e34656806");
+            assert_eq!(digest, NEW_DIGEST);
+        }
+        ref outcome => panic!("{:?}", outcome),
+    }
+    assert_eq!(pending.borrow()[0].image, "nginx:1.25");
+
+    assert_eq!(
+        client.request_line(0),
+        "GET /containers/json?all=true&filters=%7B%22label%22%3A%5B%22com.docker-rs.updater.enable%3Dtrue%22%5D%7D HTTP/1.1"
+    );
+    assert_eq!(
+        client.request_line(1),
+        "GET /distribution/docker.io/library/nginx:1.25/json HTTP/1.1"
+    );
+    assert_eq!(
+        client.request_line(3),
+        "POST /images/create?fromImage=nginx&tag=1.25 HTTP/1.1"
+    );
+    assert_eq!(
+        client.request_line(7),
+        "POST /containers/create?name=web HTTP/1.1"
+    );
+    let body = client.request_body(7);
+    assert_eq!(body["Image"], "nginx:1.25");
+    assert_eq!(
+        body["Entrypoint"],
+        serde_json::json!(["/docker-entrypoint.sh"])
+    );
+    assert_eq!(body["HostConfig"]["Privileged"], true);
+    assert_eq!(body["HostConfig"]["ShmSize"], 268435456);
+}
+
+#[test]
+fn test_updater_leaves_up_to_date_containers() {
+    let client = MockClient::new(vec![
+        http_response(200, WEB_LIST),
+        distribution(OLD_DIGEST),
+        local_image(OLD_DIGEST),
+    ]);
+
+    let results = Updater::new().check(&client).unwrap();
+
+    match results[0].outcome {
+        UpdateOutcome::UpToDate => (),
+        ref outcome => panic!("{:?}", outcome),
+    }
+    assert_eq!(client.requests.borrow().len(), 3);
+}
+
+#[test]
+fn test_updater_before_update_can_refuse() {
+    let client = MockClient::new(vec![
+        http_response(200, WEB_LIST),
+        distribution(NEW_DIGEST),
+        local_image(OLD_DIGEST),
+        http_response(
+            200,
+            "{\"status\":\"Downloaded newer image for nginx:1.25\"}\n",
+        ),
+    ]);
+
+    let mut checked = Vec::new();
+    let results = Updater::new()
+        .label("tier=frontend")
+        .before_update(|update| update.container != "web")
+        .after_update(|result| checked.push(result.container.clone()))
+        .check(&client)
+        .unwrap();
+
+    match results[0].outcome {
+        UpdateOutcome::Skipped => (),
+        ref outcome => panic!("{:?}", outcome),
+    }
+    assert_eq!(checked, vec!["web".to_string()]);
+    assert_eq!(client.requests.borrow().len(), 4);
+    assert!(client.request_line(0).contains("tier%3Dfrontend"));
+}
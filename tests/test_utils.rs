@@ -0,0 +1,261 @@
+#![cfg(all(feature = "containers", feature = "events"))]
+extern crate rust_docker;
+extern crate serde_json;
+
+use std::io::{Cursor, Read};
+use std::time::{Duration, UNIX_EPOCH};
+
+use rust_docker::api::api_utils::RequestBuilder;
+use rust_docker::api::containers::ContainerState;
+use rust_docker::api::events::Event;
+use rust_docker::ratelimit::RateLimiter;
+use rust_docker::utils::{
+    format_rfc3339, parse_rfc3339, write_chunked, Response, ResponseParser,
+    StreamHandle, StreamResponse,
+};
+
+#[test]
+fn test_response_is_complete_with_content_length() {
+    let partial = b"HTTP/1.1 200 OK\r\nContent-Length: 10\r\n\r\n{\"a\":";
+    let full = b"HTTP/1.1 200 OK\r\nContent-Length: 10\r\n\r\n{\"a\":\"bc\"}";
+
+    assert!(!Response::is_complete(b"HTTP/1.1 200 OK\r\nContent-"));
+    assert!(!Response::is_complete(partial));
+    assert!(Response::is_complete(full));
+    assert!(Response::is_complete(b"HTTP/1.1 204 No Content\r\n\r\n"));
+}
+
+#[test]
+fn test_response_is_complete_with_chunked_body() {
+    let head = "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n";
+    let partial = format!("{}5\r\nhello\r\n", head);
+    let full = format!("{}5\r\nhello\r\n0\r\n\r\n", head);
+
+    assert!(!Response::is_complete(partial.as_bytes()));
+    assert!(Response::is_complete(full.as_bytes()));
+    assert_eq!(
+        Response::parse_http_response(full.into_bytes())
+            .unwrap()
+            .body,
+        "hello"
+    );
+}
+
+#[test]
+fn test_response_parser_reads_incrementally() {
+    let full = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n\
+                 5;ext=1\r\nhello\r\n6\r\n world\r\n0\r\nX-Trailer: a\r\n\r\n";
+    let mut parser = ResponseParser::new();
+    for end in 0..full.len() {
+        assert!(!parser.is_complete(&full[..end]), "complete at {}", end);
+    }
+    assert!(parser.is_complete(full));
+
+    let resp = Response::parse_http_response(full.to_vec()).unwrap();
+    assert_eq!(resp.body, "hello world");
+    assert_eq!(resp.header("transfer-encoding"), Some("chunked"));
+}
+
+#[test]
+fn test_parse_malformed_responses() {
+    let malformed: &[&[u8]] = &[
+        b"",
+        b"\r\n\r\n",
+        b"HTTP/1.1\r\n\r\n",
+        b"HTTP/1.1 abc OK\r\n\r\n",
+        b"SSH-2.0-OpenSSH\r\n\r\n",
+        b"HTTP/1.1 200 OK\r\nContent-Length: x\r\n\r\n",
+        b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\nzz\r\n",
+        b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n9\r\nab",
+    ];
+    for res in malformed {
+        assert!(Response::parse_http_response(res.to_vec()).is_err());
+    }
+
+    let resp = Response::parse_http_response(
+        b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nNoColon\r\n\r\n{}trailing"
+            .to_vec(),
+    )
+    .unwrap();
+    assert_eq!(resp.body, "{}");
+    assert_eq!(resp.headers.len(), 1);
+}
+
+#[test]
+fn test_stream_response_from_reader() {
+    let stream = |res: &[u8]| {
+        StreamResponse::from_reader(
+            Box::new(Cursor::new(res.to_vec())),
+            StreamHandle::new(),
+        )
+    };
+
+    let malformed: &[&[u8]] = &[
+        b"",
+        b"\r\n\r\n",
+        b"HTTP/1.1\r\n\r\n",
+        b"HTTP/1.1 abc OK\r\n\r\n",
+        b"SSH-2.0-OpenSSH\r\n\r\n",
+        b"HTTP/1.1 200 OK\r\nContent-Length: x\r\n\r\n",
+        b"HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\n",
+    ];
+    for res in malformed {
+        assert!(stream(res).is_err());
+    }
+
+    let mut resp = stream(
+        b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nNoColon\r\n\
+          Api-Version: 1.43\r\n\r\n{}trailing",
+    )
+    .unwrap();
+    assert_eq!(resp.status_code, 200);
+    assert_eq!(resp.headers.len(), 2);
+    assert_eq!(resp.headers["api-version"], "1.43");
+    let mut body = String::new();
+    resp.read_to_string(&mut body).unwrap();
+    assert_eq!(body, "{}");
+
+    let mut resp = stream(
+        b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n\
+          5\r\nhello\r\n6\r\n world\r\n0\r\n\r\n",
+    )
+    .unwrap();
+    let mut body = String::new();
+    resp.read_to_string(&mut body).unwrap();
+    assert_eq!(body, "hello world");
+}
+
+#[test]
+fn test_parse_rfc3339() {
+    assert_eq!(
+        parse_rfc3339("2018-05-01T10:00:01.123456789Z"),
+        Some(UNIX_EPOCH + Duration::new(1_525_168_801, 123_456_789))
+    );
+    assert_eq!(
+        parse_rfc3339("2018-05-01T12:00:01+02:00"),
+        Some(UNIX_EPOCH + Duration::from_secs(1_525_168_801))
+    );
+    assert!(parse_rfc3339("0001-01-01T00:00:00Z").is_some());
+    assert_eq!(parse_rfc3339("2018-05-01 10:00:01"), None);
+    assert_eq!(parse_rfc3339("2018-13-01T10:00:01Z"), None);
+    assert_eq!(parse_rfc3339("2018-05-01T10:00:01ééé"), None);
+    assert_eq!(parse_rfc3339("2018-05-01T10:00:01+é:00"), None);
+    assert_eq!(parse_rfc3339("2018-05-01T10:00:01+00:é"), None);
+}
+
+#[test]
+fn test_format_rfc3339() {
+    let time = UNIX_EPOCH + Duration::new(1_525_168_801, 123_456_789);
+    assert_eq!(format_rfc3339(time), "2018-05-01T10:00:01.123456789Z");
+    assert_eq!(parse_rfc3339(&format_rfc3339(time)), Some(time));
+    assert_eq!(format_rfc3339(UNIX_EPOCH), "1970-01-01T00:00:00Z");
+}
+
+#[test]
+fn test_typed_timestamps() {
+    let state: ContainerState = serde_json::from_str(
+        r#"{"Status": "exited", "Running": false, "Paused": false,
+            "Restarting": false, "OOMKilled": false, "Dead": false,
+            "Pid": 0, "ExitCode": 0, "Error": "",
+            "StartedAt": "2018-05-01T10:00:01.123456789Z",
+            "FinishedAt": "0001-01-01T00:00:00Z"}"#,
+    )
+    .unwrap();
+    assert_eq!(
+        state.started_at,
+        Some(UNIX_EPOCH + Duration::new(1_525_168_801, 123_456_789))
+    );
+    assert_eq!(state.finished_at, None);
+
+    let json = serde_json::to_value(&state).unwrap();
+    assert_eq!(json["StartedAt"], "2018-05-01T10:00:01.123456789Z");
+    assert_eq!(json["FinishedAt"], "0001-01-01T00:00:00Z");
+
+    let event: Event =
+        serde_json::from_str(r#"{"Type": "container", "time": 1367854155}"#)
+            .unwrap();
+    assert_eq!(
+        event.time,
+        Some(UNIX_EPOCH + Duration::from_secs(1367854155))
+    );
+    let json = serde_json::to_value(&event).unwrap();
+    assert_eq!(json["time"], 1367854155);
+
+    let bad: Result<ContainerState, _> =
+        serde_json::from_str(r#"{"StartedAt": "yesterday"}"#);
+    assert!(bad.is_err());
+}
+
+#[test]
+fn test_request_builder_headers() {
+    let request = RequestBuilder::new("post", "/build")
+        .header("Content-Type", "application/x-tar")
+        .header("X-Registry-Config", "e30=")
+        .body("tar")
+        .build()
+        .unwrap();
+    assert_eq!(
+        request,
+        "POST /build HTTP/1.1\r\nHost: v1.37\r\nContent-Type: application/x-tar\r\n\
+         X-Registry-Config: e30=\r\nContent-Length: 3\r\n\r\ntar"
+    );
+
+    let request = RequestBuilder::new("GET", "/info")
+        .header("traceparent", "00-0af7651916cd43dd-b7ad6b71-01")
+        .build()
+        .unwrap();
+    assert_eq!(
+        request,
+        "GET /info HTTP/1.1\r\nHost: v1.37\r\n\
+         traceparent: 00-0af7651916cd43dd-b7ad6b71-01\r\n\r\n"
+    );
+
+    assert!(RequestBuilder::new("GET", "/info")
+        .header("X-Injected", "a\r\nHost: evil")
+        .build()
+        .is_err());
+    assert!(RequestBuilder::new("GET", "/info")
+        .header("Bad Name", "value")
+        .build()
+        .is_err());
+    assert!(RequestBuilder::new("PATCH", "/info").build().is_err());
+}
+
+#[test]
+fn test_chunked_upload() {
+    let request = RequestBuilder::new("POST", "/build")
+        .header("Content-Type", "application/x-tar")
+        .body("ignored")
+        .chunked()
+        .build()
+        .unwrap();
+    assert_eq!(
+        request,
+        "POST /build HTTP/1.1\r\nHost: v1.37\r\nContent-Type: application/x-tar\r\n\
+         Transfer-Encoding: chunked\r\n\r\n"
+    );
+
+    let mut written: Vec<u8> = Vec::new();
+    let body = vec![b'a'; 40000];
+    let len = write_chunked(&mut written, &mut &body[..]).unwrap();
+    assert_eq!(len, 40000);
+    let expected = format!(
+        "8000\r\n{}\r\n1c40\r\n{}\r\n0\r\n\r\n",
+        "a".repeat(32768),
+        "a".repeat(7232)
+    );
+    assert_eq!(String::from_utf8(written).unwrap(), expected);
+}
+
+#[test]
+fn test_rate_limiter_refills() {
+    let limiter = RateLimiter::new(2, Duration::from_millis(100));
+
+    assert!(limiter.try_acquire());
+    assert!(limiter.try_acquire());
+    assert!(!limiter.try_acquire());
+
+    // A token is back after half the period.
+    limiter.acquire();
+    assert!(!limiter.try_acquire());
+}
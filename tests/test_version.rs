@@ -0,0 +1,135 @@
+extern crate rust_docker;
+extern crate serde_json;
+
+mod common;
+
+use common::{http_response, MockClient};
+
+use rust_docker::api::version::{ApiFeature, ApiVersion, Version};
+
+impl Version for MockClient {}
+
+#[test]
+fn test_api_version() {
+    assert_eq!(ApiVersion::parse("1.41"), Some(ApiVersion::new(1, 41)));
+    assert_eq!(ApiVersion::parse("v1.37"), Some(ApiVersion::new(1, 37)));
+    assert_eq!(ApiVersion::parse("1"), None);
+    assert!(ApiVersion::new(1, 9) < ApiVersion::new(1, 40));
+    assert!(ApiVersion::new(2, 0) > ApiVersion::new(1, 44));
+    assert_eq!(ApiVersion::new(1, 40).to_string(), "1.40");
+}
+
+fn version_response(api_version: &str) -> String {
+    http_response(
+        200,
+        &format!(
+            r#"{{"Version": "20.10.7", "ApiVersion": "{}",
+                "MinAPIVersion": "1.12", "Os": "linux", "Arch": "amd64",
+                "Experimental": false}}"#,
+            api_version
+        ),
+    )
+}
+
+#[test]
+fn test_capabilities() {
+    let client = MockClient::new(vec![
+        version_response("1.41"),
+        http_response(
+            200,
+            r#"{"ID": "7TRN", "OSType": "linux", "CgroupVersion": "2",
+                "SecurityOptions": ["name=seccomp,profile=default", "name=rootless"],
+                "Swarm": {"LocalNodeState": "active", "NodeID": "n1"},
+                "ExperimentalBuild": true}"#,
+        ),
+    ]);
+
+    let capabilities = client.capabilities().unwrap();
+
+    assert_eq!(client.request_line(0), "GET /version HTTP/1.1");
+    assert_eq!(client.request_line(1), "GET /info HTTP/1.1");
+    assert_eq!(capabilities.version, "20.10.7");
+    assert_eq!(capabilities.api_version, ApiVersion::new(1, 41));
+    assert_eq!(capabilities.min_api_version, ApiVersion::new(1, 12));
+    assert!(capabilities.buildkit);
+    assert!(capabilities.swarm_active);
+    assert!(capabilities.cgroup_v2);
+    assert!(capabilities.rootless);
+    assert!(capabilities.experimental);
+    assert!(capabilities.supports(ApiFeature::DeviceRequests));
+    assert!(capabilities.supports(ApiFeature::CreatePlatform));
+    assert!(!capabilities.supports(ApiFeature::MultipleNetworksOnCreate));
+}
+
+#[test]
+fn test_capabilities_of_old_daemon() {
+    let client = MockClient::new(vec![
+        version_response("1.30"),
+        http_response(
+            200,
+            r#"{"OSType": "linux", "SecurityOptions": null,
+                "Swarm": {"LocalNodeState": "inactive"}}"#,
+        ),
+    ]);
+
+    let capabilities = client.capabilities().unwrap();
+
+    assert!(!capabilities.buildkit);
+    assert!(!capabilities.swarm_active);
+    assert!(!capabilities.cgroup_v2);
+    assert!(!capabilities.rootless);
+    assert!(capabilities.supports(ApiFeature::HealthStartPeriod));
+    assert!(!capabilities.supports(ApiFeature::PullPlatform));
+    assert!(!capabilities.supports(ApiFeature::BuildKit));
+}
+
+#[test]
+fn test_daemon_report() {
+    let client = MockClient::new(vec![
+        version_response("1.43"),
+        http_response(
+            200,
+            r#"{"ID": "7TRN", "Name": "node-1", "Containers": 2,
+                "Plugins": {"Volume": ["local"], "Network": ["bridge", "host"],
+                    "Authorization": null, "Log": ["json-file"]}}"#,
+        ),
+        http_response(
+            200,
+            r#"{"LayersSize": 1092588,
+                "Images": [{"Id": "sha256:2b8f", "RepoTags": ["busybox:latest"],
+                    "Size": 1092588, "SharedSize": -1, "Containers": 1}],
+                "Containers": [{"Id": "e90e3465", "Names": ["/top"],
+                    "Image": "busybox", "State": "exited", "SizeRw": 12288,
+                    "SizeRootFs": 1104876}],
+                "Volumes": [{"Name": "data", "Driver": "local",
+                    "UsageData": {"Size": 4096, "RefCount": 1}},
+                    {"Name": "remote", "Driver": "nfs",
+                    "UsageData": {"Size": -1, "RefCount": -1}}],
+                "BuildCache": null}"#,
+        ),
+        http_response(
+            200,
+            r#"[{"Id": "5724e2c8", "Name": "vieux/sshfs:latest",
+                 "Enabled": true, "PluginReference": "localhost:5000/vieux/sshfs:latest"}]"#,
+        ),
+    ]);
+
+    let report = client.daemon_report().unwrap();
+
+    assert_eq!(client.request_line(2), "GET /system/df HTTP/1.1");
+    assert_eq!(client.request_line(3), "GET /plugins HTTP/1.1");
+    assert_eq!(report.info.name, "node-1");
+    assert_eq!(report.info.plugins.network, vec!["bridge", "host"]);
+    assert!(report.info.plugins.authorization.is_empty());
+    assert_eq!(report.data_usage.images[0].shared_size, -1);
+    assert_eq!(report.data_usage.containers_size(), 12288);
+    assert_eq!(report.data_usage.volumes_size(), 4096);
+    assert_eq!(report.data_usage.build_cache_size(), 0);
+    assert!(report.plugins[0].enabled);
+
+    let json = serde_json::to_value(&report).unwrap();
+    assert_eq!(json["Version"]["ApiVersion"], "1.43");
+    assert_eq!(json["DataUsage"]["Volumes"][1]["Driver"], "nfs");
+    assert_eq!(json["Plugins"][0]["Name"], "vieux/sshfs:latest");
+    assert!(json["CollectedAt"].as_str().unwrap().ends_with('Z'));
+}
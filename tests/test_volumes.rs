@@ -0,0 +1,130 @@
+#![cfg(all(feature = "containers", feature = "volumes"))]
+extern crate rust_docker;
+extern crate serde_json;
+
+mod common;
+
+use common::{chunked_http_response, http_response, MockClient};
+
+use rust_docker::api::containers::Containers;
+use rust_docker::api::volumes::{VolumeCreateBuilder, Volumes};
+
+impl Containers for MockClient {}
+impl Volumes for MockClient {}
+
+#[test]
+fn test_volume_create_builder_nfs_options() {
+    let config = VolumeCreateBuilder::new("data")
+        .nfs("10.0.0.2", "/exports/data", Some("rw"))
+        .label("app", "web")
+        .build()
+        .unwrap();
+
+    assert_eq!(config.driver, "local");
+    assert_eq!(config.driver_opts["type"], "nfs");
+    assert_eq!(config.driver_opts["o"], "addr=10.0.0.2,rw");
+    assert_eq!(config.driver_opts["device"], ":/exports/data");
+    assert_eq!(config.labels["app"], "web");
+}
+
+#[test]
+fn test_volume_create_builder_verifies_bind_path() {
+    let res = VolumeCreateBuilder::new("data")
+        .bind("/this/path/does/not/exist")
+        .verify_host_path(true)
+        .build();
+    assert!(res.is_err());
+
+    let res = VolumeCreateBuilder::new("data")
+        .bind("/tmp")
+        .verify_host_path(true)
+        .build();
+    assert!(res.is_ok());
+}
+
+#[test]
+fn test_list_volumes_handles_null() {
+    let client = MockClient::new(vec![http_response(
+        200,
+        "{\"Volumes\":null,\"Warnings\":null}",
+    )]);
+
+    assert!(client.list_volumes().unwrap().is_empty());
+}
+
+#[test]
+fn test_owned_volume_is_removed_when_dropped() {
+    let client = MockClient::new(vec![
+        http_response(
+            201,
+            r#"{"Name": "data", "Driver": "local",
+                "Mountpoint": "/var/lib/docker/volumes/data/_data"}"#,
+        ),
+        http_response(204, ""),
+    ]);
+
+    {
+        let config = VolumeCreateBuilder::new("data").build().unwrap();
+        let volume = client.create_volume_owned(config).unwrap();
+        assert_eq!(volume.name(), "data");
+        assert_eq!(volume.volume().driver, "local");
+    }
+
+    assert_eq!(
+        client.request_line(1),
+        "DELETE /volumes/data?force=true HTTP/1.1"
+    );
+}
+
+#[test]
+fn test_backup_volume_through_a_helper_container() {
+    let client = MockClient::new(vec![
+        http_response(200, r#"{"Name": "pgdata", "Driver": "local"}"#),
+        http_response(201, r#"{"Id": "b7c1d2e3f4a5", "Warnings": []}"#),
+        chunked_http_response(200, &["volume/", "PG_VERSION"]),
+        http_response(204, ""),
+    ]);
+    let mut backup = Vec::new();
+
+    client.backup_volume("pgdata", &mut backup).unwrap();
+
+    assert_eq!(backup, b"volume/PG_VERSION");
+    assert_eq!(client.request_line(0), "GET /volumes/pgdata HTTP/1.1");
+    assert_eq!(
+        client.request_line(1),
+        "POST /containers/create?name= HTTP/1.1"
+    );
+    assert_eq!(
+        client.request_body(1)["HostConfig"]["Mounts"][0]["Source"],
+        "pgdata"
+    );
+    assert_eq!(
+        client.request_line(2),
+        "GET /containers/b7c1d2e3f4a5/archive?path=%2Fvolume HTTP/1.1"
+    );
+    assert_eq!(
+        client.request_line(3),
+        "DELETE /containers/b7c1d2e3f4a5?force=true&v=true HTTP/1.1"
+    );
+}
+
+#[test]
+fn test_restore_volume_removes_the_helper_on_error() {
+    let client = MockClient::new(vec![
+        http_response(201, r#"{"Id": "b7c1d2e3f4a5", "Warnings": []}"#),
+        http_response(400, r#"{"message": "not a tar archive"}"#),
+        http_response(204, ""),
+    ]);
+
+    let result = client.restore_volume("pgdata", &mut &b"garbage"[..]);
+
+    assert!(result.is_err());
+    assert_eq!(
+        client.request_line(1),
+        "PUT /containers/b7c1d2e3f4a5/archive?path=%2F HTTP/1.1"
+    );
+    assert!(client.requests.borrow()[1].ends_with("\r\n\r\ngarbage"));
+    assert!(client
+        .request_line(2)
+        .starts_with("DELETE /containers/b7c1d2e3f4a5"));
+}